@@ -76,6 +76,16 @@ impl FormatterConfig {
         Self { tab_size, max_line_length, sort_module_level_items }
     }
 
+    pub fn tab_size(mut self, tab_size: usize) -> Self {
+        self.tab_size = tab_size;
+        self
+    }
+
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = max_line_length;
+        self
+    }
+
     pub fn sort_module_level_items(mut self, sort_module_level_items: bool) -> Self {
         self.sort_module_level_items = sort_module_level_items;
         self
@@ -1,17 +1,22 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::Context;
 use lsp_server::ErrorCode;
 use lsp_types::request::WorkspaceConfiguration;
-use lsp_types::{ClientCapabilities, ConfigurationItem, ConfigurationParams};
+use lsp_types::{
+    ClientCapabilities, ConfigurationItem, ConfigurationParams, DiagnosticSeverity, Url,
+};
 use serde_json::Value;
 use tracing::{debug, error, warn};
 
+use crate::env_config;
 use crate::lsp::capabilities::client::ClientCapabilitiesExt;
 use crate::lsp::result::{LSPResult, LSPResultEx};
-use crate::server::client::Requester;
+use crate::server::client::{Notifier, Requester};
 use crate::server::schedule::Task;
+use crate::state::State;
 
 // TODO(mkaput): Write a macro that will auto-generate this struct and the `reload` logic.
 // TODO(mkaput): Write a test that checks that fields in this struct are sorted alphabetically.
@@ -23,7 +28,7 @@ use crate::server::schedule::Task;
 /// Therefore, holding any references or copies of this struct or its values for
 /// longer periods of time should be avoided, unless the copy will be reactively updated on
 /// `workspace/didChangeConfiguration` requests.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct Config {
     /// A user-provided path to the `core` crate source code for use in projects where `core` is
     /// unmanaged by the toolchain.
@@ -38,14 +43,173 @@ pub struct Config {
     /// The property is set by the user under the `cairo1.traceMacroDiagnostics` key in client
     /// configuration.
     pub trace_macro_diagnostics: bool,
+    /// Size limit (in kilobytes) above which expensive analysis features are skipped for a file.
+    ///
+    /// Files larger than this still get parser diagnostics and basic completions, but semantic
+    /// diagnostics, semantic tokens and similar whole-file features are not computed for them,
+    /// to avoid blocking the server on pathological inputs.
+    ///
+    /// The property is set by the user under the `cairo1.maxFileSizeKb` key in client
+    /// configuration.
+    pub max_file_size_kb: u64,
+    /// Time limit (in milliseconds) for handling a single background request.
+    ///
+    /// Requests exceeding this limit are answered with an error and their computation is
+    /// cancelled, so that a single misbehaving query cannot make the server unresponsive.
+    /// No limit is enforced if this is `None`.
+    ///
+    /// The property is set by the user under the `cairo1.requestTimeoutMs` key in client
+    /// configuration.
+    pub request_timeout_ms: Option<u64>,
+    /// Whether to show inlay hints with generic arguments inferred for calls of generic functions
+    /// made without explicit generic arguments.
+    ///
+    /// The property is set by the user under the `cairo1.inlayHints.genericArgs` key in client
+    /// configuration.
+    pub inlay_hints_generic_args: bool,
+    /// Whether to show inlay hints with types inferred for `let` statements without an explicit
+    /// type.
+    ///
+    /// The property is set by the user under the `cairo1.inlayHints.typeHints` key in client
+    /// configuration.
+    pub inlay_hints_type_hints: bool,
+    /// Whether to complete items which are not in scope, along with an edit adding the `use`
+    /// statement importing them.
+    ///
+    /// The property is set by the user under the `cairo1.completions.autoImport` key in client
+    /// configuration.
+    pub completions_auto_import: bool,
+    /// Whether to compute diagnostics only for open files and the modules they belong to.
+    ///
+    /// This skips analysing the whole workspace on every change, which is expensive in large
+    /// workspaces, at the cost of errors in files which are not open not being reported until
+    /// they are opened.
+    ///
+    /// The property is set by the user under the `cairo1.diagnostics.openFilesOnly` key in client
+    /// configuration.
+    pub diagnostics_open_files_only: bool,
+    /// Severities to report diagnostics with instead of their own, keyed by the error code or the
+    /// kind of the diagnostic, e.g. `E0001` or `unused_import`.
+    ///
+    /// Diagnostics mapped to `None` are not reported at all.
+    ///
+    /// The property is set by the user under the `cairo1.diagnostics.severityOverrides` key in
+    /// client configuration, as a map to one of `error`, `warning`, `information`, `hint` or
+    /// `off`.
+    pub diagnostics_severity_overrides: HashMap<String, Option<DiagnosticSeverity>>,
+    /// Whether to run `scarb build` when a file of a Scarb package is saved, and report
+    /// diagnostics found by it along with the ones computed by the language server.
+    ///
+    /// This reports errors which are only found by compiling the package, e.g. during code
+    /// generation.
+    ///
+    /// The property is set by the user under the `cairo1.diagnostics.scarbBuild` key in client
+    /// configuration.
+    pub diagnostics_scarb_build: bool,
+    /// Maximum number of diagnostics reported for a single file.
+    ///
+    /// Diagnostics above the limit are replaced with a single one saying how many were
+    /// suppressed, so that files with thousands of errors, e.g. generated ones, do not overwhelm
+    /// the client.
+    /// No limit is enforced if this is `None`.
+    ///
+    /// The property is set by the user under the `cairo1.diagnostics.maxPerFile` key in client
+    /// configuration.
+    pub diagnostics_max_per_file: Option<usize>,
+    /// Base URL of the documentation of error codes, which diagnostics link to.
+    ///
+    /// The documentation of an error code is expected under the code appended to this URL,
+    /// e.g. `https://example.com/errors/E0001`.
+    /// Diagnostics do not link to any documentation if this is `None`.
+    ///
+    /// The property is set by the user under the `cairo1.diagnostics.docsBaseUrl` key in client
+    /// configuration.
+    pub diagnostics_docs_base_url: Option<Url>,
+    /// Maximum width of lines produced by the formatter, above which they are broken.
+    ///
+    /// This enforces a line width for all files formatted by the language server, without
+    /// requiring a formatter configuration file.
+    /// The default width of the formatter is used if this is `None`.
+    ///
+    /// The property is set by the user under the `cairo1.formatter.maxLineWidth` key in client
+    /// configuration.
+    pub formatter_max_line_width: Option<usize>,
+    /// Interval between replacements of the analysis database with an empty one, which frees
+    /// memory used by results of queries which are no longer needed.
+    ///
+    /// Large values effectively disable swapping, trading memory for speed, while small ones
+    /// trade CPU time for a smaller memory footprint.
+    /// Defaults to [`Config::DEFAULT_DB_REPLACE_INTERVAL`].
+    ///
+    /// The [`env_config::CAIRO_LS_DB_REPLACE_INTERVAL`] environment variable takes precedence over
+    /// it.
+    ///
+    /// The property is set by the user under the `cairo1.memory.dbReplaceIntervalSecs` key in
+    /// client configuration, in seconds.
+    pub db_replace_interval: Duration,
+    /// A user-provided path to the `scarb` executable, used instead of the one found in the
+    /// environment, e.g. when multiple toolchains are installed.
+    ///
+    /// The [`env_config::CAIRO_LS_SCARB`] environment variable takes precedence over it.
+    ///
+    /// The property is set by the user under the `cairo1.scarbPath` key in client configuration.
+    pub scarb_path: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            unmanaged_core_path: None,
+            trace_macro_diagnostics: false,
+            max_file_size_kb: Self::DEFAULT_MAX_FILE_SIZE_KB,
+            request_timeout_ms: None,
+            inlay_hints_generic_args: true,
+            inlay_hints_type_hints: true,
+            completions_auto_import: true,
+            diagnostics_open_files_only: false,
+            diagnostics_severity_overrides: HashMap::new(),
+            diagnostics_scarb_build: false,
+            diagnostics_max_per_file: None,
+            diagnostics_docs_base_url: None,
+            formatter_max_line_width: None,
+            db_replace_interval: env_config::db_replace_interval_override()
+                .unwrap_or(Self::DEFAULT_DB_REPLACE_INTERVAL),
+            scarb_path: None,
+        }
+    }
 }
 
 impl Config {
+    /// Default value of [`Config::max_file_size_kb`].
+    pub const DEFAULT_MAX_FILE_SIZE_KB: u64 = 2048;
+
+    /// Default value of [`Config::db_replace_interval`].
+    pub const DEFAULT_DB_REPLACE_INTERVAL: Duration = Duration::from_secs(300);
+
+    /// Checks whether a file of the given length (in bytes) exceeds [`Config::max_file_size_kb`].
+    pub fn is_file_too_large(&self, len: usize) -> bool {
+        len as u64 > self.max_file_size_kb.saturating_mul(1024)
+    }
+
     /// Reloads the configuration from the language client.
     pub fn reload(
         &mut self,
         requester: &mut Requester<'_>,
         client_capabilities: &ClientCapabilities,
+    ) -> LSPResult<()> {
+        self.reload_then(requester, client_capabilities, |_, _, _| {})
+    }
+
+    /// Reloads the configuration from the language client, and calls `then` once the new
+    /// configuration is applied.
+    ///
+    /// The `then` callback is not called if the client does not support `workspace/configuration`
+    /// requests or if the request fails.
+    pub fn reload_then(
+        &mut self,
+        requester: &mut Requester<'_>,
+        client_capabilities: &ClientCapabilities,
+        then: impl Fn(&mut State, Notifier, &mut Requester<'_>) + Clone + 'static,
     ) -> LSPResult<()> {
         if !client_capabilities.workspace_configuration_support() {
             warn!(
@@ -61,6 +225,52 @@ impl Config {
                 scope_uri: None,
                 section: Some("cairo1.traceMacroDiagnostics".to_owned()),
             },
+            ConfigurationItem { scope_uri: None, section: Some("cairo1.maxFileSizeKb".to_owned()) },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.requestTimeoutMs".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.inlayHints.genericArgs".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.inlayHints.typeHints".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.completions.autoImport".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.diagnostics.openFilesOnly".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.diagnostics.severityOverrides".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.diagnostics.scarbBuild".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.diagnostics.maxPerFile".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.diagnostics.docsBaseUrl".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.formatter.maxLineWidth".to_owned()),
+            },
+            ConfigurationItem {
+                scope_uri: None,
+                section: Some("cairo1.memory.dbReplaceIntervalSecs".to_owned()),
+            },
+            ConfigurationItem { scope_uri: None, section: Some("cairo1.scarbPath".to_owned()) },
         ];
         let expected_len = items.len();
 
@@ -76,8 +286,9 @@ impl Config {
 
             // This conversion is O(1), and makes popping from front also O(1).
             let mut response = VecDeque::from(response);
+            let then = then.clone();
 
-            Task::local(move |state, _, _, _| {
+            Task::local(move |state, notifier, requester, _| {
                 state.config.unmanaged_core_path = response
                     .pop_front()
                     .as_ref()
@@ -86,8 +297,90 @@ impl Config {
                     .map(Into::into);
                 state.config.trace_macro_diagnostics =
                     response.pop_front().as_ref().and_then(Value::as_bool).unwrap_or_default();
+                state.config.max_file_size_kb = response
+                    .pop_front()
+                    .as_ref()
+                    .and_then(Value::as_u64)
+                    .unwrap_or(Self::DEFAULT_MAX_FILE_SIZE_KB);
+                state.config.request_timeout_ms =
+                    response.pop_front().as_ref().and_then(Value::as_u64).filter(|&ms| ms > 0);
+                state.config.inlay_hints_generic_args =
+                    response.pop_front().as_ref().and_then(Value::as_bool).unwrap_or(true);
+                state.config.inlay_hints_type_hints =
+                    response.pop_front().as_ref().and_then(Value::as_bool).unwrap_or(true);
+                state.config.completions_auto_import =
+                    response.pop_front().as_ref().and_then(Value::as_bool).unwrap_or(true);
+                state.config.diagnostics_open_files_only =
+                    response.pop_front().as_ref().and_then(Value::as_bool).unwrap_or_default();
+                let diagnostics_severity_overrides = response
+                    .pop_front()
+                    .as_ref()
+                    .and_then(Value::as_object)
+                    .map(|overrides| {
+                        overrides
+                            .iter()
+                            .filter_map(|(key, value)| {
+                                let severity = parse_severity_override(value);
+                                if severity.is_none() {
+                                    warn!("invalid severity override for `{key}`: {value}");
+                                }
+                                Some((key.clone(), severity?))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if diagnostics_severity_overrides != state.config.diagnostics_severity_overrides {
+                    // Reported diagnostics are compared before being converted to LSP, so the
+                    // change would not be noticed otherwise.
+                    state.pull_diagnostics_cache.clear();
+                    state.config.diagnostics_severity_overrides = diagnostics_severity_overrides;
+                }
+                state.config.diagnostics_scarb_build =
+                    response.pop_front().as_ref().and_then(Value::as_bool).unwrap_or_default();
+                state.config.diagnostics_max_per_file = response
+                    .pop_front()
+                    .as_ref()
+                    .and_then(Value::as_u64)
+                    .filter(|&max| max > 0)
+                    .map(|max| max as usize);
+                state.config.diagnostics_docs_base_url = response
+                    .pop_front()
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .filter(|s| !s.is_empty())
+                    .and_then(|url| {
+                        // Make sure codes are appended to the URL instead of replacing its last
+                        // path segment.
+                        let url = if url.ends_with('/') { url.into() } else { format!("{url}/") };
+                        Url::parse(&url)
+                            .inspect_err(|err| warn!("invalid docs base URL `{url}`: {err}"))
+                            .ok()
+                    });
+                state.config.formatter_max_line_width = response
+                    .pop_front()
+                    .as_ref()
+                    .and_then(Value::as_u64)
+                    .filter(|&width| width > 0)
+                    .map(|width| width as usize);
+                let db_replace_interval =
+                    response.pop_front().as_ref().and_then(Value::as_u64).map(Duration::from_secs);
+                state.config.db_replace_interval = env_config::db_replace_interval_override()
+                    .or(db_replace_interval)
+                    .unwrap_or(Self::DEFAULT_DB_REPLACE_INTERVAL);
+                let scarb_path = response
+                    .pop_front()
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .filter(|s| !s.is_empty())
+                    .map(PathBuf::from);
+                if scarb_path != state.config.scarb_path {
+                    state.scarb_toolchain.set_configured_path(scarb_path.clone());
+                    state.config.scarb_path = scarb_path;
+                }
 
                 debug!("reloaded configuration: {:#?}", state.config);
+
+                then(state, notifier, requester);
             })
         };
 
@@ -98,3 +391,16 @@ impl Config {
             .inspect_err(|e| warn!("{e:?}"))
     }
 }
+
+/// Parses a value of [`Config::diagnostics_severity_overrides`], where `Some(None)` means the
+/// diagnostic is turned off.
+fn parse_severity_override(value: &Value) -> Option<Option<DiagnosticSeverity>> {
+    Some(match value.as_str()? {
+        "error" => Some(DiagnosticSeverity::ERROR),
+        "warning" => Some(DiagnosticSeverity::WARNING),
+        "information" => Some(DiagnosticSeverity::INFORMATION),
+        "hint" => Some(DiagnosticSeverity::HINT),
+        "off" => None,
+        _ => return None,
+    })
+}
@@ -2,6 +2,10 @@
 //!
 //! The [`report_to_logs`] function logs each variable value as a debug message.
 //!
+//! Variables corresponding to properties of the client configuration, like
+//! [`CAIRO_LS_DB_REPLACE_INTERVAL`] and [`CAIRO_LS_SCARB`], take precedence over them, so that
+//! they can be used to override the configuration regardless of the client.
+//!
 //! **Note**: Each variable should be described by a name constant, accessor function and entry
 //! in the [`report_to_logs`] function.
 
@@ -15,17 +19,16 @@ use tracing::debug;
 pub const CAIRO_LS_DB_REPLACE_INTERVAL: &'_ str = "CAIRO_LS_DB_REPLACE_INTERVAL";
 pub const CAIRO_LS_LOG: &'_ str = "CAIRO_LS_LOG";
 pub const CAIRO_LS_PROFILE: &'_ str = "CAIRO_LS_PROFILE";
+pub const CAIRO_LS_SCARB: &'_ str = "CAIRO_LS_SCARB";
 pub const SCARB: &'_ str = "SCARB";
 
-/// Interval between compiler database regenerations (to free unused memory).
-pub fn db_replace_interval() -> Duration {
-    const DEFAULT: u64 = 300;
-
+/// Interval between compiler database regenerations (to free unused memory), overriding the one
+/// configured in the client.
+pub fn db_replace_interval_override() -> Option<Duration> {
     env::var(CAIRO_LS_DB_REPLACE_INTERVAL)
         .ok()
         .and_then(|v| v.parse().ok())
         .map(Duration::from_secs)
-        .unwrap_or_else(|| Duration::from_secs(DEFAULT))
 }
 
 /// LS tracing filter, see [`tracing_subscriber::EnvFilter`] for more.
@@ -38,16 +41,25 @@ pub fn tracing_profile() -> bool {
     env::var_os(CAIRO_LS_PROFILE).map(env_to_bool).unwrap_or_default()
 }
 
-/// Path to the Scarb binary to call during analysis.
+/// Path to the Scarb binary to call during analysis, overriding the one configured in the client.
+pub fn scarb_path_override() -> Option<PathBuf> {
+    env::var_os(CAIRO_LS_SCARB).filter(|path| !path.is_empty()).map(PathBuf::from)
+}
+
+/// Path to the Scarb binary to call during analysis, unless the client configures one.
 pub fn scarb_path() -> Option<PathBuf> {
     env::var_os(SCARB).map(PathBuf::from)
 }
 
 /// Print all environment variables values (or defaults) as debug messages in logs.
 pub fn report_to_logs() {
-    debug!("{CAIRO_LS_DB_REPLACE_INTERVAL}={:?}", db_replace_interval());
+    debug!("{CAIRO_LS_DB_REPLACE_INTERVAL}={:?}", db_replace_interval_override());
     debug!("{CAIRO_LS_LOG}={}", log_env_filter());
     debug!("{CAIRO_LS_PROFILE}={}", tracing_profile());
+    debug!(
+        "{CAIRO_LS_SCARB}={}",
+        scarb_path_override().map(|p| p.display().to_string()).unwrap_or_default()
+    );
     debug!("{SCARB}={}", scarb_path().map(|p| p.display().to_string()).unwrap_or_default());
 }
 
@@ -0,0 +1,281 @@
+use std::collections::HashSet;
+use std::iter;
+
+use cairo_lang_defs::ids::{ImplItemId, LookupItemId, TopLevelLanguageElementId, TraitItemId};
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_filesystem::span::TextSpan;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
+use cairo_lang_semantic::items::functions::GenericFunctionId;
+use cairo_lang_semantic::lookup_item::LookupItemEx;
+use cairo_lang_semantic::{Expr, FunctionId};
+use cairo_lang_syntax::node::helpers::PathSegmentEx;
+use cairo_lang_syntax::node::ids::SyntaxStablePtrId;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, Terminal, TypedStablePtr, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams, Range,
+    SymbolKind,
+};
+
+use crate::ide::navigation::references::{
+    declaration_identifier, find_references, identifier_span, resolve_identifier,
+};
+use crate::ide::utils::{get_copied_origin, is_method_call};
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Find the function at a given text document position, which becomes the root of the call
+/// hierarchy.
+pub fn prepare(
+    params: CallHierarchyPrepareParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<CallHierarchyItem>> {
+    let file = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
+    let position = params.text_document_position_params.position.to_cairo(db, file);
+    let identifier = db.find_identifier_at_position(file, position)?;
+    let function = function_definition(db, &identifier)?;
+    Some(vec![call_hierarchy_item(db, function)?])
+}
+
+/// Find all calls of the function of the item in the workspace, grouped by the calling function.
+///
+/// Calls of impl functions through their trait are included if the impl is known at the call site.
+pub fn incoming_calls(
+    params: CallHierarchyIncomingCallsParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let syntax_db = db.upcast();
+    let function = item_function(db, &params.item)?;
+    let name = declaration_identifier(db, function)?.text(syntax_db);
+
+    // Calls of impl functions usually resolve to the function of the trait.
+    let mut references = find_references(db, function, HashSet::from([name.clone()]));
+    if let Some(trait_function) = implemented_trait_function(db, function) {
+        references.extend(find_references(db, trait_function, HashSet::from([name])));
+    }
+    let mut references = references.into_iter().collect::<Vec<_>>();
+    references.sort_by_key(|&(file, span)| {
+        (db.url_for_file(file).map(|url| url.to_string()), span.start)
+    });
+
+    let mut calls: OrderedHashMap<SyntaxStablePtrId, Vec<Range>> = OrderedHashMap::default();
+    for (file, span) in references {
+        let Some(range) = span.position_in_file(db.upcast(), file) else { continue };
+        let Some(identifier) = db.find_identifier_at_position(file, range.start) else { continue };
+        let Some(call) = callee_call(db, &identifier) else { continue };
+        if call_target(db, &call).is_some_and(|callee| callee != function) {
+            continue;
+        }
+        let Some(caller) = iter::successors(Some(identifier.as_syntax_node()), SyntaxNode::parent)
+            .find(|node| is_function(node.kind(syntax_db)))
+        else {
+            continue;
+        };
+        calls.entry(caller.stable_ptr()).or_default().push(range.to_lsp(db, file));
+    }
+
+    Some(
+        calls
+            .into_iter()
+            .filter_map(|(caller, from_ranges)| {
+                Some(CallHierarchyIncomingCall {
+                    from: call_hierarchy_item(db, caller)?,
+                    from_ranges,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Find all functions called in the body of the function of the item.
+///
+/// Calls of trait functions are resolved to the function of the impl, if it is known.
+pub fn outgoing_calls(
+    params: CallHierarchyOutgoingCallsParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let syntax_db = db.upcast();
+    let function = item_function(db, &params.item)?;
+    let file = db.file_for_url(&params.item.uri)?;
+
+    let mut calls: OrderedHashMap<SyntaxStablePtrId, Vec<Range>> = OrderedHashMap::default();
+    for node in function.lookup(syntax_db).descendants(syntax_db) {
+        if node.kind(syntax_db) != SyntaxKind::ExprFunctionCall {
+            continue;
+        }
+        let call = ast::ExprFunctionCall::from_syntax_node(syntax_db, node);
+        let Some(callee) = call_target(db, &call) else { continue };
+
+        let Some(segment) = call.path(syntax_db).elements(syntax_db).last().cloned() else {
+            continue;
+        };
+        let Some((call_file, span)) = identifier_span(db, &segment.identifier_ast(syntax_db))
+        else {
+            continue;
+        };
+        let Some(range) = span.position_in_file(db.upcast(), call_file) else { continue };
+        if call_file == file {
+            calls.entry(callee).or_default().push(range.to_lsp(db, file));
+        }
+    }
+
+    Some(
+        calls
+            .into_iter()
+            .filter_map(|(callee, from_ranges)| {
+                Some(CallHierarchyOutgoingCall {
+                    to: call_hierarchy_item(db, callee)?,
+                    from_ranges,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Finds the definition of the function called by the call expression.
+fn call_target(db: &AnalysisDatabase, call: &ast::ExprFunctionCall) -> Option<SyntaxStablePtrId> {
+    let syntax_db = db.upcast();
+    let node = call.as_syntax_node();
+    let function_with_body = db.find_lookup_item(&node)?.function_with_body()?;
+    // Method calls are represented by the whole `receiver.method()` expression.
+    let expr_ptr = match node.parent() {
+        Some(parent) if is_method_call(syntax_db, call) => parent.stable_ptr(),
+        _ => node.stable_ptr(),
+    };
+    let expr_id = db.lookup_expr_by_ptr(function_with_body, ast::ExprPtr(expr_ptr)).ok()?;
+    let Expr::FunctionCall(expr) = db.expr_semantic(function_with_body, expr_id) else {
+        return None;
+    };
+    Some(callee_definition(db, expr.function))
+}
+
+/// Finds the definition of the called function.
+///
+/// Calls through traits are resolved to the impl function if the impl is known, and to the trait
+/// function otherwise.
+fn callee_definition(db: &AnalysisDatabase, function: FunctionId) -> SyntaxStablePtrId {
+    let defs_db = db.upcast();
+    match function.get_concrete(db).generic_function {
+        GenericFunctionId::Free(id) => id.stable_ptr(defs_db).untyped(),
+        GenericFunctionId::Extern(id) => id.stable_ptr(defs_db).untyped(),
+        GenericFunctionId::Impl(id) => match id.impl_function(db).ok().flatten() {
+            Some(impl_function) => impl_function.stable_ptr(defs_db).untyped(),
+            None => id.function.stable_ptr(defs_db).untyped(),
+        },
+        GenericFunctionId::Trait(id) => id.trait_function(db).stable_ptr(defs_db).untyped(),
+    }
+}
+
+/// Finds the definition of the function the call hierarchy item was created for.
+fn item_function(db: &AnalysisDatabase, item: &CallHierarchyItem) -> Option<SyntaxStablePtrId> {
+    let file = db.file_for_url(&item.uri)?;
+    let identifier =
+        db.find_identifier_at_position(file, item.selection_range.start.to_cairo(db, file))?;
+    function_definition(db, &identifier)
+}
+
+/// Finds the definition of the function the identifier refers to or which it names in the function
+/// declaration.
+fn function_definition(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<SyntaxStablePtrId> {
+    let syntax_db = db.upcast();
+    let node = identifier.as_syntax_node();
+    // The name of an impl function resolves to the impl, so declarations are matched manually.
+    let function = match node.parent() {
+        Some(declaration) if declaration.kind(syntax_db) == SyntaxKind::FunctionDeclaration => {
+            declaration.parent()?.stable_ptr()
+        }
+        _ => resolve_identifier(db, identifier)?,
+    };
+    is_function(function.lookup(syntax_db).kind(syntax_db)).then_some(function)
+}
+
+/// Creates a call hierarchy item for the function definition.
+fn call_hierarchy_item(
+    db: &AnalysisDatabase,
+    function: SyntaxStablePtrId,
+) -> Option<CallHierarchyItem> {
+    let syntax_db = db.upcast();
+    let node = function.lookup(syntax_db);
+    let kind = node.kind(syntax_db);
+    if !is_function(kind) {
+        return None;
+    }
+
+    let name = declaration_identifier(db, function)?;
+    let (file, selection_span) = identifier_span(db, &name)?;
+    let span =
+        get_copied_origin(db, function.file_id(syntax_db), node.span_without_trivia(syntax_db))
+            .filter(|&(origin_file, _)| origin_file == file)
+            .map_or(selection_span, |(_, span)| span);
+
+    let is_method = node.parent().and_then(|parent| parent.parent()).is_some_and(|body| {
+        matches!(body.kind(syntax_db), SyntaxKind::ImplBody | SyntaxKind::TraitBody)
+    });
+    let detail = db.find_lookup_item(&node).map(|item| match item {
+        LookupItemId::ModuleItem(item) => item.full_path(db.upcast()),
+        LookupItemId::TraitItem(TraitItemId::Function(function)) => function.full_path(db.upcast()),
+        LookupItemId::TraitItem(item) => item.full_path(db.upcast()),
+        LookupItemId::ImplItem(ImplItemId::Function(function)) => function.full_path(db.upcast()),
+        LookupItemId::ImplItem(item) => item.full_path(db.upcast()),
+    });
+
+    Some(CallHierarchyItem {
+        name: name.text(syntax_db).to_string(),
+        kind: if is_method { SymbolKind::METHOD } else { SymbolKind::FUNCTION },
+        tags: None,
+        detail,
+        uri: db.url_for_file(file)?,
+        range: range_in_file(db, file, span)?,
+        selection_range: range_in_file(db, file, selection_span)?,
+        data: None,
+    })
+}
+
+fn range_in_file(db: &AnalysisDatabase, file: FileId, span: TextSpan) -> Option<Range> {
+    Some(span.position_in_file(db.upcast(), file)?.to_lsp(db, file))
+}
+
+/// Checks whether the node of the kind defines a function.
+fn is_function(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::FunctionWithBody
+            | SyntaxKind::TraitItemFunction
+            | SyntaxKind::ItemExternFunction
+    )
+}
+
+/// Finds the call expression of which the identifier names the called function, e.g. `foo` in
+/// `a::foo()` or `a.foo()`.
+fn callee_call(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<ast::ExprFunctionCall> {
+    let syntax_db = db.upcast();
+    let call =
+        db.first_ancestor_of_kind(identifier.as_syntax_node(), SyntaxKind::ExprFunctionCall)?;
+    let call = ast::ExprFunctionCall::from_syntax_node(syntax_db, call);
+    let segment = call.path(syntax_db).elements(syntax_db).last().cloned()?;
+    (segment.identifier_ast(syntax_db).stable_ptr() == identifier.stable_ptr()).then_some(call)
+}
+
+/// Finds the trait function implemented by the function, if it is a function of an impl.
+fn implemented_trait_function(
+    db: &AnalysisDatabase,
+    function: SyntaxStablePtrId,
+) -> Option<SyntaxStablePtrId> {
+    let Some(LookupItemId::ImplItem(ImplItemId::Function(impl_function))) =
+        db.find_lookup_item(&function.lookup(db.upcast()))
+    else {
+        return None;
+    };
+    let trait_function = db.impl_function_trait_function(impl_function).ok()?;
+    Some(trait_function.stable_ptr(db.upcast()).untyped())
+}
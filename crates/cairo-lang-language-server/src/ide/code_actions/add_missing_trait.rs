@@ -11,12 +11,13 @@ use cairo_lang_semantic::resolve::Resolver;
 use cairo_lang_syntax::node::kind::SyntaxKind;
 use cairo_lang_syntax::node::{SyntaxNode, TypedStablePtr, TypedSyntaxNode, ast};
 use cairo_lang_utils::Upcast;
-use lsp_types::{CodeAction, CodeActionKind, Range, TextEdit, Url, WorkspaceEdit};
+use lsp_types::{CodeAction, CodeActionKind, Position, Range, TextEdit, Url, WorkspaceEdit};
 use tracing::debug;
 
+use crate::ide::code_actions::CodeActionData;
 use crate::ide::utils::find_methods_for_type;
-use crate::lang::db::{AnalysisDatabase, LsSemanticGroup};
-use crate::lang::lsp::{LsProtoGroup, ToLsp};
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
 
 /// Create a Quick Fix code action to add a missing trait given a `CannotCallMethod` diagnostic.
 pub fn add_missing_trait(db: &AnalysisDatabase, node: &SyntaxNode, uri: Url) -> Vec<CodeAction> {
@@ -62,19 +63,12 @@ fn missing_traits_actions(
         return None;
     }
 
-    let module_start_offset =
-        if let Some(ModuleId::Submodule(submodule_id)) = db.find_module_containing_node(node) {
-            let module_def_ast = submodule_id.stable_ptr(db.upcast()).lookup(syntax_db);
-            if let ast::MaybeModuleBody::Some(body) = module_def_ast.body(syntax_db) {
-                body.items(syntax_db).as_syntax_node().span_start_without_trivia(syntax_db)
-            } else {
-                TextOffset::default()
-            }
-        } else {
-            TextOffset::default()
-        };
-    let module_start_position =
-        module_start_offset.position_in_file(db.upcast(), file_id).unwrap().to_lsp();
+    // The trait is imported in the module containing the node, found again when the edit is
+    // resolved.
+    let position = node
+        .span_start_without_trivia(syntax_db)
+        .position_in_file(db.upcast(), file_id)?
+        .to_lsp(db, file_id);
     let relevant_methods = find_methods_for_type(db, resolver, ty, stable_ptr);
     let current_module = db.find_module_containing_node(node)?;
     let module_visible_traits = db.visible_traits_from_module(current_module)?;
@@ -86,14 +80,12 @@ fn missing_traits_actions(
                 code_actions.push(CodeAction {
                     title: format!("Import {}", trait_path),
                     kind: Some(CodeActionKind::QUICKFIX),
-                    edit: Some(WorkspaceEdit {
-                        changes: Some(HashMap::from_iter([(uri.clone(), vec![TextEdit {
-                            range: Range::new(module_start_position, module_start_position),
-                            new_text: format!("use {};\n", trait_path),
-                        }])])),
-                        document_changes: None,
-                        change_annotations: None,
-                    }),
+                    data: CodeActionData::ImportTrait {
+                        uri: uri.clone(),
+                        position,
+                        trait_path: trait_path.clone(),
+                    }
+                    .into_value(),
                     diagnostics: None,
                     ..Default::default()
                 });
@@ -102,3 +94,37 @@ fn missing_traits_actions(
     }
     Some(code_actions)
 }
+
+/// Create an edit importing the trait at the start of the module containing the position.
+pub fn import_trait_edit(
+    db: &AnalysisDatabase,
+    uri: Url,
+    position: Position,
+    trait_path: &str,
+) -> Option<WorkspaceEdit> {
+    let syntax_db = db.upcast();
+    let file_id = db.file_for_url(&uri)?;
+    let node = db.find_syntax_node_at_position(file_id, position.to_cairo(db, file_id))?;
+    let module_start_offset =
+        if let Some(ModuleId::Submodule(submodule_id)) = db.find_module_containing_node(&node) {
+            let module_def_ast = submodule_id.stable_ptr(db.upcast()).lookup(syntax_db);
+            if let ast::MaybeModuleBody::Some(body) = module_def_ast.body(syntax_db) {
+                body.items(syntax_db).as_syntax_node().span_start_without_trivia(syntax_db)
+            } else {
+                TextOffset::default()
+            }
+        } else {
+            TextOffset::default()
+        };
+    let module_start_position =
+        module_start_offset.position_in_file(db.upcast(), file_id)?.to_lsp(db, file_id);
+
+    Some(WorkspaceEdit {
+        changes: Some(HashMap::from_iter([(uri, vec![TextEdit {
+            range: Range::new(module_start_position, module_start_position),
+            new_text: format!("use {};\n", trait_path),
+        }])])),
+        document_changes: None,
+        change_annotations: None,
+    })
+}
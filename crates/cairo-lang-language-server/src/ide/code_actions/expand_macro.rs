@@ -1,8 +1,13 @@
+use std::collections::HashMap;
+
 use cairo_lang_syntax::node::SyntaxNode;
 use cairo_lang_syntax::node::kind::SyntaxKind;
-use lsp_types::{CodeAction, Command};
+use lsp_types::{CodeAction, CodeActionKind, Command, Position, Url, WorkspaceEdit};
 
+use crate::ide::code_actions::CodeActionData;
+use crate::ide::macros::expand::{self, InPlaceMacroCall, find_in_place_macro_call};
 use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo};
 
 /// Code actions for macro expansion.
 pub fn expand_macro(db: &AnalysisDatabase, node: SyntaxNode) -> Vec<CodeAction> {
@@ -29,3 +34,44 @@ pub fn expand_macro(db: &AnalysisDatabase, node: SyntaxNode) -> Vec<CodeAction>
 
     result
 }
+
+/// Create a refactoring code action replacing the macro call at the position with its expansion.
+///
+/// Code generated by an inline macro is put into the scope of the call, where the names it
+/// introduces are no longer hidden from the surrounding code, which the title warns about.
+pub fn expand_macro_in_place(
+    db: &AnalysisDatabase,
+    node: SyntaxNode,
+    uri: Url,
+    position: Position,
+) -> Option<CodeAction> {
+    let title = match find_in_place_macro_call(db, node)? {
+        InPlaceMacroCall::Inline(_) => "Expand macro in place (loses macro hygiene)",
+        InPlaceMacroCall::Item(_) => "Expand macros of item in place",
+    };
+
+    Some(CodeAction {
+        title: title.to_owned(),
+        kind: Some(CodeActionKind::REFACTOR_INLINE),
+        data: CodeActionData::ExpandMacroInPlace { uri, position }.into_value(),
+        ..Default::default()
+    })
+}
+
+/// Create an edit replacing the macro call at the position with its expansion.
+pub fn expand_macro_in_place_edit(
+    db: &AnalysisDatabase,
+    uri: Url,
+    position: Position,
+) -> Option<WorkspaceEdit> {
+    let file_id = db.file_for_url(&uri)?;
+    let node = db.find_syntax_node_at_position(file_id, position.to_cairo(db, file_id))?;
+    let call = find_in_place_macro_call(db, node)?;
+    let edit = expand::expand_macro_in_place(db, call)?;
+
+    Some(WorkspaceEdit {
+        changes: Some(HashMap::from_iter([(uri, vec![edit])])),
+        document_changes: None,
+        change_annotations: None,
+    })
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::lookup_item::LookupItemEx;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::{CodeAction, CodeActionKind, Diagnostic, Range, TextEdit, Url, WorkspaceEdit};
+
+use crate::ide::utils::{enum_path_in_module, line_indent, matched_enum, pattern_variant_name};
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Create a Quick Fix code action adding arms for all enum variants not covered by a `match`
+/// expression, given a missing match arm diagnostic.
+///
+/// The arms are placed after the existing ones, but before a trailing `_` arm, and their bodies
+/// are `panic!("not implemented")`.
+pub fn fill_match_arms(
+    db: &AnalysisDatabase,
+    diagnostic: Diagnostic,
+    uri: Url,
+) -> Option<CodeAction> {
+    let syntax_db = db.upcast();
+    let file_id = db.file_for_url(&uri)?;
+    // The diagnostic is reported on the whole `match` expression.
+    let node =
+        db.find_syntax_node_at_position(file_id, diagnostic.range.start.to_cairo(db, file_id))?;
+    let match_node = db.first_ancestor_of_kind(node, SyntaxKind::ExprMatch)?;
+    let match_expr = ast::ExprMatch::from_syntax_node(syntax_db, match_node.clone());
+
+    let function_with_body =
+        db.collect_lookup_items_stack(&match_node)?.into_iter().next()?.function_with_body()?;
+    let enum_id = matched_enum(db, function_with_body, &match_expr)?.enum_id(db);
+    // Variants are referred to through the path under which the enum is visible at the match.
+    let module_id = db.find_module_containing_node(&match_node)?;
+    let enum_path = enum_path_in_module(db, enum_id, module_id);
+
+    let arms = match_expr.arms(syntax_db);
+    let arm_elements = arms.elements(syntax_db);
+    let covered = arm_elements
+        .iter()
+        .flat_map(|arm| arm.patterns(syntax_db).elements(syntax_db))
+        .filter_map(|pattern| pattern_variant_name(syntax_db, &pattern))
+        .collect::<Vec<_>>();
+    let new_arms = db
+        .enum_variants(enum_id)
+        .ok()?
+        .iter()
+        .filter(|(name, _)| !covered.contains(name))
+        .map(|(name, variant_id)| {
+            let has_data = db
+                .variant_semantic(enum_id, *variant_id)
+                .is_ok_and(|variant| !variant.ty.is_unit(db));
+            let data = if has_data { "(_)" } else { "" };
+            format!("{enum_path}::{name}{data} => panic!(\"not implemented\"),")
+        })
+        .collect::<Vec<_>>();
+    if new_arms.is_empty() {
+        return None;
+    }
+
+    let position_of = |node: &SyntaxNode, end: bool| {
+        let offset = if end {
+            node.span_end_without_trivia(syntax_db)
+        } else {
+            node.span_start_without_trivia(syntax_db)
+        };
+        offset.position_in_file(db.upcast(), file_id)
+    };
+    let is_wildcard = |arm: &ast::MatchArm| {
+        arm.patterns(syntax_db)
+            .elements(syntax_db)
+            .iter()
+            .all(|pattern| matches!(pattern, ast::Pattern::Underscore(_)))
+    };
+
+    let (range, new_text) = match arm_elements.as_slice() {
+        [] => {
+            // Arms of an empty `match` are placed on separate lines, indented one level deeper.
+            let indent = line_indent(db, file_id, position_of(&match_node, false)?.line)?;
+            let start = position_of(&match_expr.lbrace(syntax_db).as_syntax_node(), true)?;
+            let end = position_of(&match_expr.rbrace(syntax_db).as_syntax_node(), false)?;
+            let arms_text =
+                new_arms.iter().map(|arm| format!("\n{indent}    {arm}")).collect::<String>();
+            (
+                Range::new(start.to_lsp(db, file_id), end.to_lsp(db, file_id)),
+                format!("{arms_text}\n{indent}"),
+            )
+        }
+        [.., last] if is_wildcard(last) => {
+            let position = position_of(&last.as_syntax_node(), false)?;
+            let indent = " ".repeat(position.col);
+            let position = position.to_lsp(db, file_id);
+            let arms_text =
+                new_arms.iter().map(|arm| format!("{arm}\n{indent}")).collect::<String>();
+            (Range::new(position, position), arms_text)
+        }
+        [first, ..] => {
+            let indent = " ".repeat(position_of(&first.as_syntax_node(), false)?.col);
+            let position = position_of(&arms.as_syntax_node(), true)?.to_lsp(db, file_id);
+            // Arms are separated by commas, the last arm is followed by one unless it is the tail.
+            let separator = if arms.has_tail(syntax_db) { "," } else { "" };
+            let arms_text =
+                new_arms.iter().map(|arm| format!("\n{indent}{arm}")).collect::<String>();
+            (Range::new(position, position), format!("{separator}{arms_text}"))
+        }
+    };
+
+    Some(CodeAction {
+        title: "Fill missing match arms".to_owned(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(uri, vec![TextEdit { range, new_text }])])),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        ..Default::default()
+    })
+}
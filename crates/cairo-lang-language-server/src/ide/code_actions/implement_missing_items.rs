@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use cairo_lang_defs::ids::ImplDefLongId;
+use cairo_lang_filesystem::span::{TextOffset, TextSpan};
+use cairo_lang_semantic::ConcreteTraitId;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, Terminal, TypedStablePtr, TypedSyntaxNode, ast};
+use cairo_lang_utils::{Intern, Upcast};
+use lsp_types::{CodeAction, CodeActionKind, Diagnostic, Range, TextEdit, Url, WorkspaceEdit};
+use smol_str::SmolStr;
+
+use crate::ide::utils::line_indent;
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToLsp};
+
+/// Title of the code action, shared by its Quick Fix and refactoring variants.
+pub const TITLE: &str = "Implement missing trait items";
+
+/// Create a code action adding stubs of all trait items missing in the impl containing the node.
+///
+/// Functions are given the signatures declared in the trait, with generic parameters of the trait
+/// replaced by the arguments of the impl, and `panic!("not implemented")` bodies. Types are given
+/// `()` as a placeholder. Constants and impls have no sensible placeholder and are left out.
+///
+/// If the diagnostic is given, the action is a Quick Fix for it, otherwise the node must be in the
+/// header of the impl and the action is a refactoring.
+pub fn implement_missing_items(
+    db: &AnalysisDatabase,
+    node: SyntaxNode,
+    diagnostic: Option<Diagnostic>,
+    uri: Url,
+) -> Option<CodeAction> {
+    let syntax_db = db.upcast();
+    let file_id = db.file_for_url(&uri)?;
+    let impl_node = db.first_ancestor_of_kind(node.clone(), SyntaxKind::ItemImpl)?;
+    let impl_ast = ast::ItemImpl::from_syntax_node(syntax_db, impl_node.clone());
+    let body = impl_ast.body(syntax_db);
+    if diagnostic.is_none()
+        && body.as_syntax_node().span_without_trivia(syntax_db).contains(node.span(syntax_db))
+    {
+        return None;
+    }
+
+    let module_file_id = db.find_module_file_containing_node(&impl_node)?;
+    let impl_def_id = ImplDefLongId(module_file_id, impl_ast.stable_ptr()).intern(db);
+    let concrete_trait = db.impl_def_concrete_trait(impl_def_id).ok()?;
+    let trait_id = concrete_trait.trait_id(db);
+    let missing_names = db
+        .trait_required_item_names(trait_id)
+        .ok()?
+        .into_iter()
+        .filter(|name| db.impl_item_by_name(impl_def_id, name.clone()).is_ok_and(|i| i.is_none()))
+        .collect::<Vec<_>>();
+
+    let substitutions = trait_generic_args(db, &impl_ast, concrete_trait)?;
+    let indent = line_indent(
+        db,
+        file_id,
+        impl_node.span_start_without_trivia(syntax_db).position_in_file(db.upcast(), file_id)?.line,
+    )?;
+    let item_indent = format!("{indent}    ");
+
+    let trait_ast = trait_id.stable_ptr(db.upcast()).lookup(syntax_db);
+    let ast::MaybeTraitBody::Some(trait_body) = trait_ast.body(syntax_db) else { return None };
+    let items = trait_body
+        .items(syntax_db)
+        .elements(syntax_db)
+        .into_iter()
+        .filter_map(|item| match item {
+            ast::TraitItem::Function(function) => {
+                let declaration = function.declaration(syntax_db);
+                if !missing_names.contains(&declaration.name(syntax_db).text(syntax_db)) {
+                    return None;
+                }
+                let declaration = substitute(syntax_db, &declaration, &substitutions);
+                let body = "panic!(\"not implemented\")";
+                Some(format!("{declaration} {{\n{item_indent}    {body}\n{item_indent}}}"))
+            }
+            ast::TraitItem::Type(ty) => {
+                let name = ty.name(syntax_db).text(syntax_db);
+                missing_names.contains(&name).then(|| format!("type {name} = ();"))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        return None;
+    }
+
+    let position_of = |offset: TextOffset| {
+        offset.position_in_file(db.upcast(), file_id).map(|position| position.to_lsp(db, file_id))
+    };
+    let items_text =
+        items.iter().map(|item| format!("{item_indent}{item}")).collect::<Vec<_>>().join("\n\n");
+    let (range, new_text) = match body {
+        ast::MaybeImplBody::Some(body) => {
+            let impl_items = body.items(syntax_db);
+            if impl_items.elements(syntax_db).is_empty() {
+                let start = position_of(
+                    body.lbrace(syntax_db).as_syntax_node().span_end_without_trivia(syntax_db),
+                )?;
+                let end = position_of(
+                    body.rbrace(syntax_db).as_syntax_node().span_start_without_trivia(syntax_db),
+                )?;
+                (Range::new(start, end), format!("\n{items_text}\n{indent}"))
+            } else {
+                let position =
+                    position_of(impl_items.as_syntax_node().span_end_without_trivia(syntax_db))?;
+                (Range::new(position, position), format!("\n\n{items_text}"))
+            }
+        }
+        ast::MaybeImplBody::None(semicolon) => {
+            let span = semicolon.as_syntax_node().span_without_trivia(syntax_db);
+            let range = Range::new(position_of(span.start)?, position_of(span.end)?);
+            (range, format!(" {{\n{items_text}\n{indent}}}"))
+        }
+    };
+
+    let kind =
+        if diagnostic.is_some() { CodeActionKind::QUICKFIX } else { CodeActionKind::REFACTOR };
+    Some(CodeAction {
+        title: TITLE.to_owned(),
+        kind: Some(kind),
+        diagnostics: diagnostic.map(|diagnostic| vec![diagnostic]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(uri, vec![TextEdit { range, new_text }])])),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        ..Default::default()
+    })
+}
+
+/// Returns the text of the arguments of the impl for the generic parameters of the trait, by the
+/// names of the parameters.
+///
+/// Arguments are taken as written in the trait path of the impl if possible, and formatted from
+/// the semantic model otherwise.
+fn trait_generic_args(
+    db: &AnalysisDatabase,
+    impl_ast: &ast::ItemImpl,
+    concrete_trait: ConcreteTraitId,
+) -> Option<HashMap<SmolStr, String>> {
+    let syntax_db = db.upcast();
+    let written_args = match impl_ast.trait_path(syntax_db).elements(syntax_db).last()? {
+        ast::PathSegment::WithGenericArgs(segment) => {
+            segment.generic_args(syntax_db).generic_args(syntax_db).elements(syntax_db)
+        }
+        _ => vec![],
+    };
+    let params = db.trait_generic_params(concrete_trait.trait_id(db)).ok()?;
+    Some(
+        params
+            .iter()
+            .zip(concrete_trait.generic_args(db))
+            .enumerate()
+            .filter_map(|(i, (param, arg))| {
+                let name = param.id().name(db.upcast())?;
+                let text = match written_args.get(i) {
+                    Some(ast::GenericArg::Unnamed(written)) => match written.value(syntax_db) {
+                        ast::GenericArgValue::Expr(expr) => {
+                            expr.as_syntax_node().get_text_without_trivia(syntax_db)
+                        }
+                        ast::GenericArgValue::Underscore(_) => arg.format(db),
+                    },
+                    _ => arg.format(db),
+                };
+                Some((name, text))
+            })
+            .collect(),
+    )
+}
+
+/// Returns the text of the function declaration, with single segment paths naming a key of the
+/// substitutions replaced by its value.
+fn substitute(
+    db: &dyn SyntaxGroup,
+    declaration: &ast::FunctionDeclaration,
+    substitutions: &HashMap<SmolStr, String>,
+) -> String {
+    let node = declaration.as_syntax_node();
+    let start = node.span_start_without_trivia(db);
+    let mut text = node.clone().get_text_without_trivia(db);
+    let replacements = node
+        .descendants(db)
+        .filter(|node| node.kind(db) == SyntaxKind::ExprPath)
+        .filter_map(|node| {
+            let path = ast::ExprPath::from_syntax_node(db, node.clone());
+            let segments = path.elements(db);
+            let [ast::PathSegment::Simple(segment)] = segments.as_slice() else {
+                return None;
+            };
+            let replacement = substitutions.get(&segment.ident(db).text(db))?;
+            let span = node.span_without_trivia(db);
+            let relative = TextSpan {
+                start: TextOffset::default().add_width(span.start - start),
+                end: TextOffset::default().add_width(span.end - start),
+            };
+            Some((relative, replacement))
+        })
+        .collect::<Vec<_>>();
+    // Replace from the end, so the spans of the remaining replacements stay valid.
+    for (span, replacement) in replacements.into_iter().rev() {
+        text.replace_range(span.to_str_range(), replacement);
+    }
+    text
+}
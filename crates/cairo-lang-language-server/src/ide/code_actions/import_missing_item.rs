@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use cairo_lang_defs::ids::NamedLanguageElementId;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{Terminal, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use itertools::Itertools;
+use lsp_types::{CodeAction, CodeActionKind, Diagnostic, Position, Url, WorkspaceEdit};
+
+use crate::ide::code_actions::CodeActionData;
+use crate::ide::utils::use_statement_edit;
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo};
+
+/// Create Quick Fix code actions importing an item with the name of an unresolved path, one for
+/// each item with that name visible from the current module, given a `PathNotFound` diagnostic.
+pub fn import_missing_item(
+    db: &AnalysisDatabase,
+    diagnostic: Diagnostic,
+    uri: Url,
+) -> Option<Vec<CodeAction>> {
+    let syntax_db = db.upcast();
+    let file_id = db.file_for_url(&uri)?;
+    let position = diagnostic.range.start;
+    let identifier = db.find_identifier_at_position(file_id, position.to_cairo(db, file_id))?;
+
+    // Only the first segment of a path can be brought into scope by importing it.
+    let segment = identifier.as_syntax_node().parent()?;
+    let path = segment.parent()?;
+    if path.kind(syntax_db) != SyntaxKind::ExprPath
+        || ast::ExprPath::from_syntax_node(syntax_db, path).elements(syntax_db)[0].as_syntax_node()
+            != segment
+    {
+        return None;
+    }
+
+    let name = identifier.text(syntax_db);
+    let module_id = db.find_module_containing_node(&identifier.as_syntax_node())?;
+    let importables = db.visible_importables_from_module(module_id)?;
+    let actions = importables
+        .iter()
+        .filter(|(item, _)| item.name(db.upcast()) == name)
+        .map(|(_, path)| path)
+        .unique()
+        .map(|path| CodeAction {
+            title: format!("Import {path}"),
+            kind: Some(CodeActionKind::QUICKFIX),
+            data: CodeActionData::ImportItem { uri: uri.clone(), position, path: path.clone() }
+                .into_value(),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            ..Default::default()
+        })
+        .collect();
+    Some(actions)
+}
+
+/// Create an edit importing the path into the module containing the position, placed among the
+/// existing `use` statements of the module.
+pub fn import_item_edit(
+    db: &AnalysisDatabase,
+    uri: Url,
+    position: Position,
+    path: &str,
+) -> Option<WorkspaceEdit> {
+    let file_id = db.file_for_url(&uri)?;
+    let node = db.find_syntax_node_at_position(file_id, position.to_cairo(db, file_id))?;
+    let module_id = db.find_module_containing_node(&node)?;
+    let edit = use_statement_edit(db, file_id, module_id, path)?;
+
+    Some(WorkspaceEdit {
+        changes: Some(HashMap::from_iter([(uri, vec![edit])])),
+        document_changes: None,
+        change_annotations: None,
+    })
+}
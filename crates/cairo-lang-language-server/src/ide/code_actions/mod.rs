@@ -1,23 +1,40 @@
 use cairo_lang_syntax::node::SyntaxNode;
 use lsp_types::{
-    CodeAction, CodeActionOrCommand, CodeActionParams, CodeActionResponse, Diagnostic,
-    NumberOrString,
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    Diagnostic, NumberOrString, Position, Range, Url,
 };
-use tracing::debug;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
 
+use crate::config::Config;
 use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
+use crate::lang::diagnostics::lsp_diagnostic_kind;
 use crate::lang::lsp::{LsProtoGroup, ToCairo};
 
 mod add_missing_trait;
 mod expand_macro;
+mod fill_match_arms;
+mod implement_missing_items;
+mod import_missing_item;
+mod organize_imports;
+mod remove_unused_import;
 mod rename_unused_variable;
 
 /// Compute commands for a given text document and range. These commands are typically code fixes to
 /// either fix problems or to beautify/refactor code.
-pub fn code_actions(params: CodeActionParams, db: &AnalysisDatabase) -> Option<CodeActionResponse> {
+///
+/// If `resolve_edits` is set, edits of code actions which are costly to compute are left out and
+/// computed by [`resolve`] once the user selects the action.
+pub fn code_actions(
+    params: CodeActionParams,
+    db: &AnalysisDatabase,
+    config: &Config,
+    resolve_edits: bool,
+) -> Option<CodeActionResponse> {
     let mut actions = Vec::with_capacity(params.context.diagnostics.len());
     let file_id = db.file_for_url(&params.text_document.uri)?;
-    let node = db.find_syntax_node_at_position(file_id, params.range.start.to_cairo())?;
+    let node =
+        db.find_syntax_node_at_position(file_id, params.range.start.to_cairo(db, file_id))?;
     for diagnostic in params.context.diagnostics.iter() {
         actions.extend(
             get_code_actions_for_diagnostic(db, &node, diagnostic, &params)
@@ -25,11 +42,116 @@ pub fn code_actions(params: CodeActionParams, db: &AnalysisDatabase) -> Option<C
                 .map(CodeActionOrCommand::from),
         );
     }
+    // Several diagnostics can be fixed by the same action, e.g. each missing arm of a `match`.
+    actions.dedup_by(|action, previous| match (action, previous) {
+        (CodeActionOrCommand::CodeAction(action), CodeActionOrCommand::CodeAction(previous)) => {
+            action.edit.is_some() && action.title == previous.title && action.edit == previous.edit
+        }
+        _ => false,
+    });
+    // The Quick Fix of a missing trait items diagnostic is also offered as a refactoring anywhere
+    // in the header of the impl.
+    if !actions.iter().any(|action| match action {
+        CodeActionOrCommand::CodeAction(action) => action.title == implement_missing_items::TITLE,
+        _ => false,
+    }) {
+        actions.extend(
+            implement_missing_items::implement_missing_items(
+                db,
+                node.clone(),
+                None,
+                params.text_document.uri.clone(),
+            )
+            .map(CodeActionOrCommand::from),
+        );
+    }
+    actions.extend(
+        expand_macro::expand_macro_in_place(
+            db,
+            node.clone(),
+            params.text_document.uri.clone(),
+            params.range.start,
+        )
+        .map(CodeActionOrCommand::from),
+    );
     actions.extend(expand_macro::expand_macro(db, node).into_iter().map(CodeActionOrCommand::from));
 
+    // Source actions are not shown alongside quick fixes, so compute them only when asked for.
+    if is_kind_requested(&params, &CodeActionKind::SOURCE_ORGANIZE_IMPORTS) {
+        actions.extend(
+            organize_imports::organize_imports(db, params.text_document.uri.clone(), config)
+                .map(CodeActionOrCommand::from),
+        );
+    }
+
+    if !resolve_edits {
+        actions = actions
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => {
+                    CodeActionOrCommand::CodeAction(resolve(action, db))
+                }
+                command => command,
+            })
+            .collect();
+    }
+
     Some(actions)
 }
 
+/// Compute the edit of a code action selected by the user, which was left out by
+/// [`code_actions`].
+pub fn resolve(mut action: CodeAction, db: &AnalysisDatabase) -> CodeAction {
+    if action.edit.is_some() {
+        return action;
+    }
+    let Some(data) = action.data.take() else { return action };
+    let data = match serde_json::from_value::<CodeActionData>(data) {
+        Ok(data) => data,
+        Err(err) => {
+            error!("failed to deserialize code action data: {err}");
+            return action;
+        }
+    };
+
+    action.edit = match data {
+        CodeActionData::RenameUnusedVariable { uri, range } => {
+            Some(rename_unused_variable::rename_unused_variable_edit(uri, range))
+        }
+        CodeActionData::ImportTrait { uri, position, trait_path } => {
+            add_missing_trait::import_trait_edit(db, uri, position, &trait_path)
+        }
+        CodeActionData::ImportItem { uri, position, path } => {
+            import_missing_item::import_item_edit(db, uri, position, &path)
+        }
+        CodeActionData::ExpandMacroInPlace { uri, position } => {
+            expand_macro::expand_macro_in_place_edit(db, uri, position)
+        }
+    };
+    action
+}
+
+/// Identity of a code action, stored in its data to compute its edit when it is resolved.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum CodeActionData {
+    /// Prefix the variable at the range with an `_`.
+    RenameUnusedVariable { uri: Url, range: Range },
+    /// Import the trait in the module containing the position.
+    ImportTrait { uri: Url, position: Position, trait_path: String },
+    /// Import the item at the path among the `use` statements of the module containing the
+    /// position.
+    ImportItem { uri: Url, position: Position, path: String },
+    /// Replace the macro call at the position with its expansion.
+    ExpandMacroInPlace { uri: Url, position: Position },
+}
+
+impl CodeActionData {
+    fn into_value(self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+}
+
 /// Generate code actions for a given diagnostic.
 ///
 /// # Arguments
@@ -49,19 +171,16 @@ fn get_code_actions_for_diagnostic(
     params: &CodeActionParams,
 ) -> Vec<CodeAction> {
     let code = match &diagnostic.code {
-        Some(NumberOrString::String(code)) => code,
+        Some(NumberOrString::String(code)) => Some(code.as_str()),
         Some(NumberOrString::Number(code)) => {
             debug!("diagnostic code is not a string: `{code}`");
-            return vec![];
-        }
-        None => {
-            debug!("diagnostic code is missing");
-            return vec![];
+            None
         }
+        None => None,
     };
 
-    match code.as_str() {
-        "E0001" => {
+    match (code, lsp_diagnostic_kind(diagnostic)) {
+        (Some("E0001"), _) => {
             vec![rename_unused_variable::rename_unused_variable(
                 db,
                 node,
@@ -69,10 +188,62 @@ fn get_code_actions_for_diagnostic(
                 params.text_document.uri.clone(),
             )]
         }
-        "E0002" => add_missing_trait::add_missing_trait(db, node, params.text_document.uri.clone()),
-        code => {
-            debug!("no code actions for diagnostic code: {code}");
+        (Some("E0002"), _) => {
+            add_missing_trait::add_missing_trait(db, node, params.text_document.uri.clone())
+        }
+        // The fix applies to the `match` expression the diagnostic is reported on.
+        (_, Some("missing_match_arm")) if is_at_cursor(diagnostic, params) => {
+            fill_match_arms::fill_match_arms(
+                db,
+                diagnostic.clone(),
+                params.text_document.uri.clone(),
+            )
+            .into_iter()
+            .collect()
+        }
+        (_, Some("path_not_found")) if is_at_cursor(diagnostic, params) => {
+            import_missing_item::import_missing_item(
+                db,
+                diagnostic.clone(),
+                params.text_document.uri.clone(),
+            )
+            .unwrap_or_default()
+        }
+        (_, Some("unused_import")) if is_at_cursor(diagnostic, params) => {
+            remove_unused_import::remove_unused_import(
+                db,
+                diagnostic.clone(),
+                params.text_document.uri.clone(),
+            )
+            .into_iter()
+            .collect()
+        }
+        (_, Some("missing_trait_items")) => implement_missing_items::implement_missing_items(
+            db,
+            node.clone(),
+            Some(diagnostic.clone()),
+            params.text_document.uri.clone(),
+        )
+        .into_iter()
+        .collect(),
+        (code, kind) => {
+            debug!("no code actions for diagnostic with code {code:?} and kind {kind:?}");
             vec![]
         }
     }
 }
+
+/// Checks whether the range of the diagnostic contains the position code actions are requested at.
+fn is_at_cursor(diagnostic: &Diagnostic, params: &CodeActionParams) -> bool {
+    diagnostic.range.start <= params.range.start && params.range.start <= diagnostic.range.end
+}
+
+/// Checks whether the client explicitly asked for code actions of the given kind.
+fn is_kind_requested(params: &CodeActionParams, kind: &CodeActionKind) -> bool {
+    params.context.only.as_ref().is_some_and(|only| {
+        only.iter().any(|requested| {
+            kind.as_str() == requested.as_str()
+                || kind.as_str().starts_with(&format!("{}.", requested.as_str()))
+        })
+    })
+}
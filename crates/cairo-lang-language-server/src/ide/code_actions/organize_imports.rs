@@ -0,0 +1,182 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_diagnostics::DiagnosticsBuilder;
+use cairo_lang_filesystem::ids::{FileId, FileKind, FileLongId, VirtualFile};
+use cairo_lang_formatter::get_formatted_file;
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_parser::parser::Parser;
+use cairo_lang_parser::utils::SimpleParserDatabase;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::diagnostic::SemanticDiagnosticKind;
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::ids::SyntaxStablePtrId;
+use cairo_lang_syntax::node::{TypedStablePtr, TypedSyntaxNode, ast};
+use cairo_lang_utils::{Intern, Upcast};
+use itertools::Itertools;
+use lsp_types::{CodeAction, CodeActionKind, TextEdit, Url, WorkspaceEdit};
+
+use super::remove_unused_import::remove_item;
+use crate::config::Config;
+use crate::ide::formatter::formatter_config;
+use crate::lang::db::AnalysisDatabase;
+use crate::lang::lsp::{LsProtoGroup, ToLsp};
+
+/// Create a code action that sorts, deduplicates and merges top-level `use` items of a file, and
+/// removes unused imports.
+///
+/// Returns `None` if imports are already organized.
+///
+/// The items are formatted like the whole file is by the formatting request.
+pub fn organize_imports(db: &AnalysisDatabase, uri: Url, config: &Config) -> Option<CodeAction> {
+    let syntax_db: &dyn SyntaxGroup = db.upcast();
+    let file_id = db.file_for_url(&uri)?;
+    let unused_imports = unused_imports(db, file_id);
+
+    // Items with attributes (e.g. `#[cfg(test)]`) are left untouched, as merging them with other
+    // items would change their meaning.
+    let use_items = db
+        .file_module_syntax(file_id)
+        .ok()?
+        .items(syntax_db)
+        .elements(syntax_db)
+        .into_iter()
+        .filter_map(|item| match item {
+            ast::ModuleItem::Use(item)
+                if item.attributes(syntax_db).elements(syntax_db).is_empty() =>
+            {
+                Some(item)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let first_item = use_items.first()?;
+
+    // Imported leaves grouped by visibility and the path of the module they are imported from.
+    let mut groups: BTreeSet<(String, Vec<String>, String)> = Default::default();
+    for item in &use_items {
+        let visibility = item.visibility(syntax_db).as_syntax_node().get_text_without_trivia(db);
+        let mut leaves = vec![];
+        collect_leaves(syntax_db, item.use_path(syntax_db), &mut vec![], &mut leaves);
+        for (path, leaf) in leaves {
+            if unused_imports.contains(&leaf.stable_ptr().untyped()) {
+                continue;
+            }
+            let leaf_text = leaf.as_syntax_node().get_text_without_trivia(db);
+            groups.insert((visibility.clone(), path, leaf_text));
+        }
+    }
+
+    let organized = groups
+        .into_iter()
+        .group_by(|(visibility, path, _)| (visibility.clone(), path.clone()))
+        .into_iter()
+        .map(|((visibility, path), leaves)| {
+            let visibility =
+                if visibility.is_empty() { visibility } else { format!("{visibility} ") };
+            let leaves = leaves.map(|(_, _, leaf)| leaf).collect::<Vec<_>>();
+            match leaves.as_slice() {
+                // Imports of crates (e.g. `use a;`) cannot be merged.
+                leaves if path.is_empty() => {
+                    leaves.iter().map(|leaf| format!("{visibility}use {leaf};")).join("\n")
+                }
+                [leaf] => format!("{visibility}use {}::{leaf};", path.join("::")),
+                leaves => {
+                    format!("{visibility}use {}::{{{}}};", path.join("::"), leaves.join(", "))
+                }
+            }
+        })
+        .join("\n");
+    let organized = format_use_items(&organized, config);
+
+    let original =
+        use_items.iter().map(|item| item.as_syntax_node().get_text_without_trivia(db)).join("\n");
+    if organized == original {
+        return None;
+    }
+
+    // Only the text of the items is replaced, so that comments attached to them are kept.
+    let mut edits = vec![if organized.is_empty() {
+        remove_item(db, file_id, &first_item.as_syntax_node())?
+    } else {
+        let span = first_item.as_syntax_node().span_without_trivia(syntax_db);
+        TextEdit {
+            range: span.position_in_file(db.upcast(), file_id)?.to_lsp(db, file_id),
+            new_text: organized,
+        }
+    }];
+    for item in &use_items[1..] {
+        edits.push(remove_item(db, file_id, &item.as_syntax_node())?);
+    }
+
+    Some(CodeAction {
+        title: "Organize imports".to_owned(),
+        kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(uri, edits)])),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        ..Default::default()
+    })
+}
+
+/// Collects use path leaves of imports reported as unused in any module defined in the file.
+fn unused_imports(db: &AnalysisDatabase, file_id: FileId) -> HashSet<SyntaxStablePtrId> {
+    db.file_modules(file_id)
+        .ok()
+        .into_iter()
+        .flat_map(|modules| modules.iter().copied().collect::<Vec<_>>())
+        .filter_map(|module_id| db.module_semantic_diagnostics(module_id).ok())
+        .flat_map(|diagnostics| diagnostics.get_all())
+        .filter_map(|diagnostic| match diagnostic.kind {
+            SemanticDiagnosticKind::UnusedImport(use_id) => {
+                Some(use_id.stable_ptr(db.upcast()).untyped())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Flattens a use path into a list of leaves, each with the path of the module it is imported
+/// from.
+fn collect_leaves(
+    db: &dyn SyntaxGroup,
+    use_path: ast::UsePath,
+    prefix: &mut Vec<String>,
+    leaves: &mut Vec<(Vec<String>, ast::UsePathLeaf)>,
+) {
+    match use_path {
+        ast::UsePath::Leaf(leaf) => leaves.push((prefix.clone(), leaf)),
+        ast::UsePath::Single(single) => {
+            prefix.push(single.ident(db).as_syntax_node().get_text_without_trivia(db));
+            collect_leaves(db, single.use_path(db), prefix, leaves);
+            prefix.pop();
+        }
+        ast::UsePath::Multi(multi) => {
+            for use_path in multi.use_paths(db).elements(db) {
+                collect_leaves(db, use_path, prefix, leaves);
+            }
+        }
+    }
+}
+
+/// Formats use items with the Cairo formatter, so that they are sorted in the same order as
+/// the formatter sorts module level items.
+fn format_use_items(use_items: &str, config: &Config) -> String {
+    let db = &SimpleParserDatabase::default();
+    let virtual_file = FileLongId::Virtual(VirtualFile {
+        parent: Default::default(),
+        name: Default::default(),
+        content: Default::default(),
+        code_mappings: Default::default(),
+        kind: FileKind::Module,
+    })
+    .intern(db);
+    let syntax_root =
+        Parser::parse_file(db, &mut DiagnosticsBuilder::default(), virtual_file, use_items)
+            .as_syntax_node();
+
+    let formatter_config = formatter_config(config).sort_module_level_items(true);
+    get_formatted_file(db, &syntax_root, formatter_config).trim_end().to_owned()
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_filesystem::span::TextSpan;
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::{CodeAction, CodeActionKind, Diagnostic, TextEdit, Url, WorkspaceEdit};
+
+use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Create a Quick Fix code action removing an unused import, given an unused import diagnostic.
+///
+/// If the import is one of the items of a `{}` group, only the item is removed, otherwise the whole
+/// `use` item is.
+pub fn remove_unused_import(
+    db: &AnalysisDatabase,
+    diagnostic: Diagnostic,
+    uri: Url,
+) -> Option<CodeAction> {
+    let file_id = db.file_for_url(&uri)?;
+    let node =
+        db.find_syntax_node_at_position(file_id, diagnostic.range.start.to_cairo(db, file_id))?;
+    let leaf = db.first_ancestor_of_kind(node, SyntaxKind::UsePathLeaf)?;
+    let edit = remove_use_path(db, file_id, leaf)?;
+
+    Some(CodeAction {
+        title: "Remove unused import".to_owned(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(uri, vec![edit])])),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        ..Default::default()
+    })
+}
+
+/// Returns an edit removing the use path from its parent, removing the parent as well if nothing
+/// would be left of it.
+fn remove_use_path(db: &AnalysisDatabase, file_id: FileId, path: SyntaxNode) -> Option<TextEdit> {
+    let syntax_db: &dyn SyntaxGroup = db.upcast();
+    let parent = path.parent()?;
+    match parent.kind(syntax_db) {
+        SyntaxKind::ItemUse => remove_item(db, file_id, &parent),
+        SyntaxKind::UsePathSingle => remove_use_path(db, file_id, parent),
+        SyntaxKind::UsePathList => {
+            let multi = parent.parent()?;
+            let list = ast::UsePathList::from_syntax_node(syntax_db, parent.clone());
+            match list.elements(syntax_db).as_slice() {
+                [_] => remove_use_path(db, file_id, multi),
+                // Collapse the braces around the remaining item.
+                [first, second] => {
+                    let remaining = if first.as_syntax_node() == path { second } else { first };
+                    let span = multi.span_without_trivia(syntax_db);
+                    Some(TextEdit {
+                        range: span.position_in_file(db.upcast(), file_id)?.to_lsp(db, file_id),
+                        new_text: remaining.as_syntax_node().get_text_without_trivia(syntax_db),
+                    })
+                }
+                _ => {
+                    // Elements are interleaved with separators.
+                    let children = db.get_children(parent);
+                    let index = children.iter().position(|child| *child == path)?;
+                    let span = match (children.get(index + 1), children.get(index + 2)) {
+                        // Remove the element together with the separator following it.
+                        (_, Some(next)) => TextSpan {
+                            start: path.span_start_without_trivia(syntax_db),
+                            end: next.span_start_without_trivia(syntax_db),
+                        },
+                        // Keep the trailing separator of the list.
+                        (Some(separator), None) => TextSpan {
+                            start: children[index - 2].span_end_without_trivia(syntax_db),
+                            end: separator.span_end_without_trivia(syntax_db),
+                        },
+                        // Remove the last element together with the separator preceding it.
+                        (None, None) => TextSpan {
+                            start: children[index - 2].span_end_without_trivia(syntax_db),
+                            end: path.span_end_without_trivia(syntax_db),
+                        },
+                    };
+                    Some(TextEdit {
+                        range: span.position_in_file(db.upcast(), file_id)?.to_lsp(db, file_id),
+                        new_text: String::new(),
+                    })
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns an edit removing the whole `use` item, along with its line if nothing else is on it.
+/// Comments preceding the item are kept.
+pub(crate) fn remove_item(
+    db: &AnalysisDatabase,
+    file_id: FileId,
+    item: &SyntaxNode,
+) -> Option<TextEdit> {
+    let syntax_db = db.upcast();
+    let span = TextSpan {
+        start: item.span_start_without_trivia(syntax_db),
+        // Include the trailing trivia, which ends with the newline after the item.
+        end: item.span(syntax_db).end,
+    };
+    let mut range = span.position_in_file(db.upcast(), file_id)?;
+    let content = db.file_content(file_id)?;
+    let line = content.lines().nth(range.start.line)?;
+    if line.chars().take(range.start.col).all(char::is_whitespace) {
+        range.start.col = 0;
+    }
+    Some(TextEdit { range: range.to_lsp(db, file_id), new_text: String::new() })
+}
@@ -2,8 +2,9 @@ use std::collections::HashMap;
 
 use cairo_lang_syntax::node::SyntaxNode;
 use cairo_lang_utils::Upcast;
-use lsp_types::{CodeAction, Diagnostic, TextEdit, Url, WorkspaceEdit};
+use lsp_types::{CodeAction, Diagnostic, Range, TextEdit, Url, WorkspaceEdit};
 
+use crate::ide::code_actions::CodeActionData;
 use crate::lang::db::AnalysisDatabase;
 
 /// Create a code action that prefixes an unused variable with an `_`.
@@ -15,18 +16,23 @@ pub fn rename_unused_variable(
 ) -> CodeAction {
     CodeAction {
         title: format!("Rename to `_{}`", node.get_text(db.upcast())),
-        edit: Some(WorkspaceEdit {
-            changes: Some(HashMap::from_iter([(
-                uri,
-                // The diagnostic range is just the first char of the variable name, so we can just
-                // pass an underscore as the new text it won't replace the current variable name,
-                // and it will prefix it with `_`
-                vec![TextEdit { range: diagnostic.range, new_text: "_".to_owned() }],
-            )])),
-            document_changes: None,
-            change_annotations: None,
-        }),
+        data: CodeActionData::RenameUnusedVariable { uri, range: diagnostic.range }.into_value(),
         diagnostics: Some(vec![diagnostic]),
         ..Default::default()
     }
 }
+
+/// Create an edit prefixing the variable at the range with an `_`.
+pub fn rename_unused_variable_edit(uri: Url, range: Range) -> WorkspaceEdit {
+    WorkspaceEdit {
+        changes: Some(HashMap::from_iter([(
+            uri,
+            // The diagnostic range is just the first char of the variable name, so we can just
+            // pass an underscore as the new text it won't replace the current variable name,
+            // and it will prefix it with `_`
+            vec![TextEdit { range, new_text: "_".to_owned() }],
+        )])),
+        document_changes: None,
+        change_annotations: None,
+    }
+}
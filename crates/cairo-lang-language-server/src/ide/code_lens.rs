@@ -0,0 +1,95 @@
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::TopLevelLanguageElementId;
+use cairo_lang_filesystem::ids::CrateId;
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_syntax::node::helpers::QueryAttrs;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{Terminal, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::{CodeLens, CodeLensParams, Command};
+use serde_json::json;
+
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup};
+use crate::lang::lsp::{LsProtoGroup, ToLsp};
+
+#[cfg(test)]
+#[path = "code_lens_test.rs"]
+mod test;
+
+/// Compute code lenses running each `#[test]` function of a file.
+///
+/// Tests are run with `scarb test` for the manifest the crate of the file was loaded from, so
+/// lenses are only provided for crates of Scarb workspace members.
+/// Scarb runs all tests whose paths contain the filter, so tests whose path is a part of the path
+/// of another test of the crate cannot be run alone, and get no lens.
+pub fn code_lens(params: CodeLensParams, db: &AnalysisDatabase) -> Option<Vec<CodeLens>> {
+    let syntax_db = db.upcast();
+    let file = db.file_for_url(&params.text_document.uri)?;
+    let crate_id = db.file_modules(file).ok()?.first()?.owning_crate(db.upcast());
+    let Some(manifest_path) = db.scarb_manifest_path(crate_id) else {
+        return Some(vec![]);
+    };
+    let root = db.file_syntax(file).ok()?;
+    let crate_test_paths = crate_test_paths(db, crate_id);
+
+    let mut lenses = vec![];
+    for node in root.descendants(syntax_db) {
+        // Only free functions can be tests, so skip functions in traits and impls.
+        if node.kind(syntax_db) != SyntaxKind::FunctionWithBody
+            || node.parent().map(|parent| parent.kind(syntax_db))
+                != Some(SyntaxKind::ModuleItemList)
+        {
+            continue;
+        }
+        let function = ast::FunctionWithBody::from_syntax_node(syntax_db, node.clone());
+        if !function.has_attr(syntax_db, "test") {
+            continue;
+        }
+
+        let Some(module_id) = db.find_module_containing_node(&node) else { continue };
+        let name = function.declaration(syntax_db).name(syntax_db);
+        let test_path = format!("{}::{}", module_id.full_path(db.upcast()), name.text(syntax_db));
+        if crate_test_paths.iter().any(|path| *path != test_path && path.contains(&test_path)) {
+            continue;
+        }
+        let Some(range) = name
+            .as_syntax_node()
+            .span_without_trivia(syntax_db)
+            .position_in_file(db.upcast(), file)
+        else {
+            continue;
+        };
+
+        lenses.push(CodeLens {
+            range: range.to_lsp(db, file),
+            command: Some(Command {
+                title: "▶ Run test".to_string(),
+                command: "cairo.runTest".to_string(),
+                arguments: Some(vec![json!({
+                    "testPath": test_path,
+                    "manifestPath": manifest_path,
+                })]),
+            }),
+            data: None,
+        });
+    }
+
+    Some(lenses)
+}
+
+/// Returns full paths of all `#[test]` functions of the crate.
+fn crate_test_paths(db: &AnalysisDatabase, crate_id: CrateId) -> Vec<String> {
+    let defs_db: &dyn DefsGroup = db.upcast();
+    defs_db
+        .crate_modules(crate_id)
+        .iter()
+        .filter_map(|module_id| defs_db.module_free_functions(*module_id).ok())
+        .flat_map(|functions| {
+            functions
+                .iter()
+                .filter(|(_, function)| function.has_attr(db.upcast(), "test"))
+                .map(|(function_id, _)| function_id.full_path(defs_db))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
@@ -0,0 +1,72 @@
+use assert_fs::TempDir;
+use assert_fs::prelude::*;
+use cairo_lang_filesystem::db::CrateSettings;
+use indoc::indoc;
+use lsp_types::{CodeLensParams, TextDocumentIdentifier, Url};
+use serde_json::json;
+
+use super::code_lens;
+use crate::Tricks;
+use crate::lang::db::AnalysisDatabase;
+use crate::lang::lsp::PositionEncoding;
+use crate::project::Crate;
+
+const CAIRO: &str = indoc! {r#"
+    #[test]
+    fn it_works() {}
+
+    #[test]
+    fn it_works_too() {}
+
+    mod tests {
+        #[test]
+        fn test_add() {}
+    }
+"#};
+
+/// Returns lines of lenses of [`CAIRO`] along with paths of tests they run.
+fn lenses(manifest_path: Option<&str>) -> Vec<(u32, String)> {
+    let t = TempDir::new().unwrap();
+    let lib = t.child("src/lib.cairo");
+    lib.write_str(CAIRO).unwrap();
+
+    let mut db = AnalysisDatabase::new(&Tricks::default(), PositionEncoding::default());
+    Crate {
+        name: "hello".into(),
+        discriminator: Some("hello".into()),
+        root: t.child("src").to_path_buf(),
+        custom_main_file_stems: None,
+        settings: CrateSettings { name: Some("hello".into()), ..Default::default() },
+        manifest_path: manifest_path.map(|path| t.child(path).to_path_buf()),
+    }
+    .apply(&mut db);
+
+    let params = CodeLensParams {
+        text_document: TextDocumentIdentifier { uri: Url::from_file_path(lib.path()).unwrap() },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    code_lens(params, &db)
+        .unwrap()
+        .into_iter()
+        .map(|lens| {
+            let arguments = lens.command.unwrap().arguments.unwrap();
+            assert_eq!(arguments[0]["manifestPath"], json!(t.child("Scarb.toml").path()));
+            (lens.range.start.line, arguments[0]["testPath"].as_str().unwrap().to_owned())
+        })
+        .collect()
+}
+
+#[test]
+fn lenses_skip_tests_whose_path_matches_other_tests() {
+    // `hello::it_works` also matches `hello::it_works_too`, so it cannot be run alone.
+    assert_eq!(
+        lenses(Some("Scarb.toml")),
+        [(4, "hello::it_works_too".to_owned()), (8, "hello::tests::test_add".to_owned()),]
+    );
+}
+
+#[test]
+fn no_lenses_for_crates_not_loaded_from_scarb() {
+    assert_eq!(lenses(None), []);
+}
@@ -1,12 +1,12 @@
 use cairo_lang_defs::db::DefsGroup;
 use cairo_lang_defs::ids::{
-    LanguageElementId, LookupItemId, ModuleFileId, ModuleId, NamedLanguageElementId,
-    TopLevelLanguageElementId, TraitFunctionId,
+    LanguageElementId, LookupItemId, ModuleFileId, ModuleId, ModuleItemId, NamedLanguageElementId,
+    TraitFunctionId,
 };
 use cairo_lang_filesystem::db::FilesGroup;
 use cairo_lang_filesystem::ids::FileId;
-use cairo_lang_filesystem::span::TextOffset;
-use cairo_lang_semantic::corelib::{core_submodule, get_submodule};
+use cairo_lang_filesystem::span::{TextOffset, TextPosition};
+use cairo_lang_semantic::corelib::core_bool_ty;
 use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_semantic::diagnostic::{NotFoundItemType, SemanticDiagnostics};
 use cairo_lang_semantic::expr::inference::InferenceId;
@@ -15,42 +15,34 @@ use cairo_lang_semantic::items::us::SemanticUseEx;
 use cairo_lang_semantic::lookup_item::{HasResolverData, LookupItemEx};
 use cairo_lang_semantic::resolve::{ResolvedConcreteItem, ResolvedGenericItem, Resolver};
 use cairo_lang_semantic::types::peel_snapshots;
-use cairo_lang_semantic::{ConcreteTypeId, Pattern, TypeLongId};
+use cairo_lang_semantic::{ConcreteTypeId, Pattern, TypeId, TypeLongId};
 use cairo_lang_syntax::node::ast::PathSegment;
-use cairo_lang_syntax::node::{TypedStablePtr, TypedSyntaxNode, ast};
+use cairo_lang_syntax::node::{SyntaxNode, TypedStablePtr, TypedSyntaxNode, ast};
 use cairo_lang_utils::{LookupIntern, Upcast};
-use lsp_types::{CompletionItem, CompletionItemKind, InsertTextFormat, Position, Range, TextEdit};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionTextEdit,
+    InsertTextFormat, InsertTextMode, Position, Range, TextEdit,
+};
+use smol_str::SmolStr;
 use tracing::debug;
 
-use crate::ide::utils::find_methods_for_type;
+use crate::ide::completion::{CompletionImport, item_data, item_data_with_import};
+use crate::ide::utils::{
+    find_methods_for_type, matched_enum, pattern_variant_name, prelude_module, use_statement_edit,
+};
 use crate::lang::db::{AnalysisDatabase, LsSemanticGroup};
-use crate::lang::lsp::ToLsp;
+use crate::lang::lsp::{LsProtoGroup, ToLsp};
 
 pub fn generic_completions(
     db: &AnalysisDatabase,
     module_file_id: ModuleFileId,
     lookup_items: Vec<LookupItemId>,
 ) -> Vec<CompletionItem> {
-    let mut completions = vec![];
-
-    // Crates.
-    completions.extend(db.crate_configs().keys().map(|crate_id| CompletionItem {
-        label: crate_id.lookup_intern(db).name().into(),
-        kind: Some(CompletionItemKind::MODULE),
-        ..CompletionItem::default()
-    }));
+    let mut completions = crate_completions(db);
 
     // Module completions.
     if let Ok(module_items) = db.module_items(module_file_id.0) {
-        completions.extend(module_items.iter().map(|item| {
-            CompletionItem {
-                label: item.name(db.upcast()).to_string(),
-                kind: ResolvedGenericItem::from_module_item(db, *item)
-                    .ok()
-                    .map(resolved_generic_item_completion_kind),
-                ..CompletionItem::default()
-            }
-        }));
+        completions.extend(module_items.iter().map(|item| module_item_completion(db, *item)));
     }
 
     // Local variables and params.
@@ -86,6 +78,188 @@ pub fn generic_completions(
     completions
 }
 
+/// Returns completions of a segment of a `use` path, preceded by the given segments.
+///
+/// These are the children of the module, trait, or enum the preceding segments resolve to, or
+/// names of crates and items of the current module if the completed segment is the first one.
+/// The `use` item being completed is not completed itself.
+pub fn use_completions(
+    db: &AnalysisDatabase,
+    module_file_id: ModuleFileId,
+    lookup_items: Vec<LookupItemId>,
+    segments: Vec<PathSegment>,
+) -> Vec<CompletionItem> {
+    if !segments.is_empty() {
+        // `use` paths are resolved in the context of the module, regardless of the lookup item.
+        return colon_colon_completions(db, module_file_id, vec![], segments).unwrap_or_default();
+    }
+
+    let mut completions = crate_completions(db);
+    if let Ok(module_items) = db.module_items(module_file_id.0) {
+        completions.extend(
+            module_items
+                .iter()
+                .filter(|item| !lookup_items.contains(&LookupItemId::ModuleItem(**item)))
+                .map(|item| module_item_completion(db, *item)),
+        );
+    }
+    completions
+}
+
+/// Returns completions of names of all crates.
+fn crate_completions(db: &AnalysisDatabase) -> Vec<CompletionItem> {
+    db.crate_configs()
+        .keys()
+        .map(|crate_id| CompletionItem {
+            label: crate_id.lookup_intern(db).name().into(),
+            kind: Some(CompletionItemKind::MODULE),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+/// Returns a completion item for an item of a module.
+fn module_item_completion(db: &AnalysisDatabase, item: ModuleItemId) -> CompletionItem {
+    CompletionItem {
+        label: item.name(db.upcast()).to_string(),
+        kind: ResolvedGenericItem::from_module_item(db, item)
+            .ok()
+            .map(resolved_generic_item_completion_kind),
+        data: item_data(db, item.untyped_stable_ptr(db.upcast())),
+        ..CompletionItem::default()
+    }
+}
+
+/// Returns completions of the items whose names start with `prefix`, which are not in scope in
+/// the module, but can be imported into it from any crate.
+///
+/// Each completion carries an edit adding the `use` statement importing the item, placed among
+/// the existing `use` statements of the module so that they stay sorted alphabetically.
+/// If `resolve_edits` is set, the edit is computed only when the chosen item is resolved.
+pub fn auto_import_completions(
+    db: &AnalysisDatabase,
+    file_id: FileId,
+    module_file_id: ModuleFileId,
+    position: TextPosition,
+    prefix: &str,
+    resolve_edits: bool,
+) -> Option<Vec<CompletionItem>> {
+    let module_id = module_file_id.0;
+    let prelude_module = prelude_module(db, module_id)?;
+    let prefix = prefix.to_lowercase();
+    let is_in_scope = |name: &SmolStr| {
+        [module_id, prelude_module]
+            .into_iter()
+            .any(|module_id| matches!(db.module_item_by_name(module_id, name.clone()), Ok(Some(_))))
+    };
+
+    let uri = db.url_for_file(file_id)?;
+    let position = position.to_lsp(db, file_id);
+
+    let importables = db.visible_importables_from_module(module_id)?;
+    let completions = importables
+        .iter()
+        .filter_map(|(item, path)| {
+            let name = item.name(db.upcast());
+            if !name.to_lowercase().starts_with(&prefix) || is_in_scope(&name) {
+                return None;
+            }
+            let definition = item.untyped_stable_ptr(db.upcast());
+            let (additional_text_edits, data) = if resolve_edits {
+                let import = CompletionImport { uri: uri.clone(), position, path: path.clone() };
+                (None, Some(item_data_with_import(db, definition, Some(import))?))
+            } else {
+                let edit = use_statement_edit(db, file_id, module_id, path)?;
+                (Some(vec![edit]), item_data(db, definition))
+            };
+            Some(CompletionItem {
+                label: name.to_string(),
+                label_details: Some(CompletionItemLabelDetails {
+                    detail: None,
+                    description: Some(path.clone()),
+                }),
+                kind: ResolvedGenericItem::from_module_item(db, *item)
+                    .ok()
+                    .map(resolved_generic_item_completion_kind),
+                additional_text_edits,
+                data,
+                ..CompletionItem::default()
+            })
+        })
+        .collect();
+    Some(completions)
+}
+
+/// The syntactic context of a completion, determining which keywords can be typed there.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum KeywordContext {
+    /// Module scope, or a body of a trait or an impl.
+    Item,
+    /// A function body.
+    Statement,
+    /// A name of a binding in a pattern or a parameter.
+    Pattern,
+}
+
+/// Keywords starting items.
+const ITEM_KEYWORDS: [&str; 11] =
+    ["const", "enum", "extern", "fn", "impl", "mod", "pub", "struct", "trait", "type", "use"];
+
+/// Keywords starting statements and expressions, other than control flow constructs.
+const STATEMENT_KEYWORDS: [&str; 6] = ["break", "continue", "false", "let", "return", "true"];
+
+/// Modifiers of bindings.
+const PATTERN_KEYWORDS: [&str; 2] = ["mut", "ref"];
+
+/// Control flow constructs, along with snippets expanding them.
+const CONTROL_FLOW_SNIPPETS: [(&str, &str); 4] = [
+    ("if", "if $1 {\n    $0\n}"),
+    ("loop", "loop {\n    $0\n}"),
+    ("match", "match $1 {\n    $2 => $0,\n}"),
+    ("while", "while $1 {\n    $0\n}"),
+];
+
+/// Returns completions of the keywords which can be typed in the given context.
+///
+/// In function bodies, control flow constructs are completed as snippets if the client supports
+/// them, and as plain keywords otherwise.
+pub fn keyword_completions(context: KeywordContext, snippet_support: bool) -> Vec<CompletionItem> {
+    let keywords: &[&str] = match context {
+        KeywordContext::Item => &ITEM_KEYWORDS,
+        KeywordContext::Statement => &STATEMENT_KEYWORDS,
+        KeywordContext::Pattern => &PATTERN_KEYWORDS,
+    };
+    let mut completions = keywords
+        .iter()
+        .map(|keyword| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            ..CompletionItem::default()
+        })
+        .collect::<Vec<_>>();
+
+    if context == KeywordContext::Statement {
+        completions.extend(CONTROL_FLOW_SNIPPETS.iter().map(|(keyword, snippet)| {
+            if snippet_support {
+                CompletionItem {
+                    label: keyword.to_string(),
+                    kind: Some(CompletionItemKind::SNIPPET),
+                    insert_text: Some(snippet.to_string()),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    ..CompletionItem::default()
+                }
+            } else {
+                CompletionItem {
+                    label: keyword.to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    ..CompletionItem::default()
+                }
+            }
+        }));
+    }
+    completions
+}
+
 fn resolved_generic_item_completion_kind(item: ResolvedGenericItem) -> CompletionItemKind {
     match item {
         ResolvedGenericItem::GenericConstant(_) => CompletionItemKind::CONSTANT,
@@ -130,21 +304,16 @@ pub fn colon_colon_completions(
             .module_items(module_id)
             .ok()?
             .iter()
-            .map(|item| CompletionItem {
-                label: item.name(db.upcast()).to_string(),
-                kind: ResolvedGenericItem::from_module_item(db, *item)
-                    .ok()
-                    .map(resolved_generic_item_completion_kind),
-                ..CompletionItem::default()
-            })
+            .map(|item| module_item_completion(db, *item))
             .collect(),
         ResolvedConcreteItem::Trait(item) => db
             .trait_functions(item.trait_id(db))
             .unwrap_or_default()
             .iter()
-            .map(|(name, _)| CompletionItem {
+            .map(|(name, function_id)| CompletionItem {
                 label: name.to_string(),
                 kind: Some(CompletionItemKind::FUNCTION),
+                data: item_data(db, function_id.untyped_stable_ptr(db.upcast())),
                 ..CompletionItem::default()
             })
             .collect(),
@@ -154,9 +323,10 @@ pub fn colon_colon_completions(
                 db.trait_functions(trait_id.trait_id(db))
                     .unwrap_or_default()
                     .iter()
-                    .map(|(name, _)| CompletionItem {
+                    .map(|(name, function_id)| CompletionItem {
                         label: name.to_string(),
                         kind: Some(CompletionItemKind::FUNCTION),
+                        data: item_data(db, function_id.untyped_stable_ptr(db.upcast())),
                         ..CompletionItem::default()
                     })
                     .collect()
@@ -167,9 +337,10 @@ pub fn colon_colon_completions(
                 .enum_variants(enum_id.enum_id(db))
                 .unwrap_or_default()
                 .iter()
-                .map(|(name, _)| CompletionItem {
+                .map(|(name, variant_id)| CompletionItem {
                     label: name.to_string(),
                     kind: Some(CompletionItemKind::ENUM_MEMBER),
+                    data: item_data(db, variant_id.untyped_stable_ptr(db.upcast())),
                     ..CompletionItem::default()
                 })
                 .collect(),
@@ -179,11 +350,76 @@ pub fn colon_colon_completions(
     })
 }
 
+/// Returns completions of the variants of the enum matched by the `match` expression, as patterns
+/// of its arms.
+///
+/// Variants already covered by other arms are left out. Variants carrying data are completed with
+/// a placeholder binding, as a snippet if the client supports them.
+pub fn match_arm_completions(
+    db: &AnalysisDatabase,
+    lookup_items: Vec<LookupItemId>,
+    node: &SyntaxNode,
+    match_expr: ast::ExprMatch,
+    snippet_support: bool,
+) -> Option<Vec<CompletionItem>> {
+    let syntax_db = db.upcast();
+    let function_with_body = lookup_items.into_iter().next()?.function_with_body()?;
+    let Some(concrete_enum_id) = matched_enum(db, function_with_body, &match_expr) else {
+        debug!("matched type is not an enum");
+        return None;
+    };
+    let enum_id = concrete_enum_id.enum_id(db);
+    let enum_name = enum_id.name(db.upcast());
+
+    // The pattern being typed does not cover any variant yet.
+    let is_completed = |pattern: &ast::Pattern| {
+        let pattern = pattern.as_syntax_node();
+        std::iter::successors(Some(node.clone()), SyntaxNode::parent).any(|node| node == pattern)
+    };
+    let covered = match_expr
+        .arms(syntax_db)
+        .elements(syntax_db)
+        .into_iter()
+        .flat_map(|arm| arm.patterns(syntax_db).elements(syntax_db))
+        .filter(|pattern| !is_completed(pattern))
+        .filter_map(|pattern| pattern_variant_name(syntax_db, &pattern))
+        .collect::<Vec<_>>();
+
+    let completions = db
+        .enum_variants(enum_id)
+        .ok()?
+        .iter()
+        .filter(|(name, _)| !covered.contains(name))
+        .map(|(name, variant_id)| {
+            let has_data = db
+                .variant_semantic(enum_id, *variant_id)
+                .is_ok_and(|variant| !variant.ty.is_unit(db));
+            let path = format!("{enum_name}::{name}");
+            let insert_text = match (has_data, snippet_support) {
+                (false, _) => path.clone(),
+                (true, true) => format!("{path}(${{1:{}}})", name.to_lowercase()),
+                (true, false) => format!("{path}(_)"),
+            };
+            CompletionItem {
+                label: path,
+                kind: Some(CompletionItemKind::ENUM_MEMBER),
+                insert_text: Some(insert_text),
+                insert_text_format: (has_data && snippet_support)
+                    .then_some(InsertTextFormat::SNIPPET),
+                data: item_data(db, variant_id.untyped_stable_ptr(db.upcast())),
+                ..CompletionItem::default()
+            }
+        })
+        .collect();
+    Some(completions)
+}
+
 pub fn dot_completions(
     db: &AnalysisDatabase,
     file_id: FileId,
     lookup_items: Vec<LookupItemId>,
     expr: ast::ExprBinary,
+    snippet_support: bool,
 ) -> Option<Vec<CompletionItem>> {
     let syntax_db = db.upcast();
     // Get a resolver in the current context.
@@ -197,7 +433,12 @@ pub fn dot_completions(
     );
 
     // Extract lhs node.
-    let node = expr.lhs(syntax_db);
+    // Parenthesized expressions are not present in the semantic model, so the expression inside
+    // is looked up instead.
+    let mut node = expr.lhs(syntax_db);
+    while let ast::Expr::Parenthesized(parenthesized) = node {
+        node = parenthesized.expr(syntax_db);
+    }
     let stable_ptr = node.stable_ptr().untyped();
     // Get its semantic model.
     let expr_id = db.lookup_expr_by_ptr(function_with_body, node.stable_ptr()).ok()?;
@@ -222,7 +463,7 @@ pub fn dot_completions(
     } else {
         TextOffset::default()
     };
-    let position = offset.position_in_file(db.upcast(), file_id).unwrap().to_lsp();
+    let position = offset.position_in_file(db.upcast(), file_id).unwrap().to_lsp(db, file_id);
     let relevant_methods = find_methods_for_type(db, resolver, ty, stable_ptr);
 
     let mut completions = Vec::new();
@@ -234,22 +475,85 @@ pub fn dot_completions(
         completions.push(completion);
     }
 
-    // Find members of the type.
+    // Find members of the type, accessible through snapshots as well.
     let (_, long_ty) = peel_snapshots(db, ty);
     if let TypeLongId::Concrete(ConcreteTypeId::Struct(concrete_struct_id)) = long_ty {
         db.concrete_struct_members(concrete_struct_id).ok()?.iter().for_each(|(name, member)| {
             let completion = CompletionItem {
                 label: name.to_string(),
-                detail: Some(member.ty.format(db.upcast())),
                 kind: Some(CompletionItemKind::FIELD),
+                detail: Some(member.ty.format(db)),
+                data: item_data(db, member.id.untyped_stable_ptr(db.upcast())),
                 ..CompletionItem::default()
             };
             completions.push(completion);
         });
     }
+
+    completions.extend(postfix_completions(db, file_id, &expr, ty, snippet_support));
     Some(completions)
 }
 
+/// Returns postfix completions for the receiver of a member access expression, e.g. `x.match`,
+/// which replace the whole expression with the expanded template: `if`, `match` and `ref`.
+///
+/// The `if` template is only completed for receivers of the `bool` type.
+fn postfix_completions(
+    db: &AnalysisDatabase,
+    file_id: FileId,
+    expr: &ast::ExprBinary,
+    ty: TypeId,
+    snippet_support: bool,
+) -> Vec<CompletionItem> {
+    let syntax_db = db.upcast();
+    let receiver = expr.lhs(syntax_db).as_syntax_node().get_text_without_trivia(syntax_db);
+    let Some(range) = expr
+        .as_syntax_node()
+        .span_without_trivia(syntax_db)
+        .position_in_file(db.upcast(), file_id)
+        .map(|span| span.to_lsp(db, file_id))
+    else {
+        return vec![];
+    };
+
+    // Characters with a special meaning in snippets must be escaped.
+    let receiver_text = if snippet_support {
+        receiver.replace('\\', "\\\\").replace('$', "\\$").replace('}', "\\}")
+    } else {
+        receiver.clone()
+    };
+    // The templates are indented like the line of the receiver.
+    let indent = db
+        .file_content(file_id)
+        .and_then(|content| {
+            let line = content.lines().nth(range.start.line as usize)?;
+            Some(line[..line.len() - line.trim_start().len()].to_string())
+        })
+        .unwrap_or_default();
+    let body = if snippet_support { format!("\n{indent}    $0") } else { String::new() };
+    let is_bool = ty == core_bool_ty(db);
+    let templates = [
+        ("if", is_bool.then(|| format!("if {receiver_text} {{{body}\n{indent}}}"))),
+        ("match", Some(format!("match {receiver_text} {{{body}\n{indent}}}"))),
+        ("ref", Some(format!("ref {receiver_text}"))),
+    ];
+
+    templates
+        .into_iter()
+        .filter_map(|(keyword, new_text)| {
+            Some(CompletionItem {
+                label: keyword.to_string(),
+                kind: Some(CompletionItemKind::SNIPPET),
+                filter_text: Some(format!("{receiver}.{keyword}")),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: new_text? })),
+                insert_text_format: snippet_support.then_some(InsertTextFormat::SNIPPET),
+                insert_text_mode: Some(InsertTextMode::AS_IS),
+                ..CompletionItem::default()
+            })
+        })
+        .collect()
+}
+
 /// Returns a completion item for a method.
 pub fn completion_for_method(
     db: &AnalysisDatabase,
@@ -261,8 +565,6 @@ pub fn completion_for_method(
     let name = trait_function.name(db.upcast());
     db.trait_function_signature(trait_function).ok()?;
 
-    // TODO(spapini): Add signature.
-    let detail = trait_id.full_path(db.upcast());
     let mut additional_text_edits = vec![];
 
     // If the trait is not in scope, add a use statement.
@@ -279,9 +581,9 @@ pub fn completion_for_method(
         label: format!("{}()", name),
         insert_text: Some(format!("{}($0)", name)),
         insert_text_format: Some(InsertTextFormat::SNIPPET),
-        detail: Some(detail),
         kind: Some(CompletionItemKind::METHOD),
         additional_text_edits: Some(additional_text_edits),
+        data: item_data(db, trait_function.untyped_stable_ptr(db.upcast())),
         ..CompletionItem::default()
     };
     Some(completion)
@@ -296,19 +598,7 @@ fn module_has_trait(
     if db.module_traits_ids(module_id).ok()?.contains(&trait_id) {
         return Some(true);
     }
-    let mut current_top_module = module_id;
-    while let ModuleId::Submodule(submodule_id) = current_top_module {
-        current_top_module = submodule_id.parent_module(db.upcast());
-    }
-    let crate_id = match current_top_module {
-        ModuleId::CrateRoot(crate_id) => crate_id,
-        ModuleId::Submodule(_) => unreachable!("current module is not a top-level module"),
-    };
-    let edition =
-        db.crate_config(crate_id).map(|config| config.settings.edition).unwrap_or_default();
-    let prelude_submodule_name = edition.prelude_submodule_name();
-    let core_prelude_submodule = core_submodule(db, "prelude");
-    let prelude_submodule = get_submodule(db, core_prelude_submodule, prelude_submodule_name)?;
+    let prelude_submodule = prelude_module(db, module_id)?;
     for module_id in [prelude_submodule, module_id].iter().copied() {
         for use_id in db.module_uses_ids(module_id).ok()?.iter().copied() {
             if db.use_resolved_item(use_id) == Ok(ResolvedGenericItem::Trait(trait_id)) {
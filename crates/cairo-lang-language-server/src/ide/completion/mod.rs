@@ -1,27 +1,62 @@
+use cairo_lang_defs::ids::{LookupItemId, ModuleItemId};
+use cairo_lang_doc::db::DocGroup;
+use cairo_lang_doc::documentable_item::DocumentableItemId;
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_filesystem::span::TextOffset;
+use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_semantic::items::us::get_use_path_segments;
 use cairo_lang_semantic::resolve::AsSegments;
 use cairo_lang_syntax::node::ast::PathSegment;
 use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::ids::SyntaxStablePtrId;
 use cairo_lang_syntax::node::kind::SyntaxKind;
-use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode, ast};
+use cairo_lang_syntax::node::{SyntaxNode, Terminal, TypedSyntaxNode, ast};
 use cairo_lang_utils::Upcast;
-use lsp_types::{CompletionParams, CompletionResponse, CompletionTriggerKind};
+use lsp_types::{
+    CompletionItem, CompletionParams, CompletionResponse, CompletionTriggerKind, Documentation,
+    MarkupContent, MarkupKind, Position, TextEdit, Url,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tracing::debug;
 
-use self::completions::{colon_colon_completions, dot_completions, generic_completions};
+use self::completions::{
+    KeywordContext, auto_import_completions, colon_colon_completions, dot_completions,
+    generic_completions, keyword_completions, match_arm_completions, use_completions,
+};
+use crate::config::Config;
+use crate::ide::navigation::references::{declaration_identifier, identifier_span};
+use crate::ide::utils::use_statement_edit;
 use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
-use crate::lang::lsp::{LsProtoGroup, ToCairo};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
 
 mod completions;
 
 /// Compute completion items at a given cursor position.
-pub fn complete(params: CompletionParams, db: &AnalysisDatabase) -> Option<CompletionResponse> {
+///
+/// In `use` paths, the children of the module preceding the completed segment are completed, or
+/// names of crates and items of the current module in the first segment.
+/// Elsewhere, if enabled in the config, items which are not in scope are completed as well, along
+/// with the `use` statements importing them. If the client supports it, the statements are only
+/// computed when it resolves the chosen item.
+/// In patterns of `match` arms, the variants of the matched enum not covered by other arms are
+/// completed first.
+/// Keywords are completed depending on the context, with control flow constructs completed as
+/// snippets if the client supports them, and so are postfix templates like `x.match`.
+pub fn complete(
+    params: CompletionParams,
+    db: &AnalysisDatabase,
+    config: &Config,
+    snippet_support: bool,
+    resolve_edits: bool,
+) -> Option<CompletionResponse> {
     let text_document_position = params.text_document_position;
     let file_id = db.file_for_url(&text_document_position.text_document.uri)?;
-    let mut position = text_document_position.position;
-    position.character = position.character.saturating_sub(1);
+    let mut position = text_document_position.position.to_cairo(db, file_id);
+    position.col = position.col.saturating_sub(1);
 
-    let mut node = db.find_syntax_node_at_position(file_id, position.to_cairo())?;
+    let mut node = db.find_syntax_node_at_position(file_id, position)?;
     let lookup_items = db.collect_lookup_items_stack(&node)?;
     let module_file_id = db.find_module_file_containing_node(&node)?;
 
@@ -36,28 +71,292 @@ pub fn complete(params: CompletionParams, db: &AnalysisDatabase) -> Option<Compl
     let trigger_kind =
         params.context.map(|it| it.trigger_kind).unwrap_or(CompletionTriggerKind::INVOKED);
 
-    match completion_kind(db, node) {
+    // The identifier being typed, if any.
+    let prefix = (node.kind(db) == SyntaxKind::TerminalIdentifier)
+        .then(|| ast::TerminalIdentifier::from_syntax_node(db, node.clone()).text(db));
+    // The `match` expression whose arm pattern is being typed, if any.
+    let match_expr = text_document_position
+        .position
+        .to_cairo(db, file_id)
+        .offset_in_file(db.upcast(), file_id)
+        .and_then(|offset| match_arm_pattern_context(db, &node, offset));
+    // Keywords are not completed in the middle of an identifier.
+    let keyword_context = (!is_in_identifier(db, file_id, text_document_position.position))
+        .then(|| match match_expr {
+            Some(_) => Some(KeywordContext::Pattern),
+            None => keyword_context(db, &node),
+        })
+        .flatten();
+
+    match completion_kind(db, node.clone()) {
         CompletionKind::Dot(expr) => {
-            dot_completions(db, file_id, lookup_items, expr).map(CompletionResponse::Array)
+            dot_completions(db, file_id, lookup_items, expr, snippet_support)
+                .map(CompletionResponse::Array)
         }
+        CompletionKind::Use(segments) => Some(CompletionResponse::Array(use_completions(
+            db,
+            module_file_id,
+            lookup_items,
+            segments,
+        ))),
         CompletionKind::ColonColon(segments) if !segments.is_empty() => {
             colon_colon_completions(db, module_file_id, lookup_items, segments)
                 .map(CompletionResponse::Array)
         }
         _ if trigger_kind == CompletionTriggerKind::INVOKED => {
-            Some(CompletionResponse::Array(generic_completions(db, module_file_id, lookup_items)))
+            let mut completions = match_expr
+                .and_then(|match_expr| {
+                    match_arm_completions(
+                        db,
+                        lookup_items.clone(),
+                        &node,
+                        match_expr,
+                        snippet_support,
+                    )
+                })
+                .unwrap_or_default();
+            completions.extend(generic_completions(db, module_file_id, lookup_items));
+            if let Some(keyword_context) = keyword_context {
+                completions.extend(keyword_completions(keyword_context, snippet_support));
+            }
+            // Without a prefix, almost every item of every crate would be suggested.
+            if let Some(prefix) = prefix.filter(|_| config.completions_auto_import) {
+                completions.extend(
+                    auto_import_completions(
+                        db,
+                        file_id,
+                        module_file_id,
+                        position,
+                        &prefix,
+                        resolve_edits,
+                    )
+                    .unwrap_or_default(),
+                );
+            }
+            Some(CompletionResponse::Array(completions))
         }
         _ => None,
     }
 }
 
+/// Fill in the detail and documentation of a completion item highlighted by the client, and the
+/// `use` statement importing it if it was not computed by [`complete`].
+///
+/// The item is looked up by the location of its declaration, stored in the data of completion
+/// items by [`complete`], so the completion query does not have to be repeated.
+pub fn resolve(mut item: CompletionItem, db: &AnalysisDatabase) -> CompletionItem {
+    let Some(data) =
+        item.data.clone().and_then(|data| serde_json::from_value::<CompletionItemData>(data).ok())
+    else {
+        return item;
+    };
+
+    if let Some(import) = &data.import {
+        item.additional_text_edits = import_edit(db, import).map(|edit| vec![edit]);
+    }
+
+    let Some(documentable_item) = documentable_item(db, &data) else {
+        return item;
+    };
+
+    // Keep the detail filled in by `complete`, e.g. the concrete type of a struct member.
+    item.detail.get_or_insert_with(|| db.get_item_signature(documentable_item));
+    item.documentation = db.get_item_documentation(documentable_item).map(|doc| {
+        Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value: doc })
+    });
+    item
+}
+
+/// Location of the name in the declaration of the item a completion item was created for.
+#[derive(Serialize, Deserialize)]
+struct CompletionItemData {
+    uri: Url,
+    position: Position,
+    /// The import of the item to compute when the completion item is resolved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    import: Option<CompletionImport>,
+}
+
+/// The path of an item to import into the module containing the completion position.
+#[derive(Serialize, Deserialize)]
+struct CompletionImport {
+    uri: Url,
+    position: Position,
+    path: String,
+}
+
+/// Creates the data of a completion item for the item with the given definition, which allows
+/// resolving it later.
+fn item_data(db: &AnalysisDatabase, definition: SyntaxStablePtrId) -> Option<Value> {
+    item_data_with_import(db, definition, None)
+}
+
+/// Creates the data of a completion item like [`item_data`], along with the import of the item
+/// to compute when it is resolved.
+fn item_data_with_import(
+    db: &AnalysisDatabase,
+    definition: SyntaxStablePtrId,
+    import: Option<CompletionImport>,
+) -> Option<Value> {
+    let (file, span) = identifier_span(db, &declaration_identifier(db, definition)?)?;
+    let data = CompletionItemData {
+        uri: db.url_for_file(file)?,
+        position: span.start.position_in_file(db.upcast(), file)?.to_lsp(db, file),
+        import,
+    };
+    serde_json::to_value(data).ok()
+}
+
+/// Creates the edit adding the `use` statement of the import.
+fn import_edit(db: &AnalysisDatabase, import: &CompletionImport) -> Option<TextEdit> {
+    let file_id = db.file_for_url(&import.uri)?;
+    let node = db.find_syntax_node_at_position(file_id, import.position.to_cairo(db, file_id))?;
+    let module_id = db.find_module_containing_node(&node)?;
+    use_statement_edit(db, file_id, module_id, &import.path)
+}
+
+/// Finds the item declared with the name at the location from the completion item data.
+fn documentable_item(
+    db: &AnalysisDatabase,
+    data: &CompletionItemData,
+) -> Option<DocumentableItemId> {
+    let syntax_db = db.upcast();
+    let file = db.file_for_url(&data.uri)?;
+    let identifier = db.find_identifier_at_position(file, data.position.to_cairo(db, file))?;
+    let node = identifier.as_syntax_node();
+    let lookup_item = db.find_lookup_item(&node)?;
+    let name = identifier.text(syntax_db);
+
+    Some(match (node.parent()?.kind(syntax_db), lookup_item) {
+        (SyntaxKind::Member, LookupItemId::ModuleItem(ModuleItemId::Struct(struct_id))) => {
+            db.struct_members(struct_id).ok()?.get(&name)?.id.into()
+        }
+        (SyntaxKind::Variant, LookupItemId::ModuleItem(ModuleItemId::Enum(enum_id))) => {
+            (*db.enum_variants(enum_id).ok()?.get(&name)?).into()
+        }
+        _ => lookup_item.into(),
+    })
+}
+
+/// Checks whether the position is followed by a character of an identifier.
+fn is_in_identifier(db: &AnalysisDatabase, file_id: FileId, position: Position) -> bool {
+    let Some(content) = db.file_content(file_id) else { return false };
+    let Some(offset) = position.to_cairo(db, file_id).offset_in_file(db.upcast(), file_id) else {
+        return false;
+    };
+    offset.take_from(&content).starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Finds the context of the completed node, determining which keywords can be typed there.
+///
+/// Returns `None` if no keywords can be typed there, e.g. in types or names of struct members.
+fn keyword_context(db: &AnalysisDatabase, node: &SyntaxNode) -> Option<KeywordContext> {
+    // Names of bindings can be preceded by modifiers.
+    if node.kind(db) == SyntaxKind::TerminalIdentifier && is_binding_name(db, node) {
+        return Some(KeywordContext::Pattern);
+    }
+
+    for ancestor in std::iter::successors(Some(node.clone()), SyntaxNode::parent) {
+        match ancestor.kind(db) {
+            SyntaxKind::ExprBlock => return Some(KeywordContext::Statement),
+            SyntaxKind::TypeClause
+            | SyntaxKind::ReturnTypeClause
+            | SyntaxKind::GenericArgs
+            | SyntaxKind::GenericParamList
+            | SyntaxKind::Attribute
+            | SyntaxKind::Member
+            | SyntaxKind::Variant
+            | SyntaxKind::ItemUse => return None,
+            _ => {}
+        }
+    }
+    Some(KeywordContext::Item)
+}
+
+/// Checks whether the identifier is a name of a binding in a pattern or a parameter.
+fn is_binding_name(db: &AnalysisDatabase, identifier: &SyntaxNode) -> bool {
+    let Some(parent) = identifier.parent() else { return false };
+    if matches!(parent.kind(db), SyntaxKind::PatternIdentifier | SyntaxKind::Param) {
+        return true;
+    }
+
+    // Patterns without modifiers consisting of a single identifier are parsed as paths.
+    let Some(path) = parent.parent().filter(|path| {
+        path.kind(db) == SyntaxKind::ExprPath
+            && ast::ExprPath::from_syntax_node(db, path.clone()).elements(db).len() == 1
+    }) else {
+        return false;
+    };
+    let Some(path_parent) = path.parent() else { return false };
+    match path_parent.kind(db) {
+        SyntaxKind::StatementLet => {
+            ast::StatementLet::from_syntax_node(db, path_parent).pattern(db).as_syntax_node()
+                == path
+        }
+        SyntaxKind::PatternList
+        | SyntaxKind::PatternListOr
+        | SyntaxKind::PatternEnumInnerPattern => true,
+        _ => false,
+    }
+}
+
+/// Finds the `match` expression if the node is at the start of a pattern of one of its arms.
+///
+/// This is the case for a single identifier being typed as a pattern, and for an empty position
+/// after the opening brace, a comma separating the arms, or a `|` separating the patterns.
+fn match_arm_pattern_context(
+    db: &AnalysisDatabase,
+    node: &SyntaxNode,
+    offset: TextOffset,
+) -> Option<ast::ExprMatch> {
+    let parent = node.parent()?;
+    let match_node = match (node.kind(db), parent.kind(db)) {
+        // `match x { A<caret> }`
+        (SyntaxKind::TerminalIdentifier, _) => {
+            let path = parent.parent()?;
+            if path.kind(db) != SyntaxKind::ExprPath
+                || ast::ExprPath::from_syntax_node(db, path.clone()).elements(db).len() != 1
+            {
+                return None;
+            }
+            let patterns = path.parent()?;
+            if patterns.kind(db) != SyntaxKind::PatternListOr {
+                return None;
+            }
+            patterns.parent()?.parent()?.parent()?
+        }
+        // `match x { A | <caret> }`
+        (SyntaxKind::TerminalOr, SyntaxKind::PatternListOr) => {
+            parent.parent()?.parent()?.parent()?
+        }
+        // `match x { <caret>` and `match x { A => 1, <caret>`
+        (SyntaxKind::TerminalLBrace, SyntaxKind::ExprMatch) => parent,
+        (SyntaxKind::TerminalComma, SyntaxKind::MatchArms) => parent.parent()?,
+        // On the lines preceding the closing brace.
+        (SyntaxKind::TerminalRBrace, SyntaxKind::ExprMatch)
+            if offset <= node.span_start_without_trivia(db) =>
+        {
+            parent
+        }
+        _ => return None,
+    };
+    (match_node.kind(db) == SyntaxKind::ExprMatch)
+        .then(|| ast::ExprMatch::from_syntax_node(db, match_node))
+}
+
 enum CompletionKind {
     Dot(ast::ExprBinary),
     ColonColon(Vec<PathSegment>),
+    /// A segment of a `use` path, preceded by the given segments.
+    Use(Vec<PathSegment>),
 }
 
 fn completion_kind(db: &AnalysisDatabase, node: SyntaxNode) -> CompletionKind {
     debug!("node.kind: {:#?}", node.kind(db));
+    if let Some(segments) = use_path_prefix(db, &node) {
+        debug!("Use");
+        return CompletionKind::Use(segments);
+    }
     match node.kind(db) {
         SyntaxKind::TerminalDot => {
             let parent = node.parent().unwrap();
@@ -76,27 +375,6 @@ fn completion_kind(db: &AnalysisDatabase, node: SyntaxNode) -> CompletionKind {
             if grandparent.kind(db) == SyntaxKind::ExprPath {
                 return completion_kind_from_path_node(db, grandparent);
             }
-            let (use_ast, should_pop) = if parent.kind(db) == SyntaxKind::UsePathLeaf {
-                (ast::UsePath::Leaf(ast::UsePathLeaf::from_syntax_node(db, parent)), true)
-            } else if grandparent.kind(db) == SyntaxKind::UsePathLeaf {
-                (ast::UsePath::Leaf(ast::UsePathLeaf::from_syntax_node(db, grandparent)), true)
-            } else if parent.kind(db) == SyntaxKind::UsePathSingle {
-                (ast::UsePath::Single(ast::UsePathSingle::from_syntax_node(db, parent)), false)
-            } else if grandparent.kind(db) == SyntaxKind::UsePathSingle {
-                (ast::UsePath::Single(ast::UsePathSingle::from_syntax_node(db, grandparent)), false)
-            } else {
-                debug!("Generic");
-                return CompletionKind::ColonColon(vec![]);
-            };
-            let Ok(mut segments) = get_use_path_segments(db.upcast(), use_ast) else {
-                debug!("Generic");
-                return CompletionKind::ColonColon(vec![]);
-            };
-            if should_pop {
-                segments.pop();
-            }
-            debug!("ColonColon");
-            return CompletionKind::ColonColon(segments);
         }
         SyntaxKind::TerminalIdentifier => {
             let parent = node.parent().unwrap();
@@ -123,18 +401,6 @@ fn completion_kind(db: &AnalysisDatabase, node: SyntaxNode) -> CompletionKind {
                     }
                 }
             }
-            if grandparent.kind(db) == SyntaxKind::UsePathLeaf {
-                let use_ast = ast::UsePathLeaf::from_syntax_node(db, grandparent);
-                let Ok(mut segments) =
-                    get_use_path_segments(db.upcast(), ast::UsePath::Leaf(use_ast))
-                else {
-                    debug!("Generic");
-                    return CompletionKind::ColonColon(vec![]);
-                };
-                segments.pop();
-                debug!("ColonColon");
-                return CompletionKind::ColonColon(segments);
-            }
         }
         _ => (),
     }
@@ -142,6 +408,56 @@ fn completion_kind(db: &AnalysisDatabase, node: SyntaxNode) -> CompletionKind {
     CompletionKind::ColonColon(vec![])
 }
 
+/// Returns the segments of the `use` path preceding the segment completed at the node, if the node
+/// is a part of a `use` path.
+///
+/// Inside braces, e.g. `use a::{b, c}`, the segments preceding the braces are returned.
+fn use_path_prefix(db: &AnalysisDatabase, node: &SyntaxNode) -> Option<Vec<PathSegment>> {
+    let parent = node.parent()?;
+    let (use_path, should_pop) = match (node.kind(db), parent.kind(db)) {
+        // `use a::b<caret>`
+        (SyntaxKind::TerminalIdentifier, _) => {
+            let use_path = parent.parent()?;
+            match use_path.kind(db) {
+                SyntaxKind::UsePathLeaf => {
+                    (ast::UsePath::Leaf(ast::UsePathLeaf::from_syntax_node(db, use_path)), true)
+                }
+                SyntaxKind::UsePathSingle => {
+                    (ast::UsePath::Single(ast::UsePathSingle::from_syntax_node(db, use_path)), true)
+                }
+                _ => return None,
+            }
+        }
+        // `use a::<caret>`
+        (SyntaxKind::TerminalColonColon, SyntaxKind::UsePathSingle) => {
+            (ast::UsePath::Single(ast::UsePathSingle::from_syntax_node(db, parent)), false)
+        }
+        // `use <caret>`
+        (SyntaxKind::TerminalUse, SyntaxKind::ItemUse) => return Some(vec![]),
+        // `use a::{<caret>}` and `use a::{b, <caret>}`
+        (SyntaxKind::TerminalLBrace, SyntaxKind::UsePathMulti)
+        | (SyntaxKind::TerminalComma, SyntaxKind::UsePathList) => {
+            let multi = if parent.kind(db) == SyntaxKind::UsePathMulti {
+                parent
+            } else {
+                parent.parent()?
+            };
+            let single = multi.parent()?;
+            if single.kind(db) != SyntaxKind::UsePathSingle {
+                return Some(vec![]);
+            }
+            (ast::UsePath::Single(ast::UsePathSingle::from_syntax_node(db, single)), false)
+        }
+        _ => return None,
+    };
+
+    let mut segments = get_use_path_segments(db.upcast(), use_path).ok()?;
+    if should_pop {
+        segments.pop();
+    }
+    Some(segments)
+}
+
 fn completion_kind_from_path_node(db: &AnalysisDatabase, parent: SyntaxNode) -> CompletionKind {
     debug!("completion_kind_from_path_node: {}", parent.clone().get_text_without_trivia(db));
     let expr = ast::ExprPath::from_syntax_node(db, parent);
@@ -0,0 +1,82 @@
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_semantic::resolve::{ResolvedConcreteItem, ResolvedGenericItem};
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::{DocumentLink, DocumentLinkParams};
+use tracing::error;
+
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup};
+use crate::lang::inspect::defs::{ResolvedItem, find_definition};
+use crate::lang::lsp::{LsProtoGroup, ToLsp};
+
+/// Compute links from module references in a file to the files of the modules.
+///
+/// Links are provided for names of `mod` items without a body and for segments of `use` paths
+/// which refer to modules. Links to the file itself, e.g. to inline modules, and paths which can
+/// not be resolved are skipped.
+pub fn document_links(
+    params: DocumentLinkParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<DocumentLink>> {
+    let syntax_db = db.upcast();
+    let file_uri = params.text_document.uri;
+    let file = db.file_for_url(&file_uri)?;
+    let Ok(root) = db.file_syntax(file) else {
+        error!("document links failed: file '{file_uri}' does not exist");
+        return None;
+    };
+
+    let mut links = vec![];
+    for node in root.descendants(syntax_db) {
+        if node.kind(syntax_db) != SyntaxKind::TerminalIdentifier || !is_module_reference(db, &node)
+        {
+            continue;
+        }
+        let identifier = ast::TerminalIdentifier::from_syntax_node(syntax_db, node.clone());
+        let Some(lookup_items) = db.collect_lookup_items_stack(&node) else { continue };
+        let module_id = match find_definition(db, &identifier, &lookup_items) {
+            Some((ResolvedItem::Generic(ResolvedGenericItem::Module(module_id)), _))
+            | Some((ResolvedItem::Concrete(ResolvedConcreteItem::Module(module_id)), _)) => {
+                module_id
+            }
+            _ => continue,
+        };
+        let Ok(module_file) = db.module_main_file(module_id) else { continue };
+        if module_file == file {
+            continue;
+        }
+        let Some(target) = db.url_for_file(module_file) else { continue };
+        let Some(range) = node.span_without_trivia(syntax_db).position_in_file(db.upcast(), file)
+        else {
+            continue;
+        };
+
+        links.push(DocumentLink {
+            range: range.to_lsp(db, file),
+            target: Some(target),
+            tooltip: None,
+            data: None,
+        });
+    }
+
+    Some(links)
+}
+
+/// Checks whether the identifier is the name of a `mod` item without a body or a segment of a
+/// `use` path.
+fn is_module_reference(db: &AnalysisDatabase, identifier: &SyntaxNode) -> bool {
+    let syntax_db = db.upcast();
+    let Some(parent) = identifier.parent() else { return false };
+    match parent.kind(syntax_db) {
+        SyntaxKind::ItemModule => matches!(
+            ast::ItemModule::from_syntax_node(syntax_db, parent).body(syntax_db),
+            ast::MaybeModuleBody::None(_)
+        ),
+        SyntaxKind::PathSegmentSimple => parent.parent().is_some_and(|path| {
+            matches!(path.kind(syntax_db), SyntaxKind::UsePathSingle | SyntaxKind::UsePathLeaf)
+        }),
+        _ => false,
+    }
+}
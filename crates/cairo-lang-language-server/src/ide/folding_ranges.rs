@@ -0,0 +1,152 @@
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_syntax::node::SyntaxNode;
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_utils::Upcast;
+use lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams};
+use tracing::error;
+
+use crate::lang::db::AnalysisDatabase;
+use crate::lang::lsp::LsProtoGroup;
+
+/// Compute folding ranges of a file.
+///
+/// Ranges are provided for bodies of modules, traits, impls, structs and enums, for blocks and
+/// `match` expressions, for runs of consecutive comments and for runs of consecutive `use` items.
+pub fn folding_ranges(
+    params: FoldingRangeParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<FoldingRange>> {
+    let syntax_db = db.upcast();
+    let file_uri = params.text_document.uri;
+    let file = db.file_for_url(&file_uri)?;
+    let Ok(root) = db.file_syntax(file) else {
+        error!("folding ranges failed: file '{file_uri}' does not exist");
+        return None;
+    };
+
+    let mut ranges = vec![];
+    let mut comments: Option<(SyntaxKind, usize, usize)> = None;
+    for node in root.descendants(syntax_db) {
+        match node.kind(syntax_db) {
+            SyntaxKind::ModuleBody
+            | SyntaxKind::TraitBody
+            | SyntaxKind::ImplBody
+            | SyntaxKind::ItemStruct
+            | SyntaxKind::ItemEnum
+            | SyntaxKind::ExprBlock
+            | SyntaxKind::ExprMatch => {
+                ranges.extend(braces_range(db, file, &node));
+            }
+            SyntaxKind::ModuleItemList => {
+                ranges.extend(use_ranges(db, file, &node));
+            }
+            kind @ (SyntaxKind::TokenSingleLineComment
+            | SyntaxKind::TokenSingleLineInnerComment
+            | SyntaxKind::TokenSingleLineDocComment) => {
+                // Comments following code on the same line are never folded.
+                let line =
+                    start_line(db, file, &node).filter(|_| !is_trailing_comment(syntax_db, &node));
+                // Extend the current run of comments if it is of the same kind and this comment
+                // is placed on the next line, otherwise start a new run.
+                comments = match (comments, line) {
+                    (Some((run_kind, start, end)), Some(line))
+                        if run_kind == kind && end + 1 == line =>
+                    {
+                        Some((kind, start, line))
+                    }
+                    (run, line) => {
+                        ranges.extend(run.and_then(|(_, start, end)| comments_range(start, end)));
+                        line.map(|line| (kind, line, line))
+                    }
+                };
+            }
+            _ => {}
+        }
+    }
+    ranges.extend(comments.and_then(|(_, start, end)| comments_range(start, end)));
+
+    ranges.sort_by_key(|range| (range.start_line, range.end_line));
+    Some(ranges)
+}
+
+/// Folds the contents between the braces of the node, leaving the closing brace visible.
+fn braces_range(db: &AnalysisDatabase, file: FileId, node: &SyntaxNode) -> Option<FoldingRange> {
+    let syntax_db: &dyn SyntaxGroup = db.upcast();
+    let children = syntax_db.get_children(node.clone());
+    let lbrace =
+        children.iter().find(|child| child.kind(syntax_db) == SyntaxKind::TerminalLBrace)?;
+    let rbrace =
+        children.iter().find(|child| child.kind(syntax_db) == SyntaxKind::TerminalRBrace)?;
+
+    let start = start_line(db, file, lbrace)?;
+    let end = start_line(db, file, rbrace)?.checked_sub(1)?;
+    (start < end).then(|| folding_range(start, end, None))
+}
+
+/// Folds each run of consecutive `use` items in a module into a single region.
+fn use_ranges(db: &AnalysisDatabase, file: FileId, items: &SyntaxNode) -> Vec<FoldingRange> {
+    let syntax_db: &dyn SyntaxGroup = db.upcast();
+    let mut ranges = vec![];
+    let mut run: Option<(usize, usize)> = None;
+    for item in syntax_db.get_children(items.clone()).iter() {
+        let lines = (item.kind(syntax_db) == SyntaxKind::ItemUse)
+            .then(|| Some((start_line(db, file, item)?, end_line(db, file, item)?)))
+            .flatten();
+        run = match (run, lines) {
+            (Some((start, _)), Some((_, end))) => Some((start, end)),
+            (None, lines) => lines,
+            (Some((start, end)), None) => {
+                if start < end {
+                    ranges.push(folding_range(start, end, Some(FoldingRangeKind::Imports)));
+                }
+                None
+            }
+        };
+    }
+    if let Some((start, end)) = run.filter(|(start, end)| start < end) {
+        ranges.push(folding_range(start, end, Some(FoldingRangeKind::Imports)));
+    }
+    ranges
+}
+
+/// Checks whether the comment is in the trailing trivia of a terminal, i.e. it follows code on the
+/// same line.
+fn is_trailing_comment(db: &dyn SyntaxGroup, comment: &SyntaxNode) -> bool {
+    let Some(trivia) = comment.parent() else { return false };
+    let Some(terminal) = trivia.parent() else { return false };
+    db.get_children(terminal).last() == Some(&trivia)
+}
+
+fn comments_range(start_line: usize, end_line: usize) -> Option<FoldingRange> {
+    (start_line < end_line)
+        .then(|| folding_range(start_line, end_line, Some(FoldingRangeKind::Comment)))
+}
+
+fn folding_range(
+    start_line: usize,
+    end_line: usize,
+    kind: Option<FoldingRangeKind>,
+) -> FoldingRange {
+    FoldingRange {
+        start_line: start_line as u32,
+        start_character: None,
+        end_line: end_line as u32,
+        end_character: None,
+        kind,
+        collapsed_text: None,
+    }
+}
+
+/// Gets the line where the node starts, ignoring its leading trivia.
+fn start_line(db: &AnalysisDatabase, file: FileId, node: &SyntaxNode) -> Option<usize> {
+    let span = node.span_without_trivia(db.upcast());
+    Some(span.start.position_in_file(db.upcast(), file)?.line)
+}
+
+/// Gets the line where the node ends, ignoring its trailing trivia.
+fn end_line(db: &AnalysisDatabase, file: FileId, node: &SyntaxNode) -> Option<usize> {
+    let span = node.span_without_trivia(db.upcast());
+    Some(span.end.position_in_file(db.upcast(), file)?.line)
+}
@@ -1,15 +1,32 @@
+use std::ops::Range as LineRange;
+
 use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::span::TextPosition;
 use cairo_lang_formatter::{FormatterConfig, get_formatted_file};
 use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_syntax::node::SyntaxNode;
+use cairo_lang_syntax::node::ast::{ImplItem, ModuleItem, Statement, TraitItem};
 use cairo_lang_utils::Upcast;
-use lsp_types::{DocumentFormattingParams, Position, Range, TextEdit};
+use diffy::{DiffOptions, Line};
+use lsp_types::{
+    DocumentFormattingParams, DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
+    FormattingOptions, Position, Range, TextEdit,
+};
 use tracing::error;
 
+use crate::config::Config;
 use crate::lang::db::AnalysisDatabase;
-use crate::lang::lsp::LsProtoGroup;
+use crate::lang::lsp::{LsProtoGroup, ToCairo};
+
+/// Characters which trigger on-type formatting when typed.
+pub const ON_TYPE_FORMATTING_TRIGGER_CHARACTERS: [&str; 3] = ["}", ";", "\n"];
 
 /// Format a whole document.
-pub fn format(params: DocumentFormattingParams, db: &AnalysisDatabase) -> Option<Vec<TextEdit>> {
+pub fn format(
+    params: DocumentFormattingParams,
+    db: &AnalysisDatabase,
+    config: &Config,
+) -> Option<Vec<TextEdit>> {
     let file_uri = params.text_document.uri;
     let file = db.file_for_url(&file_uri)?;
 
@@ -23,7 +40,11 @@ pub fn format(params: DocumentFormattingParams, db: &AnalysisDatabase) -> Option
         return None;
     }
 
-    let new_text = get_formatted_file(db.upcast(), &node, FormatterConfig::default());
+    let Some(old_text) = db.file_content(file) else {
+        error!("formatting failed: cannot get content of file '{file_uri}'");
+        return None;
+    };
+    let new_text = formatted_file(db, &node, &old_text, &params.options, config);
 
     let Some(file_summary) = db.file_summary(file) else {
         error!("formatting failed: cannot get summary for file '{file_uri}'");
@@ -43,3 +64,249 @@ pub fn format(params: DocumentFormattingParams, db: &AnalysisDatabase) -> Option
         new_text,
     }])
 }
+
+/// Format the statement or block ended by a character typed by the user.
+///
+/// The whole file is formatted, but only the changes to the lines of the innermost statement or
+/// item containing the typed character are returned. When a new line is typed, the statement at
+/// the end of the previous line is formatted instead, and the new line itself is left untouched.
+pub fn format_on_type(
+    params: DocumentOnTypeFormattingParams,
+    db: &AnalysisDatabase,
+    config: &Config,
+) -> Option<Vec<TextEdit>> {
+    let syntax_db = db.upcast();
+    let file_uri = params.text_document_position.text_document.uri;
+    let file = db.file_for_url(&file_uri)?;
+    let position = params.text_document_position.position.to_cairo(db, file);
+    let old_text = db.file_content(file)?;
+
+    let typed = match params.ch.as_str() {
+        "}" | ";" => TextPosition { col: position.col.checked_sub(1)?, ..position },
+        "\n" => {
+            let line = position.line.checked_sub(1)?;
+            let text = old_text.lines().nth(line)?.trim_end();
+            TextPosition { line, col: text.chars().count().checked_sub(1)? }
+        }
+        _ => return None,
+    };
+
+    let Ok(root) = db.file_syntax(file) else {
+        error!("formatting failed: file '{file_uri}' does not exist");
+        return None;
+    };
+
+    // Incomplete code is common while typing, so it is silently left as is.
+    if db.file_syntax_diagnostics(file).check_error_free().is_err() {
+        return None;
+    }
+
+    let node = root.lookup_position(syntax_db, typed);
+    let region = std::iter::successors(Some(node), SyntaxNode::parent).find(|node| {
+        let kind = node.kind(syntax_db);
+        Statement::is_variant(kind)
+            || ModuleItem::is_variant(kind)
+            || TraitItem::is_variant(kind)
+            || ImplItem::is_variant(kind)
+    })?;
+    let span = region.span_without_trivia(syntax_db).position_in_file(db.upcast(), file)?;
+    let mut lines = span.start.line..span.end.line + 1;
+    if params.ch == "\n" {
+        lines.end = lines.end.min(position.line);
+    }
+
+    let new_text = formatted_file(db, &root, &old_text, &params.options, config);
+    Some(line_edits(&old_text, &new_text, lines))
+}
+
+/// Format the syntax nodes in a range of a document.
+///
+/// The range is expanded to complete nodes: the children of the smallest node containing the whole
+/// range, which contain its start and end. The whole file is formatted, but only the changes to the
+/// lines of the expanded range are returned.
+pub fn format_range(
+    params: DocumentRangeFormattingParams,
+    db: &AnalysisDatabase,
+    config: &Config,
+) -> Option<Vec<TextEdit>> {
+    let syntax_db = db.upcast();
+    let file_uri = params.text_document.uri;
+    let file = db.file_for_url(&file_uri)?;
+    let old_text = db.file_content(file)?;
+
+    let Ok(root) = db.file_syntax(file) else {
+        error!("formatting failed: file '{file_uri}' does not exist");
+        return None;
+    };
+
+    if db.file_syntax_diagnostics(file).check_error_free().is_err() {
+        error!("formatting failed: cannot properly parse '{file_uri}' exist");
+        return None;
+    }
+
+    // Whitespace around the range is not part of the selected code.
+    let range = params.range.to_cairo(db, file);
+    let Some(start) = first_char_at_or_after(&old_text, range.start) else {
+        return Some(vec![]);
+    };
+    let end = last_char_before(&old_text, range.end)
+        .filter(|end| (end.line, end.col) >= (start.line, start.col))
+        .unwrap_or(start);
+
+    let start_ancestors =
+        std::iter::successors(Some(root.lookup_position(syntax_db, start)), SyntaxNode::parent)
+            .collect::<Vec<_>>();
+    let end_ancestors =
+        std::iter::successors(Some(root.lookup_position(syntax_db, end)), SyntaxNode::parent)
+            .collect::<Vec<_>>();
+    let common = end_ancestors.iter().find(|node| start_ancestors.contains(node))?;
+    // If the range is within a single token, the token itself is formatted.
+    let child_of_common = |ancestors: &[SyntaxNode]| {
+        ancestors
+            .iter()
+            .find(|node| node.parent().as_ref() == Some(common))
+            .unwrap_or(common)
+            .clone()
+    };
+    let start_child = child_of_common(&start_ancestors);
+    let end_child = child_of_common(&end_ancestors);
+
+    let start_span =
+        start_child.span_without_trivia(syntax_db).position_in_file(db.upcast(), file)?;
+    let end_span = end_child.span_without_trivia(syntax_db).position_in_file(db.upcast(), file)?;
+    let lines = start_span.start.line..end_span.end.line + 1;
+
+    let new_text = formatted_file(db, &root, &old_text, &params.options, config);
+    Some(line_edits(&old_text, &new_text, lines))
+}
+
+/// Returns the configuration of the formatter, following the line width configured by the user.
+pub fn formatter_config(config: &Config) -> FormatterConfig {
+    let mut formatter_config = FormatterConfig::default();
+    if let Some(max_line_width) = config.formatter_max_line_width {
+        formatter_config = formatter_config.max_line_length(max_line_width);
+    }
+    formatter_config
+}
+
+/// Formats the file, following the formatting options of the editor and the configured line width.
+///
+/// The indentation follows the tab size, using tabs unless spaces are preferred. Options with a
+/// zero tab size are not valid editor settings, so the indentation of the formatter is kept for
+/// them. Whitespace at the end of lines and of the file is trimmed or kept as requested, and the
+/// final newline is only left out if the file has none and the editor does not insert it.
+fn formatted_file(
+    db: &AnalysisDatabase,
+    root: &SyntaxNode,
+    old_text: &str,
+    options: &FormattingOptions,
+    config: &Config,
+) -> String {
+    let tab_size = options.tab_size as usize;
+    let mut formatter_config = formatter_config(config);
+    if tab_size > 0 {
+        formatter_config = formatter_config.tab_size(tab_size);
+    }
+    let formatted = get_formatted_file(db.upcast(), root, formatter_config);
+
+    let mut new_text = formatted
+        .split_inclusive('\n')
+        .map(|line| {
+            let mut line = line.to_string();
+            if tab_size > 0 && !options.insert_spaces {
+                let indent = line.len() - line.trim_start_matches(' ').len();
+                line.replace_range(..indent - indent % tab_size, &"\t".repeat(indent / tab_size));
+            }
+            if options.trim_trailing_whitespace == Some(true) {
+                let content = line.trim_end();
+                line =
+                    if line.ends_with('\n') { format!("{content}\n") } else { content.to_owned() };
+            }
+            line
+        })
+        .collect::<String>();
+
+    if options.trim_final_newlines == Some(true) && new_text.ends_with('\n') {
+        new_text.truncate(new_text.trim_end_matches('\n').len() + 1);
+    }
+    if options.insert_final_newline == Some(false) && !old_text.ends_with('\n') {
+        new_text.truncate(new_text.trim_end_matches('\n').len());
+    }
+    new_text
+}
+
+/// Returns the position of the first non-whitespace character at or after the position.
+fn first_char_at_or_after(text: &str, position: TextPosition) -> Option<TextPosition> {
+    let mut col = position.col;
+    for (line, text) in text.lines().enumerate().skip(position.line) {
+        if let Some(offset) = text.chars().skip(col).position(|c| !c.is_whitespace()) {
+            return Some(TextPosition { line, col: col + offset });
+        }
+        col = 0;
+    }
+    None
+}
+
+/// Returns the position of the last non-whitespace character before the position.
+fn last_char_before(text: &str, position: TextPosition) -> Option<TextPosition> {
+    let lines = text.lines().take(position.line + 1).collect::<Vec<_>>();
+    let mut col = position.col;
+    for (line, text) in lines.iter().enumerate().rev() {
+        let prefix = text.chars().take(col).collect::<String>();
+        if let Some(col) = prefix.trim_end().chars().count().checked_sub(1) {
+            return Some(TextPosition { line, col });
+        }
+        col = usize::MAX;
+    }
+    None
+}
+
+/// Computes edits turning the old text into the new one, restricted to changes which are
+/// entirely within the given range of lines of the old text.
+///
+/// Changes to adjacent lines are merged into a single change by the diff, so changes which keep
+/// the number of lines are split back into changes of single lines.
+fn line_edits(old_text: &str, new_text: &str, lines: LineRange<usize>) -> Vec<TextEdit> {
+    let patch = DiffOptions::new().set_context_len(0).create_patch(old_text, new_text);
+    let mut edits = vec![];
+    for hunk in patch.hunks() {
+        let old_range = hunk.old_range();
+        // Hunk ranges are 1-based, except for the ones of pure insertions, which start at the line
+        // after which the new lines are inserted.
+        let start = if old_range.is_empty() { old_range.start() } else { old_range.start() - 1 };
+        let inserted = hunk
+            .lines()
+            .iter()
+            .filter_map(|line| match line {
+                Line::Insert(text) => Some(*text),
+                Line::Context(_) | Line::Delete(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        if inserted.len() == old_range.len() {
+            for (line, new_text) in (start..).zip(inserted) {
+                edits.push((line..line + 1, new_text.to_string()));
+            }
+        } else {
+            edits.push((start..start + old_range.len(), inserted.concat()));
+        }
+    }
+
+    edits
+        .into_iter()
+        .filter(|(replaced, _)| {
+            if replaced.is_empty() {
+                lines.start < replaced.start && replaced.start < lines.end
+            } else {
+                lines.start <= replaced.start && replaced.end <= lines.end
+            }
+        })
+        .map(|(replaced, new_text)| TextEdit {
+            range: Range {
+                start: Position { line: replaced.start as u32, character: 0 },
+                end: Position { line: replaced.end as u32, character: 0 },
+            },
+            new_text,
+        })
+        .collect()
+}
@@ -0,0 +1,55 @@
+//! Fuzzy matching of user queries against symbol names.
+
+#[cfg(test)]
+#[path = "fuzzy_test.rs"]
+mod test;
+
+/// Score bonus for a matched character that starts a word, e.g. `B` in `FooBar` or `b` in
+/// `foo_bar`.
+const WORD_START_BONUS: i64 = 10;
+/// Score bonus for a matched character that directly follows the previously matched one.
+const CONSECUTIVE_BONUS: i64 = 5;
+/// Score penalty for each skipped character between two matched ones.
+const GAP_PENALTY: i64 = 1;
+
+/// Matches the query against the candidate as a case-insensitive subsequence.
+///
+/// Returns `None` if the candidate does not match, or a score otherwise. Matches where query
+/// characters hit word starts and consecutive characters score higher, so that `fbr` ranks
+/// `FooBar` above `fooobar`. An empty query matches everything with a score of `0`.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<i64> {
+    let candidate = candidate.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let mut position = 0;
+
+    for query_char in query.chars() {
+        let offset = candidate[position..]
+            .iter()
+            .position(|candidate_char| candidate_char.eq_ignore_ascii_case(&query_char))?;
+        let index = position + offset;
+
+        if is_word_start(&candidate, index) {
+            score += WORD_START_BONUS;
+        }
+        match last_match {
+            Some(last) if last + 1 == index => score += CONSECUTIVE_BONUS,
+            Some(last) => score -= (index - last - 1) as i64 * GAP_PENALTY,
+            None => score -= index as i64 * GAP_PENALTY,
+        }
+
+        last_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
+
+/// Checks whether the character at the given index starts a word in an identifier.
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    let Some(previous) = index.checked_sub(1).map(|i| chars[i]) else {
+        return true;
+    };
+    let current = chars[index];
+    previous == '_' && current != '_' || previous.is_lowercase() && current.is_uppercase()
+}
@@ -0,0 +1,20 @@
+use super::fuzzy_match;
+
+#[test]
+fn matches_subsequences() {
+    assert!(fuzzy_match("fbr", "FooBar").is_some());
+    assert!(fuzzy_match("FOOBAR", "foo_bar").is_some());
+    assert!(fuzzy_match("", "anything").is_some());
+    assert!(fuzzy_match("fbz", "FooBar").is_none());
+    assert!(fuzzy_match("rab", "FooBar").is_none());
+}
+
+#[test]
+fn prefers_word_starts_and_consecutive_matches() {
+    let score = |query, candidate| fuzzy_match(query, candidate).unwrap();
+
+    assert!(score("fbr", "FooBar") > score("fbr", "fooobar"));
+    assert!(score("fbr", "foo_bar") > score("fbr", "fooobar"));
+    assert!(score("foo", "foobar") > score("foo", "fxoxo"));
+    assert!(score("bar", "Bar") > score("bar", "FooBar"));
+}
@@ -1,35 +1,55 @@
 use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
 
+use crate::ide::macros::expand::expand_macro_call;
 use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
 use crate::lang::lsp::{LsProtoGroup, ToCairo};
 
 mod render;
 
 /// Get hover information at a given text document position.
-pub fn hover(params: HoverParams, db: &AnalysisDatabase) -> Option<Hover> {
+///
+/// If the position is at a macro call, the expansion of the macro is included.
+/// If the client does not support Markdown, the contents are converted to plain text.
+pub fn hover(params: HoverParams, db: &AnalysisDatabase, markdown_support: bool) -> Option<Hover> {
     let file_id = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
-    let position = params.text_document_position_params.position.to_cairo();
+    let position = params.text_document_position_params.position.to_cairo(db, file_id);
 
-    if let Some(hover) = db
+    let hover = db
         .find_syntax_node_at_position(file_id, position)
-        .and_then(|ref node| render::literal(db, node, file_id))
-    {
-        return Some(hover);
-    }
-
-    if let Some(hover) = db
-        .find_identifier_at_position(file_id, position)
-        .and_then(|ref id| render::definition(db, id, file_id).or_else(|| render::legacy(db, id)))
-    {
-        return Some(hover);
-    }
-
-    None
-
-    // TODO(mkaput): If client only supports plaintext, strip markdown formatting here like RA.
+        .and_then(|ref node| {
+            render::attribute(db, node, file_id).or_else(|| render::literal(db, node, file_id))
+        })
+        .or_else(|| {
+            db.find_identifier_at_position(file_id, position).and_then(|ref id| {
+                render::definition(db, id, file_id).or_else(|| render::legacy(db, id, file_id))
+            })
+        });
+
+    // Expansion is unavailable if the macro is unknown, e.g. before proc macros are resolved.
+    let hover = match expand_macro_call(db, &params.text_document_position_params) {
+        Some(expansion) => render::macro_expansion(hover, &expansion),
+        None => hover?,
+    };
+
+    Some(if markdown_support { hover } else { plaintext_hover(hover) })
 }
 
 /// Convenience shortcut for building hover contents from markdown block.
 fn markdown_contents(md: String) -> HoverContents {
     HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: md })
 }
+
+/// Strips Markdown formatting from the contents of the hover.
+fn plaintext_hover(hover: Hover) -> Hover {
+    let HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }) = hover.contents
+    else {
+        return hover;
+    };
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::PlainText,
+            value: render::strip_markdown(&value),
+        }),
+        ..hover
+    }
+}
@@ -0,0 +1,136 @@
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_syntax::node::ast::Attribute;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode};
+use cairo_lang_utils::Upcast;
+use lsp_types::Hover;
+
+use crate::ide::hover::markdown_contents;
+use crate::ide::hover::render::markdown::{RULE, fenced_code_block};
+use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
+use crate::lang::lsp::ToLsp;
+
+/// Derives handled by the derive plugin of the compiler, which are not reported by
+/// [`DefsGroup::declared_derives`].
+const BUILTIN_DERIVES: [&str; 10] = [
+    "Clone",
+    "Copy",
+    "Debug",
+    "Default",
+    "Destruct",
+    "Drop",
+    "Hash",
+    "PanicDestruct",
+    "PartialEq",
+    "Serde",
+];
+
+/// Narrows down [`SyntaxNode`] to an [`Attribute`] it is part of and renders a hover with the
+/// name and description of the attribute, or of the derive if the node is an argument of
+/// `#[derive(...)]`.
+///
+/// Returns `None` if the attribute is not known to the compiler or any of its plugins.
+pub fn attribute(db: &AnalysisDatabase, node: &SyntaxNode, file_id: FileId) -> Option<Hover> {
+    let attribute_node = db.first_ancestor_of_kind(node.clone(), SyntaxKind::Attribute)?;
+    let attribute = Attribute::from_syntax_node(db, attribute_node.clone());
+    let name = attribute.attr(db).as_syntax_node().get_text_without_trivia(db);
+
+    let (md, highlighted) = match derive_argument(db, node, &attribute_node) {
+        Some(arg) if name == "derive" => {
+            let derive = arg.clone().get_text_without_trivia(db);
+            if !BUILTIN_DERIVES.contains(&derive.as_str())
+                && !db.declared_derives().contains(&derive)
+            {
+                return None;
+            }
+            let mut md = fenced_code_block(&format!("#[derive({derive})]"));
+            md += RULE;
+            md += &format!("Implements the `{derive}` trait for the type.\n");
+            (md, arg)
+        }
+        _ => {
+            if !db.allowed_attributes().contains(&name)
+                && !db.allowed_statement_attributes().contains(&name)
+            {
+                return None;
+            }
+            let mut md = fenced_code_block(&format!("#[{name}]"));
+            let description = description(&name).map(str::to_string).or_else(|| {
+                is_declared_by_plugin(db, &name)
+                    .then(|| "Attribute handled by a compiler plugin.".to_string())
+            });
+            if let Some(description) = description {
+                md += RULE;
+                md += &description;
+                md += "\n";
+            }
+            (md, attribute.attr(db).as_syntax_node())
+        }
+    };
+
+    Some(Hover {
+        contents: markdown_contents(md),
+        range: highlighted
+            .span_without_trivia(db.upcast())
+            .position_in_file(db.upcast(), file_id)
+            .map(|p| p.to_lsp(db, file_id)),
+    })
+}
+
+/// Finds the argument of the attribute containing the node.
+fn derive_argument(
+    db: &AnalysisDatabase,
+    node: &SyntaxNode,
+    attribute_node: &SyntaxNode,
+) -> Option<SyntaxNode> {
+    let arg = db.first_ancestor_of_kind(node.clone(), SyntaxKind::Arg)?;
+    let arg_attribute = db.first_ancestor_of_kind(arg.clone(), SyntaxKind::Attribute)?;
+    (&arg_attribute == attribute_node).then_some(arg)
+}
+
+/// Checks whether any of the macro plugins declares the attribute.
+fn is_declared_by_plugin(db: &AnalysisDatabase, name: &str) -> bool {
+    db.macro_plugins().iter().any(|plugin| plugin.declared_attributes().iter().any(|a| a == name))
+}
+
+/// Describes attributes of the language and of the plugins built into the compiler.
+fn description(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "allow" => "Suppresses warnings of the given lint.",
+        "available_gas" => "Sets the amount of gas available to the test.",
+        "cairofmt::skip" => "Skips formatting of the item.",
+        "cfg" => {
+            "Compiles the item only if the given configuration is enabled, e.g. `#[cfg(test)]`."
+        }
+        "constructor" => "Marks the function as the constructor of the contract.",
+        "default" => "Marks the variant as the default value of the enum for `#[derive(Default)]`.",
+        "deprecated" => "Marks the item as deprecated, using it produces a warning.",
+        "derive" => "Implements the given traits for the type, e.g. `#[derive(Copy, Drop)]`.",
+        "event" => "Defines the events which the contract or the component can emit.",
+        "external" => "Exposes the function as an entry point of the contract.",
+        "feature" => "Allows using items marked with the given unstable feature.",
+        "generate_trait" => "Generates a trait with the signatures of all functions of the impl.",
+        "ignore" => "Skips the test, unless ignored tests are requested to be run.",
+        "inline" => {
+            "Hints the compiler to inline the function. `#[inline(always)]` forces inlining, \
+             `#[inline(never)]` prevents it."
+        }
+        "l1_handler" => "Marks the function as a handler of messages sent from L1.",
+        "must_use" => "Warns if the returned value, or a value of the type, is not used.",
+        "panic_with" => {
+            "Generates a wrapper of the function, which panics with the given error if the \
+             function returns `None` or `Err`."
+        }
+        "should_panic" => "Marks the test as expected to panic.",
+        "starknet::component" => "Defines a component, which can be embedded in contracts.",
+        "starknet::contract" => "Defines a Starknet contract.",
+        "starknet::interface" => {
+            "Defines an interface of a contract and generates dispatchers for calling it."
+        }
+        "storage" => "Defines the storage of the contract or the component.",
+        "test" => "Marks the function as a test.",
+        "unstable" => "Marks the item as unstable, using it requires enabling its feature.",
+        _ => return None,
+    })
+}
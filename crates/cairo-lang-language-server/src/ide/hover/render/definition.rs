@@ -1,13 +1,16 @@
 use cairo_lang_defs::db::DefsGroup;
-use cairo_lang_doc::db::DocGroup;
+use cairo_lang_defs::ids::{ImplItemId, LookupItemId, ModuleId, ModuleItemId, TraitItemId};
+use cairo_lang_doc::documentable_item::DocumentableItemId;
+use cairo_lang_filesystem::db::FilesGroup;
 use cairo_lang_filesystem::ids::FileId;
 use cairo_lang_syntax::node::TypedSyntaxNode;
 use cairo_lang_syntax::node::ast::TerminalIdentifier;
 use cairo_lang_utils::Upcast;
+use itertools::Itertools;
 use lsp_types::Hover;
 
 use crate::ide::hover::markdown_contents;
-use crate::ide::hover::render::markdown::{RULE, fenced_code_block};
+use crate::ide::hover::render::markdown::{RULE, doc_comments, fenced_code_block};
 use crate::lang::db::AnalysisDatabase;
 use crate::lang::inspect::defs::{MemberDef, SymbolDef};
 use crate::lang::lsp::ToLsp;
@@ -25,7 +28,7 @@ pub fn definition(
             let mut md = String::new();
             md += &fenced_code_block(&item.definition_path(db));
             md += &fenced_code_block(&item.signature(db));
-            if let Some(doc) = item.documentation(db) {
+            if let Some(doc) = documentation(db, item.lookup_item_id().into()) {
                 md += RULE;
                 md += &doc;
             }
@@ -49,7 +52,7 @@ pub fn definition(
             md += &fenced_code_block(&structure.definition_path(db));
             md += &fenced_code_block(&structure.signature(db));
 
-            if let Some(doc) = db.get_item_documentation((*member).into()) {
+            if let Some(doc) = documentation(db, (*member).into()) {
                 md += RULE;
                 md += &doc;
             }
@@ -63,6 +66,63 @@ pub fn definition(
             .as_syntax_node()
             .span_without_trivia(db.upcast())
             .position_in_file(db.upcast(), file_id)
-            .map(|p| p.to_lsp()),
+            .map(|p| p.to_lsp(db, file_id)),
     })
 }
+
+/// Gets the documentation of an item from its doc comments.
+///
+/// Unlike [`DocGroup::get_item_documentation`], which joins lines of the comments with spaces, this
+/// keeps the Markdown formatting of the documentation.
+///
+/// [`DocGroup::get_item_documentation`]: cairo_lang_doc::db::DocGroup::get_item_documentation
+fn documentation(db: &AnalysisDatabase, item_id: DocumentableItemId) -> Option<String> {
+    let node = item_id.stable_location(db.upcast()).map(|location| location.syntax_node(db));
+
+    // Comments of non-inline modules at the top of their files.
+    let module_id = match item_id {
+        DocumentableItemId::Crate(crate_id) => Some(ModuleId::CrateRoot(crate_id)),
+        DocumentableItemId::LookupItem(LookupItemId::ModuleItem(ModuleItemId::Submodule(id))) => {
+            (!db.is_submodule_inline(id).unwrap_or(true)).then_some(ModuleId::Submodule(id))
+        }
+        _ => None,
+    };
+    let module_level = module_id.and_then(|module_id| {
+        let content = db.file_content(db.module_main_file(module_id).ok()?)?;
+        doc_comments(leading_comments(&content), "//!")
+    });
+
+    // Comments above the item.
+    let outer = node
+        .as_ref()
+        .and_then(|node| doc_comments(leading_comments(&node.get_text(db.upcast())), "///"));
+
+    // Comments inside the body of the item.
+    let has_inner_comments = matches!(
+        item_id,
+        DocumentableItemId::LookupItem(
+            LookupItemId::ModuleItem(ModuleItemId::FreeFunction(_) | ModuleItemId::Submodule(_))
+                | LookupItemId::ImplItem(ImplItemId::Function(_))
+                | LookupItemId::TraitItem(TraitItemId::Function(_))
+        )
+    );
+    let inner = node.filter(|_| has_inner_comments).and_then(|node| {
+        let text = node.get_text_without_inner_commentable_children(db.upcast());
+        let lines =
+            text.lines().filter(|line| !line.trim().is_empty()).skip_while(|line| is_comment(line));
+        doc_comments(lines, "//!")
+    });
+
+    let docs = [module_level, outer, inner].into_iter().flatten().collect::<Vec<_>>();
+    (!docs.is_empty()).then(|| format!("{}\n", docs.iter().map(|doc| doc.trim_end()).join(" ")))
+}
+
+/// Returns the lines of comments at the beginning of the text, skipping empty lines.
+fn leading_comments(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().filter(|line| !line.trim().is_empty()).take_while(|line| is_comment(line))
+}
+
+/// Checks whether the line is a comment.
+fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with("//")
+}
@@ -1,5 +1,6 @@
 use cairo_lang_defs::ids::{FunctionWithBodyId, LookupItemId};
 use cairo_lang_diagnostics::ToOption;
+use cairo_lang_filesystem::ids::FileId;
 use cairo_lang_semantic::Mutability;
 use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
@@ -13,28 +14,47 @@ use lsp_types::Hover;
 use crate::ide::hover::markdown_contents;
 use crate::ide::hover::render::markdown::{RULE, fenced_code_block};
 use crate::lang::db::{AnalysisDatabase, LsSemanticGroup};
+use crate::lang::lsp::ToLsp;
 
 /// Legacy hover rendering backported from Cairo 2.6.3 codebase.
 ///
 /// This logic is meant for gradual replacement with new-style hovers and eventually be removed.
-pub fn legacy(db: &AnalysisDatabase, identifier: &TerminalIdentifier) -> Option<Hover> {
+///
+/// The range of the hover is the span of the pattern or expression whose type is shown, or of the
+/// identifier itself.
+pub fn legacy(
+    db: &AnalysisDatabase,
+    identifier: &TerminalIdentifier,
+    file_id: FileId,
+) -> Option<Hover> {
     let node = identifier.as_syntax_node();
     let lookup_item_id = db.find_lookup_item(&node)?;
     let function_id = lookup_item_id.function_with_body()?;
 
     // Build texts.
     let mut hints = Vec::new();
-    if let Some(hint) = get_pattern_hint(db, function_id, node.clone()) {
+    let mut hinted_node = node.clone();
+    if let Some((hint, pattern_node)) = get_pattern_hint(db, function_id, node.clone()) {
         hints.push(hint);
-    } else if let Some(hint) = get_expr_hint(db, function_id, node.clone()) {
+        hinted_node = pattern_node;
+    } else if let Some((hint, expr_node)) = get_expr_hint(db, function_id, node.clone()) {
         hints.push(hint);
+        hinted_node = expr_node;
     };
     if let Some(hint) = get_identifier_hint(db, lookup_item_id, node) {
         hints.push(hint);
     };
 
+    if hints.is_empty() {
+        return None;
+    }
+
     let hints = hints.join(RULE);
-    Some(Hover { contents: markdown_contents(hints), range: None })
+    let range = hinted_node
+        .span_without_trivia(db.upcast())
+        .position_in_file(db.upcast(), file_id)
+        .map(|p| p.to_lsp(db, file_id));
+    Some(Hover { contents: markdown_contents(hints), range })
 }
 
 /// If the node is an identifier, retrieves a hover hint for it.
@@ -55,13 +75,14 @@ fn get_identifier_hint(
     Some(format!("`{}`", item.full_path(db)))
 }
 
-/// If the node is an expression, retrieves a hover hint for it.
+/// If the node is an expression, retrieves a hover hint for it, along with the node of the
+/// expression.
 fn get_expr_hint(
     db: &AnalysisDatabase,
     function_id: FunctionWithBodyId,
     node: SyntaxNode,
-) -> Option<String> {
-    let semantic_expr = nearest_semantic_expr(db, node, function_id)?;
+) -> Option<(String, SyntaxNode)> {
+    let (semantic_expr, expr_node) = nearest_semantic_expr(db, node, function_id)?;
     let text = match semantic_expr {
         cairo_lang_semantic::Expr::FunctionCall(call) => {
             let args = if let Ok(signature) =
@@ -95,15 +116,15 @@ fn get_expr_hint(
         _ => semantic_expr.ty().format(db),
     };
     // Format the hover text.
-    Some(fenced_code_block(&text))
+    Some((fenced_code_block(&text), expr_node))
 }
 
-/// Returns the semantic expression for the current node.
+/// Returns the semantic expression for the current node, along with its syntax node.
 fn nearest_semantic_expr(
     db: &AnalysisDatabase,
     mut node: SyntaxNode,
     function_id: FunctionWithBodyId,
-) -> Option<cairo_lang_semantic::Expr> {
+) -> Option<(cairo_lang_semantic::Expr, SyntaxNode)> {
     loop {
         let syntax_db = db.upcast();
         if Expr::is_variant(node.kind(syntax_db)) {
@@ -112,30 +133,35 @@ fn nearest_semantic_expr(
                 db.lookup_expr_by_ptr(function_id, expr_node.stable_ptr()).to_option()
             {
                 let semantic_expr = db.expr_semantic(function_id, expr_id);
-                return Some(semantic_expr);
+                return Some((semantic_expr, node));
             }
         }
+        // Arguments of inline macros are only analysed in the expanded code, so any expression
+        // containing the macro call would be unrelated to the node.
+        if node.kind(syntax_db) == SyntaxKind::ExprInlineMacro {
+            return None;
+        }
         node = node.parent()?;
     }
 }
 
-/// If the node is a pattern, retrieves a hover hint for it.
+/// If the node is a pattern, retrieves a hover hint for it, along with the node of the pattern.
 fn get_pattern_hint(
     db: &AnalysisDatabase,
     function_id: FunctionWithBodyId,
     node: SyntaxNode,
-) -> Option<String> {
-    let semantic_pattern = nearest_semantic_pat(db, node, function_id)?;
+) -> Option<(String, SyntaxNode)> {
+    let (semantic_pattern, pattern_node) = nearest_semantic_pat(db, node, function_id)?;
     // Format the hover text.
-    Some(format!("Type: `{}`", semantic_pattern.ty().format(db)))
+    Some((format!("Type: `{}`", semantic_pattern.ty().format(db)), pattern_node))
 }
 
-/// Returns the semantic pattern for the current node.
+/// Returns the semantic pattern for the current node, along with its syntax node.
 fn nearest_semantic_pat(
     db: &AnalysisDatabase,
     mut node: SyntaxNode,
     function_id: FunctionWithBodyId,
-) -> Option<cairo_lang_semantic::Pattern> {
+) -> Option<(cairo_lang_semantic::Pattern, SyntaxNode)> {
     loop {
         let syntax_db = db.upcast();
         if Pattern::is_variant(node.kind(syntax_db)) {
@@ -144,7 +170,7 @@ fn nearest_semantic_pat(
                 db.lookup_pattern_by_ptr(function_id, pattern_node.stable_ptr()).to_option()
             {
                 let semantic_pattern = db.pattern_semantic(function_id, pattern_id);
-                return Some(semantic_pattern);
+                return Some((semantic_pattern, node));
             }
         }
         node = node.parent()?;
@@ -101,7 +101,7 @@ fn number_hover(
             .as_syntax_node()
             .span_without_trivia(db.upcast())
             .position_in_file(db.upcast(), file_id)
-            .map(|position| position.to_lsp()),
+            .map(|position| position.to_lsp(db, file_id)),
     })
 }
 
@@ -126,7 +126,7 @@ fn string_hover(
             .as_syntax_node()
             .span_without_trivia(db.upcast())
             .position_in_file(db.upcast(), file_id)
-            .map(|position| position.to_lsp()),
+            .map(|position| position.to_lsp(db, file_id)),
     })
 }
 
@@ -166,6 +166,6 @@ fn short_string_hover(
             .as_syntax_node()
             .span_without_trivia(db.upcast())
             .position_in_file(db.upcast(), file_id)
-            .map(|position| position.to_lsp()),
+            .map(|position| position.to_lsp(db, file_id)),
     })
 }
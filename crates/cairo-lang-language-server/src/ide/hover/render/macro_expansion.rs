@@ -0,0 +1,19 @@
+use lsp_types::{Hover, HoverContents, MarkupContent};
+
+use crate::ide::hover::markdown_contents;
+use crate::ide::hover::render::markdown::{RULE, collapsible, fenced_code_block};
+
+/// Append the expansion of a hovered macro call to the hover, or build a hover with just the
+/// expansion if there is nothing else to show.
+pub fn macro_expansion(hover: Option<Hover>, expansion: &str) -> Hover {
+    let (mut md, range) = match hover {
+        Some(Hover { contents: HoverContents::Markup(MarkupContent { value, .. }), range }) => {
+            (format!("{}\n\n{RULE}", value.trim_end()), range)
+        }
+        Some(Hover { range, .. }) => (String::new(), range),
+        None => (String::new(), None),
+    };
+    md += &collapsible("Expansion", &fenced_code_block(expansion));
+
+    Hover { contents: markdown_contents(md), range }
+}
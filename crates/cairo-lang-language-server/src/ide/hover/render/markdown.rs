@@ -9,3 +9,92 @@ pub const RULE: &str = "---\n";
 pub fn fenced_code_block(code: &str) -> String {
     format!("```cairo\n{code}\n```\n")
 }
+
+/// Wrap the Markdown content in a block which is collapsed under the given summary.
+pub fn collapsible(summary: &str, content: &str) -> String {
+    format!("<details>\n<summary>{summary}</summary>\n\n{content}</details>\n")
+}
+
+/// Build Markdown documentation from lines of doc comments starting with the given marker, e.g.
+/// `///` or `//!`, skipping other lines.
+///
+/// The markers and a single space following them are stripped, while line breaks are kept, so that
+/// paragraphs, headings, lists and code blocks of the documentation are preserved.
+/// Fenced code blocks which do not specify a language are marked as Cairo.
+/// Returns `None` if the documentation is empty.
+pub fn doc_comments<'a>(lines: impl IntoIterator<Item = &'a str>, marker: &str) -> Option<String> {
+    let mut md = String::new();
+    let mut in_code_block = false;
+    for line in lines {
+        let Some(line) = line.trim_start().strip_prefix(marker) else {
+            continue;
+        };
+        let line = line.strip_prefix(' ').unwrap_or(line);
+        if line.trim_start().starts_with("```") {
+            if !in_code_block && line.trim() == "```" {
+                md.push_str("```cairo");
+            } else {
+                md.push_str(line);
+            }
+            in_code_block = !in_code_block;
+        } else {
+            md.push_str(line);
+        }
+        md.push('\n');
+    }
+    let md = md.trim_end();
+    (!md.trim_start().is_empty()).then(|| format!("{md}\n"))
+}
+
+/// Strip Markdown formatting from the given text, for clients which only support plain text.
+///
+/// Fences of code blocks and markers of headings are removed, horizontal rules are replaced with
+/// empty lines, backticks of inline code are removed outside of code blocks and HTML tags are
+/// removed.
+pub fn strip_markdown(md: &str) -> String {
+    let mut text = String::new();
+    let mut in_code_block = false;
+    for line in md.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            text.push_str(line);
+        } else if line == RULE.trim_end() {
+            // An empty line separates the sections.
+        } else if line.starts_with('<') && line.ends_with('>') {
+            // HTML blocks, like collapsible sections, are reduced to their text.
+            let line = strip_html_tags(line);
+            if line.is_empty() {
+                continue;
+            }
+            text.push_str(&line);
+        } else {
+            let line = match line.trim_start_matches('#') {
+                heading if heading.len() < line.len() && heading.starts_with(' ') => {
+                    heading.trim_start()
+                }
+                _ => line,
+            };
+            text.push_str(&line.replace('`', ""));
+        }
+        text.push('\n');
+    }
+    text.trim_end().to_string()
+}
+
+/// Removes HTML tags from the line, keeping the text between them.
+fn strip_html_tags(line: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
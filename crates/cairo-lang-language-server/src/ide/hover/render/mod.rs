@@ -1,8 +1,13 @@
+pub use self::attribute::*;
 pub use self::definition::*;
 pub use self::legacy::*;
 pub use self::literal::*;
+pub use self::macro_expansion::*;
+pub use self::markdown::strip_markdown;
 
+mod attribute;
 mod definition;
 mod legacy;
 mod literal;
+mod macro_expansion;
 mod markdown;
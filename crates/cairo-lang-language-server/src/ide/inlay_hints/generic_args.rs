@@ -0,0 +1,96 @@
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
+use cairo_lang_semantic::lookup_item::LookupItemEx;
+use cairo_lang_semantic::{Expr, ExprFunctionCallArg, GenericArgumentId};
+use cairo_lang_syntax::node::{TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use itertools::Itertools;
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel};
+
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup};
+use crate::lang::lsp::ToLsp;
+
+/// Creates an inlay hint with generic arguments inferred for a call of a generic function, placed
+/// right after the callee path.
+///
+/// Returns `None` if the call spells out generic arguments explicitly, if they could not be
+/// inferred, or if they are trivially obvious, i.e. each of them is the type of one of the call
+/// arguments (like in `identity(5_u32)`).
+pub fn generic_args_hint(
+    db: &AnalysisDatabase,
+    file: FileId,
+    call: ast::ExprFunctionCall,
+) -> Option<InlayHint> {
+    let syntax_db = db.upcast();
+    let path = call.path(syntax_db);
+    if path
+        .elements(syntax_db)
+        .iter()
+        .any(|segment| matches!(segment, ast::PathSegment::WithGenericArgs(_)))
+    {
+        return None;
+    }
+
+    let function_with_body = db.find_lookup_item(&call.as_syntax_node())?.function_with_body()?;
+    let expr_id = db.lookup_expr_by_ptr(function_with_body, call.stable_ptr().into()).ok()?;
+    let Expr::FunctionCall(expr) = db.expr_semantic(function_with_body, expr_id) else {
+        return None;
+    };
+
+    // Impl arguments are not shown, as they are almost never interesting to the user.
+    let generic_args = expr
+        .function
+        .get_concrete(db)
+        .generic_args
+        .into_iter()
+        .filter(|arg| !matches!(arg, GenericArgumentId::Impl(_) | GenericArgumentId::NegImpl))
+        .collect::<Vec<_>>();
+    if generic_args.is_empty() {
+        return None;
+    }
+
+    let arg_types = expr
+        .args
+        .iter()
+        .map(|arg| match arg {
+            ExprFunctionCallArg::Reference(member_path) => member_path.ty(),
+            ExprFunctionCallArg::Value(expr_id) => {
+                db.expr_semantic(function_with_body, *expr_id).ty()
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut trivially_obvious = true;
+    for arg in &generic_args {
+        match arg {
+            GenericArgumentId::Type(ty) => {
+                if ty.is_missing(db) || !ty.is_var_free(db) {
+                    return None;
+                }
+                trivially_obvious &= arg_types.contains(ty);
+            }
+            _ => trivially_obvious = false,
+        }
+    }
+    if trivially_obvious {
+        return None;
+    }
+
+    let position = path
+        .as_syntax_node()
+        .span_end_without_trivia(syntax_db)
+        .position_in_file(db.upcast(), file)?
+        .to_lsp(db, file);
+    let label = format!("::<{}>", generic_args.iter().map(|arg| arg.format(db)).join(", "));
+
+    Some(InlayHint {
+        position,
+        label: InlayHintLabel::String(label),
+        kind: Some(InlayHintKind::TYPE),
+        text_edits: None,
+        tooltip: None,
+        padding_left: None,
+        padding_right: None,
+        data: None,
+    })
+}
@@ -0,0 +1,55 @@
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
+use cairo_lang_semantic::lookup_item::LookupItemEx;
+use cairo_lang_syntax::node::{TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel};
+
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup};
+use crate::lang::lsp::ToLsp;
+
+/// Creates an inlay hint with the type inferred for a `let` statement without an explicit type,
+/// placed right after the pattern.
+///
+/// Returns `None` if the statement has an explicit type, binds nothing (like `let _ = ...`), or if
+/// the type could not be inferred.
+pub fn let_type_hint(
+    db: &AnalysisDatabase,
+    file: FileId,
+    statement: ast::StatementLet,
+) -> Option<InlayHint> {
+    let syntax_db = db.upcast();
+    if matches!(statement.type_clause(syntax_db), ast::OptionTypeClause::TypeClause(_)) {
+        return None;
+    }
+    let pattern = statement.pattern(syntax_db);
+    if matches!(pattern, ast::Pattern::Underscore(_)) {
+        return None;
+    }
+
+    let function_with_body =
+        db.find_lookup_item(&statement.as_syntax_node())?.function_with_body()?;
+    let pattern_id = db.lookup_pattern_by_ptr(function_with_body, pattern.stable_ptr()).ok()?;
+    let ty = db.pattern_semantic(function_with_body, pattern_id).ty();
+    if ty.is_missing(db) || !ty.is_var_free(db) {
+        return None;
+    }
+
+    let position = pattern
+        .as_syntax_node()
+        .span_end_without_trivia(syntax_db)
+        .position_in_file(db.upcast(), file)?
+        .to_lsp(db, file);
+
+    Some(InlayHint {
+        position,
+        label: InlayHintLabel::String(format!(": {}", ty.format(db))),
+        kind: Some(InlayHintKind::TYPE),
+        text_edits: None,
+        tooltip: None,
+        padding_left: None,
+        padding_right: None,
+        data: None,
+    })
+}
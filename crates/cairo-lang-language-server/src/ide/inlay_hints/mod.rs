@@ -0,0 +1,54 @@
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::{InlayHint, InlayHintParams};
+use tracing::error;
+
+use crate::config::Config;
+use crate::lang::db::AnalysisDatabase;
+use crate::lang::lsp::{LsProtoGroup, ToCairo};
+
+mod generic_args;
+mod let_types;
+
+/// Compute inlay hints for the requested range of a file.
+pub fn inlay_hints(
+    params: InlayHintParams,
+    db: &AnalysisDatabase,
+    config: &Config,
+) -> Option<Vec<InlayHint>> {
+    let syntax_db = db.upcast();
+    let file_uri = params.text_document.uri;
+    let file = db.file_for_url(&file_uri)?;
+    let Ok(root) = db.file_syntax(file) else {
+        error!("inlay hints failed: file '{file_uri}' does not exist");
+        return None;
+    };
+
+    let range = params.range.to_cairo(db, file).offset_in_file(db.upcast(), file)?;
+
+    // Subtrees outside the requested range are skipped, so that their semantics are not resolved.
+    let mut hints = vec![];
+    let mut nodes = vec![root];
+    while let Some(node) = nodes.pop() {
+        let span = node.span_without_trivia(syntax_db);
+        if span.end < range.start || range.end < span.start {
+            continue;
+        }
+        match node.kind(syntax_db) {
+            SyntaxKind::ExprFunctionCall if config.inlay_hints_generic_args => {
+                let call = ast::ExprFunctionCall::from_syntax_node(syntax_db, node.clone());
+                hints.extend(generic_args::generic_args_hint(db, file, call));
+            }
+            SyntaxKind::StatementLet if config.inlay_hints_type_hints => {
+                let statement = ast::StatementLet::from_syntax_node(syntax_db, node.clone());
+                hints.extend(let_types::let_type_hint(db, file, statement));
+            }
+            _ => {}
+        }
+        nodes.extend(syntax_db.get_children(node).iter().rev().cloned());
+    }
+
+    Some(hints)
+}
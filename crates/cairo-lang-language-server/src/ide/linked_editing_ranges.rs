@@ -0,0 +1,56 @@
+use cairo_lang_syntax::node::ast::ModuleItem;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, Terminal, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::{LinkedEditingRangeParams, LinkedEditingRanges};
+
+use crate::ide::navigation::references::resolve_identifier;
+use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Pattern of identifiers, which the ranges must keep matching while they are edited.
+const IDENTIFIER_PATTERN: &str = "[a-zA-Z_][a-zA-Z0-9_]*";
+
+/// Find the ranges of identifiers which should be edited together with the identifier at a given
+/// text document position.
+///
+/// These are the occurrences of the same name referring to the same symbol within the outermost
+/// item containing the position, like a function or an impl, e.g. all usages of a generic
+/// parameter. Occurrences outside the item are not searched to keep the request fast.
+pub fn linked_editing_ranges(
+    params: LinkedEditingRangeParams,
+    db: &AnalysisDatabase,
+) -> Option<LinkedEditingRanges> {
+    let syntax_db = db.upcast();
+    let file = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
+    let position = params.text_document_position_params.position.to_cairo(db, file);
+    let identifier = db.find_identifier_at_position(file, position)?;
+    let target = resolve_identifier(db, &identifier)?;
+    let name = identifier.text(syntax_db);
+
+    let ranges = outermost_item(db, identifier.as_syntax_node())?
+        .descendants(syntax_db)
+        .filter(|node| node.kind(syntax_db) == SyntaxKind::TerminalIdentifier)
+        .map(|node| ast::TerminalIdentifier::from_syntax_node(syntax_db, node))
+        .filter(|other| {
+            other.text(syntax_db) == name && resolve_identifier(db, other) == Some(target)
+        })
+        .filter_map(|other| {
+            let span = other.as_syntax_node().span_without_trivia(syntax_db);
+            Some(span.position_in_file(db.upcast(), file)?.to_lsp(db, file))
+        })
+        .collect::<Vec<_>>();
+
+    // A single occurrence has nothing to be edited together with.
+    (ranges.len() > 1)
+        .then(|| LinkedEditingRanges { ranges, word_pattern: Some(IDENTIFIER_PATTERN.into()) })
+}
+
+/// Finds the outermost item containing the node, without leaving the module it is defined in.
+fn outermost_item(db: &AnalysisDatabase, node: SyntaxNode) -> Option<SyntaxNode> {
+    let syntax_db = db.upcast();
+    std::iter::successors(Some(node), SyntaxNode::parent)
+        .take_while(|node| node.kind(syntax_db) != SyntaxKind::ItemModule)
+        .filter(|node| ModuleItem::is_variant(node.kind(syntax_db)))
+        .last()
+}
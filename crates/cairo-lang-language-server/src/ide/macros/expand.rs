@@ -2,40 +2,250 @@ use std::collections::VecDeque;
 use std::sync::Arc;
 
 use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::ModuleId;
 use cairo_lang_defs::plugin::MacroPluginMetadata;
 use cairo_lang_diagnostics::DiagnosticsBuilder;
-use cairo_lang_filesystem::db::FilesGroup;
-use cairo_lang_filesystem::ids::{FileId, FileKind, FileLongId, VirtualFile};
-use cairo_lang_filesystem::span::{TextSpan, TextWidth};
+use cairo_lang_filesystem::cfg::CfgSet;
+use cairo_lang_filesystem::db::{Edition, FilesGroup, get_originating_location};
+use cairo_lang_filesystem::ids::{
+    CodeMapping, CodeOrigin, CrateId, FileId, FileKind, FileLongId, VirtualFile,
+};
+use cairo_lang_filesystem::span::{TextOffset, TextPosition, TextSpan, TextWidth};
 use cairo_lang_formatter::FormatterConfig;
 use cairo_lang_parser::db::ParserGroup;
 use cairo_lang_parser::parser::Parser;
 use cairo_lang_parser::utils::SimpleParserDatabase;
-use cairo_lang_syntax::node::ast::{ExprInlineMacro, ModuleItemList};
+use cairo_lang_syntax::node::ast::{ExprInlineMacro, ModuleItem, ModuleItemList};
+use cairo_lang_syntax::node::helpers::QueryAttrs;
 use cairo_lang_syntax::node::kind::SyntaxKind;
 use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode};
-use cairo_lang_utils::Intern;
+use cairo_lang_utils::{Intern, LookupIntern};
 use indoc::formatdoc;
-use lsp_types::TextDocumentPositionParams;
+use lsp_types::{Range, TextDocumentPositionParams, TextEdit, Url};
 
+use crate::ide::utils::line_indent;
 use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
-use crate::lang::lsp::{LsProtoGroup, ToCairo};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp, position_at};
+use crate::lsp::ext::{ExpandMacroResponse, ExpansionCodeMapping};
 
-/// Tries to expand macro, returns it as string.
-pub fn expand_macro(db: &AnalysisDatabase, params: &TextDocumentPositionParams) -> Option<String> {
+/// Tries to expand macro, returns it along with mappings of the expanded code to the code of the
+/// document it was generated from.
+pub fn expand_macro_with_mappings(
+    db: &AnalysisDatabase,
+    params: &TextDocumentPositionParams,
+) -> Option<ExpandMacroResponse> {
+    let file_id = db.file_for_url(&params.text_document.uri)?;
+    let node = db.find_syntax_node_at_position(file_id, params.position.to_cairo(db, file_id))?;
+
+    let expansion = expand_macro_at_node(db, node, false)?;
+    let code_mappings = expansion.code_mappings(db, file_id);
+    Some(ExpandMacroResponse { expansion: expansion.text, code_mappings })
+}
+
+/// Expands the macro called at the given position, returns it as string.
+///
+/// Unlike [`expand_macro_with_mappings`], this returns `None` unless the position is at the name of
+/// an inline macro or at an attribute of a module item, for which some plugin generates code.
+pub fn expand_macro_call(
+    db: &AnalysisDatabase,
+    params: &TextDocumentPositionParams,
+) -> Option<String> {
     let file_id = db.file_for_url(&params.text_document.uri)?;
-    let node = db.find_syntax_node_at_position(file_id, params.position.to_cairo())?;
+    let node = db.find_syntax_node_at_position(file_id, params.position.to_cairo(db, file_id))?;
+
+    if let Some(macro_child) =
+        db.first_ancestor_of_kind_respective_child(node.clone(), SyntaxKind::ExprInlineMacro)
+    {
+        let is_macro_name =
+            matches!(macro_child.kind(db), SyntaxKind::ExprPath | SyntaxKind::TerminalNot);
+        return if is_macro_name {
+            expand_macro_at_node(db, node, true).map(|expansion| expansion.text)
+        } else {
+            None
+        };
+    }
+
+    let attribute = db.first_ancestor_of_kind(node.clone(), SyntaxKind::Attribute)?;
+    let item =
+        db.first_ancestor_of_kind_respective_child(node.clone(), SyntaxKind::ModuleItemList)?;
+    // Only attributes of module items are handled by macro plugins.
+    if attribute.parent()?.parent()? != item {
+        return None;
+    }
+
+    expand_macro_at_node(db, node, true).map(|expansion| expansion.text)
+}
+
+/// URI scheme of virtual documents holding macro expansions.
+///
+/// Apart from the scheme and the query holding the position of the expanded macro call, the URIs
+/// are the same as of other virtual files.
+pub const EXPANSION_SCHEME: &str = "cairo-expand";
+
+/// Tries to expand macro, returns the URI of a virtual document holding the expansion.
+///
+/// The document is backed by a virtual file keyed on the source file and the position of the
+/// macro call, see [`expansion_file`]. Its content is expanded anew each time it is provided by
+/// [`expansion_document_content`], and once opened, the client keeps it in sync with the analysis
+/// database like any other document.
+pub fn expansion_document_url(
+    db: &AnalysisDatabase,
+    params: &TextDocumentPositionParams,
+) -> Option<Url> {
+    let file_id = db.file_for_url(&params.text_document.uri)?;
+    let node = db.find_syntax_node_at_position(file_id, params.position.to_cairo(db, file_id))?;
+    let (call, top_level_macro_kind) = find_macro_call(db, node)?;
+    // Only return URIs of documents that have something to show.
+    if expand_macro_call_node(db, call.clone(), top_level_macro_kind, false)?.text.is_empty() {
+        return None;
+    }
+
+    let position = call.span_start_without_trivia(db).position_in_file(db, file_id)?;
+    let mut url = db.url_for_file(expansion_file(db, file_id, position, top_level_macro_kind))?;
+    url.set_scheme(EXPANSION_SCHEME).ok()?;
+    url.query_pairs_mut()
+        .append_pair("line", &position.line.to_string())
+        .append_pair("col", &position.col.to_string());
+    Some(url)
+}
+
+/// Returns the virtual file holding the expansion of the macro called at the position in the file.
+///
+/// The file is keyed on the source file and the position only, so its content is empty unless
+/// the client opens the document holding it.
+fn expansion_file(
+    db: &AnalysisDatabase,
+    file: FileId,
+    call_position: TextPosition,
+    top_level_macro_kind: TopLevelMacroKind,
+) -> FileId {
+    let name = file.file_name(db);
+    let TextPosition { line, col } = call_position;
+    FileLongId::Virtual(VirtualFile {
+        parent: Some(file),
+        name: format!("{}_expanded_{line}_{col}", name.trim_end_matches(".cairo")).into(),
+        content: "".into(),
+        code_mappings: [].into(),
+        kind: match top_level_macro_kind {
+            TopLevelMacroKind::Inline => FileKind::Expr,
+            TopLevelMacroKind::Attribute => FileKind::Module,
+        },
+    })
+    .intern(db)
+}
+
+/// Finds the macro call expanded in the document with the given URI, see
+/// [`expansion_document_url`].
+///
+/// Returns `None` if the URI is not of an expansion document, or no macro is called at the
+/// position of the document anymore.
+fn find_expansion_document_call(
+    db: &AnalysisDatabase,
+    url: &Url,
+) -> Option<(FileId, SyntaxNode, TopLevelMacroKind)> {
+    if url.scheme() != EXPANSION_SCHEME {
+        return None;
+    }
+    let (mut line, mut col) = (None, None);
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "line" => line = value.parse().ok(),
+            "col" => col = value.parse().ok(),
+            _ => {}
+        }
+    }
+    let position = TextPosition { line: line?, col: col? };
+    let file = db.file_for_url(url)?;
+    let FileLongId::Virtual(VirtualFile { parent: Some(source), .. }) = file.lookup_intern(db)
+    else {
+        return None;
+    };
+
+    let node = db.find_syntax_node_at_position(source, position)?;
+    let (call, top_level_macro_kind) = find_macro_call(db, node)?;
+    let call_position = call.span_start_without_trivia(db).position_in_file(db, source)?;
+    (call_position == position
+        && expansion_file(db, source, position, top_level_macro_kind) == file)
+        .then_some((source, call, top_level_macro_kind))
+}
 
+/// Expands the macro of the document with the given URI, returns it as string.
+///
+/// See [`expansion_document_url`].
+pub fn expansion_document_content(db: &AnalysisDatabase, url: &Url) -> Option<String> {
+    let (_, call, top_level_macro_kind) = find_expansion_document_call(db, url)?;
+    expand_macro_call_node(db, call, top_level_macro_kind, false).map(|expansion| expansion.text)
+}
+
+/// Maps a position in a macro expansion document to the code it was copied from.
+///
+/// The expansion is not part of any module, so symbols in it are resolved at their origin.
+/// Returns `None` if the document is not an expansion, or the position is in generated code.
+pub fn expansion_origin(
+    db: &AnalysisDatabase,
+    url: &Url,
+    position: TextPosition,
+) -> Option<(FileId, TextPosition)> {
+    let (source, call, top_level_macro_kind) = find_expansion_document_call(db, url)?;
+    let offset = position.offset_in_file(db, db.file_for_url(url)?)?;
+
+    let expansion = expand_macro_call_node(db, call, top_level_macro_kind, false)?;
+    let (generated, origin) = expansion
+        .token_mappings(db, source)
+        .into_iter()
+        .find(|(generated, _)| generated.start <= offset && offset <= generated.end)?;
+    // Tokens copied verbatim map offsets within them, generated ones map to the whole origin.
+    let origin = if generated.width() == origin.width() {
+        origin.start.add_width(offset - generated.start)
+    } else {
+        origin.start
+    };
+    Some((source, origin.position_in_file(db, source)?))
+}
+
+/// A macro call which can be replaced in the source with its expansion.
+pub enum InPlaceMacroCall {
+    /// A call of an inline macro.
+    Inline(ExprInlineMacro),
+    /// A module item with attributes, which may be handled by macro plugins.
+    Item(ModuleItem),
+}
+
+/// Finds the macro call which the node is part of and which can be expanded in place.
+///
+/// That is the innermost inline macro containing the node, or the module item to which an
+/// attribute containing the node is attached.
+pub fn find_in_place_macro_call(
+    db: &AnalysisDatabase,
+    node: SyntaxNode,
+) -> Option<InPlaceMacroCall> {
+    if let Some(inline_macro) = db.first_ancestor_of_kind(node.clone(), SyntaxKind::ExprInlineMacro)
+    {
+        return Some(InPlaceMacroCall::Inline(ExprInlineMacro::from_syntax_node(db, inline_macro)));
+    }
+
+    let attribute = db.first_ancestor_of_kind(node.clone(), SyntaxKind::Attribute)?;
+    let item = db.first_ancestor_of_kind_respective_child(node, SyntaxKind::ModuleItemList)?;
+    // Only attributes of module items are handled by macro plugins.
+    if attribute.parent()?.parent()? != item {
+        return None;
+    }
+    Some(InPlaceMacroCall::Item(ModuleItem::from_syntax_node(db, item)))
+}
+
+/// Returns an edit replacing the macro call with the code it expands to.
+///
+/// The macro is expanded one level deep: macros called in the generated code are kept, to be
+/// expanded when the code is compiled. An item is replaced with the code generated for it by
+/// plugins, preceded by the item itself without the attributes of these plugins, unless some
+/// plugin removes the original item.
+pub fn expand_macro_in_place(db: &AnalysisDatabase, call: InPlaceMacroCall) -> Option<TextEdit> {
+    let node = match &call {
+        InPlaceMacroCall::Inline(inline_macro) => inline_macro.as_syntax_node(),
+        InPlaceMacroCall::Item(item) => item.as_syntax_node(),
+    };
     let module_id = db.find_module_file_containing_node(&node)?.0;
-    let crate_id = module_id.owning_crate(db);
-    let cfg_set = db
-        .crate_config(crate_id)
-        .and_then(|cfg| cfg.settings.cfg_set.map(Arc::new))
-        .unwrap_or(db.cfg_set());
-    let edition = db
-        .crate_config(module_id.owning_crate(db))
-        .map(|cfg| cfg.settings.edition)
-        .unwrap_or_default();
+    let (cfg_set, edition) = crate_macro_settings(db, module_id.owning_crate(db));
 
     let metadata = MacroPluginMetadata {
         cfg_set: &cfg_set,
@@ -44,30 +254,123 @@ pub fn expand_macro(db: &AnalysisDatabase, params: &TextDocumentPositionParams)
         edition,
     };
 
-    let module_file = db.module_main_file(module_id).ok()?;
-
-    let item_ast_node =
-        db.first_ancestor_of_kind_respective_child(node.clone(), SyntaxKind::ModuleItemList);
-    let macro_ast_node = db.first_ancestor_of_kind(node.clone(), SyntaxKind::ExprInlineMacro);
+    let text = match &call {
+        InPlaceMacroCall::Inline(inline_macro) => {
+            let code = db
+                .inline_macro_plugins()
+                .get(&inline_macro.path(db).as_syntax_node().get_text_without_trivia(db))?
+                .generate_code(db, inline_macro, &metadata)
+                .code?;
+            format_output(&code.content, TopLevelMacroKind::Inline)
+        }
+        InPlaceMacroCall::Item(item) => {
+            let mut keep_original_item = true;
+            let mut expanded_attributes = vec![];
+            let mut generated = vec![];
+            for plugin in db.macro_plugins() {
+                let result = plugin.generate_code(db, item.clone(), &metadata);
+                if let Some(code) = result.code {
+                    expanded_attributes.extend(plugin.declared_attributes());
+                    generated.push(code.content);
+                }
+                if result.remove_original_item {
+                    keep_original_item = false;
+                    break;
+                }
+            }
+            if generated.is_empty() && keep_original_item {
+                return None;
+            }
 
-    let (node_to_expand, top_level_macro_kind) = match (item_ast_node, macro_ast_node) {
-        (Some(item_ast_node), Some(macro_ast_node)) => {
-            if node_depth(item_ast_node.clone()) > node_depth(macro_ast_node.clone()) {
-                (item_ast_node, TopLevelMacroKind::Attribute)
-            } else {
-                (macro_ast_node, TopLevelMacroKind::Inline)
+            let mut output = vec![];
+            if keep_original_item {
+                output.push(strip_attributes(db, item, &expanded_attributes));
             }
+            output.extend(generated);
+            format_output(&output.join("\n\n"), TopLevelMacroKind::Attribute)
         }
-        (Some(item_ast_node), None) => (item_ast_node, TopLevelMacroKind::Attribute),
-        (None, Some(macro_ast_node)) => (macro_ast_node, TopLevelMacroKind::Inline),
-        (None, None) => return None,
     };
 
+    let file_id = node.stable_ptr().file_id(db);
+    let span = node.span_without_trivia(db);
+    let range = span.position_in_file(db, file_id)?;
+    let indent = line_indent(db, file_id, range.start.line)?;
+    let new_text =
+        text.lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 0 || line.is_empty() { line.to_owned() } else { format!("{indent}{line}") }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+    Some(TextEdit { range: range.to_lsp(db, file_id), new_text })
+}
+
+/// Returns the text of the item without the attributes with the given names.
+fn strip_attributes(db: &AnalysisDatabase, item: &ModuleItem, names: &[String]) -> String {
+    let node = item.as_syntax_node();
+    let start = node.span_start_without_trivia(db);
+    let mut text = node.get_text_without_trivia(db);
+    let mut spans = names
+        .iter()
+        .flat_map(|name| item.query_attr(db, name))
+        .map(|attribute| attribute.as_syntax_node().span_without_trivia(db))
+        .collect::<Vec<_>>();
+    spans.sort_by_key(|span| span.start);
+    // Remove from the end, so the spans of the remaining attributes stay valid.
+    for span in spans.into_iter().rev() {
+        let relative = TextSpan {
+            start: TextOffset::default().add_width(span.start - start),
+            end: TextOffset::default().add_width(span.end - start),
+        };
+        text.replace_range(relative.to_str_range(), "");
+    }
+    text
+}
+
+/// Expands the macro containing the node.
+///
+/// If `require_generated_code` is set, attribute macros which do not generate any code are not
+/// expanded.
+fn expand_macro_at_node(
+    db: &AnalysisDatabase,
+    node: SyntaxNode,
+    require_generated_code: bool,
+) -> Option<Expansion> {
+    let (node_to_expand, top_level_macro_kind) = find_macro_call(db, node)?;
+    expand_macro_call_node(db, node_to_expand, top_level_macro_kind, require_generated_code)
+}
+
+/// Expands the macro call found by [`find_macro_call`].
+fn expand_macro_call_node(
+    db: &AnalysisDatabase,
+    node_to_expand: SyntaxNode,
+    top_level_macro_kind: TopLevelMacroKind,
+    require_generated_code: bool,
+) -> Option<Expansion> {
+    let module_id = db.find_module_file_containing_node(&node_to_expand)?.0;
+    let (cfg_set, edition) = crate_macro_settings(db, module_id.owning_crate(db));
+
+    let metadata = MacroPluginMetadata {
+        cfg_set: &cfg_set,
+        declared_derives: &db.declared_derives(),
+        allowed_features: &Default::default(),
+        edition,
+    };
+
+    let module_file = db.module_main_file(module_id).ok()?;
+
     let files = match top_level_macro_kind {
         TopLevelMacroKind::Inline => VecDeque::from([module_file]),
         // If this is attribute or derive macro, it can return many files.
         TopLevelMacroKind::Attribute => {
-            expanded_macro_files(db, module_file, node_to_expand.clone(), &metadata)?
+            let files = expanded_macro_files(db, module_file, node_to_expand.clone(), &metadata)?;
+            // The origin file is always included.
+            if require_generated_code && files.len() == 1 {
+                return None;
+            }
+            files
         }
     };
 
@@ -75,6 +378,197 @@ pub fn expand_macro(db: &AnalysisDatabase, params: &TextDocumentPositionParams)
     expand_inline_macros(db, node_to_expand, files, &metadata, top_level_macro_kind)
 }
 
+/// Finds the macro call to expand for the node, along with its kind.
+///
+/// That is the innermost inline macro containing the node, unless the module item containing the
+/// node is nested deeper.
+fn find_macro_call(
+    db: &AnalysisDatabase,
+    node: SyntaxNode,
+) -> Option<(SyntaxNode, TopLevelMacroKind)> {
+    let item_ast_node =
+        db.first_ancestor_of_kind_respective_child(node.clone(), SyntaxKind::ModuleItemList);
+    let macro_ast_node = db.first_ancestor_of_kind(node, SyntaxKind::ExprInlineMacro);
+
+    match (item_ast_node, macro_ast_node) {
+        (Some(item_ast_node), Some(macro_ast_node)) => {
+            if node_depth(item_ast_node.clone()) > node_depth(macro_ast_node.clone()) {
+                Some((item_ast_node, TopLevelMacroKind::Attribute))
+            } else {
+                Some((macro_ast_node, TopLevelMacroKind::Inline))
+            }
+        }
+        (Some(item_ast_node), None) => Some((item_ast_node, TopLevelMacroKind::Attribute)),
+        (None, Some(macro_ast_node)) => Some((macro_ast_node, TopLevelMacroKind::Inline)),
+        (None, None) => None,
+    }
+}
+
+/// Expands all macros in the main file of the module, returns it as string.
+pub fn expand_module(db: &AnalysisDatabase, module_id: ModuleId) -> Option<String> {
+    let (cfg_set, edition) = crate_macro_settings(db, module_id.owning_crate(db));
+
+    let metadata = MacroPluginMetadata {
+        cfg_set: &cfg_set,
+        declared_derives: &db.declared_derives(),
+        allowed_features: &Default::default(),
+        edition,
+    };
+
+    let module_file = db.module_main_file(module_id).ok()?;
+    let module_syntax = db.file_module_syntax(module_file).ok()?;
+    let items = module_syntax.items(db).elements(db);
+
+    let files = expanded_items_files(db, module_file, items, &metadata)?;
+
+    expand_inline_macros(
+        db,
+        module_syntax.as_syntax_node(),
+        files,
+        &metadata,
+        TopLevelMacroKind::Attribute,
+    )
+    .map(|expansion| expansion.text)
+}
+
+/// Returns the cfg set and edition that macro plugins should use for the crate.
+fn crate_macro_settings(db: &AnalysisDatabase, crate_id: CrateId) -> (Arc<CfgSet>, Edition) {
+    let crate_config = db.crate_config(crate_id);
+    let cfg_set = crate_config
+        .as_ref()
+        .and_then(|cfg| cfg.settings.cfg_set.clone().map(Arc::new))
+        .unwrap_or(db.cfg_set());
+    let edition = crate_config.map(|cfg| cfg.settings.edition).unwrap_or_default();
+    (cfg_set, edition)
+}
+
+/// Code produced by expanding macros.
+struct Expansion {
+    /// The expanded code, formatted.
+    text: String,
+    /// The expanded code, as it was put together, before formatting.
+    raw_text: String,
+    /// Parts of the raw text copied from files produced by the expansion.
+    sections: Vec<ExpansionSection>,
+    top_level_macro_kind: TopLevelMacroKind,
+}
+
+/// A part of the raw expansion text copied from a file.
+struct ExpansionSection {
+    /// Span of the section in the raw expansion text.
+    span: TextSpan,
+    /// The file the section was copied from.
+    file: FileId,
+    /// Offset in the file at which the copied content starts.
+    file_offset: TextOffset,
+}
+
+impl Expansion {
+    /// Maps ranges of the formatted expansion to ranges of the file they were generated from.
+    ///
+    /// The mappings are found for tokens of the expansion, so that formatting does not matter.
+    /// Adjacent tokens copied verbatim, as well as adjacent tokens generated from the same code,
+    /// are merged into a single mapping.
+    fn code_mappings(&self, db: &AnalysisDatabase, file: FileId) -> Vec<ExpansionCodeMapping> {
+        // Pairs of generated and origin spans, with a flag marking spans copied verbatim.
+        let mut mappings: Vec<(TextSpan, TextSpan, bool)> = vec![];
+        for (generated, origin) in self.token_mappings(db, file) {
+            let is_copy = origin.width() == generated.width();
+            match mappings.last_mut() {
+                Some((last_generated, last_origin, last_is_copy))
+                    if *last_origin == origin
+                        || (*last_is_copy && is_copy && last_origin.end <= origin.start) =>
+                {
+                    last_generated.end = generated.end;
+                    last_origin.end = last_origin.end.max(origin.end);
+                }
+                _ => mappings.push((generated, origin, is_copy)),
+            }
+        }
+
+        let encoding = db.position_encoding();
+        mappings
+            .into_iter()
+            .filter_map(|(generated, origin, _)| {
+                let range = generated.to_str_range();
+                Some(ExpansionCodeMapping {
+                    origin: origin.position_in_file(db, file)?.to_lsp(db, file),
+                    generated: Range {
+                        start: position_at(&self.text, range.start, encoding),
+                        end: position_at(&self.text, range.end, encoding),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Maps spans of tokens of the formatted expansion to spans of the file they were generated
+    /// from.
+    fn token_mappings(&self, db: &AnalysisDatabase, file: FileId) -> Vec<(TextSpan, TextSpan)> {
+        let parser_db = SimpleParserDatabase::default();
+        let raw_tokens = tokens(&parser_db, &self.raw_text, self.top_level_macro_kind);
+        let formatted_tokens = tokens(&parser_db, &self.text, self.top_level_macro_kind);
+
+        align_tokens(&raw_tokens, &formatted_tokens)
+            .into_iter()
+            .filter_map(|(raw_token, formatted_token)| {
+                let (section_file, span) = self.locate(raw_token.0)?;
+                let (origin_file, origin) = get_originating_location(db, section_file, span);
+                (origin_file == file).then_some((formatted_token.0, origin))
+            })
+            .collect()
+    }
+
+    /// Finds the file and the span in it, which the span of the raw expansion text was copied
+    /// from.
+    fn locate(&self, span: TextSpan) -> Option<(FileId, TextSpan)> {
+        let section = self.sections.iter().find(|section| section.span.contains(span))?;
+        let start = section.file_offset.add_width(span.start - section.span.start);
+        Some((section.file, TextSpan { start, end: start.add_width(span.width()) }))
+    }
+}
+
+/// Span and text of a token.
+type Token = (TextSpan, String);
+
+/// Returns tokens of the expanded code.
+fn tokens(
+    db: &SimpleParserDatabase,
+    code: &str,
+    top_level_macro_kind: TopLevelMacroKind,
+) -> Vec<Token> {
+    parse_output(db, code, top_level_macro_kind)
+        .descendants(db)
+        .filter(|node| node.kind(db).is_terminal())
+        .map(|node| (node.span_without_trivia(db), node.get_text_without_trivia(db)))
+        .filter(|(_, text)| !text.is_empty())
+        .collect()
+}
+
+/// Pairs tokens of the expanded code before formatting with the same tokens after formatting.
+///
+/// Apart from whitespace, the formatter only adds or removes trailing commas, so tokens are paired
+/// in order, skipping commas present on one side only.
+fn align_tokens<'a>(raw: &'a [Token], formatted: &'a [Token]) -> Vec<(&'a Token, &'a Token)> {
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = vec![];
+    while i < raw.len() && j < formatted.len() {
+        if raw[i].1 == formatted[j].1 {
+            pairs.push((&raw[i], &formatted[j]));
+            i += 1;
+            j += 1;
+        } else if formatted[j].1 == "," {
+            j += 1;
+        } else if raw[i].1 == "," {
+            i += 1;
+        } else {
+            i += 1;
+            j += 1;
+        }
+    }
+    pairs
+}
+
 #[derive(Copy, Clone)]
 enum TopLevelMacroKind {
     Inline,
@@ -95,7 +589,20 @@ fn expanded_macro_files(
         .into_iter()
         .find(|e| e.as_syntax_node() == item_ast_node)?;
 
-    let mut module_queue = VecDeque::from([(module_file, vec![item])]);
+    expanded_items_files(db, module_file, vec![item], metadata)
+}
+
+/// Expands macro plugins on the given items of `module_file` and returns all files generated by
+/// plugins (origin file included).
+fn expanded_items_files(
+    db: &dyn DefsGroup,
+    module_file: FileId,
+    items: Vec<ModuleItem>,
+    metadata: &MacroPluginMetadata<'_>,
+) -> Option<VecDeque<FileId>> {
+    let syntax_db = db.upcast();
+
+    let mut module_queue = VecDeque::from([(module_file, items)]);
     let mut files = VecDeque::new();
 
     while let Some((module_file, item_asts)) = module_queue.pop_front() {
@@ -110,7 +617,7 @@ fn expanded_macro_files(
                         parent: Some(module_file),
                         name: generated.name,
                         content: generated.content.into(),
-                        code_mappings: Default::default(),
+                        code_mappings: generated.code_mappings.into(),
                         kind: FileKind::Module,
                     })
                     .intern(db);
@@ -145,8 +652,9 @@ fn expand_inline_macros(
     mut files: VecDeque<FileId>,
     metadata: &MacroPluginMetadata<'_>,
     top_level_macro_kind: TopLevelMacroKind,
-) -> Option<String> {
+) -> Option<Expansion> {
     let mut output = String::new();
+    let mut sections = vec![];
 
     expand_inline_macros_in_single_file(
         db,
@@ -154,6 +662,7 @@ fn expand_inline_macros(
         files.pop_front().unwrap(),
         &mut files,
         &mut output,
+        &mut sections,
         FileProcessorConfig::main_file(db, node_to_expand, top_level_macro_kind),
     )?;
 
@@ -164,16 +673,40 @@ fn expand_inline_macros(
             file,
             &mut files,
             &mut output,
+            &mut sections,
             FileProcessorConfig::generated_file(db, file, db.file_content(file)?.to_string())?,
         )?;
     }
 
-    if output.is_empty() { None } else { Some(format_output(&output, top_level_macro_kind)) }
+    if output.is_empty() {
+        None
+    } else {
+        Some(Expansion {
+            text: format_output(&output, top_level_macro_kind),
+            raw_text: output,
+            sections,
+            top_level_macro_kind,
+        })
+    }
 }
 
 /// Formats output string.
 fn format_output(output: &str, top_level_macro_kind: TopLevelMacroKind) -> String {
     let db = &SimpleParserDatabase::default();
+    let syntax_root = parse_output(db, output, top_level_macro_kind);
+
+    cairo_lang_formatter::get_formatted_file(db, &syntax_root, FormatterConfig::default())
+        .trim_end()
+        .trim_end_matches("\n;")
+        .to_owned()
+}
+
+/// Parses output string.
+fn parse_output(
+    db: &SimpleParserDatabase,
+    output: &str,
+    top_level_macro_kind: TopLevelMacroKind,
+) -> SyntaxNode {
     let virtual_file = FileLongId::Virtual(VirtualFile {
         parent: Default::default(),
         name: Default::default(),
@@ -183,7 +716,7 @@ fn format_output(output: &str, top_level_macro_kind: TopLevelMacroKind) -> Strin
     })
     .intern(db);
 
-    let syntax_root = match top_level_macro_kind {
+    match top_level_macro_kind {
         TopLevelMacroKind::Inline => {
             Parser::parse_file_expr(db, &mut DiagnosticsBuilder::default(), virtual_file, output)
                 .as_syntax_node()
@@ -192,12 +725,7 @@ fn format_output(output: &str, top_level_macro_kind: TopLevelMacroKind) -> Strin
             Parser::parse_file(db, &mut DiagnosticsBuilder::default(), virtual_file, output)
                 .as_syntax_node()
         }
-    };
-
-    cairo_lang_formatter::get_formatted_file(db, &syntax_root, FormatterConfig::default())
-        .trim_end()
-        .trim_end_matches("\n;")
-        .to_owned()
+    }
 }
 
 struct FileProcessorConfig {
@@ -249,34 +777,59 @@ fn expand_inline_macros_in_single_file(
     file: FileId,
     files: &mut VecDeque<FileId>,
     output: &mut String,
-    mut config: FileProcessorConfig,
+    sections: &mut Vec<ExpansionSection>,
+    config: FileProcessorConfig,
 ) -> Option<()> {
     let plugins = db.inline_macro_plugins();
+    let content_offset = TextOffset::default().add_width(config.offset_correction);
 
     if config.macros.is_empty() {
-        append_file_with_header(db, file, &config.content, output);
+        append_file_with_header(db, file, &config.content, content_offset, output, sections);
     } else {
-        // Iterate in reversed order so positions are not affected by inlining.
-        for node in config.macros.into_iter().rev() {
+        let mut content = String::new();
+        let mut code_mappings = vec![];
+        // Offset in the file up to which its content was copied.
+        let mut copied_until = content_offset;
+        for node in &config.macros {
+            let span = node.span(db);
+            // Macros nested in an inlined one are expanded along with the generated code.
+            if span.start < copied_until {
+                continue;
+            }
+
             let inline_macro = ExprInlineMacro::from_syntax_node(db, node.clone());
             let code = plugins
                 .get(&inline_macro.path(db).as_syntax_node().get_text_without_trivia(db))?
                 .generate_code(db, &inline_macro, metadata)
-                .code?
-                .content;
-
-            let offset = node.offset().sub_width(config.offset_correction);
-
-            config.content.replace_range(
-                TextSpan { start: offset, end: offset.add_width(node.width(db)) }.to_str_range(),
-                &code,
-            );
+                .code?;
+
+            let copied = TextSpan { start: copied_until, end: span.start };
+            copy_content(&config, copied, &mut content, &mut code_mappings);
+            copied_until = span.end;
+
+            let generated = appended_span(&content, &code.content);
+            code_mappings.extend(code.code_mappings.into_iter().map(|mapping| CodeMapping {
+                span: TextSpan {
+                    start: generated.start.add_width(mapping.span.start - Default::default()),
+                    end: generated.start.add_width(mapping.span.end - Default::default()),
+                },
+                origin: mapping.origin,
+            }));
+            code_mappings.push(CodeMapping {
+                span: generated,
+                origin: CodeOrigin::Span(node.span_without_trivia(db)),
+            });
+            content.push_str(&code.content);
         }
+        let content_end = content_offset.add_width(TextWidth::from_str(&config.content));
+        let copied = TextSpan { start: copied_until, end: content_end };
+        copy_content(&config, copied, &mut content, &mut code_mappings);
+
         let new_file = FileLongId::Virtual(VirtualFile {
             parent: Some(file),
             name: file.file_name(db).into(),
-            content: config.content.into(),
-            code_mappings: Default::default(),
+            content: content.into(),
+            code_mappings: code_mappings.into(),
             kind: file.kind(db),
         })
         .intern(db);
@@ -287,12 +840,45 @@ fn expand_inline_macros_in_single_file(
     Some(())
 }
 
+/// Copies the span of the processed file to the content of the file with macros inlined.
+fn copy_content(
+    config: &FileProcessorConfig,
+    span: TextSpan,
+    content: &mut String,
+    code_mappings: &mut Vec<CodeMapping>,
+) {
+    let text = TextSpan {
+        start: span.start.sub_width(config.offset_correction),
+        end: span.end.sub_width(config.offset_correction),
+    }
+    .take(&config.content);
+    if text.is_empty() {
+        return;
+    }
+
+    code_mappings.push(CodeMapping {
+        span: appended_span(content, text),
+        origin: CodeOrigin::Start(span.start),
+    });
+    content.push_str(text);
+}
+
+/// Returns the span the text will take when appended to the content.
+fn appended_span(content: &str, text: &str) -> TextSpan {
+    let start = TextOffset::default().add_width(TextWidth::from_str(content));
+    TextSpan { start, end: start.add_width(TextWidth::from_str(text)) }
+}
+
 /// Extends output with single file.
+///
+/// The `content_offset` is the offset in the file at which the content starts.
 fn append_file_with_header(
     db: &AnalysisDatabase,
     file: FileId,
     file_content: &str,
+    content_offset: TextOffset,
     output: &mut String,
+    sections: &mut Vec<ExpansionSection>,
 ) {
     let file_name = file.file_name(db);
     let file_underscore = "-".repeat(file_name.chars().count());
@@ -306,6 +892,12 @@ fn append_file_with_header(
         "
     ));
 
-    output.push_str(file_content.trim_end_matches('\n'));
+    let file_content = file_content.trim_end_matches('\n');
+    sections.push(ExpansionSection {
+        span: appended_span(output, file_content),
+        file,
+        file_offset: content_offset,
+    });
+    output.push_str(file_content);
     output.push_str("\n\n");
 }
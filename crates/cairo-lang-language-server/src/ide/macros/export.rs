@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::ModuleId;
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use tracing::warn;
+
+use crate::ide::macros::expand::expand_module;
+use crate::lang::db::AnalysisDatabase;
+
+/// Writes fully macro-expanded content of each module file of the crate named `crate_name` into
+/// the `output` directory, mirroring the module structure of the crate.
+///
+/// Returns the number of written files.
+pub fn export_expanded_crate(
+    db: &AnalysisDatabase,
+    crate_name: &str,
+    output: &Path,
+) -> Result<usize> {
+    let crate_id = db
+        .crates()
+        .into_iter()
+        .find(|crate_id| crate_id.name(db) == crate_name)
+        .with_context(|| format!("crate `{crate_name}` is not analyzed"))?;
+
+    // Inline submodules live in the same file as their parent, so only the outermost module of
+    // each file is expanded.
+    let mut module_files: OrderedHashMap<FileId, ModuleId> = OrderedHashMap::default();
+    for &module_id in db.crate_modules(crate_id).iter() {
+        if let Ok(file) = db.module_main_file(module_id) {
+            module_files.entry(file).or_insert(module_id);
+        }
+    }
+
+    let mut written = 0;
+    for (file, module_id) in module_files {
+        let Some(expanded) = expand_module(db, module_id) else {
+            warn!("failed to expand module `{}`", module_id.full_path(db));
+            continue;
+        };
+
+        let path = output.join(module_relative_path(db, module_id, file));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+        }
+        fs::write(&path, expanded + "\n")
+            .with_context(|| format!("failed to write file: {}", path.display()))?;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+/// Computes the path of the module file relative to the crate root, based on the module path.
+///
+/// For example, the file of module `hello::a::b` is placed at `a/b.cairo`.
+fn module_relative_path(db: &AnalysisDatabase, module_id: ModuleId, file: FileId) -> PathBuf {
+    let full_path = module_id.full_path(db);
+    let mut segments = full_path.split("::").skip(1).collect::<Vec<_>>();
+    segments.pop();
+    segments.into_iter().map(PathBuf::from).chain([PathBuf::from(file.file_name(db))]).collect()
+}
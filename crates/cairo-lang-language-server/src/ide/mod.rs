@@ -1,8 +1,20 @@
+pub mod call_hierarchy;
 pub mod code_actions;
+pub mod code_lens;
 pub mod completion;
+pub mod document_links;
+pub mod folding_ranges;
 pub mod formatter;
+pub mod fuzzy;
 pub mod hover;
+pub mod inlay_hints;
+pub mod linked_editing_ranges;
 pub mod macros;
 pub mod navigation;
+pub mod rename;
+pub mod selection_ranges;
 pub mod semantic_highlighting;
+pub mod signature_help;
+pub mod syntax_tree;
+pub mod type_hierarchy;
 pub mod utils;
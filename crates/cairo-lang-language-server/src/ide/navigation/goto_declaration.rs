@@ -0,0 +1,112 @@
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::NamedLanguageElementId;
+use cairo_lang_semantic::resolve::{ResolvedConcreteItem, ResolvedGenericItem};
+use cairo_lang_syntax::node::helpers::PathSegmentEx;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, Terminal, TypedStablePtr, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::request::GotoDeclarationParams;
+use lsp_types::{GotoDefinitionResponse, Location};
+
+use crate::ide::navigation::goto_definition::{definition_location, goto_definition};
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::inspect::defs::{ResolvedItem, find_definition};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Get the location of the declaration of a symbol at a given text document position.
+///
+/// Unlike the definition, the declaration of an item imported with `use` is the `use` item
+/// through which the item is visible at the position, so a chain of re-exports can be followed one
+/// `use` at a time. For all other symbols, the declaration is the definition.
+pub fn goto_declaration(
+    params: GotoDeclarationParams,
+    db: &AnalysisDatabase,
+) -> Option<GotoDefinitionResponse> {
+    let file = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
+    let position = params.text_document_position_params.position.to_cairo(db, file);
+    let identifier = db.find_identifier_at_position(file, position)?;
+
+    let Some(use_leaf) = find_use_declaration(db, &identifier) else {
+        return goto_definition(params, db);
+    };
+    let (found_file, span) = definition_location(db, use_leaf.stable_ptr().untyped());
+    let uri = db.url_for_file(found_file)?;
+    let range = span.position_in_file(db.upcast(), found_file)?.to_lsp(db, found_file);
+    Some(GotoDefinitionResponse::Scalar(Location { uri, range }))
+}
+
+/// Finds the `use` item through which the item named by the path segment is imported into the
+/// module the segment is looked up in, i.e. the current module for the first segment of a path
+/// and the module named by the previous segment otherwise.
+fn find_use_declaration(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<ast::UsePathLeaf> {
+    let syntax_db = db.upcast();
+    let node = identifier.as_syntax_node();
+    let lookup_items = db.collect_lookup_items_stack(&node)?;
+    let (_, target) = find_definition(db, identifier, &lookup_items)?;
+
+    let module_id = match previous_segment(db, identifier)? {
+        None => db.find_module_containing_node(&node)?,
+        Some(previous) => match find_definition(db, &previous, &lookup_items)? {
+            (ResolvedItem::Generic(ResolvedGenericItem::Module(module_id)), _)
+            | (ResolvedItem::Concrete(ResolvedConcreteItem::Module(module_id)), _) => module_id,
+            _ => return None,
+        },
+    };
+
+    let name = identifier.text(syntax_db);
+    db.module_uses_ids(module_id).ok()?.iter().find_map(|use_id| {
+        if use_id.name(db.upcast()) != name {
+            return None;
+        }
+        let use_leaf = use_id.stable_ptr(db.upcast()).lookup(syntax_db);
+        let leaf_identifier = use_leaf.ident(syntax_db).identifier_ast(syntax_db);
+        // A `use` item is not a declaration of itself.
+        if leaf_identifier.stable_ptr() == identifier.stable_ptr() {
+            return None;
+        }
+        let lookup_items = db.collect_lookup_items_stack(&use_leaf.as_syntax_node())?;
+        let (_, use_target) = find_definition(db, &leaf_identifier, &lookup_items)?;
+        (use_target == target).then_some(use_leaf)
+    })
+}
+
+/// Returns the identifier of the segment preceding the identifier in its path, or `Some(None)` if
+/// it is the first segment.
+///
+/// Returns `None` if the identifier is not a segment of a path.
+fn previous_segment(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<Option<ast::TerminalIdentifier>> {
+    let syntax_db = db.upcast();
+    let segment = identifier.as_syntax_node().parent()?;
+    if !matches!(
+        segment.kind(syntax_db),
+        SyntaxKind::PathSegmentSimple | SyntaxKind::PathSegmentWithGenericArgs
+    ) {
+        return None;
+    }
+    let path = segment.parent()?;
+    match path.kind(syntax_db) {
+        SyntaxKind::ExprPath => {
+            let segments = ast::ExprPath::from_syntax_node(syntax_db, path).elements(syntax_db);
+            let index = segments.iter().position(|other| other.as_syntax_node() == segment)?;
+            Some(index.checked_sub(1).map(|index| segments[index].identifier_ast(syntax_db)))
+        }
+        // In `use a::b::{c, d}`, the prefix of each segment is the segment of the closest
+        // enclosing `UsePathSingle`.
+        SyntaxKind::UsePathLeaf | SyntaxKind::UsePathSingle => Some(
+            std::iter::successors(path.parent(), SyntaxNode::parent)
+                .find(|node| node.kind(syntax_db) == SyntaxKind::UsePathSingle)
+                .map(|node| {
+                    ast::UsePathSingle::from_syntax_node(syntax_db, node)
+                        .ident(syntax_db)
+                        .identifier_ast(syntax_db)
+                }),
+        ),
+        _ => None,
+    }
+}
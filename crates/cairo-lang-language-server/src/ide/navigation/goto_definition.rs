@@ -1,28 +1,43 @@
 use cairo_lang_filesystem::db::get_originating_location;
 use cairo_lang_filesystem::ids::FileId;
 use cairo_lang_filesystem::span::{TextPosition, TextSpan};
+use cairo_lang_syntax::node::ids::SyntaxStablePtrId;
 use cairo_lang_utils::Upcast;
 use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location};
 
+use crate::ide::macros::expand::expansion_origin;
 use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
-use crate::lang::inspect::defs::find_definition;
+use crate::lang::inspect::defs::{find_ambiguous_definitions, find_definition};
 use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
 
 /// Get the definition location of a symbol at a given text document position.
+///
+/// If the symbol is ambiguous, locations of all candidate definitions are returned.
 pub fn goto_definition(
     params: GotoDefinitionParams,
     db: &AnalysisDatabase,
 ) -> Option<GotoDefinitionResponse> {
-    let file = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
-    let position = params.text_document_position_params.position.to_cairo();
-    let (found_file, span) = get_definition_location(db, file, position)?;
-    let found_uri = db.url_for_file(found_file)?;
+    let uri = &params.text_document_position_params.text_document.uri;
+    let file = db.file_for_url(uri)?;
+    let position = params.text_document_position_params.position.to_cairo(db, file);
+    let (file, position) = expansion_origin(db, uri, position).unwrap_or((file, position));
+    let mut locations = get_definition_locations(db, file, position)?
+        .into_iter()
+        .filter_map(|(found_file, span)| {
+            let uri = db.url_for_file(found_file)?;
+            let range = span.position_in_file(db.upcast(), found_file)?.to_lsp(db, found_file);
+            Some(Location { uri, range })
+        })
+        .collect::<Vec<_>>();
 
-    let range = span.position_in_file(db.upcast(), found_file)?.to_lsp();
-    Some(GotoDefinitionResponse::Scalar(Location { uri: found_uri, range }))
+    match locations.len() {
+        0 => None,
+        1 => locations.pop().map(GotoDefinitionResponse::Scalar),
+        _ => Some(GotoDefinitionResponse::Array(locations)),
+    }
 }
 
-/// Returns the file id and span of the definition of an expression from its position.
+/// Returns the file ids and spans of the definitions of an expression from its position.
 ///
 /// # Arguments
 ///
@@ -32,23 +47,37 @@ pub fn goto_definition(
 ///
 /// # Returns
 ///
-/// The [FileId] and [TextSpan] of the expression definition if found.
-fn get_definition_location(
+/// The [FileId] and [TextSpan] of the expression definition if found, or of all candidate
+/// definitions if the expression is ambiguous.
+fn get_definition_locations(
     db: &AnalysisDatabase,
     file: FileId,
     position: TextPosition,
-) -> Option<(FileId, TextSpan)> {
+) -> Option<Vec<(FileId, TextSpan)>> {
     let identifier = db.find_identifier_at_position(file, position)?;
 
-    let syntax_db = db.upcast();
     let node = db.find_syntax_node_at_position(file, position)?;
     let lookup_items = db.collect_lookup_items_stack(&node)?;
-    let (_, stable_ptr) = find_definition(db, &identifier, &lookup_items)?;
+    let mut stable_ptrs = find_ambiguous_definitions(db, &identifier, &lookup_items);
+    if stable_ptrs.is_empty() {
+        let (_, stable_ptr) = find_definition(db, &identifier, &lookup_items)?;
+        stable_ptrs.push(stable_ptr);
+    }
+    Some(stable_ptrs.into_iter().map(|stable_ptr| definition_location(db, stable_ptr)).collect())
+}
+
+/// Returns the file id and span of a definition, mapped back to the originating file if the
+/// definition was generated by a macro.
+pub fn definition_location(
+    db: &AnalysisDatabase,
+    stable_ptr: SyntaxStablePtrId,
+) -> (FileId, TextSpan) {
+    let syntax_db = db.upcast();
     let node = stable_ptr.lookup(syntax_db);
     let found_file = stable_ptr.file_id(syntax_db);
     let span = node.span_without_trivia(syntax_db);
     let width = span.width();
     let (file_id, mut span) = get_originating_location(db.upcast(), found_file, span.start_only());
     span.end = span.end.add_width(width);
-    Some((file_id, span))
+    (file_id, span)
 }
@@ -0,0 +1,140 @@
+use cairo_lang_defs::ids::{FunctionWithBodyId, LanguageElementId, LookupItemId, ModuleItemId};
+use cairo_lang_diagnostics::ToOption;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
+use cairo_lang_semantic::lookup_item::LookupItemEx;
+use cairo_lang_semantic::{TypeId, TypeLongId};
+use cairo_lang_syntax::node::ast::{Expr, Param, Pattern};
+use cairo_lang_syntax::node::ids::SyntaxStablePtrId;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, Terminal, TypedSyntaxNode, ast};
+use cairo_lang_utils::{LookupIntern, Upcast};
+use itertools::Itertools;
+use lsp_types::request::GotoTypeDefinitionParams;
+use lsp_types::{GotoDefinitionResponse, Location};
+
+use crate::ide::navigation::goto_definition::definition_location;
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Get the location of the definition of the type of a symbol at a given text document position.
+///
+/// Generic types resolve to their base definition, e.g. `Array<u32>` to `Array`. Tuples and
+/// fixed-size arrays resolve to the definitions of all their element types.
+pub fn goto_type_definition(
+    params: GotoTypeDefinitionParams,
+    db: &AnalysisDatabase,
+) -> Option<GotoDefinitionResponse> {
+    let file = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
+    let position = params.text_document_position_params.position.to_cairo(db, file);
+    let identifier = db.find_identifier_at_position(file, position)?;
+    let ty = find_type(db, &identifier)?;
+
+    let mut type_definitions = vec![];
+    collect_type_definitions(db, ty, &mut type_definitions);
+    let mut locations = type_definitions
+        .into_iter()
+        .unique()
+        .filter_map(|stable_ptr| {
+            let (found_file, span) = definition_location(db, stable_ptr);
+            let uri = db.url_for_file(found_file)?;
+            let range = span.position_in_file(db.upcast(), found_file)?.to_lsp(db, found_file);
+            Some(Location { uri, range })
+        })
+        .collect::<Vec<_>>();
+
+    match locations.len() {
+        0 => None,
+        1 => locations.pop().map(GotoDefinitionResponse::Scalar),
+        _ => Some(GotoDefinitionResponse::Array(locations)),
+    }
+}
+
+/// Finds the type of the symbol named by the identifier.
+///
+/// Parameters and struct members have the type they are declared with, while other identifiers
+/// have the type of the innermost pattern or expression which contains them, like `a.b` for `b`.
+fn find_type(db: &AnalysisDatabase, identifier: &ast::TerminalIdentifier) -> Option<TypeId> {
+    let syntax_db = db.upcast();
+    let node = identifier.as_syntax_node();
+    let parent = node.parent()?;
+    match parent.kind(syntax_db) {
+        SyntaxKind::Param => {
+            let function_id = db.find_lookup_item(&node)?.function_with_body()?;
+            let name = Param::from_syntax_node(syntax_db, parent).name(syntax_db).text(syntax_db);
+            let signature = db.function_with_body_signature(function_id).ok()?;
+            Some(signature.params.into_iter().find(|param| param.name == name)?.ty)
+        }
+        SyntaxKind::Member => {
+            let LookupItemId::ModuleItem(ModuleItemId::Struct(struct_id)) =
+                db.find_lookup_item(&node)?
+            else {
+                return None;
+            };
+            let members = db.struct_members(struct_id).ok()?;
+            Some(members.get(&identifier.text(syntax_db))?.ty)
+        }
+        _ => {
+            let function_id = db.find_lookup_item(&node)?.function_with_body()?;
+            nearest_semantic_type(db, node, function_id)
+        }
+    }
+}
+
+/// Returns the type of the innermost semantic pattern or expression containing the node.
+fn nearest_semantic_type(
+    db: &AnalysisDatabase,
+    mut node: SyntaxNode,
+    function_id: FunctionWithBodyId,
+) -> Option<TypeId> {
+    let syntax_db = db.upcast();
+    loop {
+        // Simple patterns, like `a` in `let a = 1;`, are paths, so they are checked first.
+        if Pattern::is_variant(node.kind(syntax_db)) {
+            let pattern = Pattern::from_syntax_node(syntax_db, node.clone());
+            if let Some(pattern_id) =
+                db.lookup_pattern_by_ptr(function_id, pattern.stable_ptr()).to_option()
+            {
+                return Some(db.pattern_semantic(function_id, pattern_id).ty());
+            }
+        }
+        if Expr::is_variant(node.kind(syntax_db)) {
+            let expr = Expr::from_syntax_node(syntax_db, node.clone());
+            if let Some(expr_id) = db.lookup_expr_by_ptr(function_id, expr.stable_ptr()).to_option()
+            {
+                return Some(db.expr_semantic(function_id, expr_id).ty());
+            }
+        }
+        node = node.parent()?;
+    }
+}
+
+/// Collects the definitions of the base types the type is made of.
+fn collect_type_definitions(
+    db: &AnalysisDatabase,
+    ty: TypeId,
+    definitions: &mut Vec<SyntaxStablePtrId>,
+) {
+    match ty.lookup_intern(db) {
+        TypeLongId::Concrete(concrete_type_id) => {
+            definitions.push(concrete_type_id.generic_type(db).untyped_stable_ptr(db.upcast()));
+        }
+        TypeLongId::Tuple(types) => {
+            for ty in types {
+                collect_type_definitions(db, ty, definitions);
+            }
+        }
+        TypeLongId::Snapshot(ty) | TypeLongId::FixedSizeArray { type_id: ty, .. } => {
+            collect_type_definitions(db, ty, definitions);
+        }
+        TypeLongId::GenericParameter(generic_param_id) => {
+            definitions.push(generic_param_id.untyped_stable_ptr(db.upcast()));
+        }
+        TypeLongId::Var(_)
+        | TypeLongId::Coupon(_)
+        | TypeLongId::ImplType(_)
+        | TypeLongId::TraitType(_)
+        | TypeLongId::Closure(_)
+        | TypeLongId::Missing(_) => {}
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use cairo_lang_syntax::node::ast::{self, BinaryOperator};
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, Terminal, TypedSyntaxNode};
+use cairo_lang_utils::Upcast;
+use itertools::Itertools;
+use lsp_types::{DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams};
+
+use crate::ide::navigation::references::{
+    declaration_identifier, find_references_in_files, resolve_identifier,
+};
+use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Highlight all occurrences of the symbol at a given text document position in the same file.
+///
+/// Occurrences which assign to the symbol, or bind it as `ref` or `mut`, are marked as writes.
+pub fn highlights(
+    params: DocumentHighlightParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<DocumentHighlight>> {
+    let syntax_db = db.upcast();
+    let file = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
+    let position = params.text_document_position_params.position.to_cairo(db, file);
+    let identifier = db.find_identifier_at_position(file, position)?;
+    let target = resolve_identifier(db, &identifier)?;
+
+    let mut names = HashSet::from([identifier.text(syntax_db)]);
+    names.extend(declaration_identifier(db, target).map(|declaration| declaration.text(syntax_db)));
+
+    let references = find_references_in_files(db, target, names, &[file]);
+    Some(
+        references
+            .into_iter()
+            .filter(|&(file_id, _)| file_id == file)
+            .filter_map(|(_, span)| {
+                let range = span.position_in_file(db.upcast(), file)?;
+                let kind = db
+                    .find_identifier_at_position(file, range.start)
+                    .map_or(DocumentHighlightKind::READ, |identifier| {
+                        highlight_kind(syntax_db, &identifier)
+                    });
+                Some(DocumentHighlight { range: range.to_lsp(db, file), kind: Some(kind) })
+            })
+            .sorted_by_key(|highlight| highlight.range.start)
+            .collect(),
+    )
+}
+
+/// Checks whether the identifier is written to, i.e. it is the left side of an assignment, it is
+/// passed as a `ref` argument or it is bound with a `ref` or `mut` modifier.
+fn highlight_kind(
+    db: &dyn SyntaxGroup,
+    identifier: &ast::TerminalIdentifier,
+) -> DocumentHighlightKind {
+    let is_write = || {
+        let parent = identifier.as_syntax_node().parent()?;
+        let modifiers = match parent.kind(db) {
+            SyntaxKind::Param => ast::Param::from_syntax_node(db, parent).modifiers(db),
+            SyntaxKind::PatternIdentifier => {
+                ast::PatternIdentifier::from_syntax_node(db, parent).modifiers(db)
+            }
+            SyntaxKind::PathSegmentSimple => return Some(is_written_path(db, parent.parent()?)),
+            _ => return None,
+        };
+        Some(!modifiers.elements(db).is_empty())
+    };
+
+    if is_write().unwrap_or_default() {
+        DocumentHighlightKind::WRITE
+    } else {
+        DocumentHighlightKind::READ
+    }
+}
+
+/// Checks whether the path expression is the left side of an assignment, e.g. `x = 1`, `x += 1` or
+/// `a.x = 1`, or a `ref` argument, e.g. `foo(ref x)`.
+fn is_written_path(db: &dyn SyntaxGroup, path: SyntaxNode) -> bool {
+    let Some(parent) = path.parent() else { return false };
+    match parent.kind(db) {
+        SyntaxKind::ExprBinary => {
+            let binary = ast::ExprBinary::from_syntax_node(db, parent.clone());
+            match binary.op(db) {
+                // A member is written if the member access expression is written.
+                BinaryOperator::Dot(_) => {
+                    binary.rhs(db).as_syntax_node() == path && is_written_path(db, parent)
+                }
+                BinaryOperator::Eq(_)
+                | BinaryOperator::PlusEq(_)
+                | BinaryOperator::MinusEq(_)
+                | BinaryOperator::MulEq(_)
+                | BinaryOperator::DivEq(_)
+                | BinaryOperator::ModEq(_) => binary.lhs(db).as_syntax_node() == path,
+                _ => false,
+            }
+        }
+        SyntaxKind::ArgClauseUnnamed => parent.parent().is_some_and(|arg| {
+            ast::Arg::from_syntax_node(db, arg)
+                .modifiers(db)
+                .elements(db)
+                .iter()
+                .any(|modifier| matches!(modifier, ast::Modifier::Ref(_)))
+        }),
+        _ => false,
+    }
+}
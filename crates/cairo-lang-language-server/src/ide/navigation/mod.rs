@@ -1 +1,6 @@
+pub mod goto_declaration;
 pub mod goto_definition;
+pub mod goto_type_definition;
+pub mod highlights;
+pub mod references;
+pub mod workspace_symbols;
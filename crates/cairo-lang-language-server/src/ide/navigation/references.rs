@@ -0,0 +1,352 @@
+use std::collections::HashSet;
+use std::iter;
+
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{LanguageElementId, ModuleId, NamedLanguageElementId};
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::{CrateId, FileId};
+use cairo_lang_filesystem::span::TextSpan;
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
+use cairo_lang_semantic::lookup_item::LookupItemEx;
+use cairo_lang_semantic::resolve::ResolvedGenericItem;
+use cairo_lang_semantic::{Expr, Pattern};
+use cairo_lang_syntax::node::ids::SyntaxStablePtrId;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, Terminal, TypedStablePtr, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use itertools::Itertools;
+use lsp_types::{Location, ReferenceParams};
+use smol_str::SmolStr;
+
+use crate::ide::utils::get_copied_origin;
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::inspect::defs::{ResolvedItem, find_definition};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Find all references to the symbol at a given text document position.
+///
+/// All modules of all crates are searched, including code generated by macros, as long as it was
+/// copied from the user's source code. References through `use` aliases are also found.
+pub fn references(params: ReferenceParams, db: &AnalysisDatabase) -> Option<Vec<Location>> {
+    let file = db.file_for_url(&params.text_document_position.text_document.uri)?;
+    let position = params.text_document_position.position.to_cairo(db, file);
+    let identifier = db.find_identifier_at_position(file, position)?;
+    let target = resolve_identifier(db, &identifier)?;
+    let declaration = declaration_identifier(db, target);
+
+    let mut names = HashSet::from([identifier.text(db.upcast())]);
+    names.extend(declaration.as_ref().map(|declaration| declaration.text(db.upcast())));
+
+    let mut references = find_references(db, target, names);
+    if let Some(declaration) = declaration.and_then(|declaration| identifier_span(db, &declaration))
+    {
+        references.remove(&declaration);
+        if params.context.include_declaration {
+            references.insert(declaration);
+        }
+    }
+
+    Some(
+        references
+            .into_iter()
+            .filter_map(|(file_id, span)| {
+                let uri = db.url_for_file(file_id)?;
+                let range = span.position_in_file(db.upcast(), file_id)?.to_lsp(db, file_id);
+                Some(Location { uri, range })
+            })
+            .sorted_by_key(|location| (location.uri.to_string(), location.range.start))
+            .collect(),
+    )
+}
+
+/// Scans all files for identifiers with one of the given names that resolve to the target
+/// definition.
+///
+/// Names under which the target is imported with `use ... as ...` are searched for as well.
+pub fn find_references(
+    db: &AnalysisDatabase,
+    target: SyntaxStablePtrId,
+    names: HashSet<SmolStr>,
+) -> HashSet<(FileId, TextSpan)> {
+    let files = match local_scope(db, target) {
+        Some(function) => vec![function.stable_ptr().file_id(db.upcast())],
+        None => searched_files(db, target),
+    };
+    find_references_in_files(db, target, names, &files)
+}
+
+/// Scans the given files for identifiers with one of the given names that resolve to the target
+/// definition.
+///
+/// Names under which the target is imported with `use ... as ...` in these files are searched for
+/// as well.
+///
+/// Only the function declaring the target is searched if it is a local variable or parameter.
+pub fn find_references_in_files(
+    db: &AnalysisDatabase,
+    target: SyntaxStablePtrId,
+    mut names: HashSet<SmolStr>,
+    files: &[FileId],
+) -> HashSet<(FileId, TextSpan)> {
+    let syntax_db = db.upcast();
+    let roots = match local_scope(db, target) {
+        Some(function) => {
+            let file = function.stable_ptr().file_id(syntax_db);
+            if files.contains(&file) { vec![function] } else { vec![] }
+        }
+        None => files.iter().filter_map(|&file| db.file_syntax(file).ok()).collect(),
+    };
+
+    let mut references = HashSet::new();
+    let mut searched_names = HashSet::new();
+    while !names.is_empty() {
+        searched_names.extend(names.iter().cloned());
+
+        let mut alias_names = HashSet::new();
+        for root in &roots {
+            for node in root.descendants(syntax_db) {
+                if node.kind(syntax_db) != SyntaxKind::TerminalIdentifier {
+                    continue;
+                }
+                let identifier = ast::TerminalIdentifier::from_syntax_node(syntax_db, node);
+                if !names.contains(&identifier.text(syntax_db))
+                    || resolve_identifier(db, &identifier) != Some(target)
+                {
+                    continue;
+                }
+
+                references.extend(identifier_span(db, &identifier));
+                alias_names.extend(use_alias(db, &identifier));
+            }
+        }
+
+        names = alias_names.difference(&searched_names).cloned().collect();
+    }
+    references
+}
+
+/// Returns the definition the identifier refers to, or the definition it declares.
+pub fn resolve_identifier(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<SyntaxStablePtrId> {
+    let syntax_db = db.upcast();
+    let node = identifier.as_syntax_node();
+
+    // Declarations of variables and members do not resolve to anything, so they have to be matched
+    // manually.
+    let parent = node.parent()?;
+    match parent.kind(syntax_db) {
+        SyntaxKind::Param
+        | SyntaxKind::Member
+        | SyntaxKind::GenericParamType
+        | SyntaxKind::GenericParamConst
+        | SyntaxKind::GenericParamImplNamed => return Some(parent.stable_ptr()),
+        SyntaxKind::StructArgSingle => {
+            if let Some(member) = struct_ctor_member(db, identifier) {
+                return Some(member);
+            }
+        }
+        _ => {}
+    }
+    if is_variable_pattern(db, identifier) {
+        // Local variables are identified by the identifier they are declared with.
+        return Some(node.stable_ptr());
+    }
+    if let Some(generic_param) = generic_param_in_list(db, identifier) {
+        return Some(generic_param);
+    }
+
+    let lookup_items = db.collect_lookup_items_stack(&node)?;
+    match find_definition(db, identifier, &lookup_items)? {
+        // Submodules are identified by their declaration rather than their body, so that their
+        // name can be found.
+        (
+            ResolvedItem::Generic(ResolvedGenericItem::Module(ModuleId::Submodule(submodule_id))),
+            _,
+        ) => Some(submodule_id.stable_ptr(db.upcast()).untyped()),
+        (_, stable_ptr) => Some(stable_ptr),
+    }
+}
+
+/// Finds the generic parameter named by an identifier used in the generic parameter list declaring
+/// it, e.g. `T` in `+Drop<T>` in `fn foo<T, +Drop<T>>()`, which the compiler records no
+/// resolution for.
+fn generic_param_in_list(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<SyntaxStablePtrId> {
+    let syntax_db = db.upcast();
+    let list =
+        db.first_ancestor_of_kind(identifier.as_syntax_node(), SyntaxKind::GenericParamList)?;
+    let name = identifier.text(syntax_db);
+    ast::GenericParamList::from_syntax_node(syntax_db, list)
+        .elements(syntax_db)
+        .into_iter()
+        .find_map(|param| {
+            let param_name = match &param {
+                ast::GenericParam::Type(param) => param.name(syntax_db),
+                ast::GenericParam::Const(param) => param.name(syntax_db),
+                ast::GenericParam::ImplNamed(param) => param.name(syntax_db),
+                ast::GenericParam::ImplAnonymous(_) | ast::GenericParam::NegativeImpl(_) => {
+                    return None;
+                }
+            };
+            (param_name.text(syntax_db) == name).then(|| param.stable_ptr().untyped())
+        })
+}
+
+/// Finds the member initialized by a struct constructor argument, e.g. `x` in `Foo { x: 1 }`.
+///
+/// Shorthand arguments, like `x` in `Foo { x }`, are references to variables instead.
+fn struct_ctor_member(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<SyntaxStablePtrId> {
+    let syntax_db = db.upcast();
+    let node = identifier.as_syntax_node();
+    let arg = ast::StructArgSingle::from_syntax_node(syntax_db, node.parent()?);
+    if matches!(arg.arg_expr(syntax_db), ast::OptionStructArgExpr::Empty(_)) {
+        return None;
+    }
+
+    let ctor = db.first_ancestor_of_kind(node.clone(), SyntaxKind::ExprStructCtorCall)?;
+    let ctor = ast::ExprStructCtorCall::from_syntax_node(syntax_db, ctor);
+    let function_id = db.find_lookup_item(&node)?.function_with_body()?;
+    let expr_id = db.lookup_expr_by_ptr(function_id, ctor.stable_ptr().into()).ok()?;
+    let Expr::StructCtor(ctor) = db.expr_semantic(function_id, expr_id) else { return None };
+
+    let name = identifier.text(syntax_db);
+    ctor.members
+        .iter()
+        .map(|(member_id, _)| member_id)
+        .find(|member_id| member_id.name(db.upcast()) == name)
+        .map(|member_id| member_id.untyped_stable_ptr(db.upcast()))
+}
+
+/// Checks whether the identifier declares a local variable in a pattern, e.g. `x` in `let x = 1;`.
+fn is_variable_pattern(db: &AnalysisDatabase, identifier: &ast::TerminalIdentifier) -> bool {
+    let syntax_db = db.upcast();
+    let node = identifier.as_syntax_node();
+    let Some(parent) = node.parent() else { return false };
+    let pattern_ptr: ast::PatternPtr = match parent.kind(syntax_db) {
+        SyntaxKind::PatternIdentifier => {
+            ast::PatternIdentifier::from_syntax_node(syntax_db, parent).stable_ptr().into()
+        }
+        // Patterns consisting of a single identifier are parsed as paths.
+        SyntaxKind::PathSegmentSimple => match parent.parent() {
+            Some(path) if path.kind(syntax_db) == SyntaxKind::ExprPath => {
+                ast::ExprPath::from_syntax_node(syntax_db, path).stable_ptr().into()
+            }
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    let Some(function_id) = db.find_lookup_item(&node).and_then(|item| item.function_with_body())
+    else {
+        return false;
+    };
+    db.lookup_pattern_by_ptr(function_id, pattern_ptr).is_ok_and(|pattern_id| {
+        matches!(db.pattern_semantic(function_id, pattern_id), Pattern::Variable(_))
+    })
+}
+
+/// Finds the identifier naming the definition, e.g. `foo` in `fn foo() {}`.
+pub fn declaration_identifier(
+    db: &AnalysisDatabase,
+    definition: SyntaxStablePtrId,
+) -> Option<ast::TerminalIdentifier> {
+    let syntax_db = db.upcast();
+    let node = definition.lookup(syntax_db);
+
+    let is_identifier = |node: &SyntaxNode| node.kind(syntax_db) == SyntaxKind::TerminalIdentifier;
+    let children_of = |node: &SyntaxNode| {
+        syntax_db
+            .get_children(node.clone())
+            .iter()
+            // Attributes may contain identifiers, but never the name of the definition.
+            .filter(|child| child.kind(syntax_db) != SyntaxKind::AttributeList)
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let name = if is_identifier(&node) {
+        node
+    } else {
+        // The name is either a direct child of the definition, or a child of its declaration,
+        // like in the case of trait functions.
+        let children = children_of(&node);
+        children
+            .iter()
+            .find(|child| is_identifier(child))
+            .cloned()
+            .or_else(|| children.iter().flat_map(children_of).find(is_identifier))?
+    };
+    Some(ast::TerminalIdentifier::from_syntax_node(syntax_db, name))
+}
+
+/// If the identifier is imported under an alias, e.g. `bar` in `use foo::bar as baz;`, returns the
+/// alias.
+fn use_alias(db: &AnalysisDatabase, identifier: &ast::TerminalIdentifier) -> Option<SmolStr> {
+    let syntax_db = db.upcast();
+    let leaf = db.first_ancestor_of_kind(identifier.as_syntax_node(), SyntaxKind::UsePathLeaf)?;
+    let leaf = ast::UsePathLeaf::from_syntax_node(syntax_db, leaf);
+    match leaf.alias_clause(syntax_db) {
+        ast::OptionAliasClause::AliasClause(alias_clause) => {
+            let alias = alias_clause.alias(syntax_db);
+            (alias.stable_ptr() != identifier.stable_ptr()).then(|| alias.text(syntax_db))
+        }
+        ast::OptionAliasClause::Empty(_) => None,
+    }
+}
+
+/// Returns the span of the identifier in the user's source code.
+pub fn identifier_span(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<(FileId, TextSpan)> {
+    let syntax_db = db.upcast();
+    let node = identifier.as_syntax_node();
+    get_copied_origin(db, node.stable_ptr().file_id(syntax_db), node.span_without_trivia(syntax_db))
+}
+
+/// If the target is a local variable or parameter, returns the function declaring it, which is the
+/// only place it can be referred to in.
+fn local_scope(db: &AnalysisDatabase, target: SyntaxStablePtrId) -> Option<SyntaxNode> {
+    let syntax_db = db.upcast();
+    let node = target.lookup(syntax_db);
+    // Local variables are identified by the identifier they are declared with.
+    if !matches!(node.kind(syntax_db), SyntaxKind::Param | SyntaxKind::TerminalIdentifier) {
+        return None;
+    }
+    iter::successors(node.parent(), SyntaxNode::parent).find(|ancestor| {
+        matches!(
+            ancestor.kind(syntax_db),
+            SyntaxKind::FunctionWithBody | SyntaxKind::TraitItemFunction
+        )
+    })
+}
+
+/// Collects all files of all modules of crates which may refer to the target, including files
+/// generated by macros.
+///
+/// The `core` crate is only searched if the target is defined in it, as it cannot depend on any
+/// other crate.
+fn searched_files(db: &AnalysisDatabase, target: SyntaxStablePtrId) -> Vec<FileId> {
+    let core = CrateId::core(db);
+    let target_crate = db
+        .find_module_containing_node(&target.lookup(db.upcast()))
+        .map(|module_id| module_id.owning_crate(db.upcast()));
+
+    db.crates()
+        .into_iter()
+        .filter(|&crate_id| crate_id != core || target_crate == Some(core))
+        .flat_map(|crate_id| db.crate_modules(crate_id).iter().copied().collect::<Vec<_>>())
+        .filter_map(|module_id| db.module_files(module_id).ok())
+        .flat_map(|files| files.iter().copied().collect::<Vec<_>>())
+        .unique()
+        .collect()
+}
@@ -0,0 +1,151 @@
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{
+    LanguageElementId, ModuleItemId, NamedLanguageElementId, TopLevelLanguageElementId,
+};
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_syntax::node::ids::SyntaxStablePtrId;
+use cairo_lang_utils::Upcast;
+use itertools::Itertools;
+use lsp_types::{
+    Location, OneOf, SymbolKind, WorkspaceSymbol, WorkspaceSymbolParams, WorkspaceSymbolResponse,
+};
+use smol_str::SmolStr;
+
+use crate::ide::fuzzy::fuzzy_match;
+use crate::ide::utils::get_copied_origin;
+use crate::lang::db::AnalysisDatabase;
+use crate::lang::lsp::{LsProtoGroup, ToLsp};
+
+/// Maximum number of symbols returned for a single query.
+const MAX_RESULTS: usize = 256;
+
+/// Find named items in all crates whose names fuzzy-match the query.
+///
+/// Besides module items, their members are searched too: functions, types and constants of traits
+/// and impls, variants of enums and members of structs.
+/// Items generated by macros are only included if they were copied from the user's source code.
+pub fn workspace_symbols(
+    params: WorkspaceSymbolParams,
+    db: &AnalysisDatabase,
+) -> Option<WorkspaceSymbolResponse> {
+    let symbols = db
+        .crates()
+        .into_iter()
+        .flat_map(|crate_id| db.crate_modules(crate_id).iter().copied().collect::<Vec<_>>())
+        .filter_map(|module_id| db.module_items(module_id).ok())
+        .flat_map(|items| items.iter().copied().collect::<Vec<_>>())
+        .flat_map(|item| item_symbols(db, item))
+        .filter_map(|symbol| Some((fuzzy_match(&params.query, &symbol.name)?, symbol)))
+        .sorted_by(|(score_a, symbol_a), (score_b, symbol_b)| {
+            score_b.cmp(score_a).then_with(|| symbol_a.name.cmp(&symbol_b.name))
+        })
+        .filter_map(|(_, symbol)| {
+            Some(WorkspaceSymbol {
+                name: symbol.name.to_string(),
+                kind: symbol.kind,
+                tags: None,
+                container_name: Some(symbol.container_name),
+                location: OneOf::Left(symbol_location(db, symbol.stable_ptr)?),
+                data: None,
+            })
+        })
+        .take(MAX_RESULTS)
+        .collect();
+
+    Some(WorkspaceSymbolResponse::Nested(symbols))
+}
+
+/// A named item which can be found by a workspace symbol query.
+struct Symbol {
+    name: SmolStr,
+    kind: SymbolKind,
+    /// The full path of the module or the item containing the symbol.
+    container_name: String,
+    stable_ptr: SyntaxStablePtrId,
+}
+
+/// Returns the symbols defined by a module item: the item itself followed by its members.
+fn item_symbols(db: &AnalysisDatabase, item: ModuleItemId) -> Vec<Symbol> {
+    let defs_db = db.upcast();
+    let Some(kind) = symbol_kind(item) else {
+        return vec![];
+    };
+    let mut symbols = vec![Symbol {
+        name: item.name(defs_db),
+        kind,
+        container_name: item.parent_module(defs_db).full_path(defs_db),
+        stable_ptr: item.untyped_stable_ptr(defs_db),
+    }];
+
+    let container_name = item.full_path(defs_db);
+    let mut add_member = |name: SmolStr, kind: SymbolKind, stable_ptr: SyntaxStablePtrId| {
+        symbols.push(Symbol { name, kind, container_name: container_name.clone(), stable_ptr })
+    };
+    match item {
+        ModuleItemId::Trait(trait_id) => {
+            for (name, id) in db.trait_functions(trait_id).unwrap_or_default() {
+                add_member(name, SymbolKind::METHOD, id.untyped_stable_ptr(defs_db));
+            }
+            for (name, id) in db.trait_types(trait_id).unwrap_or_default() {
+                add_member(name, SymbolKind::TYPE_PARAMETER, id.untyped_stable_ptr(defs_db));
+            }
+            for (name, id) in db.trait_constants(trait_id).unwrap_or_default() {
+                add_member(name, SymbolKind::CONSTANT, id.untyped_stable_ptr(defs_db));
+            }
+        }
+        ModuleItemId::Impl(impl_def_id) => {
+            for (name, id) in db.impl_functions(impl_def_id).unwrap_or_default() {
+                add_member(name, SymbolKind::METHOD, id.untyped_stable_ptr(defs_db));
+            }
+            for id in db.impl_types(impl_def_id).unwrap_or_default().keys() {
+                let stable_ptr = id.untyped_stable_ptr(defs_db);
+                add_member(id.name(defs_db), SymbolKind::TYPE_PARAMETER, stable_ptr);
+            }
+            for id in db.impl_constants(impl_def_id).unwrap_or_default().keys() {
+                add_member(id.name(defs_db), SymbolKind::CONSTANT, id.untyped_stable_ptr(defs_db));
+            }
+        }
+        ModuleItemId::Enum(enum_id) => {
+            for (name, id) in db.enum_variants(enum_id).unwrap_or_default() {
+                add_member(name, SymbolKind::ENUM_MEMBER, id.untyped_stable_ptr(defs_db));
+            }
+        }
+        ModuleItemId::Struct(struct_id) => {
+            for (name, member) in db.struct_members(struct_id).unwrap_or_default().iter() {
+                add_member(name.clone(), SymbolKind::FIELD, member.id.untyped_stable_ptr(defs_db));
+            }
+        }
+        _ => {}
+    }
+    symbols
+}
+
+/// Returns the symbol kind of a module item, or `None` if the item does not define a symbol.
+fn symbol_kind(item: ModuleItemId) -> Option<SymbolKind> {
+    Some(match item {
+        ModuleItemId::Use(_) => return None,
+        ModuleItemId::Constant(_) => SymbolKind::CONSTANT,
+        ModuleItemId::Submodule(_) => SymbolKind::MODULE,
+        ModuleItemId::FreeFunction(_) | ModuleItemId::ExternFunction(_) => SymbolKind::FUNCTION,
+        ModuleItemId::Struct(_) | ModuleItemId::ExternType(_) => SymbolKind::STRUCT,
+        ModuleItemId::Enum(_) => SymbolKind::ENUM,
+        ModuleItemId::TypeAlias(_) => SymbolKind::TYPE_PARAMETER,
+        ModuleItemId::Trait(_) => SymbolKind::INTERFACE,
+        ModuleItemId::Impl(_) | ModuleItemId::ImplAlias(_) => SymbolKind::OBJECT,
+    })
+}
+
+/// Returns the location of a symbol in the user's source code.
+///
+/// Returns `None` for items generated by macros, unless they were copied verbatim from an on-disk
+/// file, like items of a module processed by an attribute macro.
+fn symbol_location(db: &AnalysisDatabase, stable_ptr: SyntaxStablePtrId) -> Option<Location> {
+    let syntax_db = db.upcast();
+    let span = stable_ptr.lookup(syntax_db).span_without_trivia(syntax_db);
+    let (file_id, span) = get_copied_origin(db, stable_ptr.file_id(syntax_db), span)?;
+
+    let uri = db.url_for_file(file_id)?;
+    let range = span.position_in_file(db.upcast(), file_id)?.to_lsp(db, file_id);
+    Some(Location { uri, range })
+}
@@ -0,0 +1,254 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use anyhow::{Result, bail};
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{ModuleId, SubmoduleLongId};
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::{CrateId, Directory, FileId, FileLongId};
+use cairo_lang_parser::lexer::Lexer;
+use cairo_lang_syntax::node::ids::SyntaxStablePtrId;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{Terminal, TypedSyntaxNode, ast};
+use cairo_lang_utils::{Intern, LookupIntern, Upcast};
+use lsp_types::{
+    DocumentChangeOperation, DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier,
+    PrepareRenameResponse, RenameFile, RenameParams, ResourceOp, TextDocumentEdit,
+    TextDocumentPositionParams, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::ide::navigation::references::{
+    declaration_identifier, find_references, identifier_span, resolve_identifier,
+};
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Check whether the symbol at a given text document position can be renamed, and return the
+/// range of its name.
+///
+/// Returns `None` if there is no renameable symbol at the position, and an error if the symbol
+/// cannot be renamed.
+/// Modules declared in their own files can only be renamed if `rename_files` is set, which the
+/// client must support.
+pub fn prepare_rename(
+    params: TextDocumentPositionParams,
+    db: &AnalysisDatabase,
+    rename_files: bool,
+) -> Result<Option<PrepareRenameResponse>> {
+    let Some(file) = db.file_for_url(&params.text_document.uri) else { return Ok(None) };
+    let Some((identifier, _)) = rename_target(db, file, params.position, rename_files)? else {
+        return Ok(None);
+    };
+
+    let span = identifier.as_syntax_node().span_without_trivia(db.upcast());
+    Ok(span
+        .position_in_file(db.upcast(), file)
+        .map(|span| PrepareRenameResponse::Range(span.to_lsp(db, file))))
+}
+
+/// Rename the symbol at a given text document position in all files of the workspace.
+///
+/// Renaming a module declared in its own file also renames the file, and the directory of its
+/// submodules, if there is one. This fails unless `rename_files` is set, which the client must
+/// support.
+pub fn rename(
+    params: RenameParams,
+    db: &AnalysisDatabase,
+    rename_files: bool,
+) -> Result<Option<WorkspaceEdit>> {
+    let new_name = params.new_name;
+    validate_identifier(db, &new_name)?;
+
+    let text_document_position = params.text_document_position;
+    let Some(file) = db.file_for_url(&text_document_position.text_document.uri) else {
+        return Ok(None);
+    };
+    let Some((identifier, target)) =
+        rename_target(db, file, text_document_position.position, rename_files)?
+    else {
+        return Ok(None);
+    };
+
+    let old_name = identifier.text(db.upcast());
+    let declaration = declaration_identifier(db, target);
+    let is_alias =
+        declaration.as_ref().is_some_and(|declaration| declaration.text(db.upcast()) != old_name);
+    let mut names = HashSet::from([old_name.clone()]);
+    names.extend(declaration.as_ref().map(|declaration| declaration.text(db.upcast())));
+
+    let mut references = find_references(db, target, names);
+    references.extend(declaration.and_then(|declaration| identifier_span(db, &declaration)));
+
+    // References through `use` aliases are spelled differently than the renamed name.
+    // The alias is kept when renaming the aliased symbol, and only the alias is renamed when
+    // renaming at its usage.
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for (file_id, span) in references {
+        let Some(content) = db.file_content(file_id) else { continue };
+        if span.take(&content) != old_name {
+            continue;
+        }
+        let (Some(uri), Some(range)) =
+            (db.url_for_file(file_id), span.position_in_file(db.upcast(), file_id))
+        else {
+            continue;
+        };
+        changes
+            .entry(uri)
+            .or_default()
+            .push(TextEdit { range: range.to_lsp(db, file_id), new_text: new_name.clone() });
+    }
+    for edits in changes.values_mut() {
+        edits.sort_by_key(|edit| edit.range.start);
+    }
+
+    let file_renames = if is_alias { vec![] } else { module_file_renames(db, target, &new_name) };
+    if file_renames.is_empty() {
+        return Ok(Some(WorkspaceEdit { changes: Some(changes), ..Default::default() }));
+    }
+
+    // Files can only be renamed through document changes, which take precedence over changes.
+    let mut operations = changes
+        .into_iter()
+        .map(|(uri, edits)| {
+            DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: edits.into_iter().map(OneOf::Left).collect(),
+            })
+        })
+        .collect::<Vec<_>>();
+    operations.extend(file_renames.into_iter().map(|(old_uri, new_uri)| {
+        DocumentChangeOperation::Op(ResourceOp::Rename(RenameFile {
+            old_uri,
+            new_uri,
+            options: None,
+            annotation_id: None,
+        }))
+    }));
+    Ok(Some(WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..Default::default()
+    }))
+}
+
+/// Finds the identifier at the position and the definition it refers to or declares.
+///
+/// Fails if the identifier names a definition which cannot be edited, or a module declared in
+/// its own file, unless `rename_files` is set.
+fn rename_target(
+    db: &AnalysisDatabase,
+    file: FileId,
+    position: lsp_types::Position,
+    rename_files: bool,
+) -> Result<Option<(ast::TerminalIdentifier, SyntaxStablePtrId)>> {
+    let Some(identifier) = db.find_identifier_at_position(file, position.to_cairo(db, file)) else {
+        return Ok(None);
+    };
+    let Some(target) = resolve_identifier(db, &identifier) else { return Ok(None) };
+
+    // Aliases of definitions which cannot be edited can still be renamed.
+    let name = identifier.text(db.upcast());
+    let is_alias = declaration_identifier(db, target)
+        .is_some_and(|declaration| declaration.text(db.upcast()) != name);
+    if !is_alias {
+        if let Some(location) = read_only_location(db, target) {
+            bail!("cannot rename `{name}`, because it is defined in {location}");
+        }
+        // Renaming the module without its file would break the declaration.
+        if !rename_files && !module_file_renames(db, target, &name).is_empty() {
+            bail!("cannot rename `{name}`, because the client does not support renaming files");
+        }
+    }
+
+    Ok(Some((identifier, target)))
+}
+
+/// If the definition cannot be edited, returns a description of where it is defined.
+///
+/// Definitions in the `core` crate, in crates which are not members of the workspace, like
+/// dependencies, in code generated by macros and in files which are read-only on disk cannot be
+/// edited.
+fn read_only_location(db: &AnalysisDatabase, target: SyntaxStablePtrId) -> Option<&'static str> {
+    let syntax_db = db.upcast();
+    let node = target.lookup(syntax_db);
+
+    let target_crate =
+        db.find_module_containing_node(&node).map(|module_id| module_id.owning_crate(db.upcast()));
+    if target_crate == Some(CrateId::core(db)) {
+        return Some("the core library");
+    }
+    if target_crate.is_some_and(|crate_id| !db.is_workspace_member(crate_id)) {
+        return Some("a dependency");
+    }
+
+    let Some((file_id, _)) = declaration_identifier(db, target)
+        .and_then(|declaration| identifier_span(db, &declaration))
+    else {
+        return Some("code generated by a macro");
+    };
+    match file_id.lookup_intern(db) {
+        FileLongId::OnDisk(path) => fs::metadata(path)
+            .is_ok_and(|metadata| metadata.permissions().readonly())
+            .then_some("a read-only file"),
+        FileLongId::Virtual(_) | FileLongId::External(_) => Some("code generated by a macro"),
+    }
+}
+
+/// Checks whether the name is a valid Cairo identifier, which is not a keyword.
+fn validate_identifier(db: &AnalysisDatabase, name: &str) -> Result<()> {
+    let mut terminals = Lexer::from_text(db.upcast(), name);
+    match (terminals.next(), terminals.next()) {
+        (Some(identifier), Some(end))
+            if identifier.kind == SyntaxKind::TerminalIdentifier
+                && identifier.text == name
+                && end.kind == SyntaxKind::TerminalEndOfFile =>
+        {
+            Ok(())
+        }
+        _ => bail!("`{name}` is not a valid identifier"),
+    }
+}
+
+/// If the target is a module declared in its own file, returns renames of the module file and the
+/// directory of its submodules, if it exists.
+fn module_file_renames(
+    db: &AnalysisDatabase,
+    target: SyntaxStablePtrId,
+    new_name: &str,
+) -> Vec<(Url, Url)> {
+    let syntax_db = db.upcast();
+    let node = target.lookup(syntax_db);
+    if node.kind(syntax_db) != SyntaxKind::ItemModule {
+        return vec![];
+    }
+    let item_module = ast::ItemModule::from_syntax_node(syntax_db, node.clone());
+    if !matches!(item_module.body(syntax_db), ast::MaybeModuleBody::None(_)) {
+        return vec![];
+    }
+
+    let Some(parent_module_file_id) = db.find_module_file_containing_node(&node) else {
+        return vec![];
+    };
+    let module_id = ModuleId::Submodule(
+        SubmoduleLongId(parent_module_file_id, item_module.stable_ptr()).intern(db),
+    );
+
+    let mut renames = vec![];
+    if let Ok(file_id) = db.module_main_file(module_id) {
+        if let FileLongId::OnDisk(path) = file_id.lookup_intern(db) {
+            renames.push((path.clone(), path.with_file_name(format!("{new_name}.cairo"))));
+        }
+    }
+    if let Ok(Directory::Real(path)) = db.module_dir(module_id) {
+        if path.is_dir() {
+            renames.push((path.clone(), path.with_file_name(new_name)));
+        }
+    }
+
+    renames
+        .into_iter()
+        .filter_map(|(old_path, new_path)| {
+            Some((Url::from_file_path(old_path).ok()?, Url::from_file_path(new_path).ok()?))
+        })
+        .collect()
+}
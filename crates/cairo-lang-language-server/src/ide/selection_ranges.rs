@@ -0,0 +1,97 @@
+use std::iter;
+
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_filesystem::span::{TextOffset, TextPosition, TextSpan};
+use cairo_lang_syntax::node::SyntaxNode;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_utils::Upcast;
+use lsp_types::{Position, Range, SelectionRange, SelectionRangeParams};
+
+use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Compute the chains of nested ranges the selection can be expanded through at each of the
+/// requested positions, e.g. from a token to an expression, a statement, a block, a function and
+/// finally a module.
+pub fn selection_ranges(
+    params: SelectionRangeParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<SelectionRange>> {
+    let file = db.file_for_url(&params.text_document.uri)?;
+    Some(
+        params
+            .positions
+            .into_iter()
+            .map(|position| {
+                // The result must contain a range for each position, so fall back to an empty
+                // range at the position.
+                selection_range(db, file, position).unwrap_or(SelectionRange {
+                    range: Range::new(position, position),
+                    parent: None,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Builds the chain of ranges of the syntax nodes enclosing the position, innermost first.
+fn selection_range(
+    db: &AnalysisDatabase,
+    file: FileId,
+    position: Position,
+) -> Option<SelectionRange> {
+    let syntax_db = db.upcast();
+    let position = position.to_cairo(db, file);
+    let offset = position.offset_in_file(db.upcast(), file)?;
+
+    // When the cursor is placed between two tokens, e.g. in `ident<caret>()` or `1<caret>)`, prefer
+    // the word before it over the punctuation after it.
+    let leaf = db.find_syntax_node_at_position(file, position)?;
+    let leaf = match position.col.checked_sub(1) {
+        Some(col) if !is_word(db, &leaf) => db
+            .find_syntax_node_at_position(file, TextPosition { col, ..position })
+            .filter(|before| {
+                is_word(db, before) && before.span_without_trivia(syntax_db).end == offset
+            })
+            .unwrap_or(leaf),
+        _ => leaf,
+    };
+
+    let mut spans: Vec<TextSpan> = vec![];
+    for node in iter::successors(Some(leaf), SyntaxNode::parent) {
+        if matches!(
+            node.kind(syntax_db),
+            SyntaxKind::Trivia | SyntaxKind::TokenWhitespace | SyntaxKind::TokenNewline
+        ) {
+            continue;
+        }
+        let span = node.span_without_trivia(syntax_db);
+        // Skip nodes that do not contain the position when their trivia is excluded, e.g. a token
+        // whose leading whitespace contains the cursor, and nodes spanning the same text as their
+        // child.
+        if !contains(span, offset)
+            || spans.last().is_some_and(|&last| !span.contains(last) || span == last)
+        {
+            continue;
+        }
+        spans.push(span);
+    }
+
+    spans
+        .into_iter()
+        .rev()
+        .filter_map(|span| Some(span.position_in_file(db.upcast(), file)?.to_lsp(db, file)))
+        .fold(None, |parent, range| Some(SelectionRange { range, parent: parent.map(Box::new) }))
+}
+
+fn contains(span: TextSpan, offset: TextOffset) -> bool {
+    span.start <= offset && offset <= span.end
+}
+
+/// Checks whether the token is an identifier, a keyword or a literal.
+fn is_word(db: &AnalysisDatabase, token: &SyntaxNode) -> bool {
+    token
+        .clone()
+        .get_text_without_trivia(db.upcast())
+        .starts_with(|c: char| c.is_alphanumeric() || matches!(c, '_' | '\'' | '"'))
+}
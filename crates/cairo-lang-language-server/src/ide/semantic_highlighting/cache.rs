@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use lsp_types::{SemanticToken, Url};
+
+/// Semantic tokens last sent to the client for each file.
+///
+/// The tokens are identified by result IDs, which the client sends back in delta requests to
+/// specify the tokens the delta should be computed against.
+#[derive(Debug, Default)]
+pub struct SemanticTokensCache {
+    next_result_id: AtomicU64,
+    files: Mutex<HashMap<Url, CachedTokens>>,
+}
+
+#[derive(Debug)]
+struct CachedTokens {
+    result_id: String,
+    data: Vec<SemanticToken>,
+}
+
+impl SemanticTokensCache {
+    /// Stores the tokens of the file, replacing the previous ones, and returns their result ID.
+    pub fn store(&self, uri: Url, data: Vec<SemanticToken>) -> String {
+        let result_id = self.next_result_id.fetch_add(1, Ordering::Relaxed).to_string();
+        self.lock().insert(uri, CachedTokens { result_id: result_id.clone(), data });
+        result_id
+    }
+
+    /// Returns the tokens of the file if they were stored with the given result ID.
+    pub fn get(&self, uri: &Url, result_id: &str) -> Option<Vec<SemanticToken>> {
+        self.lock()
+            .get(uri)
+            .filter(|cached| cached.result_id == result_id)
+            .map(|cached| cached.data.clone())
+    }
+
+    /// Forgets the tokens of the file.
+    pub fn invalidate(&self, uri: &Url) {
+        self.lock().remove(uri);
+    }
+
+    /// Forgets the tokens of all files.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Url, CachedTokens>> {
+        // The map is always left in a consistent state, so a poisoned lock can be reused.
+        self.files.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
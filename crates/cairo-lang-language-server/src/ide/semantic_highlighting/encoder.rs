@@ -1,7 +1,11 @@
+use crate::lang::lsp::PositionEncoding;
+
 /// LSP protocol is using a differential position encoding to report the tokens.
 /// This encoder outputs this encoding.
 #[derive(Default)]
 pub struct TokenEncoder {
+    /// Encoding in which columns and widths of tokens are counted.
+    encoding: PositionEncoding,
     /// Line number of the last encoded token.
     last_line: u32,
     /// Column number of the last encoded token.
@@ -16,11 +20,33 @@ pub struct EncodedToken {
     pub delta_start: u32,
 }
 impl TokenEncoder {
+    /// Creates an encoder counting columns and widths of tokens in the given encoding.
+    pub fn new(encoding: PositionEncoding) -> Self {
+        Self { encoding, ..Default::default() }
+    }
+
+    /// Returns the width of a single line text in the encoding.
+    pub fn width(&self, text: &str) -> u32 {
+        self.encoding.len(text) as u32
+    }
+
     /// Skip a non newline token.
     pub fn skip(&mut self, width: u32) {
         self.col += width;
     }
 
+    /// Skips a text which may span multiple lines.
+    pub fn skip_text(&mut self, text: &str) {
+        let mut lines = text.split('\n');
+        if let Some(first) = lines.next() {
+            self.skip(self.width(first));
+        }
+        for line in lines {
+            self.next_line();
+            self.skip(self.width(line));
+        }
+    }
+
     /// Moves to the next line.
     pub fn next_line(&mut self) {
         self.line += 1;
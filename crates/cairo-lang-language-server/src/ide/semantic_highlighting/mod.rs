@@ -1,49 +1,162 @@
-use cairo_lang_filesystem::span::TextOffset;
+use cairo_lang_filesystem::span::{TextOffset, TextSpan};
 use cairo_lang_parser::db::ParserGroup;
 use cairo_lang_syntax as syntax;
+use cairo_lang_syntax::node::db::SyntaxGroup;
 use cairo_lang_syntax::node::kind::SyntaxKind;
 use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode, ast};
 use cairo_lang_utils::Upcast;
 use cairo_lang_utils::unordered_hash_map::UnorderedHashMap;
-use lsp_types::{SemanticToken, SemanticTokens, SemanticTokensParams, SemanticTokensResult};
+use lsp_types::{
+    SemanticToken, SemanticTokens, SemanticTokensDelta, SemanticTokensDeltaParams,
+    SemanticTokensEdit, SemanticTokensFullDeltaResult, SemanticTokensParams,
+    SemanticTokensRangeParams, SemanticTokensRangeResult, SemanticTokensResult, Url,
+};
 use tracing::error;
 
+pub use self::cache::SemanticTokensCache;
 use self::encoder::{EncodedToken, TokenEncoder};
 pub use self::token_kind::SemanticTokenKind;
+pub use self::token_modifier::SemanticTokenModifierKind;
 use crate::lang::db::AnalysisDatabase;
-use crate::lang::lsp::LsProtoGroup;
+use crate::lang::lsp::{LsProtoGroup, ToCairo};
+use crate::{SemanticTokenClassifier, Tricks};
 
+mod cache;
 mod encoder;
 mod token_kind;
+mod token_modifier;
 
 /// Resolve the semantic tokens of a given file.
+///
+/// The tokens are stored in the cache, so that deltas can be computed against them later.
 pub fn semantic_highlight_full(
     params: SemanticTokensParams,
     db: &AnalysisDatabase,
+    tricks: &Tricks,
+    cache: &SemanticTokensCache,
 ) -> Option<SemanticTokensResult> {
+    let file_uri = params.text_document.uri;
+    let data = file_semantic_tokens(db, tricks, &file_uri)?;
+    let result_id = cache.store(file_uri, data.clone());
+    Some(SemanticTokensResult::Tokens(SemanticTokens { result_id: Some(result_id), data }))
+}
+
+/// Resolve the semantic tokens of a given file as edits of the tokens previously sent to the
+/// client.
+///
+/// If the previous tokens are not cached anymore, all tokens are returned instead.
+pub fn semantic_highlight_full_delta(
+    params: SemanticTokensDeltaParams,
+    db: &AnalysisDatabase,
+    tricks: &Tricks,
+    cache: &SemanticTokensCache,
+) -> Option<SemanticTokensFullDeltaResult> {
+    let file_uri = params.text_document.uri;
+    let data = file_semantic_tokens(db, tricks, &file_uri)?;
+    let previous = cache.get(&file_uri, &params.previous_result_id);
+    let result_id = Some(cache.store(file_uri, data.clone()));
+    Some(match previous {
+        Some(previous) => SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+            result_id,
+            edits: token_edits(&previous, &data),
+        }),
+        None => SemanticTokensFullDeltaResult::Tokens(SemanticTokens { result_id, data }),
+    })
+}
+
+/// Resolve the semantic tokens of a given file which overlap the requested range.
+///
+/// Subtrees outside the range are skipped without resolving their tokens, so this is much cheaper
+/// than [`semantic_highlight_full`] for the visible part of a large file.
+pub fn semantic_highlight_range(
+    params: SemanticTokensRangeParams,
+    db: &AnalysisDatabase,
+    tricks: &Tricks,
+) -> Option<SemanticTokensRangeResult> {
     let file_uri = params.text_document.uri;
     let file = db.file_for_url(&file_uri)?;
     let Ok(node) = db.file_syntax(file) else {
         error!("semantic analysis failed: file '{file_uri}' does not exist");
         return None;
     };
+    let range = params.range.to_cairo(db, file).offset_in_file(db.upcast(), file)?;
+
+    let mut data: Vec<SemanticToken> = Vec::new();
+    SemanticTokensTraverser { range: Some(range), ..SemanticTokensTraverser::new(db, tricks) }
+        .find_semantic_tokens(db.upcast(), &mut data, node);
+    Some(SemanticTokensRangeResult::Tokens(SemanticTokens { result_id: None, data }))
+}
+
+/// Finds all semantic tokens of the file.
+fn file_semantic_tokens(
+    db: &AnalysisDatabase,
+    tricks: &Tricks,
+    file_uri: &Url,
+) -> Option<Vec<SemanticToken>> {
+    let file = db.file_for_url(file_uri)?;
+    let Ok(node) = db.file_syntax(file) else {
+        error!("semantic analysis failed: file '{file_uri}' does not exist");
+        return None;
+    };
 
     let mut data: Vec<SemanticToken> = Vec::new();
-    SemanticTokensTraverser::default().find_semantic_tokens(db.upcast(), &mut data, node);
-    Some(SemanticTokensResult::Tokens(SemanticTokens { result_id: None, data }))
+    SemanticTokensTraverser::new(db, tricks).find_semantic_tokens(db.upcast(), &mut data, node);
+    Some(data)
+}
+
+/// Computes the edit which turns the previous tokens into the current ones.
+///
+/// Only the part between the common prefix and suffix of both arrays is replaced, which is
+/// usually small, as tokens are encoded relative to each other.
+fn token_edits(previous: &[SemanticToken], current: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix =
+        previous.iter().zip(current).take_while(|(previous, current)| previous == current).count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    let deleted = &previous[prefix..previous.len() - suffix];
+    let inserted = &current[prefix..current.len() - suffix];
+    if deleted.is_empty() && inserted.is_empty() {
+        return vec![];
+    }
+
+    // Edits are expressed in integers of the encoded array, each token being five of them.
+    vec![SemanticTokensEdit {
+        start: (prefix * 5) as u32,
+        delete_count: (deleted.len() * 5) as u32,
+        data: Some(inserted.to_vec()),
+    }]
 }
 
 #[derive(Default)]
 struct SemanticTokensTraverser {
     encoder: TokenEncoder,
+    /// The span of the file to find tokens in, or `None` for the whole file.
+    range: Option<TextSpan>,
     /// A map from an offset in the file to semantic token kind.
     /// This map is used to override future tokens based on the context.
     /// For example: when we see the "fn" keyword, the name token is added
     /// to the map, so that instead of marking it as an identifier, we will mark it
     /// as a function name.
     offset_to_kind_lookahead: UnorderedHashMap<TextOffset, SemanticTokenKind>,
+    /// Classifies tokens as token types contributed by [`Tricks::semantic_tokens_extension`].
+    classify_extension: Option<&'static SemanticTokenClassifier>,
 }
 impl SemanticTokensTraverser {
+    fn new(db: &AnalysisDatabase, tricks: &Tricks) -> Self {
+        Self {
+            encoder: TokenEncoder::new(db.position_encoding()),
+            classify_extension: tricks
+                .semantic_tokens_extension
+                .as_ref()
+                .map(|extension| extension.classify),
+            ..Default::default()
+        }
+    }
+
     pub fn find_semantic_tokens(
         &mut self,
         db: &AnalysisDatabase,
@@ -51,6 +164,11 @@ impl SemanticTokensTraverser {
         node: SyntaxNode,
     ) {
         let syntax_db = db.upcast();
+        if !self.overlaps_range(syntax_db, &node) {
+            self.encoder.skip_text(&node.get_text(syntax_db));
+            return;
+        }
+
         let green_node = node.green_node(syntax_db);
         match &green_node.details {
             syntax::node::green::GreenNodeDetails::Token(text) => {
@@ -59,26 +177,36 @@ impl SemanticTokensTraverser {
                     return;
                 }
 
-                let width = text.len() as u32;
+                let width = self.encoder.width(text);
+                let lookahead_kind = self.offset_to_kind_lookahead.remove(&node.offset());
                 let maybe_semantic_kind = self
-                    .offset_to_kind_lookahead
-                    .remove(&node.offset())
+                    .classify_extension
+                    .and_then(|classify| classify(db, &node))
+                    .map(SemanticTokenKind::Extension)
+                    .or(lookahead_kind)
                     .or_else(|| SemanticTokenKind::from_syntax_node(db, node.clone()));
 
                 if let Some(semantic_kind) = maybe_semantic_kind {
                     let Some(text) = node.text(db) else { unreachable!() };
+                    let modifiers =
+                        SemanticTokenModifierKind::bitset_of(db, node.clone(), &semantic_kind);
 
                     if text.contains('\n') {
                         // Split multiline token into multiple single line tokens.
                         for line in text.split_inclusive('\n') {
-                            self.push_semantic_token(line.len() as u32, &semantic_kind, data);
+                            self.push_semantic_token(
+                                self.encoder.width(line),
+                                &semantic_kind,
+                                modifiers,
+                                data,
+                            );
 
                             if line.ends_with('\n') {
                                 self.encoder.next_line();
                             }
                         }
                     } else {
-                        self.push_semantic_token(width, &semantic_kind, data);
+                        self.push_semantic_token(width, &semantic_kind, modifiers, data);
                     }
                 } else {
                     self.encoder.skip(width);
@@ -133,6 +261,7 @@ impl SemanticTokensTraverser {
         &mut self,
         width: u32,
         semantic_kind: &SemanticTokenKind,
+        modifiers: u32,
         data: &mut Vec<SemanticToken>,
     ) {
         let EncodedToken { delta_line, delta_start } = self.encoder.encode(width);
@@ -142,10 +271,17 @@ impl SemanticTokensTraverser {
             delta_start,
             length: width,
             token_type: semantic_kind.as_u32(),
-            token_modifiers_bitset: 0,
+            token_modifiers_bitset: modifiers,
         });
     }
 
+    /// Checks whether the node overlaps the requested range, if there is any.
+    fn overlaps_range(&self, db: &dyn SyntaxGroup, node: &SyntaxNode) -> bool {
+        let Some(range) = self.range else { return true };
+        let span = node.span(db);
+        span.start < range.end && range.start < span.end
+    }
+
     fn mark_future_token(&mut self, offset: TextOffset, semantic_kind: SemanticTokenKind) {
         self.offset_to_kind_lookahead.insert(offset, semantic_kind);
     }
@@ -35,6 +35,10 @@ pub enum SemanticTokenKind {
     Annotation,
     InlineMacro,
     GenericParamImpl,
+    /// A token type contributed by [`Tricks::semantic_tokens_extension`], by its index.
+    ///
+    /// [`Tricks::semantic_tokens_extension`]: crate::Tricks::semantic_tokens_extension
+    Extension(usize),
 }
 impl SemanticTokenKind {
     pub fn from_syntax_node(db: &AnalysisDatabase, mut node: SyntaxNode) -> Option<Self> {
@@ -77,7 +81,9 @@ impl SemanticTokenKind {
             | SyntaxKind::TokenMul
             | SyntaxKind::TokenDiv
             | SyntaxKind::TokenMod => return Some(SemanticTokenKind::Operator),
-            SyntaxKind::TokenSingleLineComment => return Some(SemanticTokenKind::Comment),
+            SyntaxKind::TokenSingleLineComment
+            | SyntaxKind::TokenSingleLineDocComment
+            | SyntaxKind::TokenSingleLineInnerComment => return Some(SemanticTokenKind::Comment),
             SyntaxKind::TokenShortString | SyntaxKind::TokenString => {
                 return Some(SemanticTokenKind::String);
             }
@@ -207,6 +213,8 @@ impl SemanticTokenKind {
             SemanticTokenKind::Annotation => 18,
             SemanticTokenKind::InlineMacro => 19,
             SemanticTokenKind::GenericParamImpl => 20,
+            // Contributed token types follow the default ones in the legend.
+            SemanticTokenKind::Extension(index) => 21 + *index as u32,
         }
     }
     pub fn legend() -> Vec<SemanticTokenType> {
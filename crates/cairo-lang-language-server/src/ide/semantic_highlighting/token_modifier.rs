@@ -0,0 +1,163 @@
+use cairo_lang_defs::ids::{LanguageElementId, StatementItemId};
+use cairo_lang_semantic::VarId;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
+use cairo_lang_semantic::lookup_item::LookupItemEx;
+use cairo_lang_semantic::resolve::{ResolvedConcreteItem, ResolvedGenericItem};
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::SemanticTokenModifier;
+
+use crate::ide::semantic_highlighting::SemanticTokenKind;
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::inspect::defs::VariableDef;
+
+pub enum SemanticTokenModifierKind {
+    /// The name in a declaration of an item, member, variant, parameter or variable.
+    Declaration,
+    /// A `mut` variable or parameter, or a `ref` parameter.
+    Mutable,
+    /// A constant.
+    Readonly,
+    /// A doc comment.
+    Documentation,
+}
+impl SemanticTokenModifierKind {
+    /// Finds the modifiers of a token of the given kind, returns them as a bitset of their
+    /// [`SemanticTokenModifierKind::as_u32`] indices.
+    pub fn bitset_of(db: &AnalysisDatabase, node: SyntaxNode, kind: &SemanticTokenKind) -> u32 {
+        Self::from_syntax_node(db, node, kind)
+            .into_iter()
+            .fold(0, |bitset, modifier| bitset | 1 << modifier.as_u32())
+    }
+
+    fn from_syntax_node(
+        db: &AnalysisDatabase,
+        node: SyntaxNode,
+        kind: &SemanticTokenKind,
+    ) -> Vec<Self> {
+        let syntax_db = db.upcast();
+        match node.kind(syntax_db) {
+            SyntaxKind::TokenIdentifier => {}
+            SyntaxKind::TokenSingleLineDocComment | SyntaxKind::TokenSingleLineInnerComment => {
+                return vec![Self::Documentation];
+            }
+            _ => return vec![],
+        }
+        let Some(identifier) = node.parent() else { return vec![] };
+        let Some(parent) = identifier.parent() else { return vec![] };
+
+        match parent.kind(syntax_db) {
+            SyntaxKind::PatternIdentifier => {
+                let mut modifiers = vec![Self::Declaration];
+                if VariableDef::new(db, identifier).is_some_and(|var| var.is_mutable()) {
+                    modifiers.push(Self::Mutable);
+                }
+                modifiers
+            }
+            SyntaxKind::Param => {
+                let mut modifiers = vec![Self::Declaration];
+                if VariableDef::new(db, parent).is_some_and(|var| var.is_mutable()) {
+                    modifiers.push(Self::Mutable);
+                }
+                modifiers
+            }
+            SyntaxKind::ItemConstant | SyntaxKind::TraitItemConstant => {
+                vec![Self::Declaration, Self::Readonly]
+            }
+            SyntaxKind::ItemModule
+            | SyntaxKind::ItemStruct
+            | SyntaxKind::ItemEnum
+            | SyntaxKind::ItemTrait
+            | SyntaxKind::ItemImpl
+            | SyntaxKind::ItemImplAlias
+            | SyntaxKind::ItemTypeAlias
+            | SyntaxKind::ItemExternType
+            | SyntaxKind::FunctionDeclaration
+            | SyntaxKind::Member
+            | SyntaxKind::Variant
+            | SyntaxKind::GenericParamType
+            | SyntaxKind::GenericParamConst
+            | SyntaxKind::GenericParamImplNamed
+            | SyntaxKind::TraitItemType
+            | SyntaxKind::TraitItemImpl => vec![Self::Declaration],
+            // Only variables and constants, which are highlighted as enum members, have modifiers
+            // at their usages.
+            _ if matches!(kind, SemanticTokenKind::Variable | SemanticTokenKind::EnumMember) => {
+                Self::from_usage(
+                    db,
+                    ast::TerminalIdentifier::from_syntax_node(syntax_db, identifier),
+                )
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Finds the modifiers of an identifier referring to a variable or an item, given the item it
+    /// resolves to, or of an identifier declaring a variable in a pattern without modifiers.
+    fn from_usage(db: &AnalysisDatabase, identifier: ast::TerminalIdentifier) -> Vec<Self> {
+        let Some(lookup_items) = db.collect_lookup_items_stack(&identifier.as_syntax_node()) else {
+            return vec![];
+        };
+        for lookup_item_id in lookup_items {
+            if let Some(item) =
+                db.lookup_resolved_generic_item_by_ptr(lookup_item_id, identifier.stable_ptr())
+            {
+                return match item {
+                    ResolvedGenericItem::GenericConstant(_)
+                    | ResolvedGenericItem::Variable(VarId::Item(StatementItemId::Constant(_))) => {
+                        vec![Self::Readonly]
+                    }
+                    ResolvedGenericItem::Variable(var) => {
+                        let definition = var.untyped_stable_ptr(db.upcast()).lookup(db.upcast());
+                        if VariableDef::new(db, definition).is_some_and(|var| var.is_mutable()) {
+                            vec![Self::Mutable]
+                        } else {
+                            vec![]
+                        }
+                    }
+                    _ => vec![],
+                };
+            }
+            if let Some(item) =
+                db.lookup_resolved_concrete_item_by_ptr(lookup_item_id, identifier.stable_ptr())
+            {
+                return match item {
+                    ResolvedConcreteItem::Constant(_) => vec![Self::Readonly],
+                    _ => vec![],
+                };
+            }
+
+            // A variable pattern without modifiers is a path, which declares the variable.
+            let Some(function_id) = lookup_item_id.function_with_body() else { continue };
+            let Some(path) =
+                db.first_ancestor_of_kind(identifier.as_syntax_node(), SyntaxKind::ExprPath)
+            else {
+                continue;
+            };
+            let path_ptr = ast::ExprPath::from_syntax_node(db.upcast(), path).stable_ptr();
+            if db.lookup_pattern_by_ptr(function_id, path_ptr.into()).is_ok() {
+                return vec![Self::Declaration];
+            }
+        }
+        vec![]
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            SemanticTokenModifierKind::Declaration => 0,
+            SemanticTokenModifierKind::Mutable => 1,
+            SemanticTokenModifierKind::Readonly => 2,
+            SemanticTokenModifierKind::Documentation => 3,
+        }
+    }
+    pub fn legend() -> Vec<SemanticTokenModifier> {
+        vec![
+            SemanticTokenModifier::DECLARATION,
+            SemanticTokenModifier::new("mutable"),
+            SemanticTokenModifier::READONLY,
+            SemanticTokenModifier::DOCUMENTATION,
+        ]
+    }
+}
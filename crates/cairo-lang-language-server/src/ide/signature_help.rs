@@ -0,0 +1,126 @@
+use std::iter;
+
+use cairo_lang_doc::db::DocGroup;
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::helpers::PathSegmentEx;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{SyntaxNode, TypedSyntaxNode, ast};
+use cairo_lang_utils::Upcast;
+use lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureHelpParams, SignatureInformation,
+};
+
+use crate::ide::utils::is_method_call;
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::inspect::defs::find_definition;
+use crate::lang::lsp::{LsProtoGroup, PositionEncoding, ToCairo};
+
+/// Get the signature of the function called by the innermost call expression enclosing the cursor,
+/// together with the parameter the cursor is at.
+pub fn signature_help(params: SignatureHelpParams, db: &AnalysisDatabase) -> Option<SignatureHelp> {
+    let syntax_db = db.upcast();
+    let file = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
+    let position = params.text_document_position_params.position.to_cairo(db, file);
+    let offset = position.offset_in_file(db.upcast(), file)?;
+    let node = db.find_syntax_node_at_position(file, position)?;
+
+    // Find the innermost argument list which contains the cursor between its parentheses.
+    let call = iter::successors(Some(node), SyntaxNode::parent).find_map(|ancestor| {
+        if ancestor.kind(syntax_db) != SyntaxKind::ArgListParenthesized {
+            return None;
+        }
+        let call = ancestor.parent()?;
+        if call.kind(syntax_db) != SyntaxKind::ExprFunctionCall {
+            return None;
+        }
+        let args = ast::ArgListParenthesized::from_syntax_node(syntax_db, ancestor);
+        let lparen = args.lparen(syntax_db).as_syntax_node().span_without_trivia(syntax_db);
+        let rparen = args.rparen(syntax_db).as_syntax_node().span_without_trivia(syntax_db);
+        (lparen.end <= offset && offset <= rparen.start)
+            .then(|| ast::ExprFunctionCall::from_syntax_node(syntax_db, call))
+    })?;
+
+    let identifier = call.path(syntax_db).elements(syntax_db).last()?.identifier_ast(syntax_db);
+    let lookup_items = db.collect_lookup_items_stack(&call.as_syntax_node())?;
+    let (_, definition) = find_definition(db, &identifier, &lookup_items)?;
+    let definition = definition.lookup(syntax_db);
+    let declaration = syntax_db
+        .get_children(definition.clone())
+        .iter()
+        .find(|child| child.kind(syntax_db) == SyntaxKind::FunctionDeclaration)
+        .cloned()?;
+    let declaration = ast::FunctionDeclaration::from_syntax_node(syntax_db, declaration);
+
+    let (label, parameters) = render_signature(syntax_db, &declaration, db.position_encoding());
+
+    // The receiver of a method call is passed as the first, `self` parameter.
+    let mut active_parameter = if is_method_call(syntax_db, &call) { 1 } else { 0 };
+    active_parameter += syntax_db
+        .get_children(call.arguments(syntax_db).arguments(syntax_db).as_syntax_node())
+        .iter()
+        .filter(|child| child.kind(syntax_db) == SyntaxKind::TerminalComma)
+        .filter(|comma| comma.span_without_trivia(syntax_db).end <= offset)
+        .count() as u32;
+
+    let documentation = db
+        .collect_lookup_items_stack(&definition)
+        .and_then(|lookup_items| lookup_items.first().copied())
+        .and_then(|lookup_item| db.get_item_documentation(lookup_item.into()))
+        .map(|documentation| {
+            Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: documentation,
+            })
+        });
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation,
+            parameters: Some(parameters),
+            active_parameter: Some(active_parameter),
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    })
+}
+
+/// Renders the function declaration in a single line, e.g. `fn foo<T>(a: T, ref b: u32) -> u32`,
+/// along with the position of each parameter in it.
+fn render_signature(
+    db: &dyn SyntaxGroup,
+    declaration: &ast::FunctionDeclaration,
+    position_encoding: PositionEncoding,
+) -> (String, Vec<ParameterInformation>) {
+    let text = |node: SyntaxNode| node.get_text_without_trivia(db);
+    let signature = declaration.signature(db);
+
+    let mut label = format!(
+        "fn {}{}(",
+        text(declaration.name(db).as_syntax_node()),
+        text(declaration.generic_params(db).as_syntax_node())
+    );
+    let mut parameters = vec![];
+    for (i, param) in signature.parameters(db).elements(db).into_iter().enumerate() {
+        if i > 0 {
+            label.push_str(", ");
+        }
+        let param = text(param.as_syntax_node());
+        // Offsets are counted in code units of the position encoding, like columns of positions.
+        let start = position_encoding.len(&label) as u32;
+        label.push_str(&param);
+        let end = position_encoding.len(&label) as u32;
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, end]),
+            documentation: None,
+        });
+    }
+    label.push(')');
+    if let ast::OptionReturnTypeClause::ReturnTypeClause(ret_ty) = signature.ret_ty(db) {
+        label.push(' ');
+        label.push_str(&text(ret_ty.as_syntax_node()));
+    }
+
+    (label, parameters)
+}
@@ -0,0 +1,57 @@
+use std::fmt::Write;
+
+use cairo_lang_diagnostics::ToOption;
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_syntax::node::SyntaxNode;
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use lsp_types::TextDocumentPositionParams;
+
+use crate::lang::db::{AnalysisDatabase, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo};
+
+/// Generates a textual dump of the syntax tree of the file, with the node at the given position
+/// marked.
+///
+/// Each line describes a single node: its kind, span in the file and, for tokens, their text.
+/// Empty trivia is omitted, as it is attached to every terminal and would only obscure the tree.
+pub fn show_syntax_tree(
+    db: &AnalysisDatabase,
+    params: &TextDocumentPositionParams,
+) -> Option<String> {
+    let file_id = db.file_for_url(&params.text_document.uri)?;
+    let root = db.file_syntax(file_id).to_option()?;
+    let cursor = db.find_syntax_node_at_position(file_id, params.position.to_cairo(db, file_id));
+
+    let mut dump = String::new();
+    dump_node(db, &root, cursor.as_ref(), 0, &mut dump);
+    Some(dump)
+}
+
+/// Appends lines describing the node and its descendants to the dump.
+fn dump_node(
+    db: &dyn SyntaxGroup,
+    node: &SyntaxNode,
+    cursor: Option<&SyntaxNode>,
+    depth: usize,
+    dump: &mut String,
+) {
+    let kind = node.kind(db);
+    let span = node.span(db).to_str_range();
+    if kind == SyntaxKind::Trivia && span.is_empty() {
+        return;
+    }
+
+    write!(dump, "{:indent$}{kind:?}@{span:?}", "", indent = depth * 2).unwrap();
+    if let Some(text) = node.text(db) {
+        write!(dump, " {text:?}").unwrap();
+    }
+    if cursor == Some(node) {
+        dump.push_str(" <-- cursor");
+    }
+    dump.push('\n');
+
+    for child in db.get_children(node.clone()).iter() {
+        dump_node(db, child, cursor, depth + 1, dump);
+    }
+}
@@ -0,0 +1,256 @@
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{
+    GenericTypeId, ImplDefId, LanguageElementId, LookupItemId, ModuleId, ModuleItemId,
+    NamedLanguageElementId, TopLevelLanguageElementId, TraitId,
+};
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::expr::inference::canonic::CanonicalImpl;
+use cairo_lang_semantic::expr::inference::solver::SolutionSet;
+use cairo_lang_semantic::expr::inference::{InferenceData, InferenceId};
+use cairo_lang_semantic::items::generics::GenericParam;
+use cairo_lang_semantic::items::imp::{ImplLongId, ImplLookupContext};
+use cairo_lang_semantic::items::trt::ConcreteTraitLongId;
+use cairo_lang_semantic::types::TypeHead;
+use cairo_lang_semantic::{ConcreteTypeId, GenericArgumentId, TypeId, TypeLongId};
+use cairo_lang_syntax::node::ast;
+use cairo_lang_utils::{Intern, LookupIntern, Upcast};
+use itertools::Itertools;
+use lsp_types::{
+    SymbolKind, TypeHierarchyItem, TypeHierarchyPrepareParams, TypeHierarchySubtypesParams,
+    TypeHierarchySupertypesParams,
+};
+
+use crate::ide::navigation::references::{
+    declaration_identifier, identifier_span, resolve_identifier,
+};
+use crate::ide::utils::get_copied_origin;
+use crate::lang::db::{AnalysisDatabase, LsSemanticGroup, LsSyntaxGroup};
+use crate::lang::lsp::{LsProtoGroup, ToCairo, ToLsp};
+
+/// Find the trait, impl or type at a given text document position, which becomes the root of the
+/// type hierarchy.
+pub fn prepare(
+    params: TypeHierarchyPrepareParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let file = db.file_for_url(&params.text_document_position_params.text_document.uri)?;
+    let position = params.text_document_position_params.position.to_cairo(db, file);
+    let identifier = db.find_identifier_at_position(file, position)?;
+    let item = hierarchy_module_item(db, &identifier)?;
+    Some(vec![type_hierarchy_item(db, item)?])
+}
+
+/// Find the supertypes of the item: the trait of an impl, or the traits implemented by a type.
+///
+/// Traits implemented only through generic blanket impls, e.g. `impl TClone<T, +Copy<T>> of
+/// Clone<T>`, are included if the bounds of the impl are satisfied by the type.
+pub fn supertypes(
+    params: TypeHierarchySupertypesParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let item = item_module_item(db, &params.item)?;
+    let traits = match item {
+        ModuleItemId::Impl(impl_def_id) => vec![db.impl_def_trait(impl_def_id).ok()?],
+        _ => item_type(db, item).map(|ty| implemented_traits(db, ty)).unwrap_or_default(),
+    };
+    Some(items(db, traits.into_iter().map(ModuleItemId::Trait)))
+}
+
+/// Find the subtypes of the item: all impls of a trait in all crates.
+pub fn subtypes(
+    params: TypeHierarchySubtypesParams,
+    db: &AnalysisDatabase,
+) -> Option<Vec<TypeHierarchyItem>> {
+    let ModuleItemId::Trait(trait_id) = item_module_item(db, &params.item)? else {
+        return Some(vec![]);
+    };
+    let impls = all_impls(db)
+        .into_iter()
+        .filter(|&impl_def_id| db.impl_def_trait(impl_def_id) == Ok(trait_id));
+    Some(items(db, impls.map(ModuleItemId::Impl)))
+}
+
+/// Finds all traits implemented by the type, either directly or through blanket impls.
+fn implemented_traits(db: &AnalysisDatabase, ty: ItemType) -> Vec<TraitId> {
+    let mut traits = vec![];
+    for impl_def_id in all_impls(db) {
+        let Ok(concrete_trait_id) = db.impl_def_concrete_trait(impl_def_id) else { continue };
+        let trait_id = concrete_trait_id.trait_id(db);
+        if traits.contains(&trait_id) {
+            continue;
+        }
+        // Only the first generic argument of a trait is treated as the type implementing it.
+        let Some(GenericArgumentId::Type(self_ty)) =
+            concrete_trait_id.generic_args(db).first().cloned()
+        else {
+            continue;
+        };
+        let implemented = match self_ty.lookup_intern(db) {
+            // Blanket impls without bounds on the type, like `impl ArrayImpl<T> of
+            // ArrayTrait<T>`, apply to every type, so they are not listed.
+            TypeLongId::GenericParameter(_) => {
+                is_bounded_blanket_impl(db, impl_def_id, self_ty)
+                    && is_implemented_by(db, impl_def_id, trait_id, &ty)
+            }
+            _ => self_ty.head(db) == Some(TypeHead::Concrete(ty.generic_type)),
+        };
+        if implemented {
+            traits.push(trait_id);
+        }
+    }
+    traits
+}
+
+/// Checks whether the impl has a generic impl parameter constraining the type it is implemented
+/// for, e.g. `+Copy<T>` in `impl TClone<T, +Copy<T>> of Clone<T>`.
+fn is_bounded_blanket_impl(db: &AnalysisDatabase, impl_def_id: ImplDefId, self_ty: TypeId) -> bool {
+    let Ok(generic_params) = db.impl_def_generic_params(impl_def_id) else { return false };
+    generic_params.iter().any(|param| match param {
+        GenericParam::Impl(param) => param.concrete_trait.is_ok_and(|concrete_trait_id| {
+            concrete_trait_id.generic_args(db).first() == Some(&GenericArgumentId::Type(self_ty))
+        }),
+        _ => false,
+    })
+}
+
+/// Checks whether the impl is the one implementing the trait for the type, i.e. whether its bounds
+/// are satisfied by the type.
+///
+/// Only types without generic parameters are checked, as the impls found for generic ones depend
+/// on the generic arguments.
+fn is_implemented_by(
+    db: &AnalysisDatabase,
+    impl_def_id: ImplDefId,
+    trait_id: TraitId,
+    ty: &ItemType,
+) -> bool {
+    let Some(self_ty) = ty.concrete else { return false };
+    let Ok(generic_params) = db.trait_generic_params(trait_id) else { return false };
+
+    let mut inference_data = InferenceData::new(InferenceId::NoContext);
+    let mut inference = inference_data.inference(db);
+    let mut generic_args = vec![GenericArgumentId::Type(self_ty)];
+    for param in generic_params.iter().skip(1) {
+        let GenericParam::Type(_) = param else { return false };
+        generic_args.push(GenericArgumentId::Type(inference.new_type_var(None)));
+    }
+    let concrete_trait_id = ConcreteTraitLongId { trait_id, generic_args }.intern(db);
+    let lookup_context = ImplLookupContext::new(ty.module_id, vec![]);
+    let Ok(SolutionSet::Unique((CanonicalImpl(impl_id), _))) =
+        inference.trait_solution_set(concrete_trait_id, lookup_context)
+    else {
+        return false;
+    };
+    matches!(
+        impl_id.lookup_intern(db),
+        ImplLongId::Concrete(concrete_impl_id) if concrete_impl_id.impl_def_id(db) == impl_def_id
+    )
+}
+
+/// A type defined by a module item.
+struct ItemType {
+    generic_type: GenericTypeId,
+    /// The type itself, if it has no generic parameters.
+    concrete: Option<TypeId>,
+    module_id: ModuleId,
+}
+
+/// Returns the type defined by the module item, if it is a struct, an enum or an extern type.
+fn item_type(db: &AnalysisDatabase, item: ModuleItemId) -> Option<ItemType> {
+    let (generic_type, generic_params) = match item {
+        ModuleItemId::Struct(id) => (GenericTypeId::Struct(id), db.struct_generic_params(id)),
+        ModuleItemId::Enum(id) => (GenericTypeId::Enum(id), db.enum_generic_params(id)),
+        ModuleItemId::ExternType(id) => {
+            (GenericTypeId::Extern(id), db.extern_type_declaration_generic_params(id))
+        }
+        _ => return None,
+    };
+    let concrete = generic_params
+        .is_ok_and(|params| params.is_empty())
+        .then(|| TypeLongId::Concrete(ConcreteTypeId::new(db, generic_type, vec![])).intern(db));
+    Some(ItemType { generic_type, concrete, module_id: item.parent_module(db.upcast()) })
+}
+
+/// Collects impls defined in all modules of all crates.
+fn all_impls(db: &AnalysisDatabase) -> Vec<ImplDefId> {
+    db.crates()
+        .into_iter()
+        .flat_map(|crate_id| db.crate_modules(crate_id).iter().copied().collect::<Vec<_>>())
+        .filter_map(|module_id| db.module_impls_ids(module_id).ok())
+        .flat_map(|impls| impls.iter().copied().collect::<Vec<_>>())
+        .collect()
+}
+
+/// Finds the module item the type hierarchy item was created for.
+fn item_module_item(db: &AnalysisDatabase, item: &TypeHierarchyItem) -> Option<ModuleItemId> {
+    let file = db.file_for_url(&item.uri)?;
+    let identifier =
+        db.find_identifier_at_position(file, item.selection_range.start.to_cairo(db, file))?;
+    hierarchy_module_item(db, &identifier)
+}
+
+/// Finds the trait, impl or type the identifier refers to or which it names in its declaration.
+fn hierarchy_module_item(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+) -> Option<ModuleItemId> {
+    let definition = resolve_identifier(db, identifier)?;
+    let item = match db.find_lookup_item(&definition.lookup(db.upcast()))? {
+        LookupItemId::ModuleItem(item) => item,
+        _ => return None,
+    };
+    symbol_kind(item).map(|_| item)
+}
+
+/// Creates type hierarchy items for the module items, sorted by their location.
+fn items(
+    db: &AnalysisDatabase,
+    module_items: impl Iterator<Item = ModuleItemId>,
+) -> Vec<TypeHierarchyItem> {
+    module_items
+        .filter_map(|item| type_hierarchy_item(db, item))
+        .sorted_by_key(|item| (item.uri.to_string(), item.selection_range.start))
+        .collect()
+}
+
+/// Creates a type hierarchy item for the module item.
+///
+/// Returns `None` for items generated by macros, unless they were copied from the user's code.
+fn type_hierarchy_item(db: &AnalysisDatabase, item: ModuleItemId) -> Option<TypeHierarchyItem> {
+    let syntax_db = db.upcast();
+    let kind = symbol_kind(item)?;
+    let stable_ptr = item.untyped_stable_ptr(db.upcast());
+    let name = declaration_identifier(db, stable_ptr)?;
+    let (file, selection_span) = identifier_span(db, &name)?;
+    let span = get_copied_origin(
+        db,
+        stable_ptr.file_id(syntax_db),
+        stable_ptr.lookup(syntax_db).span_without_trivia(syntax_db),
+    )
+    .filter(|&(origin_file, _)| origin_file == file)
+    .map_or(selection_span, |(_, span)| span);
+
+    Some(TypeHierarchyItem {
+        name: item.name(db.upcast()).to_string(),
+        kind,
+        tags: None,
+        detail: Some(item.full_path(db.upcast())),
+        uri: db.url_for_file(file)?,
+        range: span.position_in_file(db.upcast(), file)?.to_lsp(db, file),
+        selection_range: selection_span.position_in_file(db.upcast(), file)?.to_lsp(db, file),
+        data: None,
+    })
+}
+
+/// Returns the symbol kind of a module item, or `None` if the item is not part of type
+/// hierarchies.
+fn symbol_kind(item: ModuleItemId) -> Option<SymbolKind> {
+    Some(match item {
+        ModuleItemId::Trait(_) => SymbolKind::INTERFACE,
+        ModuleItemId::Impl(_) => SymbolKind::OBJECT,
+        ModuleItemId::Struct(_) | ModuleItemId::ExternType(_) => SymbolKind::STRUCT,
+        ModuleItemId::Enum(_) => SymbolKind::ENUM,
+        _ => return None,
+    })
+}
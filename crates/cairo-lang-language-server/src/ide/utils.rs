@@ -1,14 +1,34 @@
-use cairo_lang_defs::ids::TraitFunctionId;
-use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_defs::ids::{
+    EnumId, FunctionWithBodyId, GenericTypeId, LanguageElementId, ModuleId, ModuleItemId,
+    NamedLanguageElementId, TopLevelLanguageElementId, TraitFunctionId,
+};
+use cairo_lang_filesystem::db::{ExternalFiles, FilesGroup};
+use cairo_lang_filesystem::ids::{CodeOrigin, FileId, FileLongId};
+use cairo_lang_filesystem::span::{TextOffset, TextSpan};
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_semantic::corelib::{core_submodule, get_submodule};
 use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_semantic::expr::inference::InferenceId;
 use cairo_lang_semantic::expr::inference::infers::InferenceEmbeddings;
 use cairo_lang_semantic::expr::inference::solver::SolutionSet;
+use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
+use cairo_lang_semantic::items::us::SemanticUseEx;
 use cairo_lang_semantic::lsp_helpers::TypeFilter;
-use cairo_lang_semantic::resolve::Resolver;
+use cairo_lang_semantic::resolve::{ResolvedGenericItem, Resolver};
+use cairo_lang_semantic::types::peel_snapshots;
+use cairo_lang_semantic::{ConcreteEnumId, ConcreteTypeId, TypeLongId};
+use cairo_lang_syntax::node::ast::{self, BinaryOperator};
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use cairo_lang_syntax::node::helpers::GetIdentifier;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{TypedStablePtr, TypedSyntaxNode};
+use cairo_lang_utils::{LookupIntern, Upcast};
+use lsp_types::{Range, TextEdit};
+use smol_str::SmolStr;
 use tracing::debug;
 
 use crate::lang::db::AnalysisDatabase;
+use crate::lang::lsp::ToLsp;
 
 /// Finds all methods that can be called on a type.
 pub fn find_methods_for_type(
@@ -59,3 +79,186 @@ pub fn find_methods_for_type(
     }
     relevant_methods
 }
+
+/// Maps a span in a file generated by macros back to the on-disk code it was copied from.
+///
+/// Returns the span unchanged for on-disk files. Returns `None` if the code was generated rather
+/// than copied, i.e. it is only mapped to the span of the macro call it originates from.
+pub fn get_copied_origin(
+    db: &AnalysisDatabase,
+    mut file_id: FileId,
+    mut span: TextSpan,
+) -> Option<(FileId, TextSpan)> {
+    loop {
+        let virtual_file = match file_id.lookup_intern(db) {
+            FileLongId::OnDisk(_) => return Some((file_id, span)),
+            FileLongId::Virtual(virtual_file) => virtual_file,
+            FileLongId::External(id) => db.ext_as_virtual(id),
+        };
+        span = virtual_file.code_mappings.iter().find_map(|mapping| match mapping.origin {
+            CodeOrigin::Start(_) => mapping.translate(span),
+            CodeOrigin::Span(_) => None,
+        })?;
+        file_id = virtual_file.parent?;
+    }
+}
+
+/// Checks whether the call is the right-hand side of a `.` operator, e.g. `a.foo()`.
+pub fn is_method_call(db: &dyn SyntaxGroup, call: &ast::ExprFunctionCall) -> bool {
+    let Some(parent) = call.as_syntax_node().parent() else { return false };
+    if parent.kind(db) != SyntaxKind::ExprBinary {
+        return false;
+    }
+    let binary = ast::ExprBinary::from_syntax_node(db, parent);
+    matches!(binary.op(db), BinaryOperator::Dot(_))
+        && binary.rhs(db).as_syntax_node().stable_ptr() == call.as_syntax_node().stable_ptr()
+}
+
+/// Finds the enum matched by the `match` expression in the function body, looking through
+/// snapshots.
+pub fn matched_enum(
+    db: &AnalysisDatabase,
+    function_with_body: FunctionWithBodyId,
+    match_expr: &ast::ExprMatch,
+) -> Option<ConcreteEnumId> {
+    // Parenthesized expressions are not present in the semantic model, so the expression inside
+    // is looked up instead.
+    let syntax_db = db.upcast();
+    let mut matched = match_expr.expr(syntax_db);
+    while let ast::Expr::Parenthesized(parenthesized) = matched {
+        matched = parenthesized.expr(syntax_db);
+    }
+    let expr_id = db.lookup_expr_by_ptr(function_with_body, matched.stable_ptr()).ok()?;
+    let ty = db.expr_semantic(function_with_body, expr_id).ty();
+    match peel_snapshots(db, ty).1 {
+        TypeLongId::Concrete(ConcreteTypeId::Enum(concrete_enum_id)) => Some(concrete_enum_id),
+        _ => None,
+    }
+}
+
+/// Returns the name of the enum variant a pattern of a `match` arm refers to, e.g. `Some` for
+/// `Option::Some(x)`, or `None` if it is not a variant pattern.
+pub fn pattern_variant_name(db: &dyn SyntaxGroup, pattern: &ast::Pattern) -> Option<SmolStr> {
+    match pattern {
+        ast::Pattern::Enum(pattern) => Some(pattern.path(db).identifier(db)),
+        ast::Pattern::Path(path) => Some(path.identifier(db)),
+        _ => None,
+    }
+}
+
+/// Returns an edit adding a `use` statement importing the path into the module.
+///
+/// The statement is inserted before the first existing `use` statement of the module which sorts
+/// after it, or after the last one, or at the beginning of the module if it has none.
+pub fn use_statement_edit(
+    db: &AnalysisDatabase,
+    file_id: FileId,
+    module_id: ModuleId,
+    path: &str,
+) -> Option<TextEdit> {
+    let syntax_db = db.upcast();
+    let items = match module_id {
+        ModuleId::CrateRoot(_) => None,
+        ModuleId::Submodule(submodule_id) => {
+            match submodule_id.stable_ptr(db.upcast()).lookup(syntax_db).body(syntax_db) {
+                ast::MaybeModuleBody::Some(body) => Some(body.items(syntax_db)),
+                ast::MaybeModuleBody::None(_) => None,
+            }
+        }
+    };
+    let items = match items {
+        Some(items) => items,
+        None => db.file_module_syntax(file_id).ok()?.items(syntax_db),
+    };
+    let uses = items
+        .elements(syntax_db)
+        .into_iter()
+        .filter_map(|item| match item {
+            ast::ModuleItem::Use(item_use) => Some(item_use),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let position_of = |offset: TextOffset| offset.position_in_file(db.upcast(), file_id);
+    let (position, new_text) = match uses.iter().find(|item_use| {
+        item_use.use_path(syntax_db).as_syntax_node().get_text_without_trivia(syntax_db).as_str()
+            > path
+    }) {
+        Some(next_use) => {
+            let position =
+                position_of(next_use.as_syntax_node().span_start_without_trivia(syntax_db))?;
+            let indent = " ".repeat(position.col);
+            (position, format!("use {path};\n{indent}"))
+        }
+        None => match uses.last() {
+            Some(last_use) => {
+                let start =
+                    position_of(last_use.as_syntax_node().span_start_without_trivia(syntax_db))?;
+                let end =
+                    position_of(last_use.as_syntax_node().span_end_without_trivia(syntax_db))?;
+                let indent = " ".repeat(start.col);
+                (end, format!("\n{indent}use {path};"))
+            }
+            None => {
+                let position =
+                    position_of(items.as_syntax_node().span_start_without_trivia(syntax_db))?;
+                let indent = " ".repeat(position.col);
+                (position, format!("use {path};\n{indent}"))
+            }
+        },
+    };
+    let position = position.to_lsp(db, file_id);
+    Some(TextEdit { range: Range::new(position, position), new_text })
+}
+
+/// Returns the leading whitespace of the line in the file.
+pub fn line_indent(db: &AnalysisDatabase, file_id: FileId, line: usize) -> Option<String> {
+    let content = db.file_content(file_id)?;
+    let line = content.lines().nth(line)?;
+    Some(line[..line.len() - line.trim_start().len()].to_string())
+}
+
+/// Returns the prelude module of the edition of the crate containing the module.
+pub fn prelude_module(db: &AnalysisDatabase, module_id: ModuleId) -> Option<ModuleId> {
+    let mut current_top_module = module_id;
+    while let ModuleId::Submodule(submodule_id) = current_top_module {
+        current_top_module = submodule_id.parent_module(db.upcast());
+    }
+    let crate_id = match current_top_module {
+        ModuleId::CrateRoot(crate_id) => crate_id,
+        ModuleId::Submodule(_) => unreachable!("current module is not a top-level module"),
+    };
+    let edition =
+        db.crate_config(crate_id).map(|config| config.settings.edition).unwrap_or_default();
+    let prelude_submodule_name = edition.prelude_submodule_name();
+    let core_prelude_submodule = core_submodule(db, "prelude");
+    get_submodule(db, core_prelude_submodule, prelude_submodule_name)
+}
+
+/// Returns the path under which the enum can be referred to in the module.
+///
+/// This is the name of the enum if it is in scope of the module, otherwise the path of the enum
+/// visible from the module, falling back to its full path.
+pub fn enum_path_in_module(db: &AnalysisDatabase, enum_id: EnumId, module_id: ModuleId) -> String {
+    let name = enum_id.name(db.upcast());
+    // Items of the module shadow the ones of the prelude.
+    let item_in_scope = [Some(module_id), prelude_module(db, module_id)]
+        .into_iter()
+        .flatten()
+        .find_map(|module_id| db.module_item_by_name(module_id, name.clone()).ok().flatten());
+    let in_scope = match item_in_scope {
+        Some(ModuleItemId::Enum(item_enum_id)) => item_enum_id == enum_id,
+        Some(ModuleItemId::Use(use_id)) => {
+            db.use_resolved_item(use_id)
+                == Ok(ResolvedGenericItem::GenericType(GenericTypeId::Enum(enum_id)))
+        }
+        _ => false,
+    };
+    if in_scope {
+        return name.to_string();
+    }
+
+    db.visible_importables_from_module(module_id)
+        .and_then(|importables| importables.get(&ModuleItemId::Enum(enum_id)).cloned())
+        .unwrap_or_else(|| enum_id.full_path(db.upcast()))
+}
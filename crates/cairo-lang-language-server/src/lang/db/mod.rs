@@ -1,10 +1,14 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
 use cairo_lang_defs::db::{DefsDatabase, DefsGroup, try_ext_as_virtual_impl};
 use cairo_lang_doc::db::DocDatabase;
 use cairo_lang_filesystem::cfg::{Cfg, CfgSet};
 use cairo_lang_filesystem::db::{
     AsFilesGroupMut, ExternalFiles, FilesDatabase, FilesGroup, init_files_group,
 };
-use cairo_lang_filesystem::ids::VirtualFile;
+use cairo_lang_filesystem::ids::{CrateId, VirtualFile};
 use cairo_lang_lowering::db::{LoweringDatabase, LoweringGroup, init_lowering_group};
 use cairo_lang_lowering::utils::InliningStrategy;
 use cairo_lang_parser::db::{ParserDatabase, ParserGroup};
@@ -15,16 +19,22 @@ use cairo_lang_starknet::starknet_plugin_suite;
 use cairo_lang_syntax::node::db::{SyntaxDatabase, SyntaxGroup};
 use cairo_lang_test_plugin::test_plugin_suite;
 use cairo_lang_utils::Upcast;
+use tracing::warn;
 
 pub use self::semantic::*;
 pub use self::swapper::*;
 pub use self::syntax::*;
 use crate::Tricks;
+use crate::lang::lsp::PositionEncoding;
 
 mod semantic;
 mod swapper;
 mod syntax;
 
+#[cfg(test)]
+#[path = "mod_test.rs"]
+mod test;
+
 /// The Cairo compiler Salsa database tailored for language server usage.
 #[salsa::database(
     DefsDatabase,
@@ -37,12 +47,23 @@ mod syntax;
 )]
 pub struct AnalysisDatabase {
     storage: salsa::Storage<Self>,
+    position_encoding: PositionEncoding,
+    /// Paths to `Scarb.toml` files of packages which crates were loaded from.
+    scarb_manifest_paths: Arc<HashMap<CrateId, PathBuf>>,
+    /// Crates which are members of the workspace, as opposed to its dependencies.
+    workspace_members: Arc<HashSet<CrateId>>,
 }
 
 impl AnalysisDatabase {
-    /// Creates a new instance of the database.
-    pub fn new(tricks: &Tricks) -> Self {
-        let mut db = Self { storage: Default::default() };
+    /// Creates a new instance of the database, which converts positions to and from LSP using
+    /// `position_encoding`.
+    pub fn new(tricks: &Tricks, position_encoding: PositionEncoding) -> Self {
+        let mut db = Self {
+            storage: Default::default(),
+            position_encoding,
+            scarb_manifest_paths: Default::default(),
+            workspace_members: Default::default(),
+        };
 
         init_files_group(&mut db);
         init_lowering_group(&mut db, InliningStrategy::Default);
@@ -57,11 +78,46 @@ impl AnalysisDatabase {
                     acc.add(suite);
                     acc
                 });
+        let plugin_suite = add_extra_inline_macro_plugins(plugin_suite, tricks);
         db.apply_plugin_suite(plugin_suite);
 
         db
     }
 
+    /// Returns the encoding of columns of LSP positions negotiated with the client.
+    pub fn position_encoding(&self) -> PositionEncoding {
+        self.position_encoding
+    }
+
+    /// Returns the path to `Scarb.toml` of the package the crate was loaded from, if any.
+    pub fn scarb_manifest_path(&self, crate_id: CrateId) -> Option<&Path> {
+        self.scarb_manifest_paths.get(&crate_id).map(PathBuf::as_path)
+    }
+
+    /// Sets the path to `Scarb.toml` of the package the crate was loaded from.
+    pub fn set_scarb_manifest_path(&mut self, crate_id: CrateId, manifest_path: Option<PathBuf>) {
+        let manifest_paths = Arc::make_mut(&mut self.scarb_manifest_paths);
+        match manifest_path {
+            Some(manifest_path) => manifest_paths.insert(crate_id, manifest_path),
+            None => manifest_paths.remove(&crate_id),
+        };
+    }
+
+    /// Checks whether the crate is a member of the workspace, rather than a dependency of it.
+    pub fn is_workspace_member(&self, crate_id: CrateId) -> bool {
+        self.workspace_members.contains(&crate_id)
+    }
+
+    /// Sets whether the crate is a member of the workspace.
+    pub fn set_workspace_member(&mut self, crate_id: CrateId, is_member: bool) {
+        let workspace_members = Arc::make_mut(&mut self.workspace_members);
+        if is_member {
+            workspace_members.insert(crate_id);
+        } else {
+            workspace_members.remove(&crate_id);
+        }
+    }
+
     /// Returns the [`CfgSet`] that should be assumed in the initial database state
     /// and in [`CfgSet`] for workspace members.
     /// This enables code fragments tagged with `#[cfg(test)]` and `#[cfg(target: 'test')]` to be
@@ -85,6 +141,19 @@ impl AnalysisDatabase {
     }
 }
 
+/// Adds inline macro plugins contributed by [`Tricks::extra_inline_macro_plugins`] to the suite,
+/// skipping ones which would replace inline macros already in it.
+fn add_extra_inline_macro_plugins(mut plugin_suite: PluginSuite, tricks: &Tricks) -> PluginSuite {
+    for (name, plugin) in tricks.extra_inline_macro_plugins.iter().flat_map(|f| f()) {
+        if plugin_suite.inline_macro_plugins.contains_key(&name) {
+            warn!("skipping extra inline macro plugin `{name}` conflicting with a registered one");
+            continue;
+        }
+        plugin_suite.add_inline_macro_plugin_ex(&name, plugin);
+    }
+    plugin_suite
+}
+
 impl salsa::Database for AnalysisDatabase {}
 impl ExternalFiles for AnalysisDatabase {
     fn try_ext_as_virtual(&self, external_id: salsa::InternId) -> Option<VirtualFile> {
@@ -94,7 +163,12 @@ impl ExternalFiles for AnalysisDatabase {
 
 impl salsa::ParallelDatabase for AnalysisDatabase {
     fn snapshot(&self) -> salsa::Snapshot<Self> {
-        salsa::Snapshot::new(AnalysisDatabase { storage: self.storage.snapshot() })
+        salsa::Snapshot::new(AnalysisDatabase {
+            storage: self.storage.snapshot(),
+            position_encoding: self.position_encoding,
+            scarb_manifest_paths: self.scarb_manifest_paths.clone(),
+            workspace_members: self.workspace_members.clone(),
+        })
     }
 }
 
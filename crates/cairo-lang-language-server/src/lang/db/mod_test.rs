@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::plugin::{InlineMacroExprPlugin, InlinePluginResult, MacroPluginMetadata};
+use cairo_lang_syntax::node::ast;
+use cairo_lang_syntax::node::db::SyntaxGroup;
+
+use super::AnalysisDatabase;
+use crate::lang::lsp::PositionEncoding;
+use crate::{InlineMacroPlugins, Tricks};
+
+/// An inline macro that generates no code, identified by its documentation.
+#[derive(Debug)]
+struct CustomMacro;
+
+impl InlineMacroExprPlugin for CustomMacro {
+    fn generate_code(
+        &self,
+        _db: &dyn SyntaxGroup,
+        _item_ast: &ast::ExprInlineMacro,
+        _metadata: &MacroPluginMetadata<'_>,
+    ) -> InlinePluginResult {
+        InlinePluginResult { code: None, diagnostics: vec![] }
+    }
+
+    fn documentation(&self) -> Option<String> {
+        Some("custom".into())
+    }
+}
+
+fn custom_inline_macro_plugins() -> InlineMacroPlugins {
+    vec![("custom".into(), Arc::new(CustomMacro)), ("array".into(), Arc::new(CustomMacro))]
+}
+
+#[test]
+fn extra_inline_macro_plugins_do_not_replace_registered_ones() {
+    let tricks = Tricks {
+        extra_inline_macro_plugins: Some(&custom_inline_macro_plugins),
+        ..Default::default()
+    };
+
+    let db = AnalysisDatabase::new(&tricks, PositionEncoding::default());
+    let plugins = db.inline_macro_plugins();
+
+    assert_eq!(plugins["custom"].documentation().as_deref(), Some("custom"));
+    assert_ne!(plugins["array"].documentation().as_deref(), Some("custom"));
+}
@@ -1,5 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
@@ -8,14 +9,20 @@ use cairo_lang_filesystem::ids::FileId;
 use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
 use cairo_lang_utils::{Intern, LookupIntern};
 use lsp_types::Url;
-use tracing::{error, warn};
+use salsa::{Database, Durability};
+use tracing::{error, trace, warn};
 
 use crate::config::Config;
 use crate::lang::db::AnalysisDatabase;
 use crate::lang::lsp::LsProtoGroup;
 use crate::server::client::Notifier;
+use crate::state::BackgroundRequests;
 use crate::toolchain::scarb::ScarbToolchain;
-use crate::{Backend, Tricks, env_config};
+use crate::{Backend, Tricks};
+
+#[cfg(test)]
+#[path = "swapper_test.rs"]
+mod test;
 
 /// Swaps entire [`AnalysisDatabase`] with empty one periodically.
 ///
@@ -26,33 +33,43 @@ use crate::{Backend, Tricks, env_config};
 /// This object realises a nuclear GC strategy by wiping the entire analysis database from time to
 /// time.
 ///
-/// The swapping period can be configured with environment variables.
-/// Consult [`env_config::db_replace_interval`] for more information.
+/// The swapping period can be configured with [`Config::db_replace_interval`].
 ///
 /// The new database has a clean state.
 /// It is expected that diagnostics will be refreshed on it as quickly as possible, otherwise
 /// the entire workspace would be recompiled at an undetermined time leading to bad UX delays.
 pub struct AnalysisDatabaseSwapper {
     last_replace: SystemTime,
-    db_replace_interval: Duration,
+    /// The longest time a due swap is deferred while background requests are in flight.
+    max_deferral: Duration,
     scarb_toolchain: ScarbToolchain,
 }
 
 impl AnalysisDatabaseSwapper {
+    /// Default value of [`AnalysisDatabaseSwapper::max_deferral`].
+    const DEFAULT_MAX_DEFERRAL: Duration = Duration::from_secs(60);
+
     /// Creates a new `AnalysisDatabaseSwapper`.
     pub fn new(scarb_toolchain: ScarbToolchain) -> Self {
         Self {
             last_replace: SystemTime::now(),
-            db_replace_interval: env_config::db_replace_interval(),
+            max_deferral: Self::DEFAULT_MAX_DEFERRAL,
             scarb_toolchain,
         }
     }
 
     /// Checks if enough time has passed since last db swap, and if so, swaps the database.
+    ///
+    /// The swap is deferred while background requests are in flight, because it would cancel
+    /// them. Steady traffic of requests could defer it forever though, so once it is overdue by
+    /// more than [`AnalysisDatabaseSwapper::max_deferral`], the requests are cancelled.
+    #[expect(clippy::too_many_arguments)]
     pub fn maybe_swap(
         &mut self,
         db: &mut AnalysisDatabase,
+        background_requests: &BackgroundRequests,
         open_files: &HashSet<Url>,
+        workspace_folders: &BTreeSet<PathBuf>,
         config: &Config,
         tricks: &Tricks,
         notifier: &Notifier,
@@ -66,27 +83,41 @@ impl AnalysisDatabaseSwapper {
             return;
         };
 
-        if elapsed <= self.db_replace_interval {
+        if elapsed <= config.db_replace_interval {
             // Not enough time passed since the last swap.
             return;
         }
 
-        self.swap(db, open_files, config, tricks, notifier)
+        if background_requests.any_in_flight() {
+            if elapsed <= config.db_replace_interval.saturating_add(self.max_deferral) {
+                trace!("background requests are in flight, deferring db swap");
+                return;
+            }
+
+            warn!("db swap has been deferred for too long, cancelling background requests");
+            // Salsa can only cancel all running queries at once. This blocks until all
+            // snapshots of the database are dropped.
+            db.salsa_runtime_mut().synthetic_write(Durability::LOW);
+        }
+
+        self.swap(db, open_files, workspace_folders, config, tricks, notifier)
     }
 
-    /// Swaps the database.
+    /// Swaps the database, regardless of the time passed since the last swap.
     #[tracing::instrument(skip_all)]
-    fn swap(
+    pub fn swap(
         &mut self,
         db: &mut AnalysisDatabase,
         open_files: &HashSet<Url>,
+        workspace_folders: &BTreeSet<PathBuf>,
         config: &Config,
         tricks: &Tricks,
         notifier: &Notifier,
     ) {
         let Ok(new_db) = catch_unwind(AssertUnwindSafe(|| {
-            let mut new_db = AnalysisDatabase::new(tricks);
+            let mut new_db = AnalysisDatabase::new(tricks, db.position_encoding());
             self.migrate_file_overrides(&mut new_db, db, open_files);
+            self.load_workspace_folders(&mut new_db, workspace_folders, config, notifier);
             self.detect_crates_for_open_files(&mut new_db, open_files, config, notifier);
             new_db
         })) else {
@@ -123,6 +154,27 @@ impl AnalysisDatabaseSwapper {
         new_db.set_file_overrides(Arc::new(new_overrides));
     }
 
+    /// Loads projects of all workspace folders, so that their crates are known even when none of
+    /// their files are open.
+    fn load_workspace_folders(
+        &self,
+        new_db: &mut AnalysisDatabase,
+        workspace_folders: &BTreeSet<PathBuf>,
+        config: &Config,
+        notifier: &Notifier,
+    ) {
+        for folder in workspace_folders {
+            Backend::load_workspace_folder(
+                new_db,
+                &self.scarb_toolchain,
+                config,
+                folder,
+                notifier,
+                None,
+            );
+        }
+    }
+
     /// Ensures all open files have their crates detected to regenerate crates state.
     fn detect_crates_for_open_files(
         &self,
@@ -137,7 +189,15 @@ impl AnalysisDatabaseSwapper {
                 continue;
             };
 
-            Backend::detect_crate_for(new_db, &self.scarb_toolchain, config, &file_path, notifier);
+            // Crates are detected silently, because the swap is not triggered by the user.
+            Backend::detect_crate_for(
+                new_db,
+                &self.scarb_toolchain,
+                config,
+                &file_path,
+                notifier,
+                None,
+            );
         }
     }
 }
@@ -0,0 +1,62 @@
+use std::collections::{BTreeSet, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use salsa::{Cancelled, Database, ParallelDatabase};
+
+use super::AnalysisDatabaseSwapper;
+use crate::Tricks;
+use crate::config::Config;
+use crate::lang::db::AnalysisDatabase;
+use crate::lang::lsp::PositionEncoding;
+use crate::server::client::Client;
+use crate::server::connection::ClientSender;
+use crate::state::BackgroundRequests;
+use crate::toolchain::scarb::ScarbToolchain;
+
+#[test]
+fn swap_cancels_requests_in_flight_once_deferred_for_too_long() {
+    let notifier = Client::new(ClientSender::disconnected()).notifier();
+    let mut swapper = AnalysisDatabaseSwapper::new(ScarbToolchain::new(notifier.clone()));
+    let tricks = Tricks::default();
+    let mut db = AnalysisDatabase::new(&tricks, PositionEncoding::default());
+    let config = Config { db_replace_interval: Duration::ZERO, ..Default::default() };
+    let background_requests = BackgroundRequests::default();
+
+    // A request which is held open until it is cancelled.
+    let guard = background_requests.start();
+    let snapshot = db.snapshot();
+    let request = thread::spawn(move || {
+        let _guard = guard;
+        Cancelled::catch(AssertUnwindSafe(|| {
+            loop {
+                snapshot.unwind_if_cancelled();
+                thread::sleep(Duration::from_millis(10));
+            }
+        }))
+    });
+
+    let mut maybe_swap = |swapper: &mut AnalysisDatabaseSwapper| {
+        let last_replace = swapper.last_replace;
+        swapper.maybe_swap(
+            &mut db,
+            &background_requests,
+            &HashSet::new(),
+            &BTreeSet::new(),
+            &config,
+            &tricks,
+            &notifier,
+        );
+        swapper.last_replace != last_replace
+    };
+
+    swapper.last_replace = SystemTime::now() - Duration::from_secs(1);
+    swapper.max_deferral = Duration::from_secs(3600);
+    assert!(!maybe_swap(&mut swapper), "swap should be deferred while the request is in flight");
+
+    swapper.max_deferral = Duration::ZERO;
+    assert!(maybe_swap(&mut swapper), "swap should not be deferred for too long");
+    assert!(request.join().unwrap().is_err(), "request should be cancelled");
+    assert!(!background_requests.any_in_flight());
+}
@@ -1,22 +1,42 @@
 use cairo_lang_diagnostics::{DiagnosticEntry, DiagnosticLocation, Diagnostics, Severity};
 use cairo_lang_filesystem::db::FilesGroup;
-use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_filesystem::ids::{FileId, FileLongId};
+use cairo_lang_filesystem::span::TextSpan;
+use cairo_lang_lowering::diagnostic::{
+    LoweringDiagnostic, LoweringDiagnosticKind, MatchDiagnostic, MatchError, MatchKind,
+};
+use cairo_lang_parser::ParserDiagnostic;
+use cairo_lang_semantic::SemanticDiagnostic;
+use cairo_lang_semantic::diagnostic::SemanticDiagnosticKind;
 use cairo_lang_utils::{LookupIntern, Upcast};
 use lsp_types::{
-    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Range,
+    CodeDescription, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag,
+    Location, NumberOrString, Range,
 };
+use serde_json::json;
 use tracing::{error, trace};
 
-use crate::lang::lsp::{LsProtoGroup, ToLsp};
+use crate::config::Config;
+use crate::lang::lsp::{LsProtoGroup, PositionEncoding};
+
+#[cfg(test)]
+#[path = "lsp_test.rs"]
+mod test;
 
 /// Converts internal diagnostics to LSP format.
-pub fn map_cairo_diagnostics_to_lsp<T: DiagnosticEntry>(
+///
+/// The severity of diagnostics is overridden according to
+/// [`Config::diagnostics_severity_overrides`], and diagnostics turned off there are dropped.
+/// Ranges are converted using the given position encoding.
+pub fn map_cairo_diagnostics_to_lsp<T: DiagnosticEntry + LsDiagnosticKind>(
     db: &T::DbType,
     diags: &mut Vec<Diagnostic>,
     diagnostics: &Diagnostics<T>,
     processed_file_id: FileId,
-    trace_macro_diagnostics: bool,
+    config: &Config,
+    position_encoding: PositionEncoding,
 ) {
+    let trace_macro_diagnostics = config.trace_macro_diagnostics;
     for diagnostic in if trace_macro_diagnostics {
         diagnostics.get_all()
     } else {
@@ -28,6 +48,7 @@ pub fn map_cairo_diagnostics_to_lsp<T: DiagnosticEntry>(
             if let Some(location) = &note.location {
                 let Some((range, file_id)) = get_mapped_range_and_add_mapping_note(
                     db,
+                    position_encoding,
                     location,
                     trace_macro_diagnostics.then_some(&mut related_information),
                     "Next note mapped from here.",
@@ -49,6 +70,7 @@ pub fn map_cairo_diagnostics_to_lsp<T: DiagnosticEntry>(
 
         let Some((range, mapped_file_id)) = get_mapped_range_and_add_mapping_note(
             db,
+            position_encoding,
             &diagnostic.location(db),
             trace_macro_diagnostics.then_some(&mut related_information),
             "Diagnostic mapped from here.",
@@ -59,33 +81,143 @@ pub fn map_cairo_diagnostics_to_lsp<T: DiagnosticEntry>(
         if mapped_file_id != processed_file_id {
             continue;
         }
+        let code = diagnostic.error_code().map(|code| code.to_string());
+        let kind = diagnostic.ls_kind();
+        let severity = match code
+            .as_deref()
+            .into_iter()
+            .chain(kind)
+            .find_map(|key| config.diagnostics_severity_overrides.get(key))
+        {
+            Some(None) => continue,
+            Some(&Some(severity)) => severity,
+            None => match diagnostic.severity() {
+                Severity::Error => DiagnosticSeverity::ERROR,
+                Severity::Warning => DiagnosticSeverity::WARNING,
+            },
+        };
+        let tags = diagnostic_tags(kind);
         diags.push(Diagnostic {
             range,
             message,
             related_information: (!related_information.is_empty()).then_some(related_information),
-            severity: Some(match diagnostic.severity() {
-                Severity::Error => DiagnosticSeverity::ERROR,
-                Severity::Warning => DiagnosticSeverity::WARNING,
-            }),
-            code: diagnostic.error_code().map(|code| NumberOrString::String(code.to_string())),
+            severity: Some(severity),
+            code_description: code.as_deref().and_then(|code| code_description(code, config)),
+            code: code.map(NumberOrString::String),
+            tags: (!tags.is_empty()).then_some(tags),
+            data: kind.map(|kind| json!({ "kind": kind })),
             ..Diagnostic::default()
         });
     }
 }
 
+/// Returns a link to the documentation of the error code, if
+/// [`Config::diagnostics_docs_base_url`] is set.
+fn code_description(code: &str, config: &Config) -> Option<CodeDescription> {
+    let href = config.diagnostics_docs_base_url.as_ref()?.join(code).ok()?;
+    Some(CodeDescription { href })
+}
+
+/// Truncates diagnostics of a file to at most `max_per_file` ones, followed by an informational
+/// diagnostic saying how many were suppressed, located at the first of them.
+///
+/// Nothing is truncated if `max_per_file` is `None`.
+pub fn limit_diagnostics(diagnostics: &mut Vec<Diagnostic>, max_per_file: Option<usize>) {
+    let Some(max_per_file) = max_per_file else { return };
+    if diagnostics.len() <= max_per_file {
+        return;
+    }
+
+    let suppressed = diagnostics.len() - max_per_file;
+    let range = diagnostics[max_per_file].range;
+    diagnostics.truncate(max_per_file);
+    diagnostics.push(Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        message: format!("{suppressed} more diagnostics suppressed"),
+        ..Diagnostic::default()
+    });
+}
+
+/// A diagnostic which can be classified into a kind, which users refer to in
+/// [`Config::diagnostics_severity_overrides`].
+///
+/// Kinds are identified by the structured kind of the diagnostic rather than its message, so that
+/// rewording a message does not change its classification.
+pub trait LsDiagnosticKind {
+    /// Returns a stable identifier of the kind of the diagnostic, if it is one users can refer to.
+    fn ls_kind(&self) -> Option<&'static str>;
+}
+
+impl LsDiagnosticKind for ParserDiagnostic {
+    fn ls_kind(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl LsDiagnosticKind for SemanticDiagnostic {
+    fn ls_kind(&self) -> Option<&'static str> {
+        Some(match &self.kind {
+            SemanticDiagnosticKind::UnusedVariable => "unused_variable",
+            SemanticDiagnosticKind::CannotCallMethod { .. } => "cannot_call_method",
+            SemanticDiagnosticKind::PathNotFound(_) => "path_not_found",
+            SemanticDiagnosticKind::UnusedImport(_) => "unused_import",
+            SemanticDiagnosticKind::MissingItemsInImpl(_) => "missing_trait_items",
+            SemanticDiagnosticKind::UnusedUse => "unused_use",
+            SemanticDiagnosticKind::UnusedConstant => "unused_constant",
+            SemanticDiagnosticKind::DeprecatedFeature { .. } => "deprecated_feature",
+            SemanticDiagnosticKind::UnstableFeature { .. } => "unstable_feature",
+            SemanticDiagnosticKind::UnhandledMustUseType(_)
+            | SemanticDiagnosticKind::UnhandledMustUseFunction => "unhandled_must_use",
+            _ => return None,
+        })
+    }
+}
+
+impl LsDiagnosticKind for LoweringDiagnostic {
+    fn ls_kind(&self) -> Option<&'static str> {
+        match &self.kind {
+            LoweringDiagnosticKind::MatchError(MatchError {
+                kind: MatchKind::Match,
+                error: MatchDiagnostic::MissingMatchArm(_),
+            }) => Some("missing_match_arm"),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the kind of an LSP diagnostic created by [`map_cairo_diagnostics_to_lsp`], which is
+/// carried in its `data`, as the compiler does not assign error codes to most diagnostics.
+pub fn lsp_diagnostic_kind(diagnostic: &Diagnostic) -> Option<&str> {
+    diagnostic.data.as_ref()?.get("kind")?.as_str()
+}
+
+/// Returns the tags for a diagnostic of the given kind, which let editors render unused code
+/// faded out and deprecated code struck through.
+fn diagnostic_tags(kind: Option<&str>) -> Vec<DiagnosticTag> {
+    match kind {
+        Some("unused_variable" | "unused_constant" | "unused_import" | "unused_use") => {
+            vec![DiagnosticTag::UNNECESSARY]
+        }
+        Some("deprecated_feature") => vec![DiagnosticTag::DEPRECATED],
+        _ => vec![],
+    }
+}
+
 /// Returns the mapped range of a location, optionally adds a note about the mapping of the
 /// location.
 fn get_mapped_range_and_add_mapping_note(
     db: &(impl Upcast<dyn FilesGroup> + ?Sized),
+    position_encoding: PositionEncoding,
     orig: &DiagnosticLocation,
     related_info: Option<&mut Vec<DiagnosticRelatedInformation>>,
     message: &str,
 ) -> Option<(Range, FileId)> {
-    let mapped = orig.user_location(db.upcast());
-    let mapped_range = get_lsp_range(db.upcast(), &mapped)?;
+    let mapped = user_location(db.upcast(), orig);
+    let mapped_range = get_lsp_range(db.upcast(), position_encoding, &mapped)?;
     if let Some(related_info) = related_info {
         if *orig != mapped {
-            if let Some(range) = get_lsp_range(db.upcast(), orig) {
+            if let Some(range) = get_lsp_range(db.upcast(), position_encoding, orig) {
                 related_info.push(DiagnosticRelatedInformation {
                     location: Location { uri: db.url_for_file(orig.file_id)?, range },
                     message: message.to_string(),
@@ -96,11 +228,59 @@ fn get_mapped_range_and_add_mapping_note(
     Some((mapped_range, mapped.file_id))
 }
 
+/// Returns the location of the user code a location in a file generated by macros originates
+/// from.
+///
+/// Unlike [`DiagnosticLocation::user_location`], which stops at the first generated file whose
+/// code mappings do not contain the whole span, this falls back to the narrowest mapping
+/// overlapping the span, so that e.g. diagnostics spanning both code copied from the user and
+/// code generated by the macro still point at the user code.
+/// The location in the generated file is returned only if no mapping overlaps the span.
+fn user_location(db: &dyn FilesGroup, location: &DiagnosticLocation) -> DiagnosticLocation {
+    let mut location = location.user_location(db);
+    loop {
+        let (parent, code_mappings) = match location.file_id.lookup_intern(db) {
+            FileLongId::OnDisk(_) => break,
+            FileLongId::Virtual(vf) => (vf.parent, vf.code_mappings),
+            FileLongId::External(id) => {
+                let vf = db.ext_as_virtual(id);
+                (vf.parent, vf.code_mappings)
+            }
+        };
+        let Some(parent) = parent else { break };
+
+        let span = location.span;
+        let Some(origin) = code_mappings
+            .iter()
+            .filter(|mapping| mapping.span.start <= span.end && span.start <= mapping.span.end)
+            .min_by_key(|mapping| mapping.span.width())
+            .and_then(|mapping| {
+                let overlap = TextSpan {
+                    start: span.start.max(mapping.span.start),
+                    end: span.end.min(mapping.span.end),
+                };
+                mapping.translate(overlap)
+            })
+        else {
+            break;
+        };
+        location = DiagnosticLocation { file_id: parent, span: origin }.user_location(db);
+    }
+    location
+}
+
 /// Converts an internal diagnostic location to an LSP range.
-fn get_lsp_range(db: &dyn FilesGroup, location: &DiagnosticLocation) -> Option<Range> {
+fn get_lsp_range(
+    db: &dyn FilesGroup,
+    position_encoding: PositionEncoding,
+    location: &DiagnosticLocation,
+) -> Option<Range> {
     let Some(span) = location.span.position_in_file(db, location.file_id) else {
         error!("failed to get range for diagnostic");
         return None;
     };
-    Some(span.to_lsp())
+    Some(Range {
+        start: position_encoding.position_to_lsp(db, location.file_id, span.start),
+        end: position_encoding.position_to_lsp(db, location.file_id, span.end),
+    })
 }
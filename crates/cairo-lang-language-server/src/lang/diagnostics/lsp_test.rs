@@ -0,0 +1,42 @@
+use cairo_lang_diagnostics::DiagnosticLocation;
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::{CodeMapping, CodeOrigin, FileKind, FileLongId, VirtualFile};
+use cairo_lang_filesystem::span::{TextOffset, TextSpan, TextWidth};
+use cairo_lang_filesystem::test_utils::FilesDatabaseForTesting;
+
+use super::user_location;
+
+fn span(start: u32, end: u32) -> TextSpan {
+    let offset = |value| TextOffset::default().add_width(TextWidth::new_for_testing(value));
+    TextSpan { start: offset(start), end: offset(end) }
+}
+
+#[test]
+fn generated_locations_are_mapped_to_user_code() {
+    let db = FilesDatabaseForTesting::default();
+    let user_file = db.intern_file(FileLongId::OnDisk("/lib.cairo".into()));
+    // Only `copied` is copied from the user code, at offset 40.
+    let generated_file = db.intern_file(FileLongId::Virtual(VirtualFile {
+        parent: Some(user_file),
+        name: "generated".into(),
+        content: "fn generated() { copied }".into(),
+        code_mappings: [CodeMapping {
+            span: span(17, 23),
+            origin: CodeOrigin::Start(span(40, 40).start),
+        }]
+        .into(),
+        kind: FileKind::Module,
+    }));
+
+    let check = |generated_span, expected| {
+        let location = DiagnosticLocation { file_id: generated_file, span: generated_span };
+        assert_eq!(user_location(&db, &location), expected);
+    };
+
+    // Spans within the copied code are mapped exactly.
+    check(span(18, 20), DiagnosticLocation { file_id: user_file, span: span(41, 43) });
+    // Spans overlapping the copied code are mapped to the overlapping part.
+    check(span(15, 23), DiagnosticLocation { file_id: user_file, span: span(40, 46) });
+    // Spans of generated code only are left in the generated file.
+    check(span(0, 5), DiagnosticLocation { file_id: generated_file, span: span(0, 5) });
+}
@@ -1,24 +1,36 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::{Arc, Mutex};
 
-use lsp_types::Url;
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::{PublishDiagnosticsParams, Url};
 use tracing::{error, trace};
 
+pub use self::lsp::lsp_diagnostic_kind;
+pub use self::pull::{PullDiagnosticsCache, document_diagnostics, workspace_diagnostics};
+pub use self::scarb_build::{ScarbBuildController, ScarbBuildDiagnostics};
 use self::trigger::trigger;
 use crate::lang::diagnostics::refresh::refresh_diagnostics;
+use crate::lsp::capabilities::client::ClientCapabilitiesExt;
 use crate::server::client::Notifier;
 use crate::server::panic::cancelled_anyhow;
 use crate::server::schedule::thread::{self, JoinHandle, ThreadPriority};
 use crate::state::{FileDiagnostics, StateSnapshot};
 
 mod lsp;
+mod pull;
 mod refresh;
+mod scarb_build;
 mod trigger;
 
 /// Schedules refreshing of diagnostics in a background thread.
 ///
 /// This structure *owns* the worker thread and is responsible for its lifecycle.
 /// Dropping it will ask the worker to stop and synchronously wait for it to finish.
+///
+/// A refresh in progress is cancelled as soon as the analysis database is mutated, e.g. by an
+/// edit, because Salsa cancels the snapshot the worker uses. The worker then proceeds with the
+/// latest scheduled refresh, skipping any scheduled in the meantime.
 pub struct DiagnosticsController {
     // NOTE: Member order matters here.
     //   The trigger MUST be dropped before worker's join handle.
@@ -26,6 +38,9 @@ pub struct DiagnosticsController {
     //   never terminate.
     trigger: trigger::Sender<WorkerArgs>,
     _worker: JoinHandle,
+    /// Files whose diagnostics are to be cleared and forgotten by the worker before its next
+    /// refresh.
+    cleared_files: Arc<Mutex<HashSet<Url>>>,
 }
 
 struct WorkerArgs {
@@ -37,13 +52,17 @@ impl DiagnosticsController {
     /// Creates a new diagnostics controller.
     pub fn new() -> Self {
         let (trigger, receiver) = trigger();
+        let cleared_files = Arc::<Mutex<HashSet<Url>>>::default();
 
         let worker = thread::Builder::new(ThreadPriority::Worker)
             .name("cairo-ls:diagnostics_controller".into())
-            .spawn(move || Self::control_loop(receiver))
+            .spawn({
+                let cleared_files = cleared_files.clone();
+                move || Self::control_loop(receiver, cleared_files)
+            })
             .expect("failed to spawn diagnostics controller thread");
 
-        Self { trigger, _worker: worker }
+        Self { trigger, _worker: worker, cleared_files }
     }
 
     /// Schedules diagnostics refreshing using current state snapshot.
@@ -51,16 +70,47 @@ impl DiagnosticsController {
         self.trigger.activate(WorkerArgs { state, notifier });
     }
 
+    /// Schedules publishing empty diagnostics for files which no longer exist, e.g. have been
+    /// deleted or renamed.
+    ///
+    /// The diagnostics are published by the worker before its next refresh, so that a refresh in
+    /// progress cannot publish stale diagnostics of the files after them.
+    /// The files are also forgotten by the worker, so their diagnostics are published again if
+    /// they come back, even if the diagnostics are the same as before.
+    pub fn clear(&self, files: Vec<Url>) {
+        self.cleared_files.lock().unwrap().extend(files);
+    }
+
     /// Runs diagnostics controller's event loop.
-    fn control_loop(receiver: trigger::Receiver<WorkerArgs>) {
+    fn control_loop(
+        receiver: trigger::Receiver<WorkerArgs>,
+        cleared_files: Arc<Mutex<HashSet<Url>>>,
+    ) {
         let mut file_diagnostics = HashMap::<Url, FileDiagnostics>::new();
 
         while let Some(WorkerArgs { state, notifier }) = receiver.wait() {
+            for file in cleared_files.lock().unwrap().drain() {
+                file_diagnostics.remove(&file);
+                notifier.notify::<PublishDiagnostics>(PublishDiagnosticsParams {
+                    uri: file,
+                    diagnostics: vec![],
+                    version: None,
+                });
+            }
+
+            // Supporting document pulls does not imply pulling the whole workspace, so diagnostics
+            // of closed files are published until the client is known to pull them.
+            let workspace_pull_diagnostics = state.client_capabilities.pull_diagnostics_support()
+                && (state.client_capabilities.workspace_diagnostic_refresh_support()
+                    || state.pull_diagnostics_cache.any_workspace_pulls());
+
             if let Err(err) = catch_unwind(AssertUnwindSafe(|| {
                 refresh_diagnostics(
                     &state.db,
                     &state.open_files,
-                    state.config.trace_macro_diagnostics,
+                    &state.config,
+                    workspace_pull_diagnostics,
+                    &state.scarb_build_diagnostics,
                     &mut file_diagnostics,
                     notifier,
                 );
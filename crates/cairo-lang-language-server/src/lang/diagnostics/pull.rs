@@ -0,0 +1,310 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use anyhow::anyhow;
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::ModuleId;
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::FileId;
+use itertools::Itertools;
+use lsp_server::ErrorCode;
+use lsp_types::notification::Notification;
+use lsp_types::{
+    DocumentDiagnosticParams, DocumentDiagnosticReport, DocumentDiagnosticReportResult,
+    FullDocumentDiagnosticReport, ProgressToken, RelatedFullDocumentDiagnosticReport,
+    RelatedUnchangedDocumentDiagnosticReport, UnchangedDocumentDiagnosticReport, Url,
+    WorkspaceDiagnosticParams, WorkspaceDiagnosticReport, WorkspaceDiagnosticReportPartialResult,
+    WorkspaceDiagnosticReportResult, WorkspaceDocumentDiagnosticReport,
+    WorkspaceFullDocumentDiagnosticReport, WorkspaceUnchangedDocumentDiagnosticReport,
+};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+use crate::config::Config;
+use crate::lang::db::AnalysisDatabase;
+use crate::lang::diagnostics::lsp::limit_diagnostics;
+use crate::lang::diagnostics::refresh::{compute_file_diagnostics, file_diagnostics_to_lsp};
+use crate::lang::lsp::LsProtoGroup;
+use crate::lsp::result::{LSPError, LSPResult};
+use crate::server::client::Notifier;
+use crate::state::FileDiagnostics;
+
+/// Diagnostics last reported to the client in responses to pull requests.
+///
+/// Reports are identified by result IDs, which the client sends back in subsequent pulls, so that
+/// files whose diagnostics did not change can be reported as unchanged.
+#[derive(Default)]
+pub struct PullDiagnosticsCache {
+    next_result_id: AtomicU64,
+    /// Incremented by every workspace pull, so that the one in flight can notice it was
+    /// superseded.
+    workspace_pull_generation: AtomicU64,
+    files: Mutex<HashMap<Url, CachedReport>>,
+}
+
+struct CachedReport {
+    result_id: String,
+    diagnostics: FileDiagnostics,
+}
+
+/// A report for a single file.
+enum Report {
+    Full { result_id: Option<String>, diagnostics: FileDiagnostics },
+    Unchanged { result_id: String },
+}
+
+impl PullDiagnosticsCache {
+    /// Returns a report of the diagnostics of the file, which is unchanged if the diagnostics
+    /// are the same as the ones reported with `previous_result_id`.
+    fn report(
+        &self,
+        uri: &Url,
+        previous_result_id: Option<&str>,
+        diagnostics: FileDiagnostics,
+    ) -> Report {
+        let mut files = self.lock();
+        if let Some(cached) = files.get(uri) {
+            if Some(cached.result_id.as_str()) == previous_result_id
+                && cached.diagnostics == diagnostics
+            {
+                return Report::Unchanged { result_id: cached.result_id.clone() };
+            }
+        }
+
+        let result_id = self.next_result_id.fetch_add(1, Ordering::Relaxed).to_string();
+        files.insert(
+            uri.clone(),
+            CachedReport { result_id: result_id.clone(), diagnostics: diagnostics.clone() },
+        );
+        Report::Full { result_id: Some(result_id), diagnostics }
+    }
+
+    /// Returns an empty report for a file whose diagnostics are not pulled, e.g. because they are
+    /// published by the server.
+    fn empty_report(&self, uri: &Url) -> Report {
+        self.lock().remove(uri);
+        Report::Full { result_id: None, diagnostics: Default::default() }
+    }
+
+    /// Forgets all reported diagnostics, so that the next pull reports all files in full, e.g.
+    /// after the configuration affecting their conversion to LSP changed.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Starts a new workspace pull, superseding the one in flight, and returns its generation.
+    fn start_workspace_pull(&self) -> u64 {
+        self.workspace_pull_generation.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Checks whether the client has pulled diagnostics of the whole workspace at least once.
+    pub fn any_workspace_pulls(&self) -> bool {
+        self.workspace_pull_generation.load(Ordering::Relaxed) > 0
+    }
+
+    /// Checks whether a workspace pull of the given generation was superseded by a newer one.
+    fn is_superseded(&self, generation: u64) -> bool {
+        self.workspace_pull_generation.load(Ordering::Relaxed) != generation
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Url, CachedReport>> {
+        // The map is always left in a consistent state, so a poisoned lock can be reused.
+        self.files.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Computes diagnostics of a single file for a `textDocument/diagnostic` request.
+///
+/// Diagnostics of open files are published by the server, so an empty report is returned for
+/// them.
+pub fn document_diagnostics(
+    params: DocumentDiagnosticParams,
+    db: &AnalysisDatabase,
+    open_files: &HashSet<Url>,
+    config: &Config,
+    cache: &PullDiagnosticsCache,
+) -> DocumentDiagnosticReportResult {
+    let uri = params.text_document.uri;
+    let report = match db.file_for_url(&uri) {
+        Some(file) if !open_files.contains(&uri) => {
+            let diagnostics =
+                compute_file_diagnostics(db, file, &uri, config, &mut HashSet::default())
+                    .unwrap_or_default();
+            cache.report(&uri, params.previous_result_id.as_deref(), diagnostics)
+        }
+        _ => cache.empty_report(&uri),
+    };
+
+    let report = match report {
+        Report::Full { result_id, diagnostics } => {
+            let mut items = db
+                .file_for_url(&uri)
+                .map(|file| file_diagnostics_to_lsp(db, file, &diagnostics, config))
+                .unwrap_or_default();
+            limit_diagnostics(&mut items, config.diagnostics_max_per_file);
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport { result_id, items },
+            })
+        }
+        Report::Unchanged { result_id } => {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            })
+        }
+    };
+    DocumentDiagnosticReportResult::Report(report)
+}
+
+/// Computes diagnostics of all files in all crates for a `workspace/diagnostic` request.
+///
+/// Diagnostics of open files are published by the server, so they are skipped, and files which
+/// were reported previously, but are open or no longer part of any crate now, get empty reports.
+/// If the client provided a partial result token, reports are streamed file by file with
+/// `$/progress` notifications and the response itself is empty.
+///
+/// No files are analysed if [`Config::diagnostics_open_files_only`] is set.
+///
+/// A workspace pull is cancelled as soon as another one is started.
+pub fn workspace_diagnostics(
+    params: WorkspaceDiagnosticParams,
+    db: &AnalysisDatabase,
+    open_files: &HashSet<Url>,
+    config: &Config,
+    cache: &PullDiagnosticsCache,
+    notifier: &Notifier,
+) -> LSPResult<WorkspaceDiagnosticReportResult> {
+    let generation = cache.start_workspace_pull();
+    let partial_result_token = params.partial_result_params.partial_result_token;
+    let mut previous_result_ids: HashMap<Url, String> = params
+        .previous_result_ids
+        .into_iter()
+        .map(|previous| (previous.uri, previous.value))
+        .collect();
+
+    let open_files_ids =
+        open_files.iter().filter_map(|uri| db.file_for_url(uri)).collect::<HashSet<FileId>>();
+
+    // Modules of open files are analysed when their diagnostics are published.
+    let mut processed_modules: HashSet<ModuleId> = open_files_ids
+        .iter()
+        .filter_map(|&file| db.file_modules(file).ok())
+        .flat_map(|modules| modules.iter().copied().collect::<Vec<_>>())
+        .collect();
+
+    // Only module main files are reported, as all other related files are analysed along with
+    // them.
+    let files: Vec<FileId> = if config.diagnostics_open_files_only {
+        vec![]
+    } else {
+        db.crates()
+            .into_iter()
+            .flat_map(|crate_id| db.crate_modules(crate_id).iter().copied().collect::<Vec<_>>())
+            .filter_map(|module_id| db.module_main_file(module_id).ok())
+            .filter(|file| !open_files_ids.contains(file))
+            .unique()
+            .collect()
+    };
+
+    let mut items = vec![];
+    let mut report = |uri: Url, report: Report| {
+        let item = workspace_report(db, uri, report, config);
+        match &partial_result_token {
+            Some(token) => notifier.notify::<WorkspaceDiagnosticPartialResult>(
+                WorkspaceDiagnosticPartialResultParams {
+                    token: token.clone(),
+                    value: WorkspaceDiagnosticReportPartialResult { items: vec![item] },
+                },
+            ),
+            None => items.push(item),
+        }
+    };
+
+    for file in files {
+        if cache.is_superseded(generation) {
+            return Err(LSPError::new(
+                anyhow!("workspace diagnostics pull has been superseded"),
+                ErrorCode::ServerCancelled,
+            ));
+        }
+
+        let Some(uri) = db.url_for_file(file) else {
+            trace!("url for file not found: {file:?}");
+            continue;
+        };
+        let Some(diagnostics) =
+            compute_file_diagnostics(db, file, &uri, config, &mut processed_modules)
+        else {
+            continue;
+        };
+
+        let previous_result_id = previous_result_ids.remove(&uri);
+        // Files without diagnostics which the client does not know about yet are not reported.
+        if previous_result_id.is_none() && diagnostics.is_empty() {
+            continue;
+        }
+
+        let file_report = cache.report(&uri, previous_result_id.as_deref(), diagnostics);
+        report(uri, file_report);
+    }
+
+    // Clear diagnostics of files reported previously which were not reported now.
+    for uri in previous_result_ids.into_keys() {
+        let file_report = cache.empty_report(&uri);
+        report(uri, file_report);
+    }
+
+    Ok(WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items }))
+}
+
+/// Converts a report of a single file to the LSP format.
+fn workspace_report(
+    db: &AnalysisDatabase,
+    uri: Url,
+    report: Report,
+    config: &Config,
+) -> WorkspaceDocumentDiagnosticReport {
+    match report {
+        Report::Full { result_id, diagnostics } => {
+            let mut items = db
+                .file_for_url(&uri)
+                .map(|file| file_diagnostics_to_lsp(db, file, &diagnostics, config))
+                .unwrap_or_default();
+            limit_diagnostics(&mut items, config.diagnostics_max_per_file);
+            WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                uri,
+                version: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport { result_id, items },
+            })
+        }
+        Report::Unchanged { result_id } => WorkspaceDocumentDiagnosticReport::Unchanged(
+            WorkspaceUnchangedDocumentDiagnosticReport {
+                uri,
+                version: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            },
+        ),
+    }
+}
+
+/// The `$/progress` notification carrying a partial result of a `workspace/diagnostic` request.
+///
+/// [`lsp_types::notification::Progress`] only supports work done progress values.
+enum WorkspaceDiagnosticPartialResult {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkspaceDiagnosticPartialResultParams {
+    token: ProgressToken,
+    value: WorkspaceDiagnosticReportPartialResult,
+}
+
+impl Notification for WorkspaceDiagnosticPartialResult {
+    type Params = WorkspaceDiagnosticPartialResultParams;
+    const METHOD: &'static str = "$/progress";
+}
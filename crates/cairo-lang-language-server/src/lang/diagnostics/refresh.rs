@@ -13,22 +13,31 @@ use cairo_lang_semantic::SemanticDiagnostic;
 use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_utils::{LookupIntern, Upcast};
 use lsp_types::notification::PublishDiagnostics;
-use lsp_types::{PublishDiagnosticsParams, Url};
+use lsp_types::{Diagnostic, PublishDiagnosticsParams, Url};
 use tracing::{error, info_span, trace};
 
+use crate::config::Config;
 use crate::lang::db::AnalysisDatabase;
-use crate::lang::diagnostics::lsp::map_cairo_diagnostics_to_lsp;
+use crate::lang::diagnostics::ScarbBuildDiagnostics;
+use crate::lang::diagnostics::lsp::{limit_diagnostics, map_cairo_diagnostics_to_lsp};
 use crate::lang::lsp::LsProtoGroup;
 use crate::server::client::Notifier;
 use crate::server::panic::is_cancelled;
 use crate::state::FileDiagnostics;
 
 /// Refresh diagnostics and send diffs to the client.
+///
+/// If the client pulls diagnostics of the whole workspace, only the diagnostics of open files are
+/// published, and the client is responsible for requesting the rest with `workspace/diagnostic`
+/// requests.
+/// Only open files are analysed as well if [`Config::diagnostics_open_files_only`] is set.
 #[tracing::instrument(skip_all)]
 pub fn refresh_diagnostics(
     db: &AnalysisDatabase,
     open_files: &HashSet<Url>,
-    trace_macro_diagnostics: bool,
+    config: &Config,
+    workspace_pull_diagnostics: bool,
+    scarb_build_diagnostics: &ScarbBuildDiagnostics,
     file_diagnostics: &mut HashMap<Url, FileDiagnostics>,
     notifier: Notifier,
 ) {
@@ -45,7 +54,8 @@ pub fn refresh_diagnostics(
             refresh_file_diagnostics(
                 db,
                 file,
-                trace_macro_diagnostics,
+                config,
+                scarb_build_diagnostics,
                 &mut processed_modules,
                 &mut files_with_set_diagnostics,
                 file_diagnostics,
@@ -56,6 +66,9 @@ pub fn refresh_diagnostics(
 
     let rest_of_files = info_span!("get_rest_of_files").in_scope(|| {
         let mut rest_of_files: HashSet<FileId> = HashSet::default();
+        if workspace_pull_diagnostics || config.diagnostics_open_files_only {
+            return rest_of_files;
+        }
         for crate_id in db.crates() {
             for module_id in db.crate_modules(crate_id).iter() {
                 // Schedule only module main files for refreshing.
@@ -76,7 +89,8 @@ pub fn refresh_diagnostics(
             refresh_file_diagnostics(
                 db,
                 file,
-                trace_macro_diagnostics,
+                config,
+                scarb_build_diagnostics,
                 &mut processed_modules,
                 &mut files_with_set_diagnostics,
                 file_diagnostics,
@@ -108,10 +122,12 @@ pub fn refresh_diagnostics(
 }
 
 /// Refresh diagnostics for a single file.
+#[expect(clippy::too_many_arguments)]
 fn refresh_file_diagnostics(
     db: &AnalysisDatabase,
     file: FileId,
-    trace_macro_diagnostics: bool,
+    config: &Config,
+    scarb_build_diagnostics: &ScarbBuildDiagnostics,
     processed_modules: &mut HashSet<ModuleId>,
     files_with_set_diagnostics: &mut HashSet<Url>,
     file_diagnostics: &mut HashMap<Url, FileDiagnostics>,
@@ -121,11 +137,58 @@ fn refresh_file_diagnostics(
         trace!("url for file not found: {:?}", file.lookup_intern(db));
         return;
     };
+    let Some(new_file_diagnostics) =
+        compute_file_diagnostics(db, file, &file_uri, config, processed_modules)
+    else {
+        return;
+    };
+
+    if !new_file_diagnostics.is_empty() {
+        files_with_set_diagnostics.insert(file_uri.clone());
+    }
+
+    // Since we are using Arcs, this comparison should be efficient.
+    if let Some(old_file_diagnostics) = file_diagnostics.get(&file_uri) {
+        if old_file_diagnostics == &new_file_diagnostics {
+            return;
+        }
+
+        file_diagnostics.insert(file_uri.clone(), new_file_diagnostics.clone());
+    };
+
+    let mut diagnostics = file_diagnostics_to_lsp(db, file, &new_file_diagnostics, config);
+    if config.diagnostics_scarb_build {
+        diagnostics = scarb_build_diagnostics.merge(&file_uri, diagnostics);
+    }
+    limit_diagnostics(&mut diagnostics, config.diagnostics_max_per_file);
+    notifier.notify::<PublishDiagnostics>(PublishDiagnosticsParams {
+        uri: file_uri,
+        diagnostics,
+        version: None,
+    });
+}
+
+/// Computes diagnostics of a single file.
+///
+/// Semantic and lowering diagnostics are computed per module, so modules in `processed_modules`
+/// are skipped, and the modules of the file are added to it.
+pub fn compute_file_diagnostics(
+    db: &AnalysisDatabase,
+    file: FileId,
+    file_uri: &Url,
+    config: &Config,
+    processed_modules: &mut HashSet<ModuleId>,
+) -> Option<FileDiagnostics> {
     let Ok(module_ids) = db.file_modules(file) else {
         trace!("modules for file not found: {:?}", file.lookup_intern(db));
-        return;
+        return None;
     };
 
+    // Semantic and lowering analysis of huge files may block the server for a long time,
+    // so only parser diagnostics are reported for them.
+    let is_too_large =
+        db.file_content(file).is_some_and(|content| config.is_file_too_large(content.len()));
+
     let mut semantic_file_diagnostics: Vec<SemanticDiagnostic> = vec![];
     let mut lowering_file_diagnostics: Vec<LoweringDiagnostic> = vec![];
 
@@ -148,7 +211,7 @@ fn refresh_file_diagnostics(
     }
 
     for &module_id in module_ids.iter() {
-        if !processed_modules.contains(&module_id) {
+        if !is_too_large && !processed_modules.contains(&module_id) {
             semantic_file_diagnostics.extend(
                 diags!(db.module_semantic_diagnostics(module_id), Result::unwrap_or_default)
                     .get_all(),
@@ -164,51 +227,44 @@ fn refresh_file_diagnostics(
 
     let parser_file_diagnostics = diags!(db.file_syntax_diagnostics(file), |r| r);
 
-    let new_file_diagnostics = FileDiagnostics {
+    Some(FileDiagnostics {
         parser: parser_file_diagnostics,
         semantic: Diagnostics::from_iter(semantic_file_diagnostics),
         lowering: Diagnostics::from_iter(lowering_file_diagnostics),
-    };
-
-    if !new_file_diagnostics.is_empty() {
-        files_with_set_diagnostics.insert(file_uri.clone());
-    }
-
-    // Since we are using Arcs, this comparison should be efficient.
-    if let Some(old_file_diagnostics) = file_diagnostics.get(&file_uri) {
-        if old_file_diagnostics == &new_file_diagnostics {
-            return;
-        }
-
-        file_diagnostics.insert(file_uri.clone(), new_file_diagnostics.clone());
-    };
+    })
+}
 
+/// Converts diagnostics of a file to the LSP format.
+pub fn file_diagnostics_to_lsp(
+    db: &AnalysisDatabase,
+    file: FileId,
+    file_diagnostics: &FileDiagnostics,
+    config: &Config,
+) -> Vec<Diagnostic> {
     let mut diags = Vec::new();
     map_cairo_diagnostics_to_lsp(
         (*db).upcast(),
         &mut diags,
-        &new_file_diagnostics.parser,
+        &file_diagnostics.parser,
         file,
-        trace_macro_diagnostics,
+        config,
+        db.position_encoding(),
     );
     map_cairo_diagnostics_to_lsp(
         (*db).upcast(),
         &mut diags,
-        &new_file_diagnostics.semantic,
+        &file_diagnostics.semantic,
         file,
-        trace_macro_diagnostics,
+        config,
+        db.position_encoding(),
     );
     map_cairo_diagnostics_to_lsp(
         (*db).upcast(),
         &mut diags,
-        &new_file_diagnostics.lowering,
+        &file_diagnostics.lowering,
         file,
-        trace_macro_diagnostics,
+        config,
+        db.position_encoding(),
     );
-
-    notifier.notify::<PublishDiagnostics>(PublishDiagnosticsParams {
-        uri: file_uri,
-        diagnostics: diags,
-        version: None,
-    });
+    diags
 }
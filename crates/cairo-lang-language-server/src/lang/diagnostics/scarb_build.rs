@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, PublishDiagnosticsParams, Range, Url};
+use serde::Deserialize;
+use tracing::{error, trace};
+
+use crate::lang::diagnostics::lsp::limit_diagnostics;
+use crate::lang::diagnostics::trigger::{self, trigger};
+use crate::lang::lsp::PositionEncoding;
+use crate::server::client::Notifier;
+use crate::server::schedule::thread::{self, JoinHandle, ThreadPriority};
+use crate::toolchain::scarb::ScarbToolchain;
+
+#[cfg(test)]
+#[path = "scarb_build_test.rs"]
+mod test;
+
+/// Time without further builds being scheduled, after which a build is started.
+///
+/// Editors with auto-save enabled save files on every change, so this prevents running a build
+/// for each keystroke.
+const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Source of diagnostics reported by `scarb build`, shown by the client next to the message.
+const SOURCE: &str = "scarb build";
+
+/// Runs `scarb build` in a background thread, to report diagnostics which are only found by
+/// compiling the package, e.g. errors of code generation.
+///
+/// This structure *owns* the worker thread and is responsible for its lifecycle.
+/// Dropping it will ask the worker to stop and synchronously wait for it to finish.
+pub struct ScarbBuildController {
+    // NOTE: Member order matters here.
+    //   The trigger MUST be dropped before worker's join handle.
+    //   Otherwise, the worker will never be requested to stop, and the worker's JoinHandle will
+    //   never terminate.
+    trigger: trigger::Sender<WorkerArgs>,
+    _worker: JoinHandle,
+}
+
+struct WorkerArgs {
+    scarb: ScarbToolchain,
+    manifest: PathBuf,
+    max_diagnostics_per_file: Option<usize>,
+    position_encoding: PositionEncoding,
+    notifier: Notifier,
+}
+
+impl ScarbBuildController {
+    /// Creates a new controller, which stores diagnostics of builds in `diagnostics`.
+    pub fn new(diagnostics: Arc<ScarbBuildDiagnostics>) -> Self {
+        let (trigger, receiver) = trigger();
+
+        let worker = thread::Builder::new(ThreadPriority::Worker)
+            .name("cairo-ls:scarb_build_controller".into())
+            .spawn(move || Self::control_loop(receiver, diagnostics))
+            .expect("failed to spawn scarb build controller thread");
+
+        Self { trigger, _worker: worker }
+    }
+
+    /// Schedules building the package of the given `Scarb.toml`.
+    ///
+    /// The build starts after no other builds are scheduled for [`DEBOUNCE`], and only the last
+    /// scheduled one is run.
+    /// Diagnostics published afterwards are limited to `max_diagnostics_per_file`, and their
+    /// columns are counted in `position_encoding`.
+    pub fn build(
+        &self,
+        scarb: ScarbToolchain,
+        manifest: PathBuf,
+        max_diagnostics_per_file: Option<usize>,
+        position_encoding: PositionEncoding,
+        notifier: Notifier,
+    ) {
+        self.trigger.activate(WorkerArgs {
+            scarb,
+            manifest,
+            max_diagnostics_per_file,
+            position_encoding,
+            notifier,
+        });
+    }
+
+    /// Runs scarb build controller's event loop.
+    fn control_loop(
+        receiver: trigger::Receiver<WorkerArgs>,
+        diagnostics: Arc<ScarbBuildDiagnostics>,
+    ) {
+        while let Some(WorkerArgs {
+            scarb,
+            manifest,
+            max_diagnostics_per_file,
+            position_encoding,
+            notifier,
+        }) = receiver.wait_debounced(DEBOUNCE)
+        {
+            let output = match scarb.build(&manifest) {
+                Ok(output) => output,
+                Err(err) => {
+                    error!("failed to build package of `{}`: {err:?}", manifest.display());
+                    continue;
+                }
+            };
+
+            let build_diagnostics =
+                parse_build_output(&String::from_utf8_lossy(&output.stdout), position_encoding);
+            for (uri, mut diagnostics) in diagnostics.replace(build_diagnostics) {
+                limit_diagnostics(&mut diagnostics, max_diagnostics_per_file);
+                notifier.notify::<PublishDiagnostics>(PublishDiagnosticsParams {
+                    uri,
+                    diagnostics,
+                    version: None,
+                });
+            }
+        }
+    }
+}
+
+/// Diagnostics reported by the last `scarb build`, merged into the ones computed by the language
+/// server when they are published.
+#[derive(Default)]
+pub struct ScarbBuildDiagnostics {
+    files: Mutex<Files>,
+}
+
+#[derive(Default)]
+struct Files {
+    /// Diagnostics reported by the last build.
+    build: HashMap<Url, Vec<Diagnostic>>,
+    /// Diagnostics computed by the language server, which were published last.
+    published: HashMap<Url, Vec<Diagnostic>>,
+}
+
+impl ScarbBuildDiagnostics {
+    /// Merges diagnostics reported by the last build of the file into the ones computed by the
+    /// language server.
+    ///
+    /// The latter are remembered, so that they can be published again when the next build
+    /// finishes.
+    pub fn merge(&self, uri: &Url, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let mut files = self.lock();
+        let merged = merge(&diagnostics, files.build.get(uri));
+        files.published.insert(uri.clone(), diagnostics);
+        merged
+    }
+
+    /// Replaces diagnostics reported by the last build and returns the merged diagnostics of all
+    /// files, whose diagnostics were reported by the previous or the new build.
+    fn replace(&self, build: HashMap<Url, Vec<Diagnostic>>) -> Vec<(Url, Vec<Diagnostic>)> {
+        let mut files = self.lock();
+        let previous = std::mem::replace(&mut files.build, build);
+        let mut uris = previous.into_keys().chain(files.build.keys().cloned()).collect::<Vec<_>>();
+        uris.sort();
+        uris.dedup();
+
+        uris.into_iter()
+            .map(|uri| {
+                let published = files.published.get(&uri).map(Vec::as_slice).unwrap_or_default();
+                let merged = merge(published, files.build.get(&uri));
+                (uri, merged)
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Files> {
+        // The maps are always left in a consistent state, so a poisoned lock can be reused.
+        self.files.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Appends diagnostics reported by the build to the ones computed by the language server,
+/// skipping the ones reported by both.
+fn merge(diagnostics: &[Diagnostic], build: Option<&Vec<Diagnostic>>) -> Vec<Diagnostic> {
+    let is_duplicate = |build_diagnostic: &Diagnostic| {
+        diagnostics.iter().any(|diagnostic| {
+            diagnostic.range == build_diagnostic.range
+                && diagnostic.message.lines().next() == build_diagnostic.message.lines().next()
+        })
+    };
+
+    let mut merged = diagnostics.to_vec();
+    merged.extend(build.into_iter().flatten().filter(|d| !is_duplicate(d)).cloned());
+    merged
+}
+
+/// A message printed by Scarb in JSON output mode.
+#[derive(Deserialize)]
+struct ScarbMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+/// Parses diagnostics of the compiler from the output of `scarb --json build`.
+///
+/// Diagnostics are printed in the same format as by the compiler itself, e.g.
+/// ```text
+/// error: Unexpected return type. Expected: "core::felt252", found: "core::bool".
+///  --> /path/to/src/lib.cairo:1:24
+/// fn foo() -> felt252 { true }
+///                        ^**^
+/// ```
+/// Lines which are not such diagnostics are skipped.
+fn parse_build_output(
+    stdout: &str,
+    position_encoding: PositionEncoding,
+) -> HashMap<Url, Vec<Diagnostic>> {
+    let mut diagnostics: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
+    for line in stdout.lines() {
+        let Ok(message) = serde_json::from_str::<ScarbMessage>(line) else {
+            trace!("skipping unrecognized scarb output: {line}");
+            continue;
+        };
+        if let Some((uri, diagnostic)) = parse_diagnostic(&message, position_encoding) {
+            diagnostics.entry(uri).or_default().push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+/// Parses a single diagnostic printed by Scarb.
+fn parse_diagnostic(
+    message: &ScarbMessage,
+    position_encoding: PositionEncoding,
+) -> Option<(Url, Diagnostic)> {
+    let severity = match message.kind.as_str() {
+        "error" => DiagnosticSeverity::ERROR,
+        "warn" | "warning" => DiagnosticSeverity::WARNING,
+        _ => return None,
+    };
+
+    let mut lines = message.message.lines();
+    let mut text = Vec::new();
+    let location = loop {
+        let line = lines.next()?;
+        match line.trim_start().strip_prefix("--> ") {
+            Some(location) => break location,
+            None => text.push(line),
+        }
+    };
+    let (path, line, col) = parse_location(location)?;
+
+    // The location is followed by the source line and the marks under the span, e.g. `^***^`.
+    // Scarb counts columns in characters, so they are converted using the source line.
+    let source_line = lines.next();
+    let width = lines.next().map_or(0, |marks| marks.trim().chars().count());
+    let (start_col, end_col) = match source_line {
+        Some(source_line) => (
+            position_encoding.col_to_lsp(source_line, col),
+            position_encoding.col_to_lsp(source_line, col + width),
+        ),
+        None => (col, col + width),
+    };
+
+    let message = text.join("\n");
+    let message = ["error: ", "warning: "]
+        .into_iter()
+        .find_map(|prefix| message.strip_prefix(prefix))
+        .unwrap_or(&message)
+        .to_string();
+    let start = Position { line, character: start_col as u32 };
+    let end = Position { line, character: end_col as u32 };
+
+    Some((
+        Url::from_file_path(path).ok()?,
+        Diagnostic {
+            range: Range { start, end },
+            severity: Some(severity),
+            source: Some(SOURCE.to_string()),
+            message,
+            ..Diagnostic::default()
+        },
+    ))
+}
+
+/// Parses a `path:line:column` location, with the line and column numbered from 1, into a path
+/// and a zero-based line and column.
+fn parse_location(location: &str) -> Option<(&Path, u32, usize)> {
+    let mut parts = location.trim().rsplitn(3, ':');
+    let col = parts.next()?.parse::<usize>().ok()?.checked_sub(1)?;
+    let line = parts.next()?.parse::<u32>().ok()?.checked_sub(1)?;
+    let path = Path::new(parts.next()?);
+    Some((path, line, col))
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range, Url};
+use serde_json::json;
+
+use super::{ScarbBuildDiagnostics, parse_build_output};
+use crate::lang::lsp::PositionEncoding;
+
+fn range(line: u32, start: u32, end: u32) -> Range {
+    Range { start: Position { line, character: start }, end: Position { line, character: end } }
+}
+
+#[test]
+fn parses_compiler_diagnostics() {
+    let stdout = [
+        json!({"type": "status", "status": "compiling", "message": "hello v0.1.0"}).to_string(),
+        json!({
+            "type": "error",
+            "message": "error: Unexpected return type. Expected: \"core::felt252\", found: \
+                        \"core::bool\".\n --> /project/src/lib.cairo:2:23\nfn foo() -> felt252 { \
+                        true }\n                      ^**^\n",
+        })
+        .to_string(),
+        json!({
+            "type": "warn",
+            "message": "warning: Unused variable. Consider ignoring by prefixing with `_`.\n --> \
+                        /project/src/a.cairo:1:16\nfn main() { let x = 5; }\n               ^\n",
+        })
+        .to_string(),
+        json!({"type": "error", "message": "could not compile `hello` due to previous error"})
+            .to_string(),
+        "not json".to_string(),
+    ]
+    .join("\n");
+
+    let mut diagnostics =
+        parse_build_output(&stdout, PositionEncoding::Utf16).into_iter().collect::<Vec<_>>();
+    diagnostics.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let [(a_uri, a), (lib_uri, lib)] = diagnostics.as_slice() else {
+        panic!("expected diagnostics of two files, got: {diagnostics:#?}");
+    };
+    assert_eq!(a_uri.path(), "/project/src/a.cairo");
+    assert_eq!(a.len(), 1);
+    assert_eq!(a[0].range, range(0, 15, 16));
+    assert_eq!(a[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(a[0].message, "Unused variable. Consider ignoring by prefixing with `_`.");
+
+    assert_eq!(lib_uri.path(), "/project/src/lib.cairo");
+    assert_eq!(lib.len(), 1);
+    assert_eq!(lib[0].range, range(1, 22, 26));
+    assert_eq!(lib[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(
+        lib[0].message,
+        r#"Unexpected return type. Expected: "core::felt252", found: "core::bool"."#
+    );
+    assert_eq!(lib[0].source.as_deref(), Some("scarb build"));
+}
+
+#[test]
+fn converts_columns_to_position_encoding() {
+    let stdout = json!({
+        "type": "warn",
+        "message": "warning: Unused variable. Consider ignoring by prefixing with `_`.\n --> \
+                    /project/src/lib.cairo:1:30\nfn main() { let s = '😀'; let x = 5; }\n       \
+                    ^\n",
+    })
+    .to_string();
+
+    for (encoding, expected) in [
+        (PositionEncoding::Utf8, range(0, 32, 33)),
+        (PositionEncoding::Utf16, range(0, 30, 31)),
+        (PositionEncoding::Utf32, range(0, 29, 30)),
+    ] {
+        let diagnostics = parse_build_output(&stdout, encoding);
+        let [diagnostic] = diagnostics.values().next().unwrap().as_slice() else {
+            panic!("expected a single diagnostic, got: {diagnostics:#?}");
+        };
+        assert_eq!(diagnostic.range, expected, "{encoding:?}");
+    }
+}
+
+#[test]
+fn merges_build_diagnostics_without_duplicates() {
+    let uri = Url::parse("file:///project/src/lib.cairo").unwrap();
+    let diagnostic = |message: &str, line| Diagnostic {
+        range: range(line, 0, 3),
+        message: message.to_string(),
+        ..Diagnostic::default()
+    };
+
+    let store = ScarbBuildDiagnostics::default();
+    let in_process = vec![diagnostic("Duplicate.\nnote: Some note.", 0)];
+    assert_eq!(store.merge(&uri, in_process.clone()), in_process);
+
+    // Files whose diagnostics were published before are published again with build ones.
+    let build = vec![diagnostic("Duplicate.", 0), diagnostic("Codegen error.", 1)];
+    let republished = store.replace(HashMap::from([(uri.clone(), build)]));
+    assert_eq!(
+        republished,
+        [(
+            uri.clone(),
+            vec![diagnostic("Duplicate.\nnote: Some note.", 0), diagnostic("Codegen error.", 1)]
+        )]
+    );
+
+    // Files without diagnostics after the next build are published again without build ones.
+    let republished = store.replace(HashMap::new());
+    assert_eq!(republished, [(uri, in_process)]);
+}
@@ -1,5 +1,6 @@
 use std::mem;
 use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 
 #[cfg(test)]
 #[path = "trigger_test.rs"]
@@ -69,6 +70,19 @@ impl<T> Receiver<T> {
     pub fn wait(&self) -> Option<T> {
         self.0.wait()
     }
+
+    /// Like [`Receiver::wait`], but after the trigger is activated, keeps waiting until it is not
+    /// activated again for the `quiet_period`, and returns the last value.
+    pub fn wait_debounced(&self, quiet_period: Duration) -> Option<T> {
+        let mut value = self.0.wait()?;
+        loop {
+            match self.0.wait_until(Some(Instant::now() + quiet_period)) {
+                State::Activated(newer) => value = newer,
+                State::Pending => return Some(value),
+                State::Disconnected => return None,
+            }
+        }
+    }
 }
 
 impl<T> Drop for Receiver<T> {
@@ -106,6 +120,16 @@ impl<T> Inner<T> {
     }
 
     fn wait(&self) -> Option<T> {
+        match self.wait_until(None) {
+            State::Activated(value) => Some(value),
+            State::Pending => unreachable!("waiting without a deadline cannot time out"),
+            State::Disconnected => None,
+        }
+    }
+
+    /// Waits for the trigger to be activated or disconnected, returning [`State::Pending`] if
+    /// neither happens before the `deadline`.
+    fn wait_until(&self, deadline: Option<Instant>) -> State<T> {
         let &Inner { state_mutex, condvar } = &self;
 
         // This loop is a regular wait-with-condition pattern,
@@ -120,10 +144,18 @@ impl<T> Inner<T> {
             match mem::replace(&mut *state_guard, idle) {
                 State::Pending => {
                     // Continue waiting.
-                    state_guard = condvar.wait(state_guard).expect(POISON_PANIC);
+                    state_guard = match deadline {
+                        None => condvar.wait(state_guard).expect(POISON_PANIC),
+                        Some(deadline) => {
+                            let timeout = deadline.saturating_duration_since(Instant::now());
+                            if timeout.is_zero() {
+                                return State::Pending;
+                            }
+                            condvar.wait_timeout(state_guard, timeout).expect(POISON_PANIC).0
+                        }
+                    };
                 }
-                State::Activated(value) => return Some(value),
-                State::Disconnected => return None,
+                state => return state,
             }
         }
     }
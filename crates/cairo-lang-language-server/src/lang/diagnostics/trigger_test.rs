@@ -1,4 +1,5 @@
 use std::thread;
+use std::time::Duration;
 
 use super::*;
 
@@ -16,6 +17,19 @@ fn test_sync() {
     assert_eq!(receiver.wait(), None);
 }
 
+#[test]
+fn test_latest_wins() {
+    let (sender, receiver) = trigger();
+
+    sender.activate(1);
+    sender.activate(2);
+    sender.activate(3);
+    assert_eq!(receiver.wait(), Some(3));
+
+    drop(sender);
+    assert_eq!(receiver.wait(), None);
+}
+
 #[test]
 fn test_threaded() {
     let (sender, receiver) = trigger();
@@ -42,3 +56,21 @@ fn test_drop_receiver() {
     // This line should just do nothing.
     sender.activate(42);
 }
+
+#[test]
+fn test_debounced() {
+    let (sender, receiver) = trigger();
+    thread::scope(|s| {
+        s.spawn(move || {
+            for i in 0..10 {
+                sender.activate(i);
+                thread::sleep(Duration::from_millis(10));
+            }
+            thread::sleep(Duration::from_millis(500));
+            sender.activate(42);
+        });
+
+        assert_eq!(receiver.wait_debounced(Duration::from_millis(200)), Some(9));
+        assert_eq!(receiver.wait_debounced(Duration::from_millis(200)), None);
+    });
+}
@@ -1,10 +1,17 @@
-use cairo_lang_filesystem::db::FilesGroup;
+use std::collections::HashMap;
+
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_filesystem::db::{CORELIB_CRATE_NAME, FilesGroup};
+use cairo_lang_filesystem::ids::{CrateId, Directory};
 use indent::indent_by;
 use indoc::formatdoc;
 use itertools::Itertools;
+use lsp_types::Url;
+use smol_str::SmolStr;
 
 use crate::lang::db::AnalysisDatabase;
-use crate::project::Crate;
+use crate::lsp::ext::{AnalyzedCrate, AnalyzedCratesChangedParams, AnalyzedCratesResponse};
+use crate::project::{Crate, ProjectManifestPath};
 
 /// Generates a Markdown text describing all crates in the database.
 pub fn inspect_analyzed_crates(db: &AnalysisDatabase) -> String {
@@ -19,6 +26,89 @@ pub fn inspect_analyzed_crates(db: &AnalysisDatabase) -> String {
     format!("# Analyzed Crates\n\n{list}")
 }
 
+/// Describes all crates in the database in a machine-readable form.
+pub fn describe_analyzed_crates(db: &AnalysisDatabase) -> AnalyzedCratesResponse {
+    let crates = db
+        .crates()
+        .into_iter()
+        .flat_map(|crate_id| Crate::reconstruct(db, crate_id))
+        .sorted_by_key(|cr| cr.name.clone())
+        .map(|cr| AnalyzedCrate {
+            name: cr.name.to_string(),
+            source_file_urls: cr
+                .source_paths()
+                .into_iter()
+                .filter_map(|path| Url::from_file_path(path).ok())
+                .collect(),
+            edition: cr.settings.edition,
+            version: cr.settings.version.as_ref().map(ToString::to_string),
+            dependencies: cr.settings.dependencies.into_keys().collect(),
+        })
+        .collect();
+    let inline_macros = db.inline_macro_plugins().keys().cloned().sorted().collect();
+
+    AnalyzedCratesResponse { crates, inline_macros }
+}
+
+/// Generates a Graphviz DOT graph of all crates in the database and dependencies between them.
+///
+/// Each crate is labelled with its kind: whether it is the corelib, a Scarb package, a crate of
+/// a `cairo_project.toml` or an unmanaged single-file crate.
+/// Dependencies on the corelib are implicit, so they are drawn with dashed edges.
+pub fn crate_graph_dot(db: &AnalysisDatabase) -> String {
+    let crates = db
+        .crates()
+        .into_iter()
+        .flat_map(|crate_id| Crate::reconstruct(db, crate_id))
+        .sorted_by_key(|cr| (cr.name.clone(), cr.discriminator.clone()))
+        .collect::<Vec<_>>();
+    let node_ids = crates
+        .iter()
+        .enumerate()
+        .map(|(i, cr)| ((cr.name.clone(), cr.discriminator.clone()), format!("c{i}")))
+        .collect::<HashMap<_, _>>();
+
+    let mut dot = String::from("digraph crates {\n");
+    for cr in &crates {
+        let id = &node_ids[&(cr.name.clone(), cr.discriminator.clone())];
+        let label = format!("{}\n{}", cr.name, crate_kind(cr));
+        dot.push_str(&format!("    {id} [label={label:?}];\n"));
+    }
+    for cr in &crates {
+        let id = &node_ids[&(cr.name.clone(), cr.discriminator.clone())];
+        let mut depends_on_core = !cr.is_core();
+        for (name, dependency) in &cr.settings.dependencies {
+            depends_on_core &= name != CORELIB_CRATE_NAME;
+            let key = (SmolStr::from(name), dependency.discriminator.clone());
+            if let Some(dependency_id) = node_ids.get(&key) {
+                dot.push_str(&format!("    {id} -> {dependency_id};\n"));
+            }
+        }
+        if let Some(core_id) = node_ids.get(&(CORELIB_CRATE_NAME.into(), None)) {
+            if depends_on_core {
+                dot.push_str(&format!("    {id} -> {core_id} [style=dashed];\n"));
+            }
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Describes how the crate has been set up.
+fn crate_kind(cr: &Crate) -> &'static str {
+    if cr.is_core() {
+        return "corelib";
+    }
+    if cr.discriminator.is_none() {
+        return "single-file crate";
+    }
+    match ProjectManifestPath::discover_from_dir(&cr.root) {
+        Some(ProjectManifestPath::Scarb(_)) => "scarb package",
+        Some(ProjectManifestPath::CairoProject(_)) => "cairo project crate",
+        None => "unmanaged crate",
+    }
+}
+
 /// Generates a Markdown fragment describing a single crate.
 fn inspect_crate(cr: Crate) -> String {
     formatdoc! {
@@ -33,3 +123,42 @@ fn inspect_crate(cr: Crate) -> String {
         settings = indent_by(4, format!("{:#?}", cr.settings)),
     }
 }
+
+/// Root directories of all crates in the database, used to detect changes in the crate set.
+pub type CrateRoots = HashMap<CrateId, Directory>;
+
+/// Collects root directories of all crates in the database.
+pub fn crate_roots(db: &AnalysisDatabase) -> CrateRoots {
+    db.crate_configs().iter().map(|(crate_id, config)| (*crate_id, config.root.clone())).collect()
+}
+
+/// Compares crate roots from before and after a change to the crate set.
+///
+/// Returns `None` if nothing has changed.
+pub fn diff_crate_roots(
+    db: &AnalysisDatabase,
+    before: &CrateRoots,
+    after: &CrateRoots,
+) -> Option<AnalyzedCratesChangedParams> {
+    let added = crate_names(db, after.keys().filter(|crate_id| !before.contains_key(crate_id)));
+    let removed = crate_names(db, before.keys().filter(|crate_id| !after.contains_key(crate_id)));
+    let changed = crate_names(
+        db,
+        after.iter().filter_map(|(crate_id, root)| {
+            before.get(crate_id).is_some_and(|old_root| old_root != root).then_some(crate_id)
+        }),
+    );
+
+    if added.is_empty() && removed.is_empty() && changed.is_empty() {
+        return None;
+    }
+    Some(AnalyzedCratesChangedParams { added, removed, changed })
+}
+
+/// Returns sorted names of the given crates.
+fn crate_names<'a>(
+    db: &AnalysisDatabase,
+    crates: impl Iterator<Item = &'a CrateId>,
+) -> Vec<String> {
+    crates.map(|crate_id| crate_id.name(db).to_string()).sorted().collect()
+}
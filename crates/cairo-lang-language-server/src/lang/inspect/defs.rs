@@ -9,6 +9,7 @@ use cairo_lang_diagnostics::ToOption;
 use cairo_lang_doc::db::DocGroup;
 use cairo_lang_parser::db::ParserGroup;
 use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::diagnostic::SemanticDiagnosticKind;
 use cairo_lang_semantic::expr::pattern::QueryPatternVariablesFromDb;
 use cairo_lang_semantic::items::function_with_body::SemanticExprLookup;
 use cairo_lang_semantic::items::functions::GenericFunctionId;
@@ -156,9 +157,9 @@ impl ItemDef {
         contexts.chain(this).map(|item| db.get_item_signature(item.into())).join("\n")
     }
 
-    /// Gets item documentation in a final form usable for display.
-    pub fn documentation(&self, db: &AnalysisDatabase) -> Option<String> {
-        db.get_item_documentation(self.lookup_item_id.into())
+    /// Gets the lookup item representing the defining item.
+    pub fn lookup_item_id(&self) -> LookupItemId {
+        self.lookup_item_id
     }
 
     /// Gets the full path (including crate name and defining trait/impl if applicable)
@@ -181,7 +182,7 @@ pub struct VariableDef {
 
 impl VariableDef {
     /// Constructs new [`VariableDef`] instance.
-    fn new(db: &AnalysisDatabase, definition_node: SyntaxNode) -> Option<Self> {
+    pub fn new(db: &AnalysisDatabase, definition_node: SyntaxNode) -> Option<Self> {
         match definition_node.kind(db.upcast()) {
             SyntaxKind::TerminalIdentifier => {
                 let definition_node = definition_node.parent()?;
@@ -258,6 +259,16 @@ impl VariableDef {
         Some(Self { name, var })
     }
 
+    /// Checks whether the variable can be modified, i.e. it is a `mut` variable or parameter, or a
+    /// `ref` parameter.
+    pub fn is_mutable(&self) -> bool {
+        match &self.var {
+            Binding::LocalVar(local) => local.is_mut,
+            Binding::LocalItem(_) => false,
+            Binding::Param(param) => param.mutability != Mutability::Immutable,
+        }
+    }
+
     /// Gets variable signature, which tries to resemble the way how it is defined in code.
     pub fn signature(&self, db: &AnalysisDatabase) -> String {
         let Self { name, var } = self;
@@ -363,6 +374,37 @@ pub fn find_definition(
     }
 }
 
+/// Finds all candidate definitions of an identifier which the compiler could not resolve because it
+/// is ambiguous, e.g. a method name provided by more than one trait in scope.
+///
+/// Returns an empty vector if the identifier is not ambiguous.
+pub fn find_ambiguous_definitions(
+    db: &AnalysisDatabase,
+    identifier: &ast::TerminalIdentifier,
+    lookup_items: &[LookupItemId],
+) -> Vec<SyntaxStablePtrId> {
+    let Some(path) = db.first_ancestor_of_kind(identifier.as_syntax_node(), SyntaxKind::ExprPath)
+    else {
+        return vec![];
+    };
+    let path_ptr = path.stable_ptr();
+
+    lookup_items
+        .iter()
+        .filter_map(|lookup_item_id| lookup_item_id.function_with_body())
+        .flat_map(|function_id| db.function_body_diagnostics(function_id).get_all())
+        .filter(|diagnostic| diagnostic.stable_location.syntax_node(db).stable_ptr() == path_ptr)
+        .flat_map(|diagnostic| match diagnostic.kind {
+            SemanticDiagnosticKind::AmbiguousTrait { trait_function_id0, trait_function_id1 } => {
+                vec![trait_function_id0, trait_function_id1]
+            }
+            _ => vec![],
+        })
+        .map(|trait_function| trait_function.stable_ptr(db.upcast()).untyped())
+        .unique()
+        .collect()
+}
+
 /// Extracts [`MemberId`] if the [`ast::TerminalIdentifier`] points to
 /// right-hand side of access member expression e.g., to `xyz` in `self.xyz`.
 fn try_extract_member(
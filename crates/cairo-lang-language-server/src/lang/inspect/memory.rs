@@ -0,0 +1,84 @@
+use cairo_lang_defs::db::{InternSubmoduleQuery, PrivModuleDataQuery};
+use cairo_lang_filesystem::db::{InternCrateQuery, InternFileQuery};
+use cairo_lang_lowering::db::{
+    ModuleLoweringDiagnosticsQuery, PrivFunctionWithBodyMultiLoweringQuery,
+};
+use cairo_lang_parser::db::FileSyntaxQuery;
+use cairo_lang_semantic::db::{
+    FunctionBodyQuery, ModuleSemanticDiagnosticsQuery, PrivModuleSemanticDataQuery,
+};
+use indoc::formatdoc;
+use salsa::debug::{DebugQueryTable, TableEntry};
+
+use crate::lang::db::AnalysisDatabase;
+
+/// Generates a Markdown text describing how much data is stored in the database.
+///
+/// Salsa does not track the memory used by its tables, so the number of entries in the tables
+/// of the most expensive queries is reported instead, as an estimate of the size of caches.
+pub fn inspect_memory_usage(db: &AnalysisDatabase) -> String {
+    let crates = count(InternCrateQuery.in_db(db));
+    let queries = [
+        ("parsed files", "file_syntax", count(FileSyntaxQuery.in_db(db))),
+        ("module definitions", "priv_module_data", count(PrivModuleDataQuery.in_db(db))),
+        (
+            "module semantics",
+            "priv_module_semantic_data",
+            count(PrivModuleSemanticDataQuery.in_db(db)),
+        ),
+        ("function bodies", "function_body", count(FunctionBodyQuery.in_db(db))),
+        (
+            "lowered functions",
+            "priv_function_with_body_multi_lowering",
+            count(PrivFunctionWithBodyMultiLoweringQuery.in_db(db)),
+        ),
+        (
+            "module semantic diagnostics",
+            "module_semantic_diagnostics",
+            count(ModuleSemanticDiagnosticsQuery.in_db(db)),
+        ),
+        (
+            "module lowering diagnostics",
+            "module_lowering_diagnostics",
+            count(ModuleLoweringDiagnosticsQuery.in_db(db)),
+        ),
+    ];
+    let total = queries.iter().map(|(_, _, entries)| entries).sum::<usize>();
+    let queries = queries
+        .into_iter()
+        .map(|(description, query, entries)| format!("- {description} (`{query}`): {entries}\n"))
+        .collect::<String>();
+
+    formatdoc! {
+        r#"
+        # Memory Usage
+
+        ## Interned
+
+        - files: {files}
+        - crates: {crates}
+        - modules: {modules}
+
+        ## Cached query results
+
+        {queries}- total: {total}
+        "#,
+        files = count(InternFileQuery.in_db(db)),
+        // Every crate has a root module, which is not interned as a submodule.
+        modules = crates + count(InternSubmoduleQuery.in_db(db)),
+    }
+}
+
+/// Counts entries stored in a query table.
+fn count(table: impl DebugQueryTable) -> usize {
+    table.entries::<EntryCount>().0
+}
+
+/// Number of entries of a query table, collected without storing the entries themselves.
+struct EntryCount(usize);
+
+impl<K, V> FromIterator<TableEntry<K, V>> for EntryCount {
+    fn from_iter<T: IntoIterator<Item = TableEntry<K, V>>>(iter: T) -> Self {
+        Self(iter.into_iter().count())
+    }
+}
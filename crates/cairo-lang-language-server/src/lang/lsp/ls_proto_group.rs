@@ -20,7 +20,8 @@ pub trait LsProtoGroup: Upcast<dyn FilesGroup> {
                 .inspect_err(|()| error!("invalid file url: {uri}"))
                 .ok()
                 .map(|path| FileId::new(self.upcast(), path)),
-            "vfs" => uri
+            // Documents of macro expansions are virtual files too.
+            "vfs" | "cairo-expand" => uri
                 .host_str()
                 .or_else(|| {
                     error!("invalid vfs url, missing host string: {uri:?}");
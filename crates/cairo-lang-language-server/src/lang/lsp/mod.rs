@@ -1,5 +1,9 @@
 pub use ls_proto_group::*;
+pub use position_encoding::*;
+pub use text_sync::*;
 pub use to_lsp::*;
 
 mod ls_proto_group;
+mod position_encoding;
+mod text_sync;
 mod to_lsp;
@@ -0,0 +1,140 @@
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::FileId;
+use cairo_lang_filesystem::span::{TextPosition, TextSpan};
+use lsp_types::{ClientCapabilities, Position, PositionEncodingKind};
+
+use crate::lsp::capabilities::client::ClientCapabilitiesExt;
+
+#[cfg(test)]
+#[path = "position_encoding_test.rs"]
+mod test;
+
+/// Encoding of columns of LSP positions, negotiated with the client.
+///
+/// Columns of [`TextPosition`]s are counted in Unicode scalar values, so converting them to and
+/// from LSP positions needs the text of the line.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PositionEncoding {
+    /// Columns are counted in bytes.
+    Utf8,
+    /// Columns are counted in UTF-16 code units, which is the default mandated by LSP.
+    #[default]
+    Utf16,
+    /// Columns are counted in Unicode scalar values.
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Picks the encoding most preferred by the client.
+    ///
+    /// Clients which do not list any encoding only support UTF-16.
+    pub fn negotiate(client_capabilities: &ClientCapabilities) -> Self {
+        client_capabilities
+            .position_encodings()
+            .iter()
+            .find_map(|kind| match kind.as_str() {
+                "utf-8" => Some(Self::Utf8),
+                "utf-16" => Some(Self::Utf16),
+                "utf-32" => Some(Self::Utf32),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the LSP identifier of the encoding.
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            Self::Utf8 => PositionEncodingKind::UTF8,
+            Self::Utf16 => PositionEncodingKind::UTF16,
+            Self::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    /// Returns the length of the text in code units of the encoding.
+    pub fn len(self, text: &str) -> usize {
+        match self {
+            Self::Utf8 => text.len(),
+            Self::Utf16 => text.encode_utf16().count(),
+            Self::Utf32 => text.chars().count(),
+        }
+    }
+
+    /// Converts a column counted in characters of the line into code units of the encoding.
+    pub fn col_to_lsp(self, line: &str, col: usize) -> usize {
+        match self {
+            Self::Utf32 => col,
+            _ => line.chars().take(col).map(|c| self.char_len(c)).sum(),
+        }
+    }
+
+    /// Converts a column counted in code units of the encoding into characters of the line.
+    ///
+    /// Columns pointing into the middle of a character are moved past it, and columns past the end
+    /// of the line are clamped to its end.
+    pub fn col_to_cairo(self, line: &str, col: usize) -> usize {
+        if self == Self::Utf32 {
+            return col;
+        }
+        let mut units = 0;
+        line.chars()
+            .take_while(|&c| {
+                let inside = units < col;
+                units += self.char_len(c);
+                inside
+            })
+            .count()
+    }
+
+    /// Converts a position in the file into an LSP position.
+    ///
+    /// The column is left as is if the file cannot be read.
+    pub fn position_to_lsp(
+        self,
+        db: &dyn FilesGroup,
+        file: FileId,
+        position: TextPosition,
+    ) -> Position {
+        let col = match line_text(db, file, position.line) {
+            Some(line) => self.col_to_lsp(&line, position.col),
+            None => position.col,
+        };
+        Position { line: position.line as u32, character: col as u32 }
+    }
+
+    /// Converts an LSP position into a position in the file.
+    ///
+    /// The column is left as is if the file cannot be read.
+    pub fn position_to_cairo(
+        self,
+        db: &dyn FilesGroup,
+        file: FileId,
+        position: Position,
+    ) -> TextPosition {
+        let line = position.line as usize;
+        let col = position.character as usize;
+        let col = match line_text(db, file, line) {
+            Some(text) => self.col_to_cairo(&text, col),
+            None => col,
+        };
+        TextPosition { line, col }
+    }
+
+    /// Returns the length of the character in code units of the encoding.
+    fn char_len(self, c: char) -> usize {
+        match self {
+            Self::Utf8 => c.len_utf8(),
+            Self::Utf16 => c.len_utf16(),
+            Self::Utf32 => 1,
+        }
+    }
+}
+
+/// Returns the text of the line of the file, without the line terminator.
+fn line_text(db: &dyn FilesGroup, file: FileId, line: usize) -> Option<String> {
+    let summary = db.file_summary(file)?;
+    let start = *summary.line_offsets.get(line)?;
+    let content = db.file_content(file)?;
+    let end = summary.line_offsets.get(line + 1).copied().unwrap_or(summary.last_offset);
+    let text = TextSpan { start, end }.take(&content);
+    Some(text.trim_end_matches(['\n', '\r']).to_string())
+}
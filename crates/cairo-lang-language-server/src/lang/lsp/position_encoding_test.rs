@@ -0,0 +1,71 @@
+use lsp_types::{ClientCapabilities, GeneralClientCapabilities, PositionEncodingKind};
+
+use super::PositionEncoding;
+
+/// Text with characters taking 1, 2 and 4 bytes in UTF-8, and 1, 1 and 2 code units in UTF-16.
+const LINE: &str = "aé😀b";
+
+#[test]
+fn col_to_lsp() {
+    let cols = |encoding: PositionEncoding| {
+        (0..=4).map(|col| encoding.col_to_lsp(LINE, col)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(cols(PositionEncoding::Utf8), [0, 1, 3, 7, 8]);
+    assert_eq!(cols(PositionEncoding::Utf16), [0, 1, 2, 4, 5]);
+    assert_eq!(cols(PositionEncoding::Utf32), [0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn col_to_cairo() {
+    let cols = |encoding: PositionEncoding, lsp_cols: &[usize]| {
+        lsp_cols.iter().map(|&col| encoding.col_to_cairo(LINE, col)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(cols(PositionEncoding::Utf8, &[0, 1, 3, 7, 8]), [0, 1, 2, 3, 4]);
+    assert_eq!(cols(PositionEncoding::Utf16, &[0, 1, 2, 4, 5]), [0, 1, 2, 3, 4]);
+    assert_eq!(cols(PositionEncoding::Utf32, &[0, 1, 2, 3, 4]), [0, 1, 2, 3, 4]);
+
+    // Columns inside a character point past it.
+    assert_eq!(cols(PositionEncoding::Utf8, &[2, 4, 6]), [2, 3, 3]);
+    assert_eq!(cols(PositionEncoding::Utf16, &[3]), [3]);
+
+    // Columns past the end of the line are clamped.
+    assert_eq!(cols(PositionEncoding::Utf8, &[100]), [4]);
+    assert_eq!(cols(PositionEncoding::Utf16, &[100]), [4]);
+}
+
+#[test]
+fn len() {
+    assert_eq!(PositionEncoding::Utf8.len(LINE), 8);
+    assert_eq!(PositionEncoding::Utf16.len(LINE), 5);
+    assert_eq!(PositionEncoding::Utf32.len(LINE), 4);
+}
+
+#[test]
+fn negotiate() {
+    let negotiate = |encodings: Option<Vec<PositionEncodingKind>>| {
+        PositionEncoding::negotiate(&ClientCapabilities {
+            general: Some(GeneralClientCapabilities {
+                position_encodings: encodings,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    };
+
+    assert_eq!(
+        PositionEncoding::negotiate(&ClientCapabilities::default()),
+        PositionEncoding::Utf16
+    );
+    assert_eq!(negotiate(None), PositionEncoding::Utf16);
+    assert_eq!(negotiate(Some(vec![])), PositionEncoding::Utf16);
+    assert_eq!(
+        negotiate(Some(vec![PositionEncodingKind::UTF8, PositionEncodingKind::UTF16])),
+        PositionEncoding::Utf8
+    );
+    assert_eq!(
+        negotiate(Some(vec!["utf-7".into(), PositionEncodingKind::UTF32])),
+        PositionEncoding::Utf32
+    );
+}
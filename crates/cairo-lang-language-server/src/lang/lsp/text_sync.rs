@@ -0,0 +1,71 @@
+use lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+
+use crate::lang::lsp::PositionEncoding;
+
+#[cfg(test)]
+#[path = "text_sync_test.rs"]
+mod test;
+
+/// Applies changes sent in a `textDocument/didChange` notification to the text of the document.
+///
+/// Changes are applied one after another, so ranges of each change refer to the text with all
+/// previous changes applied. Changes without a range replace the whole text, which is how clients
+/// using full synchronization send them.
+///
+/// Returns `None` if any range does not fit in the text.
+pub fn apply_content_changes(
+    text: &str,
+    changes: impl IntoIterator<Item = TextDocumentContentChangeEvent>,
+    encoding: PositionEncoding,
+) -> Option<String> {
+    let mut text = text.to_string();
+    for change in changes {
+        match change.range {
+            Some(range) => {
+                let start = offset(&text, range.start, encoding)?;
+                let end = offset(&text, range.end, encoding)?;
+                if start > end {
+                    return None;
+                }
+                text.replace_range(start..end, &change.text);
+            }
+            None => text = change.text,
+        }
+    }
+    Some(text)
+}
+
+/// Returns the part of the text within the LSP range.
+///
+/// Returns `None` if the range does not fit in the text.
+pub fn text_in_range(text: &str, range: Range, encoding: PositionEncoding) -> Option<&str> {
+    let start = offset(text, range.start, encoding)?;
+    let end = offset(text, range.end, encoding)?;
+    text.get(start..end)
+}
+
+/// Converts a byte offset in the text into an LSP position.
+pub fn position_at(text: &str, offset: usize, encoding: PositionEncoding) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: encoding.len(&before[line_start..]) as u32,
+    }
+}
+
+/// Converts an LSP position into a byte offset in the text.
+///
+/// Columns past the end of a line are clamped to its end, as the LSP specification requires.
+fn offset(text: &str, position: Position, encoding: PositionEncoding) -> Option<usize> {
+    let line_start = match position.line {
+        0 => 0,
+        line => text.match_indices('\n').nth(line as usize - 1)?.0 + 1,
+    };
+    let line = &text[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let col = encoding.col_to_cairo(line, position.character as usize);
+    let col_offset = line.char_indices().nth(col).map_or(line.len(), |(offset, _)| offset);
+    Some(line_start + col_offset)
+}
@@ -0,0 +1,116 @@
+use lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+
+use super::{apply_content_changes, position_at, text_in_range};
+use crate::lang::lsp::PositionEncoding;
+
+fn change(range: Option<((u32, u32), (u32, u32))>, text: &str) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: range.map(|((start_line, start_col), (end_line, end_col))| Range {
+            start: Position { line: start_line, character: start_col },
+            end: Position { line: end_line, character: end_col },
+        }),
+        range_length: None,
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn applies_incremental_changes_in_order() {
+    let text = "fn main() {\n    let x = 1;\n}\n";
+    let changes = [
+        // Rename `x` to `value`.
+        change(Some(((1, 8), (1, 9))), "value"),
+        // Insert a line, referring to the text after the first change.
+        change(Some(((1, 18), (1, 18))), "\n    let y = value;"),
+        // Remove the line break between the function and its closing brace.
+        change(Some(((2, 18), (3, 0))), " "),
+    ];
+    assert_eq!(
+        apply_content_changes(text, changes, PositionEncoding::Utf16).unwrap(),
+        "fn main() {\n    let value = 1;\n    let y = value; }\n"
+    );
+}
+
+#[test]
+fn full_text_changes_replace_text() {
+    let changes = [
+        change(Some(((0, 0), (0, 2))), "fn"),
+        change(None, "fn new() {}"),
+        change(None, "fn b() {}"),
+    ];
+    assert_eq!(
+        apply_content_changes("fn old() {}", changes, PositionEncoding::Utf16).unwrap(),
+        "fn b() {}"
+    );
+}
+
+#[test]
+fn columns_are_counted_in_encoding() {
+    let text = "let s = \"😀\"; let x = 5;\r\nx\n";
+    for (encoding, col) in
+        [(PositionEncoding::Utf8, 20), (PositionEncoding::Utf16, 18), (PositionEncoding::Utf32, 17)]
+    {
+        let changes = [change(Some(((0, col), (0, col + 1))), "y")];
+        assert_eq!(
+            apply_content_changes(text, changes, encoding).unwrap(),
+            "let s = \"😀\"; let y = 5;\r\nx\n",
+            "{encoding:?}"
+        );
+    }
+}
+
+#[test]
+fn columns_past_end_of_line_are_clamped() {
+    let text = "ab\r\ncd";
+    let changes = [change(Some(((0, 100), (1, 0))), "")];
+    assert_eq!(apply_content_changes(text, changes, PositionEncoding::Utf16).unwrap(), "abcd");
+}
+
+#[test]
+fn ranges_outside_text_are_rejected() {
+    let changes = [change(Some(((5, 0), (5, 1))), "x")];
+    assert_eq!(apply_content_changes("a\nb", changes, PositionEncoding::Utf16), None);
+
+    let changes = [change(Some(((0, 2), (0, 1))), "x")];
+    assert_eq!(apply_content_changes("abc", changes, PositionEncoding::Utf16), None);
+}
+
+#[test]
+fn slices_text_in_range() {
+    let text = "fn main() {\n    let s = \"😀\";\n}\n";
+    let range = |(start_line, start_col), (end_line, end_col)| Range {
+        start: Position { line: start_line, character: start_col },
+        end: Position { line: end_line, character: end_col },
+    };
+
+    assert_eq!(
+        text_in_range(text, range((0, 3), (1, 7)), PositionEncoding::Utf16),
+        Some("main() {\n    let")
+    );
+    assert_eq!(text_in_range(text, range((1, 13), (1, 16)), PositionEncoding::Utf16), Some("😀\""));
+    assert_eq!(text_in_range(text, range((1, 13), (1, 15)), PositionEncoding::Utf32), Some("😀\""));
+    // Columns past the end of a line are clamped to its end.
+    assert_eq!(text_in_range(text, range((2, 0), (2, 10)), PositionEncoding::Utf16), Some("}"));
+    assert_eq!(text_in_range(text, range((1, 0), (0, 0)), PositionEncoding::Utf16), None);
+    assert_eq!(text_in_range(text, range((0, 0), (5, 0)), PositionEncoding::Utf16), None);
+}
+
+#[test]
+fn positions_are_counted_in_encoding() {
+    let text = "fn main() {\n    let s = \"😀\";\n}\n";
+    let offset = text.find("\";").unwrap();
+    for (encoding, col) in
+        [(PositionEncoding::Utf8, 17), (PositionEncoding::Utf16, 15), (PositionEncoding::Utf32, 14)]
+    {
+        assert_eq!(
+            position_at(text, offset, encoding),
+            Position { line: 1, character: col },
+            "{encoding:?}"
+        );
+    }
+    assert_eq!(position_at(text, 0, PositionEncoding::Utf16), Position { line: 0, character: 0 });
+    assert_eq!(
+        position_at(text, text.len(), PositionEncoding::Utf16),
+        Position { line: 3, character: 0 }
+    );
+}
@@ -1,60 +1,70 @@
+use cairo_lang_filesystem::ids::FileId;
 use cairo_lang_filesystem::span::{TextPosition, TextPositionSpan};
+use cairo_lang_utils::Upcast;
 use lsp_types::{Position, Range};
 
+use crate::lang::db::AnalysisDatabase;
+
 /// Convert a type into its LSP equivalent.
 ///
 /// This trait should be used for conversions, where there is a direct mapping between the types,
-/// and no extra context is needed. Many conversions may need access to the compiler database,
-/// and such ones are mostly implemented in the [`LsProtoGroup`] extension trait.
+/// and the only extra context needed is the file, whose text is used to count columns in the
+/// [`PositionEncoding`] negotiated with the client. Many conversions may need more access to the
+/// compiler database, and such ones are mostly implemented in the [`LsProtoGroup`] extension
+/// trait.
 ///
 /// [`LsProtoGroup`]: crate::lang::lsp::LsProtoGroup
+/// [`PositionEncoding`]: crate::lang::lsp::PositionEncoding
 pub trait ToLsp {
     /// LSP equivalent type.
     type Output;
 
-    /// Convert the type into its LSP equivalent.
-    fn to_lsp(&self) -> Self::Output;
+    /// Convert the type, located in the given file, into its LSP equivalent.
+    fn to_lsp(&self, db: &AnalysisDatabase, file: FileId) -> Self::Output;
 }
 
 impl ToLsp for TextPosition {
     type Output = Position;
-    fn to_lsp(&self) -> Self::Output {
-        Position { line: self.line as u32, character: self.col as u32 }
+    fn to_lsp(&self, db: &AnalysisDatabase, file: FileId) -> Self::Output {
+        db.position_encoding().position_to_lsp(db.upcast(), file, *self)
     }
 }
 
 impl ToLsp for TextPositionSpan {
     type Output = Range;
-    fn to_lsp(&self) -> Self::Output {
-        Range { start: self.start.to_lsp(), end: self.end.to_lsp() }
+    fn to_lsp(&self, db: &AnalysisDatabase, file: FileId) -> Self::Output {
+        Range { start: self.start.to_lsp(db, file), end: self.end.to_lsp(db, file) }
     }
 }
 
 /// Convert an LSP type into its Cairo equivalent.
 ///
 /// This trait should be used for conversions, where there is a direct mapping between the types,
-/// and no extra context is needed. Many conversions may need access to the compiler database,
-/// and such ones are mostly implemented in the [`LsProtoGroup`] extension trait.
+/// and the only extra context needed is the file, whose text is used to count columns in the
+/// [`PositionEncoding`] negotiated with the client. Many conversions may need more access to the
+/// compiler database, and such ones are mostly implemented in the [`LsProtoGroup`] extension
+/// trait.
 ///
 /// [`LsProtoGroup`]: crate::lang::lsp::LsProtoGroup
+/// [`PositionEncoding`]: crate::lang::lsp::PositionEncoding
 pub trait ToCairo {
     /// Cairo equivalent type.
     type Output;
 
-    /// Convert an LSP type into its Cairo equivalent.
-    fn to_cairo(&self) -> Self::Output;
+    /// Convert an LSP type, located in the given file, into its Cairo equivalent.
+    fn to_cairo(&self, db: &AnalysisDatabase, file: FileId) -> Self::Output;
 }
 
 impl ToCairo for Position {
     type Output = TextPosition;
-    fn to_cairo(&self) -> Self::Output {
-        TextPosition { line: self.line as usize, col: self.character as usize }
+    fn to_cairo(&self, db: &AnalysisDatabase, file: FileId) -> Self::Output {
+        db.position_encoding().position_to_cairo(db.upcast(), file, *self)
     }
 }
 
 impl ToCairo for Range {
     type Output = TextPositionSpan;
-    fn to_cairo(&self) -> Self::Output {
-        TextPositionSpan { start: self.start.to_cairo(), end: self.end.to_cairo() }
+    fn to_cairo(&self, db: &AnalysisDatabase, file: FileId) -> Self::Output {
+        TextPositionSpan { start: self.start.to_cairo(db, file), end: self.end.to_cairo(db, file) }
     }
 }
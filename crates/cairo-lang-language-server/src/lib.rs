@@ -37,38 +37,67 @@
 //!     cairo_lang_language_server::start_with_tricks(tricks);
 //! }
 //! ```
+//!
+//! ## Running with fixed configuration
+//!
+//! By default, CairoLS pulls its [`Config`] from the language client.
+//! Headless usages, like tests or custom tools, can instead provide the configuration upfront
+//! with the [`start_with_config`] function, so that it does not depend on the client answering
+//! `workspace/configuration` requests.
+//!
+//! ```no_run
+//! # #![allow(clippy::needless_doctest_main)]
+//! use cairo_lang_language_server::{Config, Tricks};
+//!
+//! fn main() {
+//!     let config = Config { trace_macro_diagnostics: true, ..Default::default() };
+//!     cairo_lang_language_server::start_with_config(Tricks::default(), config);
+//! }
+//! ```
 
+use std::collections::BTreeSet;
 use std::panic::RefUnwindSafe;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::sync::Arc;
 use std::time::SystemTime;
 use std::{io, panic};
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use cairo_lang_compiler::db::validate_corelib;
-use cairo_lang_compiler::project::{setup_project, update_crate_roots_from_project_config};
-use cairo_lang_filesystem::db::FilesGroup;
-use cairo_lang_filesystem::ids::FileLongId;
+use cairo_lang_compiler::project::setup_project;
+use cairo_lang_defs::plugin::InlineMacroExprPlugin;
+use cairo_lang_filesystem::db::{CORELIB_VERSION, FilesGroup, FilesGroupEx};
+use cairo_lang_filesystem::ids::{CrateId, FileLongId};
 use cairo_lang_project::ProjectConfig;
+use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_semantic::plugin::PluginSuite;
+use cairo_lang_syntax::node::SyntaxNode;
+use crossbeam::channel::RecvTimeoutError;
 use lsp_server::Message;
-use lsp_types::RegistrationParams;
+use lsp_types::{ClientCapabilities, InitializeParams, RegistrationParams, SemanticTokenType};
 use tracing::{debug, error, info, warn};
 
-use crate::config::Config;
+pub use crate::config::Config;
 use crate::lang::db::AnalysisDatabase;
+use crate::lang::inspect::crates::{crate_roots, diff_crate_roots};
 use crate::lang::lsp::LsProtoGroup;
+use crate::lsp::capabilities::client::ClientCapabilitiesExt;
 use crate::lsp::capabilities::server::{
     collect_dynamic_registrations, collect_server_capabilities,
 };
-use crate::lsp::ext::{CorelibVersionMismatch, ScarbMetadataFailed};
+use crate::lsp::ext::{
+    AnalyzedCratesChanged, CorelibVersionMismatch, CorelibVersionMismatchParams,
+    ScarbMetadataFailed, ScarbMetadataFailedParams,
+};
 use crate::lsp::result::LSPResult;
-use crate::project::ProjectManifestPath;
 use crate::project::scarb::update_crate_roots;
 use crate::project::unmanaged_core_crate::try_to_init_unmanaged_core;
+use crate::project::{ProjectManifestPath, cairo_project};
 use crate::server::client::{Notifier, Requester, Responder};
 use crate::server::connection::{Connection, ConnectionInitializer};
 use crate::server::panic::is_cancelled;
+use crate::server::progress::WorkDoneProgressReporter;
 use crate::server::schedule::thread::JoinHandle;
 use crate::server::schedule::{Scheduler, Task, event_loop_thread};
 use crate::state::State;
@@ -96,8 +125,42 @@ pub struct Tricks {
     /// language server database.
     pub extra_plugin_suites:
         Option<&'static (dyn Fn() -> Vec<PluginSuite> + Send + Sync + RefUnwindSafe)>,
+    /// A function that returns a list of additional inline macro plugins, keyed by macro names,
+    /// to be loaded in the language server database.
+    ///
+    /// These are registered after all plugin suites, but never replace an inline macro of the
+    /// same name registered by any of them.
+    /// Such conflicting plugins are skipped with a warning.
+    pub extra_inline_macro_plugins:
+        Option<&'static (dyn Fn() -> InlineMacroPlugins + Send + Sync + RefUnwindSafe)>,
+    /// Additional semantic token types, along with a function classifying tokens as them.
+    ///
+    /// The default legend of semantic tokens is used if this is not set.
+    pub semantic_tokens_extension: Option<SemanticTokensExtension>,
+}
+
+/// Inline macro plugins, along with names of the macros they implement.
+pub type InlineMacroPlugins = Vec<(String, Arc<dyn InlineMacroExprPlugin>)>;
+
+/// Extends semantic highlighting with additional token types.
+#[derive(Clone)]
+pub struct SemanticTokensExtension {
+    /// Additional token types, appended to the legend of semantic tokens after the default ones.
+    pub token_types: Vec<SemanticTokenType>,
+    /// A function classifying a token as one of the additional token types, by returning its
+    /// index in `token_types`.
+    ///
+    /// It is called with syntax nodes of tokens before they are classified the default way, which
+    /// happens only if it returns `None`.
+    pub classify: &'static SemanticTokenClassifier,
 }
 
+/// A function classifying tokens as additional semantic token types.
+///
+/// See [`SemanticTokensExtension::classify`].
+pub type SemanticTokenClassifier =
+    dyn Fn(&dyn SemanticGroup, &SyntaxNode) -> Option<usize> + Send + Sync + RefUnwindSafe;
+
 /// Starts the language server.
 ///
 /// See [the top-level documentation][lib] documentation for usage examples.
@@ -113,13 +176,29 @@ pub fn start() -> ExitCode {
 ///
 /// [lib]: crate#running-with-customizations
 pub fn start_with_tricks(tricks: Tricks) -> ExitCode {
+    start_with(tricks, None)
+}
+
+/// Starts the language server with customizations and a fixed configuration.
+///
+/// The provided configuration is used as is for the whole lifetime of the language server, and it
+/// is never pulled from the language client, neither upon initialization nor on changes.
+///
+/// See [the top-level documentation][lib] documentation for usage examples.
+///
+/// [lib]: crate#running-with-fixed-configuration
+pub fn start_with_config(tricks: Tricks, config: Config) -> ExitCode {
+    start_with(tricks, Some(config))
+}
+
+fn start_with(tricks: Tricks, config: Option<Config>) -> ExitCode {
     let _log_guard = init_logging();
     set_panic_hook();
 
     info!("language server starting");
     env_config::report_to_logs();
 
-    let exit_code = match Backend::new(tricks) {
+    let exit_code = match Backend::new(tricks, config) {
         Ok(backend) => {
             if let Err(err) = backend.run().map(|handle| handle.join()) {
                 error!("language server encountered an unrecoverable error: {err}");
@@ -140,9 +219,11 @@ pub fn start_with_tricks(tricks: Tricks) -> ExitCode {
 
 /// Special function to run the language server in end-to-end tests.
 #[cfg(feature = "testing")]
-pub fn build_service_for_e2e_tests()
--> (Box<dyn FnOnce() -> BackendForTesting + Send>, lsp_server::Connection) {
-    BackendForTesting::new_for_testing(Default::default())
+pub fn build_service_for_e2e_tests(
+    tricks: Tricks,
+    config: Option<Config>,
+) -> (Box<dyn FnOnce() -> BackendForTesting + Send>, lsp_server::Connection) {
+    BackendForTesting::new_for_testing(tricks, config)
 }
 
 /// Initialize logging infrastructure for the language server.
@@ -235,11 +316,12 @@ pub struct BackendForTesting(Backend);
 impl BackendForTesting {
     fn new_for_testing(
         tricks: Tricks,
+        config: Option<Config>,
     ) -> (Box<dyn FnOnce() -> BackendForTesting + Send>, lsp_server::Connection) {
         let (connection_initializer, client) = ConnectionInitializer::memory();
 
         let init = Box::new(|| {
-            BackendForTesting(Backend::initialize(tricks, connection_initializer).unwrap())
+            BackendForTesting(Backend::initialize(tricks, config, connection_initializer).unwrap())
         });
 
         (init, client)
@@ -251,23 +333,36 @@ impl BackendForTesting {
 }
 
 impl Backend {
-    fn new(tricks: Tricks) -> Result<Self> {
+    fn new(tricks: Tricks, config: Option<Config>) -> Result<Self> {
         let connection_initializer = ConnectionInitializer::stdio();
 
-        Self::initialize(tricks, connection_initializer)
+        Self::initialize(tricks, config, connection_initializer)
     }
 
     /// Initializes the connection and crate a ready to run [`Backend`] instance.
     ///
     /// As part of the initialization flow, this function exchanges client and server capabilities.
-    fn initialize(tricks: Tricks, connection_initializer: ConnectionInitializer) -> Result<Self> {
+    ///
+    /// If `config` is provided, it is used instead of the configuration pulled from the client.
+    fn initialize(
+        tricks: Tricks,
+        config: Option<Config>,
+        connection_initializer: ConnectionInitializer,
+    ) -> Result<Self> {
         let (id, init_params) = connection_initializer.initialize_start()?;
 
+        let workspace_folders = workspace_folder_paths(&init_params);
         let client_capabilities = init_params.capabilities;
-        let server_capabilities = collect_server_capabilities(&client_capabilities);
+        let server_capabilities = collect_server_capabilities(&client_capabilities, &tricks);
 
         let connection = connection_initializer.initialize_finish(id, server_capabilities)?;
-        let state = State::new(connection.make_sender(), client_capabilities, tricks);
+        let state = State::new(
+            connection.make_sender(),
+            client_capabilities,
+            workspace_folders,
+            tricks,
+            config,
+        );
 
         Ok(Self { connection, state })
     }
@@ -305,8 +400,19 @@ impl Backend {
     fn dispatch_setup_tasks(scheduler: &mut Scheduler<'_>) {
         scheduler.local(Self::register_dynamic_capabilities);
 
-        scheduler.local(|state, _notifier, requester, _responder| {
-            let _ = state.config.reload(requester, &state.client_capabilities);
+        // Projects of workspace folders are loaded once the configuration is known, because it
+        // affects how they are loaded (e.g., which `scarb` is used).
+        scheduler.local(|state, notifier, requester, _responder| {
+            if !state.config_is_fixed && state.client_capabilities.workspace_configuration_support()
+            {
+                let _ = state.config.reload_then(
+                    requester,
+                    &state.client_capabilities,
+                    Self::load_workspace_folders,
+                );
+            } else {
+                Self::load_workspace_folders(state, notifier, requester);
+            }
         });
     }
 
@@ -316,7 +422,8 @@ impl Backend {
         requester: &mut Requester<'_>,
         _responder: Responder,
     ) {
-        let registrations = collect_dynamic_registrations(&state.client_capabilities);
+        let registrations =
+            collect_dynamic_registrations(&state.client_capabilities, &state.tricks);
 
         let _ = requester
             .request::<lsp_types::request::RegisterCapability>(
@@ -341,7 +448,14 @@ impl Backend {
     // | Commit: 46a457318d8d259376a2b458b3f814b9b795fe69 |
     // +--------------------------------------------------+
     fn event_loop(connection: &Connection, mut scheduler: Scheduler<'_>) -> Result<()> {
-        for msg in connection.incoming() {
+        loop {
+            scheduler.cancel_timed_out_requests();
+
+            let msg = match connection.recv_deadline(scheduler.next_request_deadline()) {
+                Ok(msg) => msg,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
             if connection.handle_shutdown(&msg)? {
                 break;
             }
@@ -357,23 +471,61 @@ impl Backend {
     }
 
     /// Calls [`lang::db::AnalysisDatabaseSwapper::maybe_swap`] to do its work.
-    fn maybe_swap_database(state: &mut State, notifier: Notifier) {
+    fn maybe_swap_database(state: &mut State, notifier: Notifier, _requester: &mut Requester<'_>) {
         state.db_swapper.maybe_swap(
             &mut state.db,
+            &state.background_requests,
             &state.open_files,
+            &state.workspace_folders,
             &state.config,
             &state.tricks,
             &notifier,
         );
     }
 
+    /// Drops all analysis results and caches, starting analysis from a clean state.
+    ///
+    /// Unlike [`Self::maybe_swap_database`], the database is always replaced.
+    /// Crates of workspace folders and open files are detected anew, including fresh
+    /// `scarb metadata` calls, and all diagnostics are republished once the state refresh that
+    /// follows every sync task runs on the new database.
+    fn clear_cache(state: &mut State, notifier: &Notifier) {
+        state.scarb_toolchain.clear_metadata_cache();
+        state.db_swapper.swap(
+            &mut state.db,
+            &state.open_files,
+            &state.workspace_folders,
+            &state.config,
+            &state.tricks,
+            notifier,
+        );
+        state.semantic_tokens_cache.clear();
+        state.pull_diagnostics_cache.clear();
+    }
+
     /// Calls [`lang::diagnostics::DiagnosticsController::refresh`] to do its work.
-    fn refresh_diagnostics(state: &mut State, notifier: Notifier) {
+    ///
+    /// Diagnostics of closed files may be pulled by the client instead, so it is asked to pull
+    /// them anew, if it supports that.
+    fn refresh_diagnostics(state: &mut State, notifier: Notifier, requester: &mut Requester<'_>) {
         state.diagnostics_controller.refresh(state.snapshot(), notifier);
+
+        if state.client_capabilities.pull_diagnostics_support()
+            && state.client_capabilities.workspace_diagnostic_refresh_support()
+        {
+            let _ = requester
+                .request::<lsp_types::request::WorkspaceDiagnosticRefresh>((), |()| {
+                    Task::nothing()
+                })
+                .inspect_err(|e| error!("failed to request workspace diagnostics refresh: {e:?}"));
+        }
     }
 
     /// Tries to detect the crate root the config that contains a cairo file, and add it to the
     /// system.
+    ///
+    /// Loading the project may take a while, so its progress is reported to the client, unless
+    /// `progress` is `None`.
     #[tracing::instrument(skip_all)]
     fn detect_crate_for(
         db: &mut AnalysisDatabase,
@@ -381,17 +533,94 @@ impl Backend {
         config: &Config,
         file_path: &Path,
         notifier: &Notifier,
+        mut progress: Option<(&ClientCapabilities, &mut Requester<'_>)>,
     ) {
         match ProjectManifestPath::discover(file_path) {
-            Some(ProjectManifestPath::Scarb(manifest_path)) => {
+            Some(manifest) => {
+                Self::load_project(db, scarb_toolchain, config, manifest, notifier, progress);
+            }
+
+            None => {
+                let _progress = begin_progress(&mut progress, "Setting up corelib…", notifier);
+                try_to_init_unmanaged_core(db, config, scarb_toolchain);
+
+                match setup_project(&mut *db, file_path) {
+                    Ok(crate_ids) => {
+                        for crate_id in crate_ids {
+                            db.set_workspace_member(crate_id, true);
+                        }
+                    }
+                    Err(err) => {
+                        let file_path_s = file_path.to_string_lossy();
+                        error!("error loading file {file_path_s} as a single crate: {err}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Loads projects of all workspace folders up front, so that their crates are analyzed before
+    /// any of their files are opened.
+    fn load_workspace_folders(
+        state: &mut State,
+        notifier: Notifier,
+        requester: &mut Requester<'_>,
+    ) {
+        for folder in &state.workspace_folders {
+            Self::load_workspace_folder(
+                &mut state.db,
+                &state.scarb_toolchain,
+                &state.config,
+                folder,
+                &notifier,
+                Some((&state.client_capabilities, requester)),
+            );
+        }
+    }
+
+    /// Loads the Cairo project containing a workspace folder, if there is one.
+    ///
+    /// Unlike for files, nothing is loaded for folders which are not part of any project.
+    #[tracing::instrument(skip_all, fields(folder = %folder.display()))]
+    fn load_workspace_folder(
+        db: &mut AnalysisDatabase,
+        scarb_toolchain: &ScarbToolchain,
+        config: &Config,
+        folder: &Path,
+        notifier: &Notifier,
+        progress: Option<(&ClientCapabilities, &mut Requester<'_>)>,
+    ) {
+        match ProjectManifestPath::discover_from_dir(folder) {
+            Some(manifest) => {
+                Self::load_project(db, scarb_toolchain, config, manifest, notifier, progress);
+            }
+            None => debug!("no cairo project found in workspace folder"),
+        }
+    }
+
+    /// Adds crates of the project with the given manifest to the system.
+    fn load_project(
+        db: &mut AnalysisDatabase,
+        scarb_toolchain: &ScarbToolchain,
+        config: &Config,
+        manifest: ProjectManifestPath,
+        notifier: &Notifier,
+        mut progress: Option<(&ClientCapabilities, &mut Requester<'_>)>,
+    ) {
+        match manifest {
+            ProjectManifestPath::Scarb(manifest_path) => {
+                let progress = begin_progress(&mut progress, "Loading Scarb workspace…", notifier);
                 let metadata = scarb_toolchain
                     .metadata(&manifest_path)
-                    .with_context(|| {
-                        format!("failed to refresh scarb workspace: {}", manifest_path.display())
-                    })
                     .inspect_err(|err| {
-                        warn!("{err:?}");
-                        notifier.notify::<ScarbMetadataFailed>(());
+                        warn!(
+                            "failed to refresh scarb workspace: {}: {err:?}",
+                            manifest_path.display()
+                        );
+                        notifier.notify::<ScarbMetadataFailed>(ScarbMetadataFailedParams {
+                            manifest_path: manifest_path.clone(),
+                            error: format!("{err:#}"),
+                        });
                     })
                     .ok();
 
@@ -399,44 +628,80 @@ impl Backend {
                     update_crate_roots(&metadata, db);
                 } else {
                     // Try to set up a corelib at least.
+                    progress.report("Setting up corelib…");
                     try_to_init_unmanaged_core(db, config, scarb_toolchain);
                 }
 
-                if let Err(result) = validate_corelib(db) {
-                    notifier.notify::<CorelibVersionMismatch>(result.to_string());
+                if let Err(err) = validate_corelib(db) {
+                    let found = db
+                        .crate_config(CrateId::core(db))
+                        .and_then(|config| config.settings.version)
+                        .map(|version| version.to_string())
+                        .unwrap_or_default();
+                    notifier.notify::<CorelibVersionMismatch>(CorelibVersionMismatchParams {
+                        message: err.to_string(),
+                        manifest_path,
+                        expected: CORELIB_VERSION.to_owned(),
+                        found,
+                    });
                 }
             }
 
-            Some(ProjectManifestPath::CairoProject(config_path)) => {
+            ProjectManifestPath::CairoProject(config_path) => {
                 // The base path of ProjectConfig must be absolute to ensure that all paths in Salsa
                 // DB will also be absolute.
                 assert!(config_path.is_absolute());
 
+                let _progress = begin_progress(&mut progress, "Setting up corelib…", notifier);
                 try_to_init_unmanaged_core(db, config, scarb_toolchain);
 
                 if let Ok(config) = ProjectConfig::from_file(&config_path) {
-                    update_crate_roots_from_project_config(db, &config);
+                    cairo_project::update_crate_roots(db, config);
                 };
             }
-
-            None => {
-                try_to_init_unmanaged_core(db, config, scarb_toolchain);
-
-                if let Err(err) = setup_project(&mut *db, file_path) {
-                    let file_path_s = file_path.to_string_lossy();
-                    error!("error loading file {file_path_s} as a single crate: {err}");
-                }
-            }
         }
     }
 
-    /// Reload crate detection for all open files.
+    /// Reload crate detection for all workspace folders and open files.
     fn reload(
         state: &mut State,
         notifier: &Notifier,
         requester: &mut Requester<'_>,
     ) -> LSPResult<()> {
-        state.config.reload(requester, &state.client_capabilities)?;
+        let crate_roots_before = crate_roots(&state.db);
+
+        // Loading projects may override files, e.g. roots of virtual crates, so the unsaved content
+        // of open files is captured here and restored afterwards.
+        let open_files_content = state
+            .open_files
+            .iter()
+            .filter_map(|uri| {
+                let file_id = state.db.file_for_url(uri)?;
+                Some((uri.clone(), state.db.file_overrides().get(&file_id)?.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        if !state.config_is_fixed {
+            state.config.reload(requester, &state.client_capabilities)?;
+        }
+
+        // Crates are set up from scratch, so that crates which the projects no longer produce,
+        // e.g. dependencies dropped from the manifest, stop being analyzed.
+        let core = CrateId::core(&state.db);
+        for crate_id in crate_roots_before.keys().filter(|crate_id| **crate_id != core) {
+            state.db.set_crate_config(*crate_id, None);
+        }
+
+        for folder in &state.workspace_folders {
+            Backend::load_workspace_folder(
+                &mut state.db,
+                &state.scarb_toolchain,
+                &state.config,
+                folder,
+                notifier,
+                Some((&state.client_capabilities, requester)),
+            );
+        }
 
         for uri in state.open_files.iter() {
             let Some(file_id) = state.db.file_for_url(uri) else { continue };
@@ -447,10 +712,50 @@ impl Backend {
                     &state.config,
                     &file_path,
                     notifier,
+                    Some((&state.client_capabilities, requester)),
                 );
             }
         }
 
+        for (uri, content) in open_files_content {
+            let Some(file_id) = state.db.file_for_url(&uri) else { continue };
+            if state.db.file_overrides().get(&file_id) != Some(&content) {
+                state.db.override_file_content(file_id, Some(content));
+            }
+        }
+
+        if let Some(params) =
+            diff_crate_roots(&state.db, &crate_roots_before, &crate_roots(&state.db))
+        {
+            notifier.notify::<AnalyzedCratesChanged>(params);
+        }
+
         Ok(())
     }
 }
+
+/// Begins reporting progress of loading a project to the client, unless `progress` is `None`.
+fn begin_progress(
+    progress: &mut Option<(&ClientCapabilities, &mut Requester<'_>)>,
+    title: &str,
+    notifier: &Notifier,
+) -> WorkDoneProgressReporter {
+    match progress {
+        Some((client_capabilities, requester)) => {
+            WorkDoneProgressReporter::begin(title, client_capabilities, notifier, requester)
+        }
+        None => WorkDoneProgressReporter::disabled(notifier),
+    }
+}
+
+/// Collects paths of the workspace folders the client was initialized with.
+///
+/// Falls back to the deprecated `rootUri` for clients which do not support workspace folders.
+fn workspace_folder_paths(init_params: &InitializeParams) -> BTreeSet<PathBuf> {
+    #[allow(deprecated)]
+    let uris = match &init_params.workspace_folders {
+        Some(folders) => folders.iter().map(|folder| folder.uri.clone()).collect(),
+        None => init_params.root_uri.iter().cloned().collect::<Vec<_>>(),
+    };
+    uris.into_iter().filter_map(|uri| uri.to_file_path().ok()).collect()
+}
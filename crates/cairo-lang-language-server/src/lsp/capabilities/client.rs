@@ -1,4 +1,4 @@
-use lsp_types::ClientCapabilities;
+use lsp_types::{ClientCapabilities, MarkupKind, PositionEncodingKind, ResourceOperationKind};
 
 macro_rules! try_or_default {
     ($expr:expr) => {
@@ -21,6 +21,13 @@ pub trait ClientCapabilitiesExt {
     /// The client supports dynamic registration for completion capabilities.
     fn completion_dynamic_registration(&self) -> bool;
 
+    /// The client supports snippets as insert text of completion items.
+    fn completion_snippet_support(&self) -> bool;
+
+    /// The client supports resolving additional text edits of completion items with
+    /// `completionItem/resolve` requests.
+    fn completion_resolve_additional_text_edits_support(&self) -> bool;
+
     /// The client supports dynamic registration for execute command capabilities.
     fn execute_command_dynamic_registration(&self) -> bool;
 
@@ -30,14 +37,93 @@ pub trait ClientCapabilitiesExt {
     /// The client supports dynamic registration for formatting capabilities.
     fn formatting_dynamic_registration(&self) -> bool;
 
+    /// The client supports dynamic registration for range formatting capabilities.
+    fn range_formatting_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for on-type formatting capabilities.
+    fn on_type_formatting_dynamic_registration(&self) -> bool;
+
     /// The client supports dynamic registration for hover capabilities.
     fn hover_dynamic_registration(&self) -> bool;
 
+    /// The client supports Markdown in hover contents.
+    fn hover_markdown_support(&self) -> bool;
+
     /// The client supports dynamic registration for definition capabilities.
     fn definition_dynamic_registration(&self) -> bool;
 
+    /// The client supports dynamic registration for declaration capabilities.
+    fn declaration_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for type definition capabilities.
+    fn type_definition_dynamic_registration(&self) -> bool;
+
     /// The client supports dynamic registration for code action capabilities.
     fn code_action_dynamic_registration(&self) -> bool;
+
+    /// The client supports resolving edits of code actions with `codeAction/resolve` requests.
+    fn code_action_resolve_edit_support(&self) -> bool;
+
+    /// The client supports dynamic registration for inlay hint capabilities.
+    fn inlay_hint_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for workspace symbol capabilities.
+    fn workspace_symbol_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for references capabilities.
+    fn references_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for rename capabilities.
+    fn rename_dynamic_registration(&self) -> bool;
+
+    /// The client supports workspace edits with document changes, which rename files.
+    fn workspace_edit_rename_file_support(&self) -> bool;
+
+    /// The client supports dynamic registration for signature help capabilities.
+    fn signature_help_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for code lens capabilities.
+    fn code_lens_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for document link capabilities.
+    fn document_link_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for folding range capabilities.
+    fn folding_range_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for document highlight capabilities.
+    fn document_highlight_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for selection range capabilities.
+    fn selection_range_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for call hierarchy capabilities.
+    fn call_hierarchy_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for linked editing range capabilities.
+    fn linked_editing_range_dynamic_registration(&self) -> bool;
+
+    /// The client supports dynamic registration for type hierarchy capabilities.
+    fn type_hierarchy_dynamic_registration(&self) -> bool;
+
+    /// The client supports pulling diagnostics with `textDocument/diagnostic` requests.
+    ///
+    /// This does not mean that the client also pulls diagnostics with `workspace/diagnostic`
+    /// requests.
+    fn pull_diagnostics_support(&self) -> bool;
+
+    /// The client supports the `workspace/diagnostic/refresh` request, which asks it to pull
+    /// diagnostics of the whole workspace anew.
+    fn workspace_diagnostic_refresh_support(&self) -> bool;
+
+    /// The client supports dynamic registration for pull diagnostics capabilities.
+    fn pull_diagnostics_dynamic_registration(&self) -> bool;
+
+    /// Encodings of columns of positions supported by the client, in the order of preference.
+    fn position_encodings(&self) -> Vec<PositionEncodingKind>;
+
+    /// The client supports server-initiated progress with `window/workDoneProgress/create`.
+    fn work_done_progress_support(&self) -> bool;
 }
 
 impl ClientCapabilitiesExt for ClientCapabilities {
@@ -63,6 +149,34 @@ impl ClientCapabilitiesExt for ClientCapabilities {
         try_or_default!(self.text_document.as_ref()?.completion.as_ref()?.dynamic_registration?)
     }
 
+    fn completion_snippet_support(&self) -> bool {
+        try_or_default!(
+            self.text_document
+                .as_ref()?
+                .completion
+                .as_ref()?
+                .completion_item
+                .as_ref()?
+                .snippet_support?
+        )
+    }
+
+    fn completion_resolve_additional_text_edits_support(&self) -> bool {
+        try_or_default!(
+            self.text_document
+                .as_ref()?
+                .completion
+                .as_ref()?
+                .completion_item
+                .as_ref()?
+                .resolve_support
+                .as_ref()?
+                .properties
+                .iter()
+                .any(|property| property == "additionalTextEdits")
+        )
+    }
+
     fn execute_command_dynamic_registration(&self) -> bool {
         try_or_default!(self.workspace.as_ref()?.execute_command.as_ref()?.dynamic_registration?)
     }
@@ -77,15 +191,152 @@ impl ClientCapabilitiesExt for ClientCapabilities {
         try_or_default!(self.text_document.as_ref()?.formatting.as_ref()?.dynamic_registration?)
     }
 
+    fn range_formatting_dynamic_registration(&self) -> bool {
+        try_or_default!(
+            self.text_document.as_ref()?.range_formatting.as_ref()?.dynamic_registration?
+        )
+    }
+
+    fn on_type_formatting_dynamic_registration(&self) -> bool {
+        try_or_default!(
+            self.text_document.as_ref()?.on_type_formatting.as_ref()?.dynamic_registration?
+        )
+    }
+
     fn hover_dynamic_registration(&self) -> bool {
         try_or_default!(self.text_document.as_ref()?.hover.as_ref()?.dynamic_registration?)
     }
 
+    fn hover_markdown_support(&self) -> bool {
+        try_or_default!(
+            self.text_document
+                .as_ref()?
+                .hover
+                .as_ref()?
+                .content_format
+                .as_ref()?
+                .contains(&MarkupKind::Markdown)
+        )
+    }
+
     fn definition_dynamic_registration(&self) -> bool {
         try_or_default!(self.text_document.as_ref()?.definition.as_ref()?.dynamic_registration?)
     }
 
+    fn declaration_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.declaration.as_ref()?.dynamic_registration?)
+    }
+
+    fn type_definition_dynamic_registration(&self) -> bool {
+        try_or_default!(
+            self.text_document.as_ref()?.type_definition.as_ref()?.dynamic_registration?
+        )
+    }
+
     fn code_action_dynamic_registration(&self) -> bool {
         try_or_default!(self.text_document.as_ref()?.code_action.as_ref()?.dynamic_registration?)
     }
+
+    fn code_action_resolve_edit_support(&self) -> bool {
+        try_or_default!(
+            self.text_document
+                .as_ref()?
+                .code_action
+                .as_ref()?
+                .resolve_support
+                .as_ref()?
+                .properties
+                .iter()
+                .any(|property| property == "edit")
+        )
+    }
+
+    fn inlay_hint_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.inlay_hint.as_ref()?.dynamic_registration?)
+    }
+
+    fn workspace_symbol_dynamic_registration(&self) -> bool {
+        try_or_default!(self.workspace.as_ref()?.symbol.as_ref()?.dynamic_registration?)
+    }
+
+    fn references_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.references.as_ref()?.dynamic_registration?)
+    }
+
+    fn rename_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.rename.as_ref()?.dynamic_registration?)
+    }
+
+    fn workspace_edit_rename_file_support(&self) -> bool {
+        try_or_default!({
+            let workspace_edit = self.workspace.as_ref()?.workspace_edit.as_ref()?;
+            workspace_edit.document_changes?
+                && workspace_edit
+                    .resource_operations
+                    .as_ref()?
+                    .contains(&ResourceOperationKind::Rename)
+        })
+    }
+
+    fn signature_help_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.signature_help.as_ref()?.dynamic_registration?)
+    }
+
+    fn code_lens_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.code_lens.as_ref()?.dynamic_registration?)
+    }
+
+    fn document_link_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.document_link.as_ref()?.dynamic_registration?)
+    }
+
+    fn folding_range_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.folding_range.as_ref()?.dynamic_registration?)
+    }
+
+    fn document_highlight_dynamic_registration(&self) -> bool {
+        try_or_default!(
+            self.text_document.as_ref()?.document_highlight.as_ref()?.dynamic_registration?
+        )
+    }
+
+    fn selection_range_dynamic_registration(&self) -> bool {
+        try_or_default!(
+            self.text_document.as_ref()?.selection_range.as_ref()?.dynamic_registration?
+        )
+    }
+
+    fn call_hierarchy_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.call_hierarchy.as_ref()?.dynamic_registration?)
+    }
+
+    fn linked_editing_range_dynamic_registration(&self) -> bool {
+        try_or_default!(
+            self.text_document.as_ref()?.linked_editing_range.as_ref()?.dynamic_registration?
+        )
+    }
+
+    fn type_hierarchy_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.type_hierarchy.as_ref()?.dynamic_registration?)
+    }
+
+    fn pull_diagnostics_support(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.diagnostic.is_some())
+    }
+
+    fn workspace_diagnostic_refresh_support(&self) -> bool {
+        try_or_default!(self.workspace.as_ref()?.diagnostic.as_ref()?.refresh_support?)
+    }
+
+    fn pull_diagnostics_dynamic_registration(&self) -> bool {
+        try_or_default!(self.text_document.as_ref()?.diagnostic.as_ref()?.dynamic_registration?)
+    }
+
+    fn position_encodings(&self) -> Vec<PositionEncodingKind> {
+        try_or_default!(self.general.as_ref()?.position_encodings.clone()?)
+    }
+
+    fn work_done_progress_support(&self) -> bool {
+        try_or_default!(self.window.as_ref()?.work_done_progress?)
+    }
 }
@@ -14,34 +14,58 @@
 use std::ops::Not;
 
 use lsp_types::{
-    ClientCapabilities, CodeActionProviderCapability, CompletionOptions,
-    CompletionRegistrationOptions, DefinitionOptions, DidChangeWatchedFilesRegistrationOptions,
-    DocumentFilter, ExecuteCommandOptions, ExecuteCommandRegistrationOptions, FileSystemWatcher,
-    GlobPattern, HoverProviderCapability, HoverRegistrationOptions, OneOf, Registration,
-    SaveOptions, SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
-    SemanticTokensRegistrationOptions, ServerCapabilities, TextDocumentChangeRegistrationOptions,
-    TextDocumentRegistrationOptions, TextDocumentSaveRegistrationOptions,
-    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
-    TextDocumentSyncSaveOptions,
+    CallHierarchyOptions, CallHierarchyServerCapability, ClientCapabilities, CodeActionOptions,
+    CodeActionProviderCapability, CodeLensOptions, CompletionOptions,
+    CompletionRegistrationOptions, DeclarationCapability, DeclarationOptions,
+    DeclarationRegistrationOptions, DefinitionOptions, DiagnosticOptions,
+    DiagnosticRegistrationOptions, DiagnosticServerCapabilities,
+    DidChangeWatchedFilesRegistrationOptions, DocumentFilter, DocumentHighlightOptions,
+    DocumentLinkOptions, DocumentOnTypeFormattingOptions,
+    DocumentOnTypeFormattingRegistrationOptions, DocumentRangeFormattingOptions,
+    ExecuteCommandOptions, ExecuteCommandRegistrationOptions, FileSystemWatcher,
+    FoldingProviderOptions, FoldingRangeProviderCapability, GlobPattern, HoverProviderCapability,
+    HoverRegistrationOptions, InlayHintRegistrationOptions, LinkedEditingRangeOptions,
+    LinkedEditingRangeRegistrationOptions, LinkedEditingRangeServerCapabilities, OneOf,
+    ReferencesOptions, Registration, RenameOptions, SaveOptions, SelectionRangeOptions,
+    SelectionRangeProviderCapability, SelectionRangeRegistrationOptions, SemanticTokensFullOptions,
+    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensRegistrationOptions,
+    ServerCapabilities, SignatureHelpOptions, StaticTextDocumentRegistrationOptions,
+    TextDocumentChangeRegistrationOptions, TextDocumentRegistrationOptions,
+    TextDocumentSaveRegistrationOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, TextDocumentSyncSaveOptions, TypeDefinitionProviderCapability,
+    TypeHierarchyOptions, TypeHierarchyRegistrationOptions, WorkspaceFoldersServerCapabilities,
+    WorkspaceServerCapabilities, WorkspaceSymbolOptions,
 };
 use missing_lsp_types::{
-    CodeActionRegistrationOptions, DefinitionRegistrationOptions,
-    DocumentFormattingRegistrationOptions,
+    CallHierarchyRegistrationOptions, CodeActionRegistrationOptions, CodeLensRegistrationOptions,
+    DefinitionRegistrationOptions, DocumentFormattingRegistrationOptions,
+    DocumentHighlightRegistrationOptions, DocumentLinkRegistrationOptions,
+    DocumentRangeFormattingRegistrationOptions, FoldingRangeRegistrationOptions,
+    ReferenceRegistrationOptions, RenameRegistrationOptions, SignatureHelpRegistrationOptions,
 };
 use serde::Serialize;
 
-use crate::ide::semantic_highlighting::SemanticTokenKind;
+use crate::Tricks;
+use crate::ide::formatter::ON_TYPE_FORMATTING_TRIGGER_CHARACTERS;
+use crate::ide::macros::expand::EXPANSION_SCHEME;
+use crate::ide::semantic_highlighting::{SemanticTokenKind, SemanticTokenModifierKind};
+use crate::lang::lsp::PositionEncoding;
 use crate::lsp::capabilities::client::ClientCapabilitiesExt;
+use crate::server::commands::ServerCommands;
 
 /// Returns capabilities the server wants to register statically.
-pub fn collect_server_capabilities(client_capabilities: &ClientCapabilities) -> ServerCapabilities {
+pub fn collect_server_capabilities(
+    client_capabilities: &ClientCapabilities,
+    tricks: &Tricks,
+) -> ServerCapabilities {
     ServerCapabilities {
+        position_encoding: Some(PositionEncoding::negotiate(client_capabilities).kind()),
         text_document_sync: client_capabilities
             .text_document_synchronization_dynamic_registration()
             .not()
             .then_some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
                 open_close: Some(true),
-                change: Some(TextDocumentSyncKind::FULL),
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
                 will_save: Some(false),
                 will_save_wait_until: Some(false),
                 save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
@@ -50,7 +74,7 @@ pub fn collect_server_capabilities(client_capabilities: &ClientCapabilities) ->
             })),
         completion_provider: client_capabilities.completion_dynamic_registration().not().then(
             || CompletionOptions {
-                resolve_provider: Some(false),
+                resolve_provider: Some(true),
                 trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
                 all_commit_characters: None,
                 work_done_progress_options: Default::default(),
@@ -61,7 +85,7 @@ pub fn collect_server_capabilities(client_capabilities: &ClientCapabilities) ->
             .execute_command_dynamic_registration()
             .not()
             .then(|| ExecuteCommandOptions {
-                commands: vec!["cairo.reload".to_string()],
+                commands: ServerCommands::ALL.map(String::from).to_vec(),
                 work_done_progress_options: Default::default(),
             }),
         semantic_tokens_provider: client_capabilities
@@ -69,11 +93,9 @@ pub fn collect_server_capabilities(client_capabilities: &ClientCapabilities) ->
             .not()
             .then(|| {
                 SemanticTokensOptions {
-                    legend: SemanticTokensLegend {
-                        token_types: SemanticTokenKind::legend(),
-                        token_modifiers: vec![],
-                    },
-                    full: Some(SemanticTokensFullOptions::Bool(true)),
+                    legend: semantic_tokens_legend(tricks),
+                    range: Some(true),
+                    full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
                     ..SemanticTokensOptions::default()
                 }
                 .into()
@@ -82,6 +104,14 @@ pub fn collect_server_capabilities(client_capabilities: &ClientCapabilities) ->
             .formatting_dynamic_registration()
             .not()
             .then_some(OneOf::Left(true)),
+        document_range_formatting_provider: client_capabilities
+            .range_formatting_dynamic_registration()
+            .not()
+            .then_some(OneOf::Left(true)),
+        document_on_type_formatting_provider: client_capabilities
+            .on_type_formatting_dynamic_registration()
+            .not()
+            .then(on_type_formatting_options),
         hover_provider: client_capabilities
             .hover_dynamic_registration()
             .not()
@@ -90,10 +120,80 @@ pub fn collect_server_capabilities(client_capabilities: &ClientCapabilities) ->
             .definition_dynamic_registration()
             .not()
             .then_some(OneOf::Left(true)),
+        declaration_provider: client_capabilities
+            .declaration_dynamic_registration()
+            .not()
+            .then_some(DeclarationCapability::Simple(true)),
+        type_definition_provider: client_capabilities
+            .type_definition_dynamic_registration()
+            .not()
+            .then_some(TypeDefinitionProviderCapability::Simple(true)),
         code_action_provider: client_capabilities
             .code_action_dynamic_registration()
             .not()
-            .then_some(CodeActionProviderCapability::Simple(true)),
+            .then(code_action_options)
+            .map(CodeActionProviderCapability::Options),
+        inlay_hint_provider: client_capabilities
+            .inlay_hint_dynamic_registration()
+            .not()
+            .then_some(OneOf::Left(true)),
+        workspace_symbol_provider: client_capabilities
+            .workspace_symbol_dynamic_registration()
+            .not()
+            .then_some(OneOf::Left(true)),
+        references_provider: client_capabilities
+            .references_dynamic_registration()
+            .not()
+            .then_some(OneOf::Left(true)),
+        rename_provider: client_capabilities.rename_dynamic_registration().not().then(|| {
+            OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: Default::default(),
+            })
+        }),
+        signature_help_provider: client_capabilities
+            .signature_help_dynamic_registration()
+            .not()
+            .then(signature_help_options),
+        code_lens_provider: client_capabilities
+            .code_lens_dynamic_registration()
+            .not()
+            .then_some(CodeLensOptions { resolve_provider: Some(false) }),
+        document_link_provider: client_capabilities
+            .document_link_dynamic_registration()
+            .not()
+            .then(document_link_options),
+        folding_range_provider: client_capabilities
+            .folding_range_dynamic_registration()
+            .not()
+            .then_some(FoldingRangeProviderCapability::Simple(true)),
+        document_highlight_provider: client_capabilities
+            .document_highlight_dynamic_registration()
+            .not()
+            .then_some(OneOf::Left(true)),
+        selection_range_provider: client_capabilities
+            .selection_range_dynamic_registration()
+            .not()
+            .then_some(SelectionRangeProviderCapability::Simple(true)),
+        call_hierarchy_provider: client_capabilities
+            .call_hierarchy_dynamic_registration()
+            .not()
+            .then_some(CallHierarchyServerCapability::Simple(true)),
+        linked_editing_range_provider: client_capabilities
+            .linked_editing_range_dynamic_registration()
+            .not()
+            .then_some(LinkedEditingRangeServerCapabilities::Simple(true)),
+        diagnostic_provider: (client_capabilities.pull_diagnostics_support()
+            && !client_capabilities.pull_diagnostics_dynamic_registration())
+        .then(diagnostic_options)
+        .map(DiagnosticServerCapabilities::Options),
+        workspace: Some(WorkspaceServerCapabilities {
+            workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(OneOf::Left(true)),
+            }),
+            file_operations: None,
+        }),
         ..ServerCapabilities::default()
     }
 }
@@ -101,6 +201,7 @@ pub fn collect_server_capabilities(client_capabilities: &ClientCapabilities) ->
 /// Returns registrations of capabilities the server wants to register dynamically.
 pub fn collect_dynamic_registrations(
     client_capabilities: &ClientCapabilities,
+    tricks: &Tricks,
 ) -> Vec<Registration> {
     let mut registrations = vec![];
 
@@ -116,6 +217,11 @@ pub fn collect_dynamic_registrations(
             scheme: Some("vfs".to_string()),
             pattern: None,
         },
+        DocumentFilter {
+            language: Some("cairo".to_string()),
+            scheme: Some(EXPANSION_SCHEME.to_string()),
+            pattern: None,
+        },
     ]);
     let text_document_registration_options =
         TextDocumentRegistrationOptions { document_selector: document_selector.clone() };
@@ -144,7 +250,7 @@ pub fn collect_dynamic_registrations(
             "textDocument/didChange",
             TextDocumentChangeRegistrationOptions {
                 document_selector,
-                sync_kind: 1, // TextDocumentSyncKind::FULL
+                sync_kind: 2, // TextDocumentSyncKind::INCREMENTAL
             },
         ));
 
@@ -166,7 +272,7 @@ pub fn collect_dynamic_registrations(
         let registration_options = CompletionRegistrationOptions {
             text_document_registration_options: text_document_registration_options.clone(),
             completion_options: CompletionOptions {
-                resolve_provider: Some(false),
+                resolve_provider: Some(true),
                 trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
                 all_commit_characters: None,
                 work_done_progress_options: Default::default(),
@@ -179,9 +285,9 @@ pub fn collect_dynamic_registrations(
 
     if client_capabilities.execute_command_dynamic_registration() {
         let registration_options = ExecuteCommandRegistrationOptions {
-            commands: vec!["cairo.reload".to_string()],
+            commands: ServerCommands::ALL.map(String::from).to_vec(),
             execute_command_options: ExecuteCommandOptions {
-                commands: vec!["cairo.reload".to_string()],
+                commands: ServerCommands::ALL.map(String::from).to_vec(),
                 work_done_progress_options: Default::default(),
             },
         };
@@ -193,11 +299,9 @@ pub fn collect_dynamic_registrations(
         let registration_options = SemanticTokensRegistrationOptions {
             text_document_registration_options: text_document_registration_options.clone(),
             semantic_tokens_options: SemanticTokensOptions {
-                legend: SemanticTokensLegend {
-                    token_types: SemanticTokenKind::legend(),
-                    token_modifiers: vec![],
-                },
-                full: Some(SemanticTokensFullOptions::Bool(true)),
+                legend: semantic_tokens_legend(tricks),
+                range: Some(true),
+                full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
                 ..SemanticTokensOptions::default()
             },
             static_registration_options: Default::default(),
@@ -216,6 +320,31 @@ pub fn collect_dynamic_registrations(
         registrations.push(create_registration("textDocument/formatting", registration_options));
     }
 
+    if client_capabilities.range_formatting_dynamic_registration() {
+        let registration_options = DocumentRangeFormattingRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            document_range_formatting_options: DocumentRangeFormattingOptions {
+                work_done_progress_options: Default::default(),
+            },
+        };
+
+        registrations
+            .push(create_registration("textDocument/rangeFormatting", registration_options));
+    }
+
+    if client_capabilities.on_type_formatting_dynamic_registration() {
+        let DocumentOnTypeFormattingOptions { first_trigger_character, more_trigger_character } =
+            on_type_formatting_options();
+        let registration_options = DocumentOnTypeFormattingRegistrationOptions {
+            document_selector: text_document_registration_options.document_selector.clone(),
+            first_trigger_character,
+            more_trigger_character,
+        };
+
+        registrations
+            .push(create_registration("textDocument/onTypeFormatting", registration_options));
+    }
+
     if client_capabilities.hover_dynamic_registration() {
         let registration_options = HoverRegistrationOptions {
             text_document_registration_options: text_document_registration_options.clone(),
@@ -236,18 +365,246 @@ pub fn collect_dynamic_registrations(
         registrations.push(create_registration("textDocument/definition", registration_options));
     }
 
+    if client_capabilities.declaration_dynamic_registration() {
+        let registration_options = DeclarationRegistrationOptions {
+            declaration_options: DeclarationOptions {
+                work_done_progress_options: Default::default(),
+            },
+            text_document_registration_options: text_document_registration_options.clone(),
+            static_registration_options: Default::default(),
+        };
+
+        registrations.push(create_registration("textDocument/declaration", registration_options));
+    }
+
+    if client_capabilities.type_definition_dynamic_registration() {
+        let registration_options = StaticTextDocumentRegistrationOptions {
+            document_selector: text_document_registration_options.document_selector.clone(),
+            id: None,
+        };
+
+        registrations
+            .push(create_registration("textDocument/typeDefinition", registration_options));
+    }
+
     if client_capabilities.code_action_dynamic_registration() {
         let registration_options = CodeActionRegistrationOptions {
-            text_document_registration_options,
-            code_action_options: Default::default(),
+            text_document_registration_options: text_document_registration_options.clone(),
+            code_action_options: code_action_options(),
         };
 
         registrations.push(create_registration("textDocument/codeAction", registration_options));
     }
 
+    if client_capabilities.inlay_hint_dynamic_registration() {
+        let registration_options = InlayHintRegistrationOptions {
+            inlay_hint_options: Default::default(),
+            text_document_registration_options: text_document_registration_options.clone(),
+            static_registration_options: Default::default(),
+        };
+
+        registrations.push(create_registration("textDocument/inlayHint", registration_options));
+    }
+
+    if client_capabilities.workspace_symbol_dynamic_registration() {
+        let registration_options = WorkspaceSymbolOptions {
+            work_done_progress_options: Default::default(),
+            resolve_provider: None,
+        };
+
+        registrations.push(create_registration("workspace/symbol", registration_options));
+    }
+
+    if client_capabilities.references_dynamic_registration() {
+        let registration_options = ReferenceRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            references_options: ReferencesOptions {
+                work_done_progress_options: Default::default(),
+            },
+        };
+
+        registrations.push(create_registration("textDocument/references", registration_options));
+    }
+
+    if client_capabilities.rename_dynamic_registration() {
+        let registration_options = RenameRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            rename_options: RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: Default::default(),
+            },
+        };
+
+        registrations.push(create_registration("textDocument/rename", registration_options));
+    }
+
+    if client_capabilities.signature_help_dynamic_registration() {
+        let registration_options = SignatureHelpRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            signature_help_options: signature_help_options(),
+        };
+
+        registrations.push(create_registration("textDocument/signatureHelp", registration_options));
+    }
+
+    if client_capabilities.code_lens_dynamic_registration() {
+        let registration_options = CodeLensRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            code_lens_options: CodeLensOptions { resolve_provider: Some(false) },
+        };
+
+        registrations.push(create_registration("textDocument/codeLens", registration_options));
+    }
+
+    if client_capabilities.document_link_dynamic_registration() {
+        let registration_options = DocumentLinkRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            document_link_options: document_link_options(),
+        };
+
+        registrations.push(create_registration("textDocument/documentLink", registration_options));
+    }
+
+    if client_capabilities.folding_range_dynamic_registration() {
+        let registration_options = FoldingRangeRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            folding_range_options: FoldingProviderOptions {},
+        };
+
+        registrations.push(create_registration("textDocument/foldingRange", registration_options));
+    }
+
+    if client_capabilities.document_highlight_dynamic_registration() {
+        let registration_options = DocumentHighlightRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            document_highlight_options: DocumentHighlightOptions {
+                work_done_progress_options: Default::default(),
+            },
+        };
+
+        registrations
+            .push(create_registration("textDocument/documentHighlight", registration_options));
+    }
+
+    if client_capabilities.selection_range_dynamic_registration() {
+        let registration_options = SelectionRangeRegistrationOptions {
+            selection_range_options: SelectionRangeOptions {
+                work_done_progress_options: Default::default(),
+            },
+            registration_options: StaticTextDocumentRegistrationOptions {
+                document_selector: text_document_registration_options.document_selector.clone(),
+                id: None,
+            },
+        };
+
+        registrations
+            .push(create_registration("textDocument/selectionRange", registration_options));
+    }
+
+    if client_capabilities.call_hierarchy_dynamic_registration() {
+        let registration_options = CallHierarchyRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            call_hierarchy_options: CallHierarchyOptions {
+                work_done_progress_options: Default::default(),
+            },
+        };
+
+        registrations
+            .push(create_registration("textDocument/prepareCallHierarchy", registration_options));
+    }
+
+    if client_capabilities.linked_editing_range_dynamic_registration() {
+        let registration_options = LinkedEditingRangeRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            linked_editing_range_options: LinkedEditingRangeOptions {
+                work_done_progress_options: Default::default(),
+            },
+            static_registration_options: Default::default(),
+        };
+
+        registrations
+            .push(create_registration("textDocument/linkedEditingRange", registration_options));
+    }
+
+    if client_capabilities.pull_diagnostics_dynamic_registration() {
+        let registration_options = DiagnosticRegistrationOptions {
+            text_document_registration_options: text_document_registration_options.clone(),
+            diagnostic_options: diagnostic_options(),
+            static_registration_options: Default::default(),
+        };
+
+        registrations.push(create_registration("textDocument/diagnostic", registration_options));
+    }
+
+    // `ServerCapabilities` has no field for the type hierarchy provider, so it can only be
+    // registered dynamically.
+    if client_capabilities.type_hierarchy_dynamic_registration() {
+        let registration_options = TypeHierarchyRegistrationOptions {
+            text_document_registration_options,
+            type_hierarchy_options: TypeHierarchyOptions {
+                work_done_progress_options: Default::default(),
+            },
+            static_registration_options: Default::default(),
+        };
+
+        registrations
+            .push(create_registration("textDocument/prepareTypeHierarchy", registration_options));
+    }
+
     registrations
 }
 
+fn signature_help_options() -> SignatureHelpOptions {
+    SignatureHelpOptions {
+        trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+        retrigger_characters: None,
+        work_done_progress_options: Default::default(),
+    }
+}
+
+fn document_link_options() -> DocumentLinkOptions {
+    DocumentLinkOptions {
+        resolve_provider: Some(false),
+        work_done_progress_options: Default::default(),
+    }
+}
+
+fn code_action_options() -> CodeActionOptions {
+    CodeActionOptions {
+        code_action_kinds: None,
+        work_done_progress_options: Default::default(),
+        resolve_provider: Some(true),
+    }
+}
+
+fn diagnostic_options() -> DiagnosticOptions {
+    DiagnosticOptions {
+        identifier: None,
+        // Diagnostics of a file depend on the items it uses from other files.
+        inter_file_dependencies: true,
+        workspace_diagnostics: true,
+        work_done_progress_options: Default::default(),
+    }
+}
+
+/// Returns the legend of semantic tokens, extended with token types contributed by tricks.
+fn semantic_tokens_legend(tricks: &Tricks) -> SemanticTokensLegend {
+    let mut token_types = SemanticTokenKind::legend();
+    if let Some(extension) = &tricks.semantic_tokens_extension {
+        token_types.extend(extension.token_types.iter().cloned());
+    }
+    SemanticTokensLegend { token_types, token_modifiers: SemanticTokenModifierKind::legend() }
+}
+
+fn on_type_formatting_options() -> DocumentOnTypeFormattingOptions {
+    let [first_trigger_character, more_trigger_character @ ..] =
+        ON_TYPE_FORMATTING_TRIGGER_CHARACTERS.map(String::from);
+    DocumentOnTypeFormattingOptions {
+        first_trigger_character,
+        more_trigger_character: Some(more_trigger_character.to_vec()),
+    }
+}
+
 fn create_registration(method: &str, registration_options: impl Serialize) -> Registration {
     Registration {
         id: method.to_string(),
@@ -258,8 +615,10 @@ fn create_registration(method: &str, registration_options: impl Serialize) -> Re
 
 mod missing_lsp_types {
     use lsp_types::{
-        CodeActionOptions, DefinitionOptions, DocumentFormattingOptions,
-        TextDocumentRegistrationOptions,
+        CallHierarchyOptions, CodeActionOptions, CodeLensOptions, DefinitionOptions,
+        DocumentFormattingOptions, DocumentHighlightOptions, DocumentLinkOptions,
+        DocumentRangeFormattingOptions, FoldingProviderOptions, ReferencesOptions, RenameOptions,
+        SignatureHelpOptions, TextDocumentRegistrationOptions,
     };
     use serde::{Deserialize, Serialize};
 
@@ -273,6 +632,16 @@ mod missing_lsp_types {
         pub document_formatting_options: DocumentFormattingOptions,
     }
 
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentRangeFormattingRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub document_range_formatting_options: DocumentRangeFormattingOptions,
+    }
+
     #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct DefinitionRegistrationOptions {
@@ -292,4 +661,84 @@ mod missing_lsp_types {
         #[serde(flatten)]
         pub code_action_options: CodeActionOptions,
     }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ReferenceRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub references_options: ReferencesOptions,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RenameRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub rename_options: RenameOptions,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SignatureHelpRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub signature_help_options: SignatureHelpOptions,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CodeLensRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub code_lens_options: CodeLensOptions,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentLinkRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub document_link_options: DocumentLinkOptions,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct FoldingRangeRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub folding_range_options: FoldingProviderOptions,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentHighlightRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub document_highlight_options: DocumentHighlightOptions,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CallHierarchyRegistrationOptions {
+        #[serde(flatten)]
+        pub text_document_registration_options: TextDocumentRegistrationOptions,
+
+        #[serde(flatten)]
+        pub call_hierarchy_options: CallHierarchyOptions,
+    }
 }
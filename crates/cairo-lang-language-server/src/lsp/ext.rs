@@ -1,20 +1,27 @@
 //! CairoLS extensions to the Language Server Protocol.
 
+use std::path::PathBuf;
+
+use cairo_lang_filesystem::db::Edition;
 use lsp_types::notification::Notification;
 use lsp_types::request::Request;
-use lsp_types::{TextDocumentPositionParams, Url};
+use lsp_types::{Range, TextDocumentPositionParams, Url};
 use serde::{Deserialize, Serialize};
 
 /// Provides content of virtual file from the database.
-pub(crate) struct ProvideVirtualFile;
+///
+/// If a range is given, only the part of the content within it is provided.
+pub struct ProvideVirtualFile;
 
 #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
-pub(crate) struct ProvideVirtualFileRequest {
+pub struct ProvideVirtualFileRequest {
     pub uri: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
-pub(crate) struct ProvideVirtualFileResponse {
+pub struct ProvideVirtualFileResponse {
     pub content: Option<String>,
 }
 
@@ -33,29 +40,242 @@ impl Request for ViewAnalyzedCrates {
     const METHOD: &'static str = "cairo/viewAnalyzedCrates";
 }
 
-/// Provides string with code after macros expansion.
+/// Collects machine-readable information about all Cairo crates that are currently being analyzed.
+///
+/// This is a structured counterpart of [`ViewAnalyzedCrates`].
+pub struct AnalyzedCrates;
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzedCratesResponse {
+    /// Analyzed crates, sorted by name.
+    pub crates: Vec<AnalyzedCrate>,
+    /// Names of inline macros available in all crates.
+    pub inline_macros: Vec<String>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzedCrate {
+    pub name: String,
+    /// URLs of main files of the crate, usually just its `lib.cairo`.
+    pub source_file_urls: Vec<Url>,
+    pub edition: Edition,
+    pub version: Option<String>,
+    /// Names of crates the crate depends on, sorted.
+    pub dependencies: Vec<String>,
+}
+
+impl Request for AnalyzedCrates {
+    type Params = ();
+    type Result = AnalyzedCratesResponse;
+    const METHOD: &'static str = "cairo/analyzedCrates";
+}
+
+/// Generates a Graphviz DOT graph of all Cairo crates that are currently being analyzed and
+/// dependencies between them.
+pub struct ViewCrateGraph;
+
+impl Request for ViewCrateGraph {
+    type Params = ();
+    type Result = String;
+    const METHOD: &'static str = "cairo/viewCrateGraph";
+}
+
+/// Collects information about the amount of data stored in the analysis database.
+pub struct ViewMemoryUsage;
+
+impl Request for ViewMemoryUsage {
+    type Params = ();
+    type Result = String;
+    const METHOD: &'static str = "cairo/viewMemoryUsage";
+}
+
+/// Provides string with code after macros expansion, along with mappings of the expanded code to
+/// the code it was generated from.
 pub struct ExpandMacro;
 
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandMacroResponse {
+    pub expansion: String,
+    /// Mappings of ranges of the expansion to ranges of the document it was generated from.
+    ///
+    /// Parts of the expansion which cannot be traced back to the document are not mapped.
+    pub code_mappings: Vec<ExpansionCodeMapping>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpansionCodeMapping {
+    /// Range in the document.
+    pub origin: Range,
+    /// Range in the expansion.
+    pub generated: Range,
+}
+
 impl Request for ExpandMacro {
     type Params = TextDocumentPositionParams;
-    type Result = Option<String>;
+    type Result = Option<ExpandMacroResponse>;
     const METHOD: &'static str = "cairo/expandMacro";
 }
 
+/// Provides the URI of a virtual document holding code after macros expansion.
+///
+/// The document can be read with [`ProvideVirtualFile`].
+pub struct ExpandMacroDocument;
+
+impl Request for ExpandMacroDocument {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<Url>;
+    const METHOD: &'static str = "cairo/expandMacroDocument";
+}
+
+/// Provides a textual dump of the syntax tree of the file, with the node at the position marked.
+pub struct ShowSyntaxTree;
+
+impl Request for ShowSyntaxTree {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<String>;
+    const METHOD: &'static str = "cairo/showSyntaxTree";
+}
+
 /// Notifies about corelib version mismatch.
+///
+/// The client may offer to run the `cairo.scarbUpdate` command for the workspace to fix it.
 #[derive(Debug)]
 pub struct CorelibVersionMismatch;
 
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorelibVersionMismatchParams {
+    /// Human-readable description of the mismatch.
+    pub message: String,
+    /// Path to the `Scarb.toml` of the workspace which uses the mismatched corelib.
+    pub manifest_path: PathBuf,
+    /// Version of the corelib expected by the language server.
+    pub expected: String,
+    /// Version of the corelib used by the workspace.
+    pub found: String,
+}
+
 impl Notification for CorelibVersionMismatch {
-    type Params = String;
+    type Params = CorelibVersionMismatchParams;
     const METHOD: &'static str = "cairo/corelib-version-mismatch";
 }
 
 /// Notifies about scarb metadata failure.
+///
+/// Crates of the Scarb workspace are not known after such a failure, so only the corelib is set
+/// up and analysis of the workspace is degraded.
 #[derive(Debug)]
 pub struct ScarbMetadataFailed;
 
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScarbMetadataFailedParams {
+    /// Path to the `Scarb.toml` of the workspace.
+    pub manifest_path: PathBuf,
+    /// Description of the failure.
+    pub error: String,
+}
+
 impl Notification for ScarbMetadataFailed {
-    type Params = ();
+    type Params = ScarbMetadataFailedParams;
     const METHOD: &'static str = "cairo/scarb-metadata-failed";
 }
+
+/// Notifies that the set of analyzed crates has changed after a reload.
+#[derive(Debug)]
+pub struct AnalyzedCratesChanged;
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzedCratesChangedParams {
+    /// Names of crates that are now analyzed, but were not before the reload.
+    pub added: Vec<String>,
+    /// Names of crates that are no longer analyzed.
+    pub removed: Vec<String>,
+    /// Names of crates whose root directory has changed.
+    pub changed: Vec<String>,
+}
+
+impl Notification for AnalyzedCratesChanged {
+    type Params = AnalyzedCratesChangedParams;
+    const METHOD: &'static str = "cairo/analyzed-crates-changed";
+}
+
+/// Notifies that a file exceeds the configured size limit and expensive analysis is skipped for it.
+#[derive(Debug)]
+pub struct FileTooLarge;
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTooLargeParams {
+    pub uri: Url,
+    pub size_kb: u64,
+    pub max_file_size_kb: u64,
+}
+
+impl Notification for FileTooLarge {
+    type Params = FileTooLargeParams;
+    const METHOD: &'static str = "cairo/file-too-large";
+}
+
+/// Notifies that exporting of the expanded crate, requested with the `cairo.exportExpandedCrate`
+/// command, has finished.
+#[derive(Debug)]
+pub struct ExpandedCrateExported;
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandedCrateExportedParams {
+    pub crate_name: String,
+    pub output: PathBuf,
+    /// Description of the failure, if the export did not succeed.
+    pub error: Option<String>,
+}
+
+impl Notification for ExpandedCrateExported {
+    type Params = ExpandedCrateExportedParams;
+    const METHOD: &'static str = "cairo/expanded-crate-exported";
+}
+
+/// Notifies that running of a test, requested with the `cairo.runTest` command, has finished.
+#[derive(Debug)]
+pub struct TestRunFinished;
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestRunFinishedParams {
+    pub test_path: String,
+    /// Whether the test has been run and passed.
+    pub success: bool,
+    /// Output of `scarb test`, or description of the failure if it could not be run.
+    pub output: String,
+}
+
+impl Notification for TestRunFinished {
+    type Params = TestRunFinishedParams;
+    const METHOD: &'static str = "cairo/test-run-finished";
+}
+
+/// Notifies that updating of a Scarb workspace, requested with the `cairo.scarbUpdate` command,
+/// has finished.
+#[derive(Debug)]
+pub struct ScarbUpdateFinished;
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScarbUpdateFinishedParams {
+    pub manifest_path: PathBuf,
+    /// Whether `scarb update` has been run and succeeded.
+    pub success: bool,
+    /// Output of `scarb update`, or description of the failure if it could not be run.
+    pub output: String,
+}
+
+impl Notification for ScarbUpdateFinished {
+    type Params = ScarbUpdateFinishedParams;
+    const METHOD: &'static str = "cairo/scarb-update-finished";
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::iter;
+
+use cairo_lang_compiler::project::{
+    get_main_crate_ids_from_project, update_crate_roots_from_project_config,
+};
+use cairo_lang_filesystem::db::{CORELIB_CRATE_NAME, CrateIdentifier};
+use cairo_lang_project::ProjectConfig;
+use smol_str::SmolStr;
+
+use crate::lang::db::AnalysisDatabase;
+
+#[cfg(test)]
+#[path = "cairo_project_test.rs"]
+mod test;
+
+/// Updates crate roots in the database with all crates declared in the `cairo_project.toml`.
+///
+/// Crates with roots in the directory of the project are members of the workspace, while the ones
+/// with roots outside of it, like path dependencies, are not.
+pub fn update_crate_roots(db: &mut AnalysisDatabase, mut config: ProjectConfig) {
+    link_project_crates(&mut config);
+    update_crate_roots_from_project_config(db, &config);
+
+    let base_path = config.base_path.canonicalize().unwrap_or_else(|_| config.base_path.clone());
+    let crate_ids = get_main_crate_ids_from_project(db, &config);
+    for (crate_id, root) in crate_ids.into_iter().zip(config.content.crate_roots.values()) {
+        let is_member = config
+            .absolute_crate_root(root)
+            .canonicalize()
+            .is_ok_and(|root| root.starts_with(&base_path));
+        db.set_workspace_member(crate_id, is_member);
+    }
+}
+
+/// Makes dependencies between crates of the same project refer to the crates declared in it.
+///
+/// Crates of a project are registered with their identifiers as discriminators, so dependencies
+/// on them which do not specify a discriminator would resolve to crates which do not exist.
+fn link_project_crates(config: &mut ProjectConfig) {
+    let crates_config = &config.content.crates_config;
+    let discriminators: HashMap<SmolStr, CrateIdentifier> = config
+        .content
+        .crate_roots
+        .keys()
+        .map(|identifier| {
+            let name = crates_config.get(identifier).name.clone();
+            (name.unwrap_or_else(|| identifier.clone().into()), identifier.clone())
+        })
+        .filter(|(name, _)| name != CORELIB_CRATE_NAME)
+        .collect();
+
+    let crates_config = &mut config.content.crates_config;
+    for settings in iter::once(&mut crates_config.global)
+        .chain(crates_config.override_map.iter_mut().map(|(_, settings)| settings))
+    {
+        for (name, dependency) in settings.dependencies.iter_mut() {
+            if dependency.discriminator.is_none() {
+                dependency.discriminator =
+                    discriminators.get(name.as_str()).map(|identifier| identifier.clone().into());
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use assert_fs::TempDir;
+use assert_fs::prelude::*;
+use cairo_lang_project::{PROJECT_FILE_NAME, ProjectConfig};
+use indoc::indoc;
+
+use super::link_project_crates;
+
+#[test]
+fn links_project_crates() {
+    let t = TempDir::new().unwrap();
+    let manifest = t.child(PROJECT_FILE_NAME);
+    manifest
+        .write_str(indoc! {r#"
+        [crate_roots]
+        hello = "hello"
+        world = "world"
+        renamed_world = "renamed_world"
+
+        [config.global]
+        edition = "2024_07"
+
+        [config.global.dependencies]
+        world = {}
+
+        [config.override.hello]
+        edition = "2024_07"
+
+        [config.override.hello.dependencies]
+        core = {}
+        world = {}
+        earth = { discriminator = "renamed_world" }
+        external = {}
+
+        [config.override.renamed_world]
+        name = "earth"
+        edition = "2024_07"
+        "#})
+        .unwrap();
+    let mut config = ProjectConfig::from_file(manifest.path()).unwrap();
+
+    link_project_crates(&mut config);
+
+    let discriminators = |identifier: &str| {
+        config
+            .content
+            .crates_config
+            .get(&identifier.into())
+            .dependencies
+            .iter()
+            .map(|(name, dependency)| (name.as_str(), dependency.discriminator.as_deref()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(discriminators("world"), [("world", Some("world"))]);
+    assert_eq!(
+        discriminators("hello"),
+        [
+            ("core", None),
+            ("earth", Some("renamed_world")),
+            ("external", None),
+            ("world", Some("world")),
+        ]
+    );
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use cairo_lang_defs::db::DefsGroup;
 use cairo_lang_defs::ids::ModuleId;
@@ -36,6 +36,9 @@ pub struct Crate {
 
     /// Crate settings.
     pub settings: CrateSettings,
+
+    /// Path to `Scarb.toml` of the package of the crate, if it is a member of a Scarb workspace.
+    pub manifest_path: Option<PathBuf>,
 }
 
 impl Crate {
@@ -57,6 +60,9 @@ impl Crate {
             settings: self.settings.clone(),
         };
         db.set_crate_config(crate_id, Some(crate_configuration));
+        db.set_scarb_manifest_path(crate_id, self.manifest_path.clone());
+        // Only crates of workspace members have the manifest of their package.
+        db.set_workspace_member(crate_id, self.manifest_path.is_some());
 
         if let Some(file_stems) = &self.custom_main_file_stems {
             inject_virtual_wrapper_lib(db, crate_id, file_stems);
@@ -78,8 +84,9 @@ impl Crate {
         };
 
         let custom_main_file_stems = extract_custom_file_stems(db, crate_id);
+        let manifest_path = db.scarb_manifest_path(crate_id).map(Path::to_path_buf);
 
-        Some(Self { name, discriminator, root, custom_main_file_stems, settings })
+        Some(Self { name, discriminator, root, custom_main_file_stems, settings, manifest_path })
     }
 
     /// States whether this is the `core` crate.
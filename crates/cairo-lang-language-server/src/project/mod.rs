@@ -1,6 +1,7 @@
 pub use self::crate_data::Crate;
 pub use self::project_manifest_path::*;
 
+pub(crate) mod cairo_project;
 mod crate_data;
 mod project_manifest_path;
 // TODO(mkaput): These two are `pub` temporarily.
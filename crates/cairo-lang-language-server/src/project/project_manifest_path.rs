@@ -28,9 +28,14 @@ impl ProjectManifestPath {
     ///
     /// Returns `None` if the file at `path` is detached from any Cairo project.
     ///
+    /// Directories are searched upwards, starting from the one containing the file, and the
+    /// manifest nearest to the file is chosen.
+    /// For files of a Scarb workspace member, this is the manifest of the member package, and
+    /// Scarb resolves the workspace it belongs to.
+    ///
     /// ## Precedence
     ///
-    /// The following files are searched for in order:
+    /// The following files are searched for in order in each directory:
     /// 1. `cairo_project.toml`
     /// 2. `Scarb.toml`
     ///
@@ -38,25 +43,33 @@ impl ProjectManifestPath {
     /// If there are all `cairo_project.toml`, `Scarb.toml` and `Scarb.lock`
     /// files in the same directory, the `cairo_project.toml` file will be chosen for each.
     pub fn discover(path: &Path) -> Option<ProjectManifestPath> {
-        return find_in_parent_dirs(path.to_path_buf(), PROJECT_FILE_NAME)
-            .map(ProjectManifestPath::CairoProject)
-            .or_else(|| {
-                find_in_parent_dirs(path.to_path_buf(), SCARB_TOML).map(ProjectManifestPath::Scarb)
-            });
+        Self::discover_from_dir(path.parent()?)
+    }
 
-        fn find_in_parent_dirs(mut path: PathBuf, target_file_name: &str) -> Option<PathBuf> {
-            for _ in 0..MAX_CRATE_DETECTION_DEPTH {
-                if !path.pop() {
-                    return None;
-                }
+    /// Looks for a project manifest in the given directory or its ancestors.
+    ///
+    /// Returns `None` if the directory is not part of any Cairo project.
+    /// The same rules as in [`ProjectManifestPath::discover`] apply.
+    pub fn discover_from_dir(dir: &Path) -> Option<ProjectManifestPath> {
+        let mut dir = dir.to_path_buf();
+        for _ in 0..MAX_CRATE_DETECTION_DEPTH {
+            if let Some(manifest_path) = find_in_dir(&dir, PROJECT_FILE_NAME) {
+                return Some(ProjectManifestPath::CairoProject(manifest_path));
+            }
+            if let Some(manifest_path) = find_in_dir(&dir, SCARB_TOML) {
+                return Some(ProjectManifestPath::Scarb(manifest_path));
+            }
 
-                let manifest_path = path.join(target_file_name);
-                // Check if the file exists and we can actually access it.
-                if fs::metadata(&manifest_path).is_ok() {
-                    return Some(manifest_path);
-                };
+            if !dir.pop() {
+                return None;
             }
-            None
+        }
+        return None;
+
+        fn find_in_dir(dir: &Path, target_file_name: &str) -> Option<PathBuf> {
+            let manifest_path = dir.join(target_file_name);
+            // Check if the file exists and we can actually access it.
+            fs::metadata(&manifest_path).is_ok().then_some(manifest_path)
         }
     }
 }
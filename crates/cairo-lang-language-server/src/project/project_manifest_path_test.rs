@@ -93,3 +93,85 @@ fn discover_precedence() {
     check_some(&scarb_manifest, &cairo_manifest, ProjectManifestPath::CairoProject);
     check_some(&scarb_lock, &cairo_manifest, ProjectManifestPath::CairoProject);
 }
+
+#[test]
+fn discover_from_dir() {
+    let t = TempDir::new().unwrap();
+
+    let manifest = t.child(SCARB_TOML);
+    manifest.touch().unwrap();
+
+    let nested = t.child("packages/foo");
+    nested.create_dir_all().unwrap();
+
+    let nested_manifest = t.child("packages/bar").child(SCARB_TOML);
+    nested_manifest.touch().unwrap();
+
+    let dir = t.path().to_path_buf();
+    assert_eq!(
+        ProjectManifestPath::discover_from_dir(&dir),
+        Some(ProjectManifestPath::Scarb(manifest.to_path_buf()))
+    );
+    assert_eq!(
+        ProjectManifestPath::discover_from_dir(nested.path()),
+        Some(ProjectManifestPath::Scarb(manifest.to_path_buf()))
+    );
+    assert_eq!(
+        ProjectManifestPath::discover_from_dir(t.child("packages/bar").path()),
+        Some(ProjectManifestPath::Scarb(nested_manifest.to_path_buf()))
+    );
+}
+
+#[test]
+fn discover_nested_source() {
+    let t = TempDir::new().unwrap();
+
+    let manifest = t.child("pkg").child(SCARB_TOML);
+    manifest.touch().unwrap();
+
+    let source_path = t.child("pkg/src/sub/mod.cairo");
+    source_path.touch().unwrap();
+
+    check_some(&source_path, &manifest, ProjectManifestPath::Scarb);
+}
+
+#[test]
+fn discover_workspace_member() {
+    let t = TempDir::new().unwrap();
+
+    let workspace_manifest = t.child(SCARB_TOML);
+    workspace_manifest.write_str("[workspace]\nmembers = [\"pkg\"]\n").unwrap();
+
+    let member_manifest = t.child("pkg").child(SCARB_TOML);
+    member_manifest.touch().unwrap();
+
+    let source_path = t.child("pkg/src/sub/mod.cairo");
+    source_path.touch().unwrap();
+
+    let workspace_source_path = t.child("scripts/foo.cairo");
+    workspace_source_path.touch().unwrap();
+
+    // Scarb resolves the workspace of the member package.
+    check_some(&source_path, &member_manifest, ProjectManifestPath::Scarb);
+    check_some(&workspace_source_path, &workspace_manifest, ProjectManifestPath::Scarb);
+}
+
+#[test]
+fn discover_nearest_manifest() {
+    let t = TempDir::new().unwrap();
+
+    let cairo_manifest = t.child(PROJECT_FILE_NAME);
+    cairo_manifest.touch().unwrap();
+
+    let scarb_manifest = t.child("pkg").child(SCARB_TOML);
+    scarb_manifest.touch().unwrap();
+
+    let source_path = t.child("pkg/src/lib.cairo");
+    source_path.touch().unwrap();
+
+    let outer_source_path = t.child("src/lib.cairo");
+    outer_source_path.touch().unwrap();
+
+    check_some(&source_path, &scarb_manifest, ProjectManifestPath::Scarb);
+    check_some(&outer_source_path, &cairo_manifest, ProjectManifestPath::CairoProject);
+}
@@ -164,12 +164,18 @@ pub fn update_crate_roots(metadata: &Metadata, db: &mut AnalysisDatabase) {
 
             let custom_main_file_stems = (file_stem != "lib").then_some(vec![file_stem.into()]);
 
+            // Only tests of workspace members are run, with the manifest of their package.
+            let manifest_path = package
+                .filter(|package| metadata.workspace.members.contains(&package.id))
+                .map(|package| package.manifest_path.clone().into_std_path_buf());
+
             let cr = Crate {
                 name: crate_name.into(),
                 discriminator: component.discriminator.as_ref().map(ToSmolStr::to_smolstr),
                 root: root.into(),
                 custom_main_file_stems,
                 settings,
+                manifest_path,
             };
 
             if compilation_unit.package == component.package {
@@ -220,6 +226,7 @@ pub fn update_crate_roots(metadata: &Metadata, db: &mut AnalysisDatabase) {
         // Name and discriminator don't really matter, so we take the first crate's ones.
         let name = first_crate.name.clone();
         let discriminator = first_crate.discriminator.clone();
+        let manifest_path = first_crate.manifest_path.clone();
 
         let custom_main_file_stems =
             crs.into_iter().flat_map(|cr| cr.custom_main_file_stems.unwrap()).collect();
@@ -230,6 +237,7 @@ pub fn update_crate_roots(metadata: &Metadata, db: &mut AnalysisDatabase) {
             root,
             custom_main_file_stems: Some(custom_main_file_stems),
             settings,
+            manifest_path,
         });
     }
 
@@ -6,13 +6,15 @@
 // +-----------------------------------------------------+
 
 use std::any::TypeId;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::Result;
 use lsp_server::{Notification, RequestId, Response};
 use lsp_types::notification::Notification as NotificationTrait;
 use rustc_hash::FxHashMap;
 use serde_json::Value;
-use tracing::error;
+use tracing::{error, trace};
 
 use crate::lsp::result::LSPError;
 use crate::server::connection::ClientSender;
@@ -30,7 +32,12 @@ pub struct Client<'s> {
 pub struct Notifier(ClientSender);
 
 #[derive(Clone)]
-pub struct Responder(ClientSender);
+pub struct Responder {
+    sender: ClientSender,
+    /// Flag shared with the [`Scheduler`](crate::server::schedule::Scheduler), set once the
+    /// request has been answered. Present only for requests that may time out.
+    responded: Option<Arc<AtomicBool>>,
+}
 
 pub struct Requester<'s> {
     sender: ClientSender,
@@ -42,7 +49,7 @@ impl Client<'_> {
     pub fn new(sender: ClientSender) -> Self {
         Self {
             notifier: Notifier(sender.clone()),
-            responder: Responder(sender.clone()),
+            responder: Responder { sender: sender.clone(), responded: None },
             requester: Requester {
                 sender,
                 next_request_id: 1,
@@ -74,11 +81,24 @@ impl Notifier {
 }
 
 impl Responder {
+    /// Creates a responder which sends at most one response in total among all responders sharing
+    /// the `responded` flag.
+    pub fn with_responded_flag(&self, responded: Arc<AtomicBool>) -> Self {
+        Self { sender: self.sender.clone(), responded: Some(responded) }
+    }
+
     pub fn respond<R>(&self, id: RequestId, result: Result<R, LSPError>) -> Result<()>
     where
         R: serde::Serialize,
     {
-        self.0.send(
+        if let Some(responded) = &self.responded {
+            if responded.swap(true, Ordering::SeqCst) {
+                trace!("dropping response to request {id}, it has already been answered");
+                return Ok(());
+            }
+        }
+
+        self.sender.send(
             match result {
                 Ok(res) => Response::new_ok(id, res),
                 Err(LSPError { code, error }) => {
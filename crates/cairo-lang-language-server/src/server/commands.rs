@@ -1,16 +1,89 @@
-use anyhow::bail;
+use std::path::PathBuf;
+
+use anyhow::{Context, bail};
+use lsp_types::ExecuteCommandParams;
+use serde::Deserialize;
 
 pub enum ServerCommands {
     Reload,
+    SwapDatabase,
+    ClearCache,
+    ExportExpandedCrate { crate_name: String, output: PathBuf },
+    RunTest { test_path: String, manifest_path: PathBuf },
+    ScarbUpdate { manifest_path: PathBuf },
+}
+
+impl ServerCommands {
+    /// Identifiers of all commands the server is able to execute.
+    pub const ALL: [&'static str; 6] = [
+        "cairo.reload",
+        "cairo.swapDatabase",
+        "cairo.clearCache",
+        "cairo.exportExpandedCrate",
+        "cairo.runTest",
+        "cairo.scarbUpdate",
+    ];
+}
+
+/// Arguments of the `cairo.exportExpandedCrate` command.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportExpandedCrateArgs {
+    crate_name: String,
+    output: PathBuf,
+}
+
+/// Arguments of the `cairo.runTest` command.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunTestArgs {
+    test_path: String,
+    manifest_path: PathBuf,
+}
+
+/// Arguments of the `cairo.scarbUpdate` command.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScarbUpdateArgs {
+    manifest_path: PathBuf,
 }
 
-impl TryFrom<String> for ServerCommands {
+impl TryFrom<ExecuteCommandParams> for ServerCommands {
     type Error = anyhow::Error;
 
-    fn try_from(value: String) -> anyhow::Result<Self> {
-        match value.as_str() {
+    fn try_from(params: ExecuteCommandParams) -> anyhow::Result<Self> {
+        match params.command.as_str() {
             "cairo.reload" => Ok(ServerCommands::Reload),
-            _ => bail!("Unrecognized command: {value}"),
+            "cairo.swapDatabase" => Ok(ServerCommands::SwapDatabase),
+            "cairo.clearCache" => Ok(ServerCommands::ClearCache),
+            "cairo.exportExpandedCrate" => {
+                let args = params
+                    .arguments
+                    .into_iter()
+                    .next()
+                    .context("Missing arguments for `cairo.exportExpandedCrate`")?;
+                let ExportExpandedCrateArgs { crate_name, output } = serde_json::from_value(args)?;
+                Ok(ServerCommands::ExportExpandedCrate { crate_name, output })
+            }
+            "cairo.runTest" => {
+                let args = params
+                    .arguments
+                    .into_iter()
+                    .next()
+                    .context("Missing arguments for `cairo.runTest`")?;
+                let RunTestArgs { test_path, manifest_path } = serde_json::from_value(args)?;
+                Ok(ServerCommands::RunTest { test_path, manifest_path })
+            }
+            "cairo.scarbUpdate" => {
+                let args = params
+                    .arguments
+                    .into_iter()
+                    .next()
+                    .context("Missing arguments for `cairo.scarbUpdate`")?;
+                let ScarbUpdateArgs { manifest_path } = serde_json::from_value(args)?;
+                Ok(ServerCommands::ScarbUpdate { manifest_path })
+            }
+            command => bail!("Unrecognized command: {command}"),
         }
     }
 }
@@ -6,8 +6,10 @@
 // +-----------------------------------------------------+
 
 use std::sync::{Arc, Weak};
+use std::time::Instant;
 
 use anyhow::{Result, bail};
+use crossbeam::channel::RecvTimeoutError;
 use lsp_server::{
     Connection as LSPConnection, IoThreads, Message, Notification, Request, RequestId, Response,
 };
@@ -77,9 +79,13 @@ impl Connection {
         ClientSender { weak_sender: Arc::downgrade(&self.sender) }
     }
 
-    /// An iterator over incoming messages from the client.
-    pub fn incoming(&self) -> crossbeam::channel::Iter<'_, Message> {
-        self.receiver.iter()
+    /// Receives the next message from the client, waiting no longer than until `deadline`, if
+    /// given.
+    pub fn recv_deadline(&self, deadline: Option<Instant>) -> Result<Message, RecvTimeoutError> {
+        match deadline {
+            Some(deadline) => self.receiver.recv_deadline(deadline),
+            None => self.receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        }
     }
 
     /// Check and respond to any incoming shutdown requests; returns `true` if the server should be
@@ -140,6 +146,12 @@ pub struct ClientSender {
 }
 
 impl ClientSender {
+    /// Creates a sender which is not connected to any client.
+    #[cfg(test)]
+    pub fn disconnected() -> Self {
+        Self { weak_sender: Weak::new() }
+    }
+
     pub fn send(&self, msg: Message) -> Result<()> {
         let Some(sender) = self.weak_sender.upgrade() else {
             bail!("the connection with the client has been closed");
@@ -2,6 +2,7 @@ pub mod client;
 pub mod commands;
 pub mod connection;
 pub mod panic;
+pub mod progress;
 pub mod schedule;
 
 mod routing;
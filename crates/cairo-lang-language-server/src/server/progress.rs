@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use lsp_types::notification::Progress;
+use lsp_types::request::WorkDoneProgressCreate;
+use lsp_types::{
+    ClientCapabilities, NumberOrString, ProgressParams, ProgressParamsValue, ProgressToken,
+    WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressCreateParams, WorkDoneProgressEnd,
+    WorkDoneProgressReport,
+};
+use tracing::error;
+
+use crate::lsp::capabilities::client::ClientCapabilitiesExt;
+use crate::server::client::{Notifier, Requester};
+use crate::server::schedule::Task;
+
+/// Source of unique tokens of server-initiated progress.
+static NEXT_TOKEN: AtomicU32 = AtomicU32::new(0);
+
+/// Server-initiated progress of a long-running operation, shown by the client with
+/// `window/workDoneProgress` notifications, e.g. as a spinner in the status bar.
+///
+/// The progress begins when this object is created and ends when it is dropped.
+/// If the client does not support work done progress, this object does nothing.
+pub struct WorkDoneProgressReporter {
+    /// Token of the progress, `None` if the client does not support work done progress.
+    token: Option<ProgressToken>,
+    notifier: Notifier,
+}
+
+impl WorkDoneProgressReporter {
+    /// Creates a progress token on the client and begins the progress with the given title.
+    pub fn begin(
+        title: impl Into<String>,
+        client_capabilities: &ClientCapabilities,
+        notifier: &Notifier,
+        requester: &mut Requester<'_>,
+    ) -> Self {
+        if !client_capabilities.work_done_progress_support() {
+            return Self::disabled(notifier);
+        }
+
+        let token = NumberOrString::String(format!(
+            "cairo-ls/progress/{}",
+            NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        // Progress is reported right away, without waiting for the token to be created, because
+        // the operation blocks the main loop, so the response would not be handled until it ends.
+        if let Err(err) = requester.request::<WorkDoneProgressCreate>(
+            WorkDoneProgressCreateParams { token: token.clone() },
+            |()| Task::nothing(),
+        ) {
+            error!("failed to create work done progress: {err:?}");
+            return Self::disabled(notifier);
+        }
+
+        let this = Self { token: Some(token), notifier: notifier.clone() };
+        this.notify(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: title.into(),
+            ..Default::default()
+        }));
+        this
+    }
+
+    /// Creates a progress which is not shown to the client.
+    pub fn disabled(notifier: &Notifier) -> Self {
+        Self { token: None, notifier: notifier.clone() }
+    }
+
+    /// Reports a message describing the current step of the operation.
+    pub fn report(&self, message: impl Into<String>) {
+        self.notify(WorkDoneProgress::Report(WorkDoneProgressReport {
+            message: Some(message.into()),
+            ..Default::default()
+        }));
+    }
+
+    fn notify(&self, value: WorkDoneProgress) {
+        if let Some(token) = &self.token {
+            self.notifier.notify::<Progress>(ProgressParams {
+                token: token.clone(),
+                value: ProgressParamsValue::WorkDone(value),
+            });
+        }
+    }
+}
+
+impl Drop for WorkDoneProgressReporter {
+    fn drop(&mut self) {
+        self.notify(WorkDoneProgress::End(WorkDoneProgressEnd::default()));
+    }
+}
@@ -11,18 +11,32 @@ use anyhow::anyhow;
 use lsp_server::{ErrorCode, ExtractError, Notification, Request, RequestId};
 use lsp_types::notification::{
     Cancel, DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles,
-    DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    DidChangeWorkspaceFolders, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
     Notification as NotificationTrait, SetTrace,
 };
 use lsp_types::request::{
-    CodeActionRequest, Completion, ExecuteCommand, Formatting, GotoDefinition, HoverRequest,
-    Request as RequestTrait, SemanticTokensFullRequest,
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, Completion,
+    DocumentDiagnosticRequest, DocumentHighlightRequest, DocumentLinkRequest, ExecuteCommand,
+    FoldingRangeRequest, Formatting, GotoDeclaration, GotoDefinition, GotoTypeDefinition,
+    HoverRequest, InlayHintRequest, LinkedEditingRange, OnTypeFormatting, PrepareRenameRequest,
+    RangeFormatting, References, Rename, Request as RequestTrait, ResolveCompletionItem,
+    SelectionRangeRequest, SemanticTokensFullDeltaRequest, SemanticTokensFullRequest,
+    SemanticTokensRangeRequest, SignatureHelpRequest, TypeHierarchyPrepare, TypeHierarchySubtypes,
+    TypeHierarchySupertypes, WorkspaceDiagnosticRequest, WorkspaceSymbolRequest,
 };
 use tracing::{error, trace, warn};
 
 use super::client::Responder;
-use crate::lsp::ext::{ExpandMacro, ProvideVirtualFile, ViewAnalyzedCrates};
+use crate::ide::macros::export::export_expanded_crate;
+use crate::lsp::ext::{
+    AnalyzedCrates, ExpandMacro, ExpandMacroDocument, ExpandedCrateExported,
+    ExpandedCrateExportedParams, ProvideVirtualFile, ScarbUpdateFinished,
+    ScarbUpdateFinishedParams, ShowSyntaxTree, TestRunFinished, TestRunFinishedParams,
+    ViewAnalyzedCrates, ViewCrateGraph, ViewMemoryUsage,
+};
 use crate::lsp::result::{LSPError, LSPResult, LSPResultEx};
+use crate::server::commands::ServerCommands;
 use crate::server::panic::cancelled_anyhow;
 use crate::server::schedule::{BackgroundSchedule, Task};
 use crate::state::State;
@@ -33,37 +47,165 @@ pub fn request<'a>(request: Request) -> Task<'a> {
     let id = request.id.clone();
 
     match request.method.as_str() {
+        AnalyzedCrates::METHOD => {
+            background_request_task::<AnalyzedCrates>(request, BackgroundSchedule::Worker)
+        }
+        CallHierarchyIncomingCalls::METHOD => {
+            background_request_task::<CallHierarchyIncomingCalls>(
+                request,
+                BackgroundSchedule::Worker,
+            )
+        }
+        CallHierarchyOutgoingCalls::METHOD => {
+            background_request_task::<CallHierarchyOutgoingCalls>(
+                request,
+                BackgroundSchedule::LatencySensitive,
+            )
+        }
+        CallHierarchyPrepare::METHOD => background_request_task::<CallHierarchyPrepare>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
         CodeActionRequest::METHOD => background_request_task::<CodeActionRequest>(
             request,
             BackgroundSchedule::LatencySensitive,
         ),
+        CodeActionResolveRequest::METHOD => background_request_task::<CodeActionResolveRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        CodeLensRequest::METHOD => background_request_task::<CodeLensRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
         Completion::METHOD => {
             background_request_task::<Completion>(request, BackgroundSchedule::LatencySensitive)
         }
-        ExecuteCommand::METHOD => local_request_task::<ExecuteCommand>(request),
+        DocumentDiagnosticRequest::METHOD => background_request_task::<DocumentDiagnosticRequest>(
+            request,
+            BackgroundSchedule::Worker,
+        ),
+        DocumentHighlightRequest::METHOD => background_request_task::<DocumentHighlightRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        DocumentLinkRequest::METHOD => background_request_task::<DocumentLinkRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        ExecuteCommand::METHOD => execute_command_task(request),
         ExpandMacro::METHOD => {
             background_request_task::<ExpandMacro>(request, BackgroundSchedule::Worker)
         }
+        ExpandMacroDocument::METHOD => {
+            background_request_task::<ExpandMacroDocument>(request, BackgroundSchedule::Worker)
+        }
+        FoldingRangeRequest::METHOD => background_request_task::<FoldingRangeRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
         Formatting::METHOD => {
             background_request_task::<Formatting>(request, BackgroundSchedule::LatencySensitive)
         }
+        GotoDeclaration::METHOD => background_request_task::<GotoDeclaration>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
         GotoDefinition::METHOD => {
             background_request_task::<GotoDefinition>(request, BackgroundSchedule::LatencySensitive)
         }
+        GotoTypeDefinition::METHOD => background_request_task::<GotoTypeDefinition>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
         HoverRequest::METHOD => {
             background_request_task::<HoverRequest>(request, BackgroundSchedule::LatencySensitive)
         }
+        InlayHintRequest::METHOD => background_request_task::<InlayHintRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        LinkedEditingRange::METHOD => background_request_task::<LinkedEditingRange>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        OnTypeFormatting::METHOD => background_request_task::<OnTypeFormatting>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        PrepareRenameRequest::METHOD => background_request_task::<PrepareRenameRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
         ProvideVirtualFile::METHOD => background_request_task::<ProvideVirtualFile>(
             request,
             BackgroundSchedule::LatencySensitive,
         ),
+        RangeFormatting::METHOD => background_request_task::<RangeFormatting>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        References::METHOD => {
+            background_request_task::<References>(request, BackgroundSchedule::Worker)
+        }
+        Rename::METHOD => background_request_task::<Rename>(request, BackgroundSchedule::Worker),
+        ResolveCompletionItem::METHOD => background_request_task::<ResolveCompletionItem>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        SelectionRangeRequest::METHOD => background_request_task::<SelectionRangeRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        SemanticTokensFullDeltaRequest::METHOD => background_request_task::<
+            SemanticTokensFullDeltaRequest,
+        >(request, BackgroundSchedule::Worker),
         SemanticTokensFullRequest::METHOD => background_request_task::<SemanticTokensFullRequest>(
             request,
             BackgroundSchedule::Worker,
         ),
+        SemanticTokensRangeRequest::METHOD => {
+            background_request_task::<SemanticTokensRangeRequest>(
+                request,
+                BackgroundSchedule::LatencySensitive,
+            )
+        }
+        ShowSyntaxTree::METHOD => {
+            background_request_task::<ShowSyntaxTree>(request, BackgroundSchedule::Worker)
+        }
+        SignatureHelpRequest::METHOD => background_request_task::<SignatureHelpRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
         ViewAnalyzedCrates::METHOD => {
             background_request_task::<ViewAnalyzedCrates>(request, BackgroundSchedule::Worker)
         }
+        ViewCrateGraph::METHOD => {
+            background_request_task::<ViewCrateGraph>(request, BackgroundSchedule::Worker)
+        }
+        ViewMemoryUsage::METHOD => {
+            background_request_task::<ViewMemoryUsage>(request, BackgroundSchedule::Worker)
+        }
+        TypeHierarchyPrepare::METHOD => background_request_task::<TypeHierarchyPrepare>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
+        TypeHierarchySubtypes::METHOD => {
+            background_request_task::<TypeHierarchySubtypes>(request, BackgroundSchedule::Worker)
+        }
+        TypeHierarchySupertypes::METHOD => {
+            background_request_task::<TypeHierarchySupertypes>(request, BackgroundSchedule::Worker)
+        }
+        WorkspaceDiagnosticRequest::METHOD => {
+            background_request_task::<WorkspaceDiagnosticRequest>(
+                request,
+                BackgroundSchedule::Worker,
+            )
+        }
+        WorkspaceSymbolRequest::METHOD => background_request_task::<WorkspaceSymbolRequest>(
+            request,
+            BackgroundSchedule::LatencySensitive,
+        ),
 
         method => {
             warn!("received request {method} which does not have a handler");
@@ -88,6 +230,9 @@ pub fn notification<'a>(notification: Notification) -> Task<'a> {
         DidChangeWatchedFiles::METHOD => {
             local_notification_task::<DidChangeWatchedFiles>(notification)
         }
+        DidChangeWorkspaceFolders::METHOD => {
+            local_notification_task::<DidChangeWorkspaceFolders>(notification)
+        }
         DidCloseTextDocument::METHOD => {
             local_notification_task::<DidCloseTextDocument>(notification)
         }
@@ -124,14 +269,118 @@ fn local_request_task<'a, R: traits::SyncRequestHandler>(
     }))
 }
 
+/// Most commands need mutable access to the state and are run on the main thread, but exporting
+/// the expanded crate, running tests and updating Scarb workspaces may take a long time, so they
+/// are run in the background instead.
+/// The request is answered immediately, and the client is notified when the command finishes.
+fn execute_command_task<'a>(request: Request) -> Result<Task<'a>, LSPError> {
+    let (id, params) = cast_request::<ExecuteCommand>(request.clone())?;
+
+    match ServerCommands::try_from(params) {
+        Ok(ServerCommands::ExportExpandedCrate { crate_name, output }) => {
+            Ok(Task::background(BackgroundSchedule::Worker, move |state: &State| {
+                let state_snapshot = state.snapshot();
+                let guard = state.background_requests.start();
+                Box::new(move |notifier, responder| {
+                    let _guard = guard;
+                    respond::<ExecuteCommand>(id, Ok(None), &responder);
+
+                    let result = catch_unwind(AssertUnwindSafe(|| {
+                        export_expanded_crate(&state_snapshot.db, &crate_name, &output)
+                    }))
+                    .unwrap_or_else(|err| {
+                        Err(cancelled_anyhow(err, "exporting expanded crate was cancelled")
+                            .unwrap_or_else(|_| {
+                                anyhow!("caught panic while exporting expanded crate")
+                            }))
+                    });
+
+                    let error = result.err().map(|err| {
+                        error!("failed to export expanded crate `{crate_name}`: {err:?}");
+                        format!("{err:#}")
+                    });
+                    notifier.notify::<ExpandedCrateExported>(ExpandedCrateExportedParams {
+                        crate_name,
+                        output,
+                        error,
+                    });
+                })
+            }))
+        }
+        Ok(ServerCommands::RunTest { test_path, manifest_path }) => {
+            Ok(Task::background(BackgroundSchedule::Worker, move |state: &State| {
+                let scarb = state.scarb_toolchain.clone();
+                Box::new(move |notifier, responder| {
+                    respond::<ExecuteCommand>(id, Ok(None), &responder);
+
+                    let (success, output) = match scarb.test(&manifest_path, &test_path) {
+                        Ok(output) => (
+                            output.status.success(),
+                            format!(
+                                "{}{}",
+                                String::from_utf8_lossy(&output.stdout),
+                                String::from_utf8_lossy(&output.stderr)
+                            ),
+                        ),
+                        Err(err) => {
+                            error!("failed to run test `{test_path}`: {err:?}");
+                            (false, format!("{err:#}"))
+                        }
+                    };
+                    notifier.notify::<TestRunFinished>(TestRunFinishedParams {
+                        test_path,
+                        success,
+                        output,
+                    });
+                })
+            }))
+        }
+        Ok(ServerCommands::ScarbUpdate { manifest_path }) => {
+            Ok(Task::background(BackgroundSchedule::Worker, move |state: &State| {
+                let scarb = state.scarb_toolchain.clone();
+                Box::new(move |notifier, responder| {
+                    respond::<ExecuteCommand>(id, Ok(None), &responder);
+
+                    // The workspace is reloaded once the change of `Scarb.lock` is noticed.
+                    let (success, output) = match scarb.update(&manifest_path) {
+                        Ok(output) => (
+                            output.status.success(),
+                            format!(
+                                "{}{}",
+                                String::from_utf8_lossy(&output.stdout),
+                                String::from_utf8_lossy(&output.stderr)
+                            ),
+                        ),
+                        Err(err) => {
+                            error!(
+                                "failed to update scarb workspace: {}: {err:?}",
+                                manifest_path.display()
+                            );
+                            (false, format!("{err:#}"))
+                        }
+                    };
+                    notifier.notify::<ScarbUpdateFinished>(ScarbUpdateFinishedParams {
+                        manifest_path,
+                        success,
+                        output,
+                    });
+                })
+            }))
+        }
+        _ => local_request_task::<ExecuteCommand>(request),
+    }
+}
+
 fn background_request_task<'a, R: traits::BackgroundDocumentRequestHandler>(
     request: Request,
     schedule: BackgroundSchedule,
 ) -> Result<Task<'a>, LSPError> {
     let (id, params) = cast_request::<R>(request)?;
-    Ok(Task::background(schedule, move |state: &State| {
+    Ok(Task::background_request(id.clone(), schedule, move |state: &State| {
         let state_snapshot = state.snapshot();
+        let guard = state.background_requests.start();
         Box::new(move |notifier, responder| {
+            let _guard = guard;
             let result = catch_unwind(AssertUnwindSafe(|| {
                 R::run_with_snapshot(state_snapshot, notifier, params)
             }))
@@ -8,34 +8,62 @@
 use cairo_lang_filesystem::db::{
     AsFilesGroupMut, FilesGroup, FilesGroupEx, PrivRawFileContentQuery,
 };
+use cairo_lang_project::PROJECT_FILE_NAME;
+use lsp_server::ErrorCode;
 use lsp_types::notification::{
-    DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles, DidCloseTextDocument,
-    DidOpenTextDocument, DidSaveTextDocument, Notification,
+    DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles,
+    DidChangeWorkspaceFolders, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification,
 };
 use lsp_types::request::{
-    CodeActionRequest, Completion, ExecuteCommand, Formatting, GotoDefinition, HoverRequest,
-    Request, SemanticTokensFullRequest,
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, Completion,
+    DocumentDiagnosticRequest, DocumentHighlightRequest, DocumentLinkRequest, ExecuteCommand,
+    FoldingRangeRequest, Formatting, GotoDeclaration, GotoDeclarationParams, GotoDefinition,
+    GotoTypeDefinition, GotoTypeDefinitionParams, HoverRequest, InlayHintRequest,
+    LinkedEditingRange, OnTypeFormatting, PrepareRenameRequest, RangeFormatting, References,
+    Rename, Request, ResolveCompletionItem, SelectionRangeRequest, SemanticTokensFullDeltaRequest,
+    SemanticTokensFullRequest, SemanticTokensRangeRequest, SignatureHelpRequest,
+    TypeHierarchyPrepare, TypeHierarchySubtypes, TypeHierarchySupertypes,
+    WorkspaceDiagnosticRequest, WorkspaceSymbolRequest,
 };
 use lsp_types::{
-    CodeActionParams, CodeActionResponse, CompletionParams, CompletionResponse,
-    DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    CodeAction, CodeActionParams, CodeActionResponse, CodeLens, CodeLensParams, CompletionItem,
+    CompletionParams, CompletionResponse, DidChangeConfigurationParams,
+    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidChangeWorkspaceFoldersParams,
     DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
-    DocumentFormattingParams, ExecuteCommandParams, GotoDefinitionParams, GotoDefinitionResponse,
-    Hover, HoverParams, SemanticTokensParams, SemanticTokensResult, TextDocumentContentChangeEvent,
-    TextDocumentPositionParams, TextEdit, Url,
+    DocumentDiagnosticParams, DocumentDiagnosticReportResult, DocumentFormattingParams,
+    DocumentHighlight, DocumentHighlightParams, DocumentLink, DocumentLinkParams,
+    DocumentOnTypeFormattingParams, DocumentRangeFormattingParams, ExecuteCommandParams,
+    FileChangeType, FoldingRange, FoldingRangeParams, GotoDefinitionParams, GotoDefinitionResponse,
+    Hover, HoverParams, InlayHint, InlayHintParams, LinkedEditingRangeParams, LinkedEditingRanges,
+    Location, PrepareRenameResponse, ReferenceParams, RenameParams, SelectionRange,
+    SelectionRangeParams, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
+    SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
+    SemanticTokensResult, SignatureHelp, SignatureHelpParams, TextDocumentPositionParams, TextEdit,
+    TypeHierarchyItem, TypeHierarchyPrepareParams, TypeHierarchySubtypesParams,
+    TypeHierarchySupertypesParams, Url, WorkspaceDiagnosticParams, WorkspaceDiagnosticReportResult,
+    WorkspaceEdit, WorkspaceSymbolParams, WorkspaceSymbolResponse,
 };
 use serde_json::Value;
-use tracing::error;
+use tracing::{debug, error};
 
-use crate::lang::lsp::LsProtoGroup;
+use crate::lang::lsp::{LsProtoGroup, text_in_range};
+use crate::lsp::capabilities::client::ClientCapabilitiesExt;
 use crate::lsp::ext::{
-    ExpandMacro, ProvideVirtualFile, ProvideVirtualFileRequest, ProvideVirtualFileResponse,
-    ViewAnalyzedCrates,
+    AnalyzedCrates, AnalyzedCratesResponse, ExpandMacro, ExpandMacroDocument, ExpandMacroResponse,
+    FileTooLarge, FileTooLargeParams, ProvideVirtualFile, ProvideVirtualFileRequest,
+    ProvideVirtualFileResponse, ShowSyntaxTree, ViewAnalyzedCrates, ViewCrateGraph,
+    ViewMemoryUsage,
 };
-use crate::lsp::result::{LSPError, LSPResult};
+use crate::lsp::result::{LSPError, LSPResult, LSPResultEx};
+use crate::project::ProjectManifestPath;
 use crate::server::client::{Notifier, Requester};
 use crate::server::commands::ServerCommands;
 use crate::state::{State, StateSnapshot};
+use crate::toolchain::scarb::{SCARB_LOCK, SCARB_TOML};
 use crate::{Backend, ide, lang};
 
 /// A request handler that needs mutable access to the session.
@@ -73,6 +101,39 @@ pub trait SyncNotificationHandler: Notification {
     ) -> LSPResult<()>;
 }
 
+impl BackgroundDocumentRequestHandler for CallHierarchyPrepare {
+    #[tracing::instrument(name = "textDocument/prepareCallHierarchy", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: CallHierarchyPrepareParams,
+    ) -> LSPResult<Option<Vec<CallHierarchyItem>>> {
+        Ok(ide::call_hierarchy::prepare(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for CallHierarchyIncomingCalls {
+    #[tracing::instrument(name = "callHierarchy/incomingCalls", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> LSPResult<Option<Vec<CallHierarchyIncomingCall>>> {
+        Ok(ide::call_hierarchy::incoming_calls(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for CallHierarchyOutgoingCalls {
+    #[tracing::instrument(name = "callHierarchy/outgoingCalls", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> LSPResult<Option<Vec<CallHierarchyOutgoingCall>>> {
+        Ok(ide::call_hierarchy::outgoing_calls(params, &snapshot.db))
+    }
+}
+
 impl BackgroundDocumentRequestHandler for CodeActionRequest {
     #[tracing::instrument(name = "textDocument/codeAction", skip_all)]
     fn run_with_snapshot(
@@ -80,7 +141,56 @@ impl BackgroundDocumentRequestHandler for CodeActionRequest {
         _notifier: Notifier,
         params: CodeActionParams,
     ) -> Result<Option<CodeActionResponse>, LSPError> {
-        Ok(ide::code_actions::code_actions(params, &snapshot.db))
+        Ok(ide::code_actions::code_actions(
+            params,
+            &snapshot.db,
+            &snapshot.config,
+            snapshot.client_capabilities.code_action_resolve_edit_support(),
+        ))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for CodeActionResolveRequest {
+    #[tracing::instrument(name = "codeAction/resolve", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: CodeAction,
+    ) -> LSPResult<CodeAction> {
+        Ok(ide::code_actions::resolve(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for CodeLensRequest {
+    #[tracing::instrument(name = "textDocument/codeLens", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: CodeLensParams,
+    ) -> LSPResult<Option<Vec<CodeLens>>> {
+        Ok(ide::code_lens::code_lens(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for DocumentHighlightRequest {
+    #[tracing::instrument(name = "textDocument/documentHighlight", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: DocumentHighlightParams,
+    ) -> LSPResult<Option<Vec<DocumentHighlight>>> {
+        Ok(ide::navigation::highlights::highlights(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for DocumentLinkRequest {
+    #[tracing::instrument(name = "textDocument/documentLink", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: DocumentLinkParams,
+    ) -> LSPResult<Option<Vec<DocumentLink>>> {
+        Ok(ide::document_links::document_links(params, &snapshot.db))
     }
 }
 
@@ -96,13 +206,30 @@ impl SyncRequestHandler for ExecuteCommand {
         requester: &mut Requester<'_>,
         params: ExecuteCommandParams,
     ) -> LSPResult<Option<Value>> {
-        let command = ServerCommands::try_from(params.command);
+        let command = ServerCommands::try_from(params);
 
         if let Ok(cmd) = command {
             match cmd {
                 ServerCommands::Reload => {
                     Backend::reload(state, &notifier, requester)?;
                 }
+                ServerCommands::SwapDatabase => {
+                    state.db_swapper.swap(
+                        &mut state.db,
+                        &state.open_files,
+                        &state.workspace_folders,
+                        &state.config,
+                        &state.tricks,
+                        &notifier,
+                    );
+                }
+                ServerCommands::ClearCache => {
+                    Backend::clear_cache(state, &notifier);
+                }
+                // These commands are run in the background, see `routing::execute_command_task`.
+                ServerCommands::ExportExpandedCrate { .. }
+                | ServerCommands::RunTest { .. }
+                | ServerCommands::ScarbUpdate { .. } => {}
             }
         }
 
@@ -110,6 +237,17 @@ impl SyncRequestHandler for ExecuteCommand {
     }
 }
 
+impl BackgroundDocumentRequestHandler for FoldingRangeRequest {
+    #[tracing::instrument(name = "textDocument/foldingRange", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: FoldingRangeParams,
+    ) -> LSPResult<Option<Vec<FoldingRange>>> {
+        Ok(ide::folding_ranges::folding_ranges(params, &snapshot.db))
+    }
+}
+
 impl BackgroundDocumentRequestHandler for HoverRequest {
     #[tracing::instrument(name = "textDocument/hover", skip_all)]
     fn run_with_snapshot(
@@ -117,7 +255,135 @@ impl BackgroundDocumentRequestHandler for HoverRequest {
         _notifier: Notifier,
         params: HoverParams,
     ) -> LSPResult<Option<Hover>> {
-        Ok(ide::hover::hover(params, &snapshot.db))
+        Ok(ide::hover::hover(
+            params,
+            &snapshot.db,
+            snapshot.client_capabilities.hover_markdown_support(),
+        ))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for InlayHintRequest {
+    #[tracing::instrument(name = "textDocument/inlayHint", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: InlayHintParams,
+    ) -> LSPResult<Option<Vec<InlayHint>>> {
+        if is_file_too_large(&snapshot, &params.text_document.uri) {
+            return Ok(None);
+        }
+
+        Ok(ide::inlay_hints::inlay_hints(params, &snapshot.db, &snapshot.config))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for References {
+    #[tracing::instrument(name = "textDocument/references", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: ReferenceParams,
+    ) -> LSPResult<Option<Vec<Location>>> {
+        Ok(ide::navigation::references::references(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for PrepareRenameRequest {
+    #[tracing::instrument(name = "textDocument/prepareRename", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: TextDocumentPositionParams,
+    ) -> LSPResult<Option<PrepareRenameResponse>> {
+        ide::rename::prepare_rename(
+            params,
+            &snapshot.db,
+            snapshot.client_capabilities.workspace_edit_rename_file_support(),
+        )
+        .with_failure_code(ErrorCode::RequestFailed)
+    }
+}
+
+impl BackgroundDocumentRequestHandler for Rename {
+    #[tracing::instrument(name = "textDocument/rename", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: RenameParams,
+    ) -> LSPResult<Option<WorkspaceEdit>> {
+        ide::rename::rename(
+            params,
+            &snapshot.db,
+            snapshot.client_capabilities.workspace_edit_rename_file_support(),
+        )
+        .with_failure_code(ErrorCode::RequestFailed)
+    }
+}
+
+impl BackgroundDocumentRequestHandler for SelectionRangeRequest {
+    #[tracing::instrument(name = "textDocument/selectionRange", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: SelectionRangeParams,
+    ) -> LSPResult<Option<Vec<SelectionRange>>> {
+        Ok(ide::selection_ranges::selection_ranges(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for SignatureHelpRequest {
+    #[tracing::instrument(name = "textDocument/signatureHelp", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: SignatureHelpParams,
+    ) -> LSPResult<Option<SignatureHelp>> {
+        Ok(ide::signature_help::signature_help(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for TypeHierarchyPrepare {
+    #[tracing::instrument(name = "textDocument/prepareTypeHierarchy", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: TypeHierarchyPrepareParams,
+    ) -> LSPResult<Option<Vec<TypeHierarchyItem>>> {
+        Ok(ide::type_hierarchy::prepare(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for TypeHierarchySupertypes {
+    #[tracing::instrument(name = "typeHierarchy/supertypes", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: TypeHierarchySupertypesParams,
+    ) -> LSPResult<Option<Vec<TypeHierarchyItem>>> {
+        Ok(ide::type_hierarchy::supertypes(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for TypeHierarchySubtypes {
+    #[tracing::instrument(name = "typeHierarchy/subtypes", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: TypeHierarchySubtypesParams,
+    ) -> LSPResult<Option<Vec<TypeHierarchyItem>>> {
+        Ok(ide::type_hierarchy::subtypes(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for WorkspaceSymbolRequest {
+    #[tracing::instrument(name = "workspace/symbol", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: WorkspaceSymbolParams,
+    ) -> LSPResult<Option<WorkspaceSymbolResponse>> {
+        Ok(ide::navigation::workspace_symbols::workspace_symbols(params, &snapshot.db))
     }
 }
 
@@ -128,7 +394,40 @@ impl BackgroundDocumentRequestHandler for Formatting {
         _notifier: Notifier,
         params: DocumentFormattingParams,
     ) -> LSPResult<Option<Vec<TextEdit>>> {
-        Ok(ide::formatter::format(params, &snapshot.db))
+        Ok(ide::formatter::format(params, &snapshot.db, &snapshot.config))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for LinkedEditingRange {
+    #[tracing::instrument(name = "textDocument/linkedEditingRange", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: LinkedEditingRangeParams,
+    ) -> LSPResult<Option<LinkedEditingRanges>> {
+        Ok(ide::linked_editing_ranges::linked_editing_ranges(params, &snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for RangeFormatting {
+    #[tracing::instrument(name = "textDocument/rangeFormatting", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: DocumentRangeFormattingParams,
+    ) -> LSPResult<Option<Vec<TextEdit>>> {
+        Ok(ide::formatter::format_range(params, &snapshot.db, &snapshot.config))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for OnTypeFormatting {
+    #[tracing::instrument(name = "textDocument/onTypeFormatting", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: DocumentOnTypeFormattingParams,
+    ) -> LSPResult<Option<Vec<TextEdit>>> {
+        Ok(ide::formatter::format_on_type(params, &snapshot.db, &snapshot.config))
     }
 }
 
@@ -140,23 +439,27 @@ impl SyncNotificationHandler for DidChangeTextDocument {
     )]
     fn run(
         state: &mut State,
-        _notifier: Notifier,
+        notifier: Notifier,
         _requester: &mut Requester<'_>,
         params: DidChangeTextDocumentParams,
     ) -> LSPResult<()> {
-        let text = if let Ok([TextDocumentContentChangeEvent { text, .. }]) =
-            TryInto::<[_; 1]>::try_into(params.content_changes)
-        {
-            text
-        } else {
-            error!("unexpected format of document change");
+        let Some(file) = state.db.file_for_url(&params.text_document.uri) else {
             return Ok(());
         };
 
-        if let Some(file) = state.db.file_for_url(&params.text_document.uri) {
-            state.db.override_file_content(file, Some(text.into()));
+        let old_text = state.db.file_content(file).unwrap_or_default();
+        let Some(text) = lang::lsp::apply_content_changes(
+            &old_text,
+            params.content_changes,
+            state.db.position_encoding(),
+        ) else {
+            error!("document change does not fit in the document");
+            return Ok(());
         };
 
+        check_file_size(state, &notifier, &params.text_document.uri, text.len());
+        state.db.override_file_content(file, Some(text.into()));
+
         Ok(())
     }
 }
@@ -169,6 +472,10 @@ impl SyncNotificationHandler for DidChangeConfiguration {
         requester: &mut Requester<'_>,
         _params: DidChangeConfigurationParams,
     ) -> LSPResult<()> {
+        if state.config_is_fixed {
+            debug!("configuration is fixed, ignoring configuration change");
+            return Ok(());
+        }
         state.config.reload(requester, &state.client_capabilities)
     }
 }
@@ -189,18 +496,82 @@ impl SyncNotificationHandler for DidChangeWatchedFiles {
             }
         }
 
+        // Clear diagnostics of deleted cairo files, which includes old paths of renamed files.
+        // Open files are skipped, as their content is still provided by the client.
+        let deleted_files = params
+            .changes
+            .iter()
+            .filter(|change| {
+                change.typ == FileChangeType::DELETED
+                    && is_cairo_file_path(&change.uri)
+                    && !state.open_files.contains(&change.uri)
+            })
+            .map(|change| change.uri.clone())
+            .collect();
+        state.diagnostics_controller.clear(deleted_files);
+
+        // Forget cached Scarb metadata which depends on changed Scarb files.
+        for change in &params.changes {
+            let Ok(changed_file_path) = change.uri.to_file_path() else { continue };
+            let changed_file_name = changed_file_path.file_name().unwrap_or_default();
+            if [SCARB_TOML, SCARB_LOCK].map(Some).contains(&changed_file_name.to_str()) {
+                state.scarb_toolchain.invalidate_metadata(&changed_file_path);
+            }
+        }
+
         // Reload workspace if a config file has changed.
+        // Changes are debounced, so that the workspace is reloaded at most once per notification.
+        let mut needs_reload = false;
         for change in params.changes {
             let changed_file_path = change.uri.to_file_path().unwrap_or_default();
             let changed_file_name = changed_file_path.file_name().unwrap_or_default();
-            // TODO(pmagiera): react to Scarb.lock. Keep in mind Scarb does save Scarb.lock on each
-            //  metadata call, so it is easy to fall in a loop here.
-            if ["Scarb.toml", "cairo_project.toml"].map(Some).contains(&changed_file_name.to_str())
-            {
-                Backend::reload(state, &notifier, requester)?;
+            match changed_file_name.to_str() {
+                Some(SCARB_TOML | PROJECT_FILE_NAME) => needs_reload = true,
+                // Scarb does save Scarb.lock on each metadata call, including the one made while
+                // reloading, so only changes of its content are reacted to, not to loop forever.
+                Some(SCARB_LOCK) => {
+                    needs_reload |= state.scarb_toolchain.is_lock_changed(&changed_file_path)
+                }
+                _ => {}
             }
         }
 
+        if needs_reload {
+            Backend::reload(state, &notifier, requester)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SyncNotificationHandler for DidChangeWorkspaceFolders {
+    #[tracing::instrument(name = "workspace/didChangeWorkspaceFolders", skip_all)]
+    fn run(
+        state: &mut State,
+        notifier: Notifier,
+        requester: &mut Requester<'_>,
+        params: DidChangeWorkspaceFoldersParams,
+    ) -> LSPResult<()> {
+        // Crates of removed folders are kept until the next database swap, because open files may
+        // still depend on them.
+        for folder in params.event.removed {
+            let Ok(folder_path) = folder.uri.to_file_path() else { continue };
+            state.workspace_folders.remove(&folder_path);
+        }
+
+        for folder in params.event.added {
+            let Ok(folder_path) = folder.uri.to_file_path() else { continue };
+            Backend::load_workspace_folder(
+                &mut state.db,
+                &state.scarb_toolchain,
+                &state.config,
+                &folder_path,
+                &notifier,
+                Some((&state.client_capabilities, requester)),
+            );
+            state.workspace_folders.insert(folder_path);
+        }
+
         Ok(())
     }
 }
@@ -218,6 +589,8 @@ impl SyncNotificationHandler for DidCloseTextDocument {
         params: DidCloseTextDocumentParams,
     ) -> LSPResult<()> {
         state.open_files.remove(&params.text_document.uri);
+        state.too_large_files.remove(&params.text_document.uri);
+        state.semantic_tokens_cache.invalidate(&params.text_document.uri);
         if let Some(file) = state.db.file_for_url(&params.text_document.uri) {
             state.db.override_file_content(file, None);
         }
@@ -234,7 +607,7 @@ impl SyncNotificationHandler for DidOpenTextDocument {
     fn run(
         state: &mut State,
         notifier: Notifier,
-        _requester: &mut Requester<'_>,
+        requester: &mut Requester<'_>,
         params: DidOpenTextDocumentParams,
     ) -> LSPResult<()> {
         let uri = params.text_document.uri;
@@ -250,12 +623,17 @@ impl SyncNotificationHandler for DidOpenTextDocument {
                 &state.config,
                 &path,
                 &notifier,
+                Some((&state.client_capabilities, requester)),
             );
         }
 
+        let text = params.text_document.text;
+        check_file_size(state, &notifier, &uri, text.len());
+
         if let Some(file_id) = state.db.file_for_url(&uri) {
+            state.semantic_tokens_cache.invalidate(&uri);
             state.open_files.insert(uri);
-            state.db.override_file_content(file_id, Some(params.text_document.text.into()));
+            state.db.override_file_content(file_id, Some(text.into()));
         }
 
         Ok(())
@@ -270,7 +648,7 @@ impl SyncNotificationHandler for DidSaveTextDocument {
     )]
     fn run(
         state: &mut State,
-        _notifier: Notifier,
+        notifier: Notifier,
         _requester: &mut Requester<'_>,
         params: DidSaveTextDocumentParams,
     ) -> LSPResult<()> {
@@ -279,10 +657,39 @@ impl SyncNotificationHandler for DidSaveTextDocument {
             state.db.override_file_content(file, None);
         }
 
+        if state.config.diagnostics_scarb_build {
+            if let Some(ProjectManifestPath::Scarb(manifest)) = params
+                .text_document
+                .uri
+                .to_file_path()
+                .ok()
+                .and_then(|path| ProjectManifestPath::discover(&path))
+            {
+                state.scarb_build_controller.build(
+                    state.scarb_toolchain.clone(),
+                    manifest,
+                    state.config.diagnostics_max_per_file,
+                    state.db.position_encoding(),
+                    notifier,
+                );
+            }
+        }
+
         Ok(())
     }
 }
 
+impl BackgroundDocumentRequestHandler for GotoDeclaration {
+    #[tracing::instrument(name = "textDocument/declaration", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: GotoDeclarationParams,
+    ) -> LSPResult<Option<GotoDefinitionResponse>> {
+        Ok(ide::navigation::goto_declaration::goto_declaration(params, &snapshot.db))
+    }
+}
+
 impl BackgroundDocumentRequestHandler for GotoDefinition {
     #[tracing::instrument(name = "textDocument/definition", skip_all)]
     fn run_with_snapshot(
@@ -294,6 +701,17 @@ impl BackgroundDocumentRequestHandler for GotoDefinition {
     }
 }
 
+impl BackgroundDocumentRequestHandler for GotoTypeDefinition {
+    #[tracing::instrument(name = "textDocument/typeDefinition", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: GotoTypeDefinitionParams,
+    ) -> LSPResult<Option<GotoDefinitionResponse>> {
+        Ok(ide::navigation::goto_type_definition::goto_type_definition(params, &snapshot.db))
+    }
+}
+
 impl BackgroundDocumentRequestHandler for Completion {
     #[tracing::instrument(name = "textDocument/completion", skip_all)]
     fn run_with_snapshot(
@@ -301,7 +719,24 @@ impl BackgroundDocumentRequestHandler for Completion {
         _notifier: Notifier,
         params: CompletionParams,
     ) -> LSPResult<Option<CompletionResponse>> {
-        Ok(ide::completion::complete(params, &snapshot.db))
+        Ok(ide::completion::complete(
+            params,
+            &snapshot.db,
+            &snapshot.config,
+            snapshot.client_capabilities.completion_snippet_support(),
+            snapshot.client_capabilities.completion_resolve_additional_text_edits_support(),
+        ))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for ResolveCompletionItem {
+    #[tracing::instrument(name = "completionItem/resolve", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: CompletionItem,
+    ) -> LSPResult<CompletionItem> {
+        Ok(ide::completion::resolve(params, &snapshot.db))
     }
 }
 
@@ -312,7 +747,87 @@ impl BackgroundDocumentRequestHandler for SemanticTokensFullRequest {
         _notifier: Notifier,
         params: SemanticTokensParams,
     ) -> LSPResult<Option<SemanticTokensResult>> {
-        Ok(ide::semantic_highlighting::semantic_highlight_full(params, &snapshot.db))
+        if is_file_too_large(&snapshot, &params.text_document.uri) {
+            return Ok(None);
+        }
+
+        Ok(ide::semantic_highlighting::semantic_highlight_full(
+            params,
+            &snapshot.db,
+            &snapshot.tricks,
+            &snapshot.semantic_tokens_cache,
+        ))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for SemanticTokensFullDeltaRequest {
+    #[tracing::instrument(name = "textDocument/semanticTokens/full/delta", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: SemanticTokensDeltaParams,
+    ) -> LSPResult<Option<SemanticTokensFullDeltaResult>> {
+        if is_file_too_large(&snapshot, &params.text_document.uri) {
+            return Ok(None);
+        }
+
+        Ok(ide::semantic_highlighting::semantic_highlight_full_delta(
+            params,
+            &snapshot.db,
+            &snapshot.tricks,
+            &snapshot.semantic_tokens_cache,
+        ))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for SemanticTokensRangeRequest {
+    #[tracing::instrument(name = "textDocument/semanticTokens/range", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: SemanticTokensRangeParams,
+    ) -> LSPResult<Option<SemanticTokensRangeResult>> {
+        // Ranges are bounded by the viewport, so they are highlighted even in large files.
+        Ok(ide::semantic_highlighting::semantic_highlight_range(
+            params,
+            &snapshot.db,
+            &snapshot.tricks,
+        ))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for DocumentDiagnosticRequest {
+    #[tracing::instrument(name = "textDocument/diagnostic", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: DocumentDiagnosticParams,
+    ) -> LSPResult<DocumentDiagnosticReportResult> {
+        Ok(lang::diagnostics::document_diagnostics(
+            params,
+            &snapshot.db,
+            &snapshot.open_files,
+            &snapshot.config,
+            &snapshot.pull_diagnostics_cache,
+        ))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for WorkspaceDiagnosticRequest {
+    #[tracing::instrument(name = "workspace/diagnostic", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        notifier: Notifier,
+        params: WorkspaceDiagnosticParams,
+    ) -> LSPResult<WorkspaceDiagnosticReportResult> {
+        lang::diagnostics::workspace_diagnostics(
+            params,
+            &snapshot.db,
+            &snapshot.open_files,
+            &snapshot.config,
+            &snapshot.pull_diagnostics_cache,
+            &notifier,
+        )
     }
 }
 
@@ -323,16 +838,49 @@ impl BackgroundDocumentRequestHandler for ProvideVirtualFile {
         _notifier: Notifier,
         params: ProvideVirtualFileRequest,
     ) -> LSPResult<ProvideVirtualFileResponse> {
-        let content = snapshot
-            .db
-            .file_for_url(&params.uri)
-            .and_then(|file_id| snapshot.db.file_content(file_id))
-            .map(|content| content.to_string());
+        let content = if params.uri.scheme() == ide::macros::expand::EXPANSION_SCHEME {
+            ide::macros::expand::expansion_document_content(&snapshot.db, &params.uri)
+        } else {
+            snapshot
+                .db
+                .file_for_url(&params.uri)
+                .and_then(|file_id| snapshot.db.file_content(file_id))
+                .map(|content| content.to_string())
+        };
+
+        let content = match params.range {
+            Some(range) => content.and_then(|content| {
+                text_in_range(&content, range, snapshot.db.position_encoding()).map(Into::into)
+            }),
+            None => content,
+        };
 
         Ok(ProvideVirtualFileResponse { content })
     }
 }
 
+impl BackgroundDocumentRequestHandler for AnalyzedCrates {
+    #[tracing::instrument(name = "cairo/analyzedCrates", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        _params: (),
+    ) -> LSPResult<AnalyzedCratesResponse> {
+        Ok(lang::inspect::crates::describe_analyzed_crates(&snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for ShowSyntaxTree {
+    #[tracing::instrument(name = "cairo/showSyntaxTree", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: TextDocumentPositionParams,
+    ) -> LSPResult<Option<String>> {
+        Ok(ide::syntax_tree::show_syntax_tree(&snapshot.db, &params))
+    }
+}
+
 impl BackgroundDocumentRequestHandler for ViewAnalyzedCrates {
     #[tracing::instrument(name = "cairo/viewAnalyzedCrates", skip_all)]
     fn run_with_snapshot(
@@ -344,17 +892,73 @@ impl BackgroundDocumentRequestHandler for ViewAnalyzedCrates {
     }
 }
 
+impl BackgroundDocumentRequestHandler for ViewCrateGraph {
+    #[tracing::instrument(name = "cairo/viewCrateGraph", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        _params: (),
+    ) -> LSPResult<String> {
+        Ok(lang::inspect::crates::crate_graph_dot(&snapshot.db))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for ViewMemoryUsage {
+    #[tracing::instrument(name = "cairo/viewMemoryUsage", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        _params: (),
+    ) -> LSPResult<String> {
+        Ok(lang::inspect::memory::inspect_memory_usage(&snapshot.db))
+    }
+}
+
 impl BackgroundDocumentRequestHandler for ExpandMacro {
     #[tracing::instrument(name = "cairo/expandMacro", skip_all)]
     fn run_with_snapshot(
         snapshot: StateSnapshot,
         _notifier: Notifier,
         params: TextDocumentPositionParams,
-    ) -> LSPResult<Option<String>> {
-        Ok(ide::macros::expand::expand_macro(&snapshot.db, &params))
+    ) -> LSPResult<Option<ExpandMacroResponse>> {
+        Ok(ide::macros::expand::expand_macro_with_mappings(&snapshot.db, &params))
+    }
+}
+
+impl BackgroundDocumentRequestHandler for ExpandMacroDocument {
+    #[tracing::instrument(name = "cairo/expandMacroDocument", skip_all)]
+    fn run_with_snapshot(
+        snapshot: StateSnapshot,
+        _notifier: Notifier,
+        params: TextDocumentPositionParams,
+    ) -> LSPResult<Option<Url>> {
+        Ok(ide::macros::expand::expansion_document_url(&snapshot.db, &params))
     }
 }
 
 fn is_cairo_file_path(file_path: &Url) -> bool {
     file_path.path().ends_with(".cairo")
 }
+
+/// Notifies the client if an open file of the given length exceeds the size limit set in the
+/// configuration, once until the file is closed or shrinks below the limit.
+fn check_file_size(state: &mut State, notifier: &Notifier, uri: &Url, len: usize) {
+    if !state.config.is_file_too_large(len) {
+        state.too_large_files.remove(uri);
+    } else if state.too_large_files.insert(uri.clone()) {
+        notifier.notify::<FileTooLarge>(FileTooLargeParams {
+            uri: uri.clone(),
+            size_kb: len.div_ceil(1024) as u64,
+            max_file_size_kb: state.config.max_file_size_kb,
+        });
+    }
+}
+
+/// Checks whether the file at `uri` exceeds the size limit set in the configuration.
+fn is_file_too_large(snapshot: &StateSnapshot, uri: &Url) -> bool {
+    snapshot
+        .db
+        .file_for_url(uri)
+        .and_then(|file_id| snapshot.db.file_content(file_id))
+        .is_some_and(|content| snapshot.config.is_file_too_large(content.len()))
+}
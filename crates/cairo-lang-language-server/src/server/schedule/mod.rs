@@ -5,10 +5,19 @@
 // | Commit: 46a457318d8d259376a2b458b3f814b9b795fe69  |
 // +---------------------------------------------------+
 
-use anyhow::Result;
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use lsp_server::{ErrorCode, RequestId};
+use salsa::{Database, Durability};
+use tracing::{error, warn};
 
 use self::task::BackgroundTaskBuilder;
 use self::thread::{JoinHandle, ThreadPriority};
+use crate::lsp::result::LSPError;
 use crate::server::client::{Client, Notifier, Requester, Responder};
 use crate::server::connection::ClientSender;
 use crate::state::State;
@@ -35,13 +44,23 @@ pub fn event_loop_thread(
         .spawn(func)?)
 }
 
-type SyncTaskHook = Box<dyn Fn(&mut State, Notifier)>;
+type SyncTaskHook = Box<dyn Fn(&mut State, Notifier, &mut Requester<'_>)>;
 
 pub struct Scheduler<'s> {
     state: &'s mut State,
     client: Client<'s>,
     background_pool: thread::Pool,
     sync_task_hooks: Vec<SyncTaskHook>,
+    pending_requests: Vec<PendingRequest>,
+}
+
+/// A request handled in the background that is subject to the request timeout.
+struct PendingRequest {
+    id: RequestId,
+    timeout: Duration,
+    deadline: Instant,
+    /// Set once the request has been answered, either by its handler or due to the timeout.
+    responded: Arc<AtomicBool>,
 }
 
 impl<'s> Scheduler<'s> {
@@ -51,6 +70,7 @@ impl<'s> Scheduler<'s> {
             client: Client::new(sender),
             background_pool: thread::Pool::new(),
             sync_task_hooks: Default::default(),
+            pending_requests: Default::default(),
         }
     }
 
@@ -69,13 +89,27 @@ impl<'s> Scheduler<'s> {
                 func(self.state, notifier.clone(), &mut self.client.requester, responder);
 
                 for hook in &self.sync_task_hooks {
-                    hook(self.state, notifier.clone());
+                    hook(self.state, notifier.clone(), &mut self.client.requester);
                 }
             }
-            Task::Background(BackgroundTaskBuilder { schedule, builder: func }) => {
+            Task::Nothing => {}
+            Task::Background(BackgroundTaskBuilder { schedule, request_id, builder: func }) => {
                 let static_func = func(self.state);
                 let notifier = self.client.notifier();
-                let responder = self.client.responder();
+                let mut responder = self.client.responder();
+                if let (Some(id), Some(timeout_ms)) =
+                    (request_id, self.state.config.request_timeout_ms)
+                {
+                    let timeout = Duration::from_millis(timeout_ms);
+                    let responded = Arc::new(AtomicBool::new(false));
+                    responder = responder.with_responded_flag(responded.clone());
+                    self.pending_requests.push(PendingRequest {
+                        id,
+                        timeout,
+                        deadline: Instant::now() + timeout,
+                        responded,
+                    });
+                }
                 let task = move || static_func(notifier, responder);
                 match schedule {
                     BackgroundSchedule::Worker => {
@@ -99,6 +133,49 @@ impl<'s> Scheduler<'s> {
         self.dispatch(Task::local(func));
     }
 
+    /// Returns the earliest deadline among background requests that are still being handled.
+    pub fn next_request_deadline(&mut self) -> Option<Instant> {
+        self.pending_requests.retain(|request| !request.responded.load(Ordering::SeqCst));
+        self.pending_requests.iter().map(|request| request.deadline).min()
+    }
+
+    /// Answers background requests that exceeded their deadline with an error and cancels
+    /// their computation.
+    pub fn cancel_timed_out_requests(&mut self) {
+        let now = Instant::now();
+        let (timed_out, pending): (Vec<_>, Vec<_>) = mem::take(&mut self.pending_requests)
+            .into_iter()
+            .partition(|request| request.deadline <= now);
+        self.pending_requests = pending;
+
+        let responder = self.client.responder();
+        let mut any_timed_out = false;
+        for PendingRequest { id, timeout, responded, .. } in timed_out {
+            if responded.swap(true, Ordering::SeqCst) {
+                continue;
+            }
+            any_timed_out = true;
+
+            warn!("request {id} timed out after {timeout:?}");
+            let result: Result<(), LSPError> = Err(LSPError::new(
+                anyhow!("request timed out after {timeout:?}"),
+                ErrorCode::RequestFailed,
+            ));
+            if let Err(err) = responder.respond(id.clone(), result) {
+                error!("failed to respond to timed out request {id}: {err}");
+            }
+        }
+
+        if any_timed_out {
+            // Salsa can only cancel all running queries at once, so this also cancels other
+            // requests being handled at the moment, which will be answered accordingly.
+            // Running this as a local task makes sure diagnostics are refreshed afterwards.
+            self.local(|state, _, _, _| {
+                state.db.salsa_runtime_mut().synthetic_write(Durability::LOW);
+            });
+        }
+    }
+
     /// Registers a hook to be called each time a synchronous task is executed.
     ///
     /// All hooks are called right after task execution, in the same thread and with the same
@@ -106,8 +183,11 @@ impl<'s> Scheduler<'s> {
     /// This mechanism is useful for doing various bookkeeping in reaction to user interaction
     /// such as scheduling diagnostics computation, starting manual GC, etc.
     /// This includes reacting to state changes, though note that this hook will be called even
-    /// after tasks that do not mutate the state.
-    pub fn on_sync_task(&mut self, hook: impl Fn(&mut State, Notifier) + 'static) {
+    /// after tasks that do not mutate the state, except for [`Task::nothing`].
+    pub fn on_sync_task(
+        &mut self,
+        hook: impl Fn(&mut State, Notifier, &mut Requester<'_>) + 'static,
+    ) {
         self.sync_task_hooks.push(Box::new(hook));
     }
 }
@@ -41,6 +41,9 @@ pub enum BackgroundSchedule {
 pub enum Task<'s> {
     Background(BackgroundTaskBuilder<'s>),
     Sync(SyncTask<'s>),
+    /// A task that does nothing, so unlike other sync tasks, it is not followed by sync task
+    /// hooks.
+    Nothing,
 }
 
 // The reason why this isn't just a 'static background closure
@@ -54,6 +57,9 @@ pub enum Task<'s> {
 // task pool.
 pub struct BackgroundTaskBuilder<'s> {
     pub schedule: BackgroundSchedule,
+    /// ID of the request this task answers, if any.
+    /// Such tasks are subject to the request timeout.
+    pub request_id: Option<RequestId>,
     pub builder: BackgroundFnBuilder<'s>,
 }
 
@@ -67,7 +73,24 @@ impl<'s> Task<'s> {
         schedule: BackgroundSchedule,
         func: impl FnOnce(&State) -> Box<dyn FnOnce(Notifier, Responder) + Send + 'static> + 's,
     ) -> Self {
-        Self::Background(BackgroundTaskBuilder { schedule, builder: Box::new(func) })
+        Self::Background(BackgroundTaskBuilder {
+            schedule,
+            request_id: None,
+            builder: Box::new(func),
+        })
+    }
+
+    /// Creates a new background task that answers the request with the given `id`.
+    pub fn background_request(
+        id: RequestId,
+        schedule: BackgroundSchedule,
+        func: impl FnOnce(&State) -> Box<dyn FnOnce(Notifier, Responder) + Send + 'static> + 's,
+    ) -> Self {
+        Self::Background(BackgroundTaskBuilder {
+            schedule,
+            request_id: Some(id),
+            builder: Box::new(func),
+        })
     }
 
     /// Creates a new local task.
@@ -89,8 +112,8 @@ impl<'s> Task<'s> {
         })
     }
 
-    /// Creates a local task that does nothing.
+    /// Creates a task that does nothing.
     pub fn nothing() -> Self {
-        Self::local(move |_, _, _, _| {})
+        Self::Nothing
     }
 }
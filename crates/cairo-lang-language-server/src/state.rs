@@ -1,5 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use cairo_lang_diagnostics::Diagnostics;
@@ -11,8 +12,12 @@ use salsa::ParallelDatabase;
 
 use crate::Tricks;
 use crate::config::Config;
+use crate::ide::semantic_highlighting::SemanticTokensCache;
 use crate::lang::db::{AnalysisDatabase, AnalysisDatabaseSwapper};
-use crate::lang::diagnostics::DiagnosticsController;
+use crate::lang::diagnostics::{
+    DiagnosticsController, PullDiagnosticsCache, ScarbBuildController, ScarbBuildDiagnostics,
+};
+use crate::lang::lsp::PositionEncoding;
 use crate::server::client::Client;
 use crate::server::connection::ClientSender;
 use crate::toolchain::scarb::ScarbToolchain;
@@ -21,12 +26,24 @@ use crate::toolchain::scarb::ScarbToolchain;
 pub struct State {
     pub db: AnalysisDatabase,
     pub open_files: Owned<HashSet<Url>>,
+    /// Root directories of the workspace folders opened in the client.
+    pub workspace_folders: BTreeSet<PathBuf>,
+    /// Open files the client has been notified about exceeding [`Config::max_file_size_kb`].
+    pub too_large_files: HashSet<Url>,
     pub config: Owned<Config>,
+    /// Whether the configuration was provided upfront, in which case it is never pulled from the
+    /// client.
+    pub config_is_fixed: bool,
     pub client_capabilities: Owned<ClientCapabilities>,
     pub scarb_toolchain: ScarbToolchain,
     pub db_swapper: AnalysisDatabaseSwapper,
     pub tricks: Owned<Tricks>,
     pub diagnostics_controller: DiagnosticsController,
+    pub semantic_tokens_cache: Arc<SemanticTokensCache>,
+    pub pull_diagnostics_cache: Arc<PullDiagnosticsCache>,
+    pub scarb_build_controller: ScarbBuildController,
+    pub scarb_build_diagnostics: Arc<ScarbBuildDiagnostics>,
+    pub background_requests: BackgroundRequests,
 }
 
 #[derive(Clone, Default, PartialEq, Eq)]
@@ -47,21 +64,33 @@ impl State {
     pub fn new(
         sender: ClientSender,
         client_capabilities: ClientCapabilities,
+        workspace_folders: BTreeSet<PathBuf>,
         tricks: Tricks,
+        config: Option<Config>,
     ) -> Self {
         let notifier = Client::new(sender).notifier();
         let scarb_toolchain = ScarbToolchain::new(notifier);
         let db_swapper = AnalysisDatabaseSwapper::new(scarb_toolchain.clone());
+        let scarb_build_diagnostics = Arc::<ScarbBuildDiagnostics>::default();
+        let position_encoding = PositionEncoding::negotiate(&client_capabilities);
 
         Self {
-            db: AnalysisDatabase::new(&tricks),
+            db: AnalysisDatabase::new(&tricks, position_encoding),
             open_files: Default::default(),
-            config: Default::default(),
+            workspace_folders,
+            too_large_files: Default::default(),
+            config_is_fixed: config.is_some(),
+            config: Owned::new(config.unwrap_or_default().into()),
             client_capabilities: Owned::new(client_capabilities.into()),
             scarb_toolchain,
             db_swapper,
             tricks: Owned::new(tricks.into()),
             diagnostics_controller: DiagnosticsController::new(),
+            semantic_tokens_cache: Default::default(),
+            pull_diagnostics_cache: Default::default(),
+            scarb_build_controller: ScarbBuildController::new(scarb_build_diagnostics.clone()),
+            scarb_build_diagnostics,
+            background_requests: Default::default(),
         }
     }
 
@@ -70,6 +99,11 @@ impl State {
             db: self.db.snapshot(),
             open_files: self.open_files.snapshot(),
             config: self.config.snapshot(),
+            client_capabilities: self.client_capabilities.snapshot(),
+            tricks: self.tricks.snapshot(),
+            semantic_tokens_cache: self.semantic_tokens_cache.clone(),
+            pull_diagnostics_cache: self.pull_diagnostics_cache.clone(),
+            scarb_build_diagnostics: self.scarb_build_diagnostics.clone(),
         }
     }
 }
@@ -79,10 +113,41 @@ pub struct StateSnapshot {
     pub db: salsa::Snapshot<AnalysisDatabase>,
     pub open_files: Snapshot<HashSet<Url>>,
     pub config: Snapshot<Config>,
+    pub client_capabilities: Snapshot<ClientCapabilities>,
+    pub tricks: Snapshot<Tricks>,
+    pub semantic_tokens_cache: Arc<SemanticTokensCache>,
+    pub pull_diagnostics_cache: Arc<PullDiagnosticsCache>,
+    pub scarb_build_diagnostics: Arc<ScarbBuildDiagnostics>,
 }
 
 impl std::panic::UnwindSafe for StateSnapshot {}
 
+/// Tracks requests handled in the background with a snapshot of the state.
+///
+/// Mutating the analysis database cancels all queries running on its snapshots, so mutations
+/// which can be postponed, like swapping the database, should wait until no requests are in
+/// flight.
+#[derive(Debug, Default)]
+pub struct BackgroundRequests(Arc<()>);
+
+/// Marks a request as being in flight until dropped.
+#[derive(Debug)]
+pub struct BackgroundRequestGuard {
+    _token: Arc<()>,
+}
+
+impl BackgroundRequests {
+    /// Marks a request as being in flight until the returned guard is dropped.
+    pub fn start(&self) -> BackgroundRequestGuard {
+        BackgroundRequestGuard { _token: self.0.clone() }
+    }
+
+    /// Checks whether any request is in flight.
+    pub fn any_in_flight(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+}
+
 /// Represents owned value that can be mutated.
 /// Allows creating snapshot from self.
 #[derive(Debug, Default)]
@@ -1,15 +1,35 @@
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, OnceLock};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{Context, Result, bail};
-use lsp_types::notification::Notification;
+use lsp_types::notification::{Notification, ShowMessage};
+use lsp_types::{MessageType, ShowMessageParams};
 use scarb_metadata::{Metadata, MetadataCommand};
-use tracing::{error, warn};
+use tracing::{error, info, trace, warn};
 
 use crate::env_config;
 use crate::server::client::Notifier;
 
+#[cfg(test)]
+#[path = "scarb_test.rs"]
+mod test;
+
 pub const SCARB_TOML: &str = "Scarb.toml";
+pub const SCARB_LOCK: &str = "Scarb.lock";
+
+/// Time after which a `scarb test` run is cancelled.
+const TEST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Interval of checking whether a `scarb test` run has finished or should be cancelled.
+const TEST_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 // TODO(mkaput): Collect `scarb --version` and display it in the editor UI.
 /// The ultimate object for invoking Scarb.
@@ -20,9 +40,22 @@ pub const SCARB_TOML: &str = "Scarb.toml";
 /// This object is small and cheap to clone, so it can be passed around freely.
 #[derive(Clone)]
 pub struct ScarbToolchain {
-    /// Cached path to the `scarb` executable.
+    /// Path to the `scarb` executable set with [`ScarbToolchain::set_configured_path`], which
+    /// takes precedence over the one found in the environment.
+    configured_path: Arc<RwLock<Option<PathBuf>>>,
+
+    /// Cached path to the `scarb` executable found in the environment.
     scarb_path_cell: Arc<OnceLock<Option<PathBuf>>>,
 
+    /// Results of `scarb metadata` calls, keyed by the path to `Scarb.toml`.
+    metadata_cache: Arc<Mutex<HashMap<PathBuf, CachedMetadata>>>,
+
+    /// Digests of `Scarb.lock` contents, as last seen by this object.
+    lock_digests: Arc<Mutex<LockDigests>>,
+
+    /// Flag cancelling the `scarb test` run in progress, if there is one.
+    test_run: Arc<Mutex<Option<Arc<AtomicBool>>>>,
+
     /// The notifier object used to send notifications to the language client.
     notifier: Notifier,
 
@@ -34,18 +67,81 @@ pub struct ScarbToolchain {
 
 impl ScarbToolchain {
     /// Constructs a new [`ScarbToolchain`].
+    ///
+    /// The path to the `scarb` executable given in [`env_config::CAIRO_LS_SCARB`] is used right
+    /// away, before the client configuration is loaded.
     pub fn new(notifier: Notifier) -> Self {
-        ScarbToolchain { scarb_path_cell: Default::default(), notifier, is_silent: false }
+        let toolchain = ScarbToolchain {
+            configured_path: Default::default(),
+            scarb_path_cell: Default::default(),
+            metadata_cache: Default::default(),
+            lock_digests: Default::default(),
+            test_run: Default::default(),
+            notifier,
+            is_silent: false,
+        };
+        toolchain.set_configured_path(None);
+        toolchain
+    }
+
+    /// Sets the path to the `scarb` executable configured by the user in the client.
+    ///
+    /// The path given in [`env_config::CAIRO_LS_SCARB`] overrides the configured one, and both
+    /// take precedence over the one in [`env_config::SCARB`].
+    /// Paths which do not point to a file are ignored, and the user is notified about them.
+    pub fn set_configured_path(&self, config_path: Option<PathBuf>) {
+        let (source, path) = match env_config::scarb_path_override() {
+            Some(path) => (env_config::CAIRO_LS_SCARB, Some(path)),
+            None => ("cairo1.scarbPath", config_path),
+        };
+
+        let path = path.filter(|path| {
+            if path.is_file() {
+                info!("using scarb executable from {source}: {}", path.display());
+                return true;
+            }
+
+            let message = format!(
+                "Scarb executable set in {source} does not exist: {}. Falling back to the one \
+                 found in the environment.",
+                path.display()
+            );
+            warn!("{message}");
+            if !self.is_silent {
+                self.notifier.notify::<ShowMessage>(ShowMessageParams {
+                    typ: MessageType::WARNING,
+                    message,
+                });
+            }
+            false
+        });
+
+        let mut configured_path =
+            self.configured_path.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if *configured_path != path {
+            // Another Scarb may resolve workspaces differently.
+            self.metadata_cache().clear();
+            *configured_path = path;
+        }
     }
 
     /// Finds the path to the `scarb` executable to use.
     ///
     /// This method may send notifications to the language client if there are any actionable issues
     /// with the found `scarb` installation or if it could not be found.
-    fn discover(&self) -> Option<&Path> {
+    fn discover(&self) -> Option<PathBuf> {
+        let configured_path =
+            self.configured_path.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        if configured_path.is_some() {
+            return configured_path;
+        }
+
         self.scarb_path_cell
             .get_or_init(|| {
                 let path = env_config::scarb_path();
+                if let Some(path) = &path {
+                    info!("using scarb executable from {}: {}", env_config::SCARB, path.display());
+                }
                 // TODO(mkaput): Perhaps we should display this notification again after reloading?
                 if path.is_none() {
                     if self.is_silent {
@@ -59,8 +155,7 @@ impl ScarbToolchain {
                 }
                 path
             })
-            .as_ref()
-            .map(PathBuf::as_path)
+            .clone()
     }
 
     /// Creates a clone instance of this object that will be in _silent mode_.
@@ -73,6 +168,8 @@ impl ScarbToolchain {
             self.clone()
         } else {
             Self {
+                configured_path: self.configured_path.clone(),
+
                 // Disassociate this instance from the shared path cell if it has not been
                 // initialized yet.
                 //
@@ -90,6 +187,12 @@ impl ScarbToolchain {
                     None => Default::default(),
                 },
 
+                metadata_cache: self.metadata_cache.clone(),
+
+                lock_digests: self.lock_digests.clone(),
+
+                test_run: self.test_run.clone(),
+
                 notifier: self.notifier.clone(),
 
                 is_silent: true,
@@ -106,12 +209,20 @@ impl ScarbToolchain {
     ///
     /// This method may send notifications to the language client, informing the user about
     /// the progress of the operation or any actionable issues.
+    ///
+    /// The result is cached and reused as long as neither the manifests of the workspace nor its
+    /// `Scarb.lock` are modified.
     #[tracing::instrument(skip(self))]
     pub fn metadata(&self, manifest: &Path) -> Result<Metadata> {
         let Some(scarb_path) = self.discover() else {
             bail!("could not find scarb executable");
         };
 
+        if let Some(cached) = self.metadata_cache().get(manifest).filter(|c| c.is_fresh()) {
+            trace!("reusing cached scarb metadata");
+            return Ok(cached.metadata.clone());
+        }
+
         if !self.is_silent {
             self.notifier.notify::<ScarbResolvingStart>(());
         }
@@ -127,8 +238,232 @@ impl ScarbToolchain {
             self.notifier.notify::<ScarbResolvingFinish>(());
         }
 
+        if let Ok(metadata) = &result {
+            let cached = CachedMetadata::new(manifest, metadata.clone());
+            // Scarb may have just rewritten the lock, which must not be taken for a user change.
+            self.lock_digests().is_changed(&cached.lock);
+            self.metadata_cache().insert(manifest.to_path_buf(), cached);
+        }
+
         result
     }
+
+    /// Forgets cached results of `scarb metadata` which depend on the changed file.
+    ///
+    /// Scarb rewrites `Scarb.lock` on each `scarb metadata` call, so results are forgotten after
+    /// changes to it only if it was modified after they were computed.
+    pub fn invalidate_metadata(&self, changed_file: &Path) {
+        self.metadata_cache().retain(|_, cached| !cached.is_invalidated_by(changed_file));
+    }
+
+    /// Forgets all cached results of `scarb metadata`.
+    pub fn clear_metadata_cache(&self) {
+        self.metadata_cache().clear();
+    }
+
+    fn metadata_cache(&self) -> MutexGuard<'_, HashMap<PathBuf, CachedMetadata>> {
+        self.metadata_cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Checks whether the content of the `Scarb.lock` file has changed since it was last seen,
+    /// either by this method or by a `scarb metadata` call.
+    ///
+    /// Scarb rewrites `Scarb.lock` on each `scarb metadata` call, so reacting to every change of
+    /// this file would make reloading the workspace loop forever. Comparing contents lets through
+    /// only actual changes, like dependency bumps.
+    pub fn is_lock_changed(&self, lock: &Path) -> bool {
+        self.lock_digests().is_changed(lock)
+    }
+
+    fn lock_digests(&self) -> MutexGuard<'_, LockDigests> {
+        self.lock_digests.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Calls `scarb test` for the given `Scarb.toml`, running only tests whose full path contains
+    /// the given filter.
+    ///
+    /// This is a blocking operation that may be long-running, as it compiles the package before
+    /// running the tests. It should only be called from within a background task.
+    /// Only one run is in progress at a time: starting a run cancels the previous one, and runs
+    /// taking longer than [`TEST_TIMEOUT`] are cancelled too, killing the process.
+    ///
+    /// The output of the command is captured and returned, because the standard output of this
+    /// process is used for communicating with the language client.
+    #[tracing::instrument(skip(self))]
+    pub fn test(&self, manifest: &Path, filter: &str) -> Result<Output> {
+        let Some(scarb_path) = self.discover() else {
+            bail!("could not find scarb executable");
+        };
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Some(previous) = self.test_run().replace(cancelled.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+
+        let child = Command::new(scarb_path)
+            .arg("--manifest-path")
+            .arg(manifest)
+            .args(["test", "--filter", filter])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("failed to execute: scarb test")?;
+        wait_with_output(child, TEST_TIMEOUT, &cancelled).context("failed to execute: scarb test")
+    }
+
+    fn test_run(&self) -> MutexGuard<'_, Option<Arc<AtomicBool>>> {
+        self.test_run.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Calls `scarb update` for the given `Scarb.toml`, updating dependencies in `Scarb.lock` to
+    /// their latest compatible versions.
+    ///
+    /// This is a blocking operation that may be long-running, as it does network requests. It
+    /// should only be called from within a background task.
+    ///
+    /// The output of the command is captured and returned, because the standard output of this
+    /// process is used for communicating with the language client.
+    #[tracing::instrument(skip(self))]
+    pub fn update(&self, manifest: &Path) -> Result<Output> {
+        let Some(scarb_path) = self.discover() else {
+            bail!("could not find scarb executable");
+        };
+
+        Command::new(scarb_path)
+            .arg("--manifest-path")
+            .arg(manifest)
+            .arg("update")
+            .output()
+            .context("failed to execute: scarb update")
+    }
+
+    /// Calls `scarb build` for the given `Scarb.toml`, with messages printed in JSON format.
+    ///
+    /// This is a blocking operation that may be long-running. It should only be called from within
+    /// a background task.
+    ///
+    /// The output of the command is captured and returned, because the standard output of this
+    /// process is used for communicating with the language client.
+    #[tracing::instrument(skip(self))]
+    pub fn build(&self, manifest: &Path) -> Result<Output> {
+        let Some(scarb_path) = self.discover() else {
+            bail!("could not find scarb executable");
+        };
+
+        Command::new(scarb_path)
+            .arg("--manifest-path")
+            .arg(manifest)
+            .args(["--json", "build"])
+            .output()
+            .context("failed to execute: scarb build")
+    }
+}
+
+/// Result of a `scarb metadata` call.
+struct CachedMetadata {
+    metadata: Metadata,
+    /// Path to `Scarb.lock` of the workspace.
+    lock: PathBuf,
+    /// Files which the result depends on, with their modification times at the time it was
+    /// computed: the manifest it was called for, manifests of workspace members and `Scarb.lock`.
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl CachedMetadata {
+    fn new(manifest: &Path, metadata: Metadata) -> Self {
+        let lock = metadata.workspace.root.join(SCARB_LOCK).into_std_path_buf();
+        let member_manifests = metadata
+            .packages
+            .iter()
+            .filter(|package| metadata.workspace.members.contains(&package.id))
+            .map(|package| package.manifest_path.clone().into_std_path_buf());
+
+        let mut paths = vec![manifest.to_path_buf(), lock.clone()];
+        for path in member_manifests {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        let stamps = paths.into_iter().map(|path| (path.clone(), modified(&path))).collect();
+
+        Self { metadata, lock, stamps }
+    }
+
+    /// Checks whether none of the files the result depends on have been modified.
+    fn is_fresh(&self) -> bool {
+        self.stamps.iter().all(|(path, stamp)| modified(path) == *stamp)
+    }
+
+    /// Checks whether a change of the file makes the result outdated.
+    fn is_invalidated_by(&self, changed_file: &Path) -> bool {
+        if changed_file == self.lock {
+            !self.is_fresh()
+        } else {
+            self.stamps.iter().any(|(path, _)| path == changed_file)
+        }
+    }
+}
+
+/// Digests of `Scarb.lock` files contents, keyed by their paths.
+#[derive(Default)]
+struct LockDigests(HashMap<PathBuf, Option<u64>>);
+
+impl LockDigests {
+    /// Checks whether the content of the file differs from the one seen last time, and remembers
+    /// the current one.
+    ///
+    /// Files which were not seen before are considered changed.
+    fn is_changed(&mut self, lock: &Path) -> bool {
+        let digest = fs::read(lock).ok().map(|content| {
+            let mut hasher = DefaultHasher::new();
+            content.hash(&mut hasher);
+            hasher.finish()
+        });
+        self.0.insert(lock.to_path_buf(), digest) != Some(digest)
+    }
+}
+
+/// Waits for the child process to exit and collects its output, killing it once `timeout` elapses
+/// or `cancelled` is set.
+fn wait_with_output(mut child: Child, timeout: Duration, cancelled: &AtomicBool) -> Result<Output> {
+    // Pipes are drained in separate threads, so that the process does not block on full pipes.
+    let stdout = child.stdout.take().map(read_to_end_in_thread);
+    let stderr = child.stderr.take().map(read_to_end_in_thread);
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if cancelled.load(Ordering::Relaxed) || Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            if cancelled.load(Ordering::Relaxed) {
+                bail!("cancelled by another run");
+            }
+            bail!("timed out after {timeout:?}");
+        }
+        thread::sleep(TEST_POLL_INTERVAL);
+    };
+
+    let join = |reader: Option<JoinHandle<Vec<u8>>>| {
+        reader.map(|reader| reader.join().unwrap_or_default()).unwrap_or_default()
+    };
+    Ok(Output { status, stdout: join(stdout), stderr: join(stderr) })
+}
+
+/// Reads the pipe to the end in a new thread.
+fn read_to_end_in_thread(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = vec![];
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+/// Returns the modification time of the file, if it exists.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
 }
 
 #[derive(Debug)]
@@ -0,0 +1,67 @@
+use std::fs;
+use std::process::{Command, Stdio};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use assert_fs::TempDir;
+use assert_fs::prelude::*;
+
+use super::{LockDigests, SCARB_LOCK, wait_with_output};
+
+#[test]
+fn lock_digests() {
+    let t = TempDir::new().unwrap();
+    let lock = t.child(SCARB_LOCK);
+    let mut digests = LockDigests::default();
+
+    // Not seen before.
+    assert!(digests.is_changed(lock.path()));
+    assert!(!digests.is_changed(lock.path()));
+
+    lock.write_str("version = 1\n").unwrap();
+    assert!(digests.is_changed(lock.path()));
+    assert!(!digests.is_changed(lock.path()));
+
+    // Rewriting the same content, like Scarb does on each `scarb metadata` call.
+    lock.write_str("version = 1\n").unwrap();
+    assert!(!digests.is_changed(lock.path()));
+
+    lock.write_str("version = 1\n\n[[package]]\nname = \"foo\"\n").unwrap();
+    assert!(digests.is_changed(lock.path()));
+
+    fs::remove_file(lock.path()).unwrap();
+    assert!(digests.is_changed(lock.path()));
+}
+
+#[cfg(unix)]
+#[test]
+fn wait_with_output_kills_process_after_timeout() {
+    let child = Command::new("sleep").arg("10").spawn().unwrap();
+    let start = Instant::now();
+    let err =
+        wait_with_output(child, Duration::from_millis(100), &AtomicBool::new(false)).unwrap_err();
+    assert!(err.to_string().contains("timed out"), "{err}");
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[cfg(unix)]
+#[test]
+fn wait_with_output_kills_cancelled_process() {
+    let child = Command::new("sleep").arg("10").spawn().unwrap();
+    let err = wait_with_output(child, Duration::from_secs(60), &AtomicBool::new(true)).unwrap_err();
+    assert!(err.to_string().contains("cancelled"), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn wait_with_output_collects_output() {
+    let child = Command::new("echo")
+        .arg("hello")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let output = wait_with_output(child, Duration::from_secs(60), &AtomicBool::new(false)).unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"hello\n");
+}
@@ -1,10 +1,24 @@
+use std::fs;
+
+use cairo_lang_filesystem::db::Edition;
 use cairo_lang_language_server::lsp;
+use cairo_lang_language_server::lsp::ext::{
+    AnalyzedCratesChanged, AnalyzedCratesChangedParams, ScarbMetadataFailed, ScarbUpdateFinished,
+};
 use indoc::indoc;
-use lsp_types::{ExecuteCommandParams, lsp_request};
+use lsp_server::Message;
+use lsp_types::notification::Notification;
+use lsp_types::{
+    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidCloseTextDocumentParams,
+    ExecuteCommandParams, FileChangeType, FileEvent, HoverContents, HoverParams, MarkupContent,
+    Position, Range, TextDocumentContentChangeEvent, TextDocumentPositionParams,
+    VersionedTextDocumentIdentifier, lsp_notification, lsp_request,
+};
 use pretty_assertions::assert_eq;
+use serde_json::json;
 
 use crate::support::normalize::normalize;
-use crate::support::sandbox;
+use crate::support::{MockClient, sandbox};
 
 #[test]
 fn cairo_projects() {
@@ -36,7 +50,9 @@ fn cairo_projects() {
 
     let output = ls.send_request::<lsp::ext::ViewAnalyzedCrates>(());
 
-    assert_eq!(normalize(&ls, output), indoc! {r#"
+    assert_eq!(
+        normalize(&ls, output),
+        indoc! {r#"
             # Analyzed Crates
 
             - `core`: `["[CAIRO_SOURCE]/corelib/src/lib.cairo"]`
@@ -101,7 +117,8 @@ fn cairo_projects() {
                     },
                 }
                 ```
-        "#});
+        "#}
+    );
 }
 
 #[test]
@@ -128,4 +145,393 @@ fn test_reload() {
     let actual = ls.send_request::<lsp::ext::ViewAnalyzedCrates>(());
 
     assert_eq!(expected, actual);
+
+    // Nothing has changed, so the server must not notify about changed crates.
+    assert!(!ls.trace().iter().any(|message| matches!(
+        message,
+        Message::Notification(notification) if notification.method == AnalyzedCratesChanged::METHOD
+    )));
+}
+
+#[test]
+fn test_reload_notifies_about_changed_crates() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+                world = "world"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+            "world/lib.cairo" => "fn main() {}",
+            "world2/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    fs::write(
+        ls.as_ref().file_absolute_path("cairo_project.toml"),
+        indoc! {r#"
+        [crate_roots]
+        hello = "src"
+        world = "world2"
+        foo = "world"
+    "#},
+    )
+    .unwrap();
+
+    ls.send_request::<lsp_request!("workspace/executeCommand")>(ExecuteCommandParams {
+        command: "cairo.reload".into(),
+        ..Default::default()
+    });
+
+    let params = ls.wait_for_notification::<AnalyzedCratesChanged>(|_| true);
+    assert_eq!(
+        params,
+        AnalyzedCratesChangedParams {
+            added: vec!["foo".to_owned()],
+            removed: vec![],
+            changed: vec!["world".to_owned()],
+        }
+    );
+}
+
+#[test]
+fn test_reload_notifies_about_removed_crates() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+                world = "world"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+            "world/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    fs::write(
+        ls.as_ref().file_absolute_path("cairo_project.toml"),
+        indoc! {r#"
+        [crate_roots]
+        hello = "src"
+    "#},
+    )
+    .unwrap();
+
+    ls.send_request::<lsp_request!("workspace/executeCommand")>(ExecuteCommandParams {
+        command: "cairo.reload".into(),
+        ..Default::default()
+    });
+
+    let params = ls.wait_for_notification::<AnalyzedCratesChanged>(|_| true);
+    assert_eq!(
+        params,
+        AnalyzedCratesChangedParams {
+            added: vec![],
+            removed: vec!["world".to_owned()],
+            changed: vec![],
+        }
+    );
+
+    let analyzed_crates = ls.send_request::<lsp::ext::ViewAnalyzedCrates>(());
+    assert!(!analyzed_crates.contains("`world`"));
+}
+
+#[test]
+fn scarb_metadata_failure_is_reported() {
+    let mut ls = sandbox! {
+        files {
+            // The package has no version, so `scarb metadata` fails even if Scarb is available.
+            "Scarb.toml" => indoc! {r#"
+                [package]
+                name = "hello"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open("src/lib.cairo");
+
+    let params = ls.wait_for_notification::<ScarbMetadataFailed>(|_| true);
+    assert_eq!(params.manifest_path, ls.as_ref().file_absolute_path("Scarb.toml"));
+    assert!(!params.error.is_empty());
+}
+
+#[test]
+fn scarb_update_failure_is_reported() {
+    let mut ls = sandbox! {
+        files {
+            // The package has no version, so `scarb update` fails even if Scarb is available.
+            "Scarb.toml" => indoc! {r#"
+                [package]
+                name = "hello"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    let manifest_path = ls.as_ref().file_absolute_path("Scarb.toml");
+    ls.send_request::<lsp_request!("workspace/executeCommand")>(ExecuteCommandParams {
+        command: "cairo.scarbUpdate".into(),
+        arguments: vec![json!({ "manifestPath": manifest_path })],
+        ..Default::default()
+    });
+
+    let params = ls.wait_for_notification::<ScarbUpdateFinished>(|_| true);
+    assert_eq!(params.manifest_path, manifest_path);
+    assert!(!params.success);
+    assert!(!params.output.is_empty());
+}
+
+#[test]
+fn detached_file_is_analyzed_as_single_file_crate() {
+    let mut ls = sandbox! {
+        files {
+            "src/sub/hello.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/sub/hello.cairo");
+
+    let output = ls.send_request::<lsp::ext::ViewAnalyzedCrates>(());
+    let output = normalize(&ls, output);
+    assert!(output.contains("- `hello`: `[\"[ROOT]/src/sub/hello.cairo\"]`"), "{output}");
+}
+
+/// The crate of a file which does not belong to any known project is detected when the file is
+/// opened.
+#[test]
+fn crate_is_detected_when_file_is_opened() {
+    let mut ls = sandbox! {
+        files {
+            "scripts/hello.cairo" => "fn main() {}",
+        }
+    };
+
+    let crate_names = |ls: &mut MockClient| {
+        ls.send_request::<lsp::ext::AnalyzedCrates>(())
+            .crates
+            .into_iter()
+            .map(|cr| cr.name)
+            .collect::<Vec<_>>()
+    };
+    assert!(!crate_names(&mut ls).contains(&"hello".to_owned()));
+
+    ls.open_and_wait_for_diagnostics("scripts/hello.cairo");
+
+    let hello = ls
+        .send_request::<lsp::ext::AnalyzedCrates>(())
+        .crates
+        .into_iter()
+        .find(|cr| cr.name == "hello")
+        .expect("crate of the opened file should be detected");
+    assert_eq!(hello.source_file_urls, [ls.doc_id("scripts/hello.cairo").uri]);
+}
+
+#[test]
+fn analyzed_crates_are_described() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "hello"
+                world = "world"
+
+                [config.override.hello]
+                edition = "2023_10"
+
+                [config.override.hello.dependencies]
+                world = {}
+            "#},
+            "hello/lib.cairo" => "fn main() {}",
+            "world/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("hello/lib.cairo");
+
+    let response = ls.send_request::<lsp::ext::AnalyzedCrates>(());
+
+    let crates = response
+        .crates
+        .iter()
+        .map(|cr| (cr.name.as_str(), cr.edition, cr.dependencies.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        crates,
+        [
+            ("core", Edition::V2024_07, vec![]),
+            ("hello", Edition::V2023_10, vec!["world".to_owned()]),
+            ("world", Edition::V2023_01, vec![]),
+        ]
+    );
+
+    let hello = &response.crates[1];
+    assert_eq!(hello.source_file_urls, [ls.doc_id("hello/lib.cairo").uri]);
+    assert_eq!(hello.version, None);
+
+    assert!(response.inline_macros.iter().any(|name| name == "array"));
+}
+
+#[test]
+fn crate_graph_is_exported_as_dot() {
+    let mut ls = sandbox! {
+        files {
+            "project/cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "hello"
+                world = "world"
+
+                [config.override.hello]
+                edition = "2024_07"
+
+                [config.override.hello.dependencies]
+                world = {}
+            "#},
+            "project/hello/lib.cairo" => "fn main() {}",
+            "project/world/lib.cairo" => "fn main() {}",
+            "detached/foo.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("project/hello/lib.cairo");
+    ls.open_and_wait_for_diagnostics("detached/foo.cairo");
+
+    let output = ls.send_request::<lsp::ext::ViewCrateGraph>(());
+
+    assert_eq!(
+        output,
+        indoc! {r#"
+        digraph crates {
+            c0 [label="core\ncorelib"];
+            c1 [label="foo\nsingle-file crate"];
+            c2 [label="hello\ncairo project crate"];
+            c3 [label="world\ncairo project crate"];
+            c1 -> c0 [style=dashed];
+            c2 -> c3;
+            c2 -> c0 [style=dashed];
+            c3 -> c0 [style=dashed];
+        }
+    "#}
+    );
+}
+
+#[test]
+fn test_reload_keeps_unsaved_changes() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "fn main() {}\n",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    let uri = ls.doc_id("src/lib.cairo").uri;
+    // Inserts the text at the start of the file.
+    let change = |version: i32, text: &str| DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier { uri: uri.clone(), version },
+        content_changes: vec![TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(0, 0), Position::new(0, 0))),
+            range_length: None,
+            text: text.to_owned(),
+        }],
+    };
+
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(change(
+        1,
+        "fn foo() { missing_a(); }\n",
+    ));
+    ls.wait_for_notification::<lsp_notification!("textDocument/publishDiagnostics")>(|params| {
+        params.uri == uri && params.diagnostics.len() == 1
+    });
+
+    ls.send_request::<lsp_request!("workspace/executeCommand")>(ExecuteCommandParams {
+        command: "cairo.reload".into(),
+        ..Default::default()
+    });
+
+    let hover = ls
+        .send_request::<lsp_request!("textDocument/hover")>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: ls.doc_id("src/lib.cairo"),
+                position: Position::new(0, 4),
+            },
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap();
+    let HoverContents::Markup(MarkupContent { value, .. }) = hover.contents else {
+        panic!("unexpected hover contents: {:?}", hover.contents);
+    };
+    assert!(value.contains("fn foo()"), "{value}");
+
+    // The change is applied on top of the unsaved content, so both functions must be reported.
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(change(
+        2,
+        "fn bar() { missing_b(); }\n",
+    ));
+    let diagnostics = ls
+        .wait_for_notification::<lsp_notification!("textDocument/publishDiagnostics")>(|params| {
+            params.uri == uri && params.diagnostics.len() == 2
+        });
+    let lines = diagnostics
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [(0, "Function not found."), (1, "Function not found.")]);
+}
+
+#[test]
+fn diagnostics_of_deleted_file_are_cleared() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "mod foo;",
+            "src/foo.cairo" => "fn foo() { missing(); }",
+        }
+    };
+    let uri = ls.doc_id("src/foo.cairo").uri;
+    let changed = |typ: FileChangeType| DidChangeWatchedFilesParams {
+        changes: vec![FileEvent { uri: uri.clone(), typ }],
+    };
+
+    // The file is opened to have its diagnostics published first, but is closed before it is
+    // deleted, because diagnostics of open files are kept.
+    ls.open("src/foo.cairo");
+    ls.wait_for_notification::<lsp_notification!("textDocument/publishDiagnostics")>(|params| {
+        params.uri == uri && params.diagnostics.len() == 1
+    });
+    ls.send_notification::<lsp_notification!("textDocument/didClose")>(
+        DidCloseTextDocumentParams { text_document: ls.doc_id("src/foo.cairo") },
+    );
+
+    let path = ls.as_ref().file_absolute_path("src/foo.cairo");
+    let content = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    ls.send_notification::<lsp_notification!("workspace/didChangeWatchedFiles")>(changed(
+        FileChangeType::DELETED,
+    ));
+    ls.wait_for_notification::<lsp_notification!("textDocument/publishDiagnostics")>(|params| {
+        params.uri == uri && params.diagnostics.is_empty()
+    });
+
+    // Diagnostics of a restored file must be published again, even though they have not changed.
+    fs::write(&path, content).unwrap();
+    ls.send_notification::<lsp_notification!("workspace/didChangeWatchedFiles")>(changed(
+        FileChangeType::CREATED,
+    ));
+    ls.open("src/foo.cairo");
+    ls.wait_for_notification::<lsp_notification!("textDocument/publishDiagnostics")>(|params| {
+        params.uri == uri && params.diagnostics.len() == 1
+    });
 }
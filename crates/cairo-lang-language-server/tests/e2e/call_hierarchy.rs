@@ -0,0 +1,125 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{
+    CallHierarchyIncomingCallsParams, CallHierarchyItem, CallHierarchyOutgoingCallsParams,
+    CallHierarchyPrepareParams, ClientCapabilities, DynamicRegistrationClientCapabilities,
+    TextDocumentClientCapabilities, TextDocumentPositionParams, lsp_request,
+};
+
+use crate::support::cursor::{peek_caret, peek_selection};
+use crate::support::{cursors, sandbox};
+
+cairo_lang_test_utils::test_file_test!(
+    call_hierarchy,
+    "tests/test_data/call_hierarchy",
+    {
+        functions: "functions.txt",
+        methods: "methods.txt",
+    },
+    test_call_hierarchy
+);
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: base.text_document.or_else(Default::default).map(|it| {
+            TextDocumentClientCapabilities {
+                call_hierarchy: Some(DynamicRegistrationClientCapabilities {
+                    dynamic_registration: Some(false),
+                }),
+                ..it
+            }
+        }),
+        ..base
+    }
+}
+
+/// Perform call hierarchy test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then prepares the call hierarchy at each caret position and reports the incoming
+/// and outgoing calls of the found function, along with the ranges of the calls.
+fn test_call_hierarchy(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut outputs = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let items = ls.send_request::<lsp_request!("textDocument/prepareCallHierarchy")>(
+            CallHierarchyPrepareParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: ls.doc_id("src/lib.cairo"),
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+            },
+        );
+        let Some(item) = items.and_then(|items| items.into_iter().next()) else {
+            report.push_str("No call hierarchy.\n");
+            outputs.insert(format!("Call hierarchy #{n}"), report);
+            continue;
+        };
+        report.push_str(&describe_item(&cairo, &item));
+
+        let incoming = ls
+            .send_request::<lsp_request!("callHierarchy/incomingCalls")>(
+                CallHierarchyIncomingCallsParams {
+                    item: item.clone(),
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+            )
+            .unwrap_or_default();
+        for call in incoming {
+            report.push_str("\nincoming from ");
+            report.push_str(&describe_item(&cairo, &call.from));
+            for range in call.from_ranges {
+                report.push_str(&format!("call: {}", peek_selection(&cairo, &range)));
+            }
+        }
+
+        let outgoing = ls
+            .send_request::<lsp_request!("callHierarchy/outgoingCalls")>(
+                CallHierarchyOutgoingCallsParams {
+                    item,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+            )
+            .unwrap_or_default();
+        for call in outgoing {
+            report.push_str("\noutgoing to ");
+            report.push_str(&describe_item(&cairo, &call.to));
+            for range in call.from_ranges {
+                report.push_str(&format!("call: {}", peek_selection(&cairo, &range)));
+            }
+        }
+
+        outputs.insert(format!("Call hierarchy #{n}"), report);
+    }
+
+    TestRunnerResult::success(outputs)
+}
+
+fn describe_item(cairo: &str, item: &CallHierarchyItem) -> String {
+    let detail = item.detail.as_deref().unwrap_or_default();
+    let mut description = format!("{} ({:?}, {detail})\n", item.name, item.kind);
+    if item.uri.path().ends_with("src/lib.cairo") {
+        description.push_str(&peek_selection(cairo, &item.selection_range));
+    } else {
+        description.push_str(&format!("{}\n", item.uri.path().rsplit('/').next().unwrap()));
+    }
+    description
+}
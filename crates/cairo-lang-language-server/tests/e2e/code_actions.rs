@@ -1,11 +1,14 @@
 use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
 use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
 use lsp_types::{
-    ClientCapabilities, CodeActionContext, CodeActionOrCommand, CodeActionParams,
+    ClientCapabilities, CodeActionCapabilityResolveSupport, CodeActionClientCapabilities,
+    CodeActionContext, CodeActionKind, CodeActionOrCommand, CodeActionParams,
     HoverClientCapabilities, MarkupKind, Range, TextDocumentClientCapabilities, lsp_request,
 };
+use serde_json::json;
 
 use crate::support::cursor::peek_caret;
+use crate::support::text_edits::apply_edits;
 use crate::support::{cursors, sandbox};
 
 cairo_lang_test_utils::test_file_test!(
@@ -14,10 +17,33 @@ cairo_lang_test_utils::test_file_test!(
     {
         missing_trait: "missing_trait.txt",
         macro_expand: "macro_expand.txt",
+        fill_match_arms: "fill_match_arms.txt",
+        import_missing_item: "import_missing_item.txt",
+        remove_unused_import: "remove_unused_import.txt",
+        implement_missing_items: "implement_missing_items.txt",
+        expand_macro_in_place: "expand_macro_in_place.txt",
     },
     test_quick_fix
 );
 
+cairo_lang_test_utils::test_file_test!(
+    quick_fix_resolve,
+    "tests/test_data/code_actions",
+    {
+        resolve: "resolve.txt",
+    },
+    test_quick_fix_resolve
+);
+
+cairo_lang_test_utils::test_file_test!(
+    organize_imports,
+    "tests/test_data/code_actions",
+    {
+        organize_imports: "organize_imports.txt",
+    },
+    test_organize_imports
+);
+
 fn caps(base: ClientCapabilities) -> ClientCapabilities {
     ClientCapabilities {
         text_document: base.text_document.or_else(Default::default).map(|it| {
@@ -85,6 +111,143 @@ fn test_quick_fix(
     TestRunnerResult::success(actions)
 }
 
+fn caps_with_resolve(base: ClientCapabilities) -> ClientCapabilities {
+    let base = caps(base);
+    ClientCapabilities {
+        text_document: base.text_document.map(|it| TextDocumentClientCapabilities {
+            code_action: Some(CodeActionClientCapabilities {
+                resolve_support: Some(CodeActionCapabilityResolveSupport {
+                    properties: vec!["edit".to_string()],
+                }),
+                ..Default::default()
+            }),
+            ..it
+        }),
+        ..base
+    }
+}
+
+/// Perform quick fix resolve test.
+///
+/// This function works like [`test_quick_fix`], but the client supports resolving edits of code
+/// actions, so the edits are expected to be missing from the code actions until they are resolved.
+fn test_quick_fix_resolve(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+        client_capabilities = caps_with_resolve;
+    };
+
+    let diagnostics = ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut actions = OrderedHashMap::default();
+
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = String::new();
+
+        report.push_str(&peek_caret(&cairo, position));
+        let code_action_params = CodeActionParams {
+            text_document: ls.doc_id("src/lib.cairo"),
+            range: Range { start: position, end: position },
+            context: CodeActionContext {
+                // Only diagnostics at the caret are sent, like clients do.
+                diagnostics: diagnostics
+                    .diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.range.start.line == position.line)
+                    .cloned()
+                    .collect(),
+                only: None,
+                trigger_kind: None,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let code_actions = ls
+            .send_request::<lsp_request!("textDocument/codeAction")>(code_action_params)
+            .expect("Code actions request failed.");
+        let resolved = code_actions
+            .into_iter()
+            .map(|code_action_or_command| match code_action_or_command {
+                CodeActionOrCommand::CodeAction(code_action) if code_action.data.is_some() => {
+                    assert!(code_action.edit.is_none(), "edit should be resolved lazily");
+                    CodeActionOrCommand::CodeAction(
+                        ls.send_request::<lsp_request!("codeAction/resolve")>(code_action),
+                    )
+                }
+                code_action_or_command => code_action_or_command,
+            })
+            .collect();
+        report.push_str(&render_code_actions_or_commands(resolved));
+        actions.insert(format!("Code action #{}", n), report);
+    }
+
+    TestRunnerResult::success(actions)
+}
+
+/// Perform organize imports test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file,
+/// requests the organize imports code action and applies its edits to the code.
+fn test_organize_imports(
+    inputs: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let cairo = inputs["cairo_code"].clone();
+    let max_line_width = args.get("max_line_width").map(|value| value.parse::<usize>().unwrap());
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+        client_capabilities = caps;
+        workspace_configuration = json!({
+            "cairo1": {
+                "formatter": {
+                    "maxLineWidth": max_line_width,
+                }
+            }
+        });
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let code_action_params = CodeActionParams {
+        text_document: ls.doc_id("src/lib.cairo"),
+        range: Range::default(),
+        context: CodeActionContext {
+            diagnostics: vec![],
+            only: Some(vec![CodeActionKind::SOURCE_ORGANIZE_IMPORTS]),
+            trigger_kind: None,
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let code_actions = ls
+        .send_request::<lsp_request!("textDocument/codeAction")>(code_action_params)
+        .expect("Code actions request failed.");
+
+    let organized = match code_actions.as_slice() {
+        [] => "No code actions.".to_string(),
+        [CodeActionOrCommand::CodeAction(code_action)] => {
+            let changes = code_action.edit.as_ref().and_then(|edit| edit.changes.as_ref()).unwrap();
+            let edits = changes.get(&ls.doc_id("src/lib.cairo").uri).unwrap();
+            apply_edits(&cairo, edits)
+        }
+        code_actions => panic!("Unexpected code actions: {code_actions:?}"),
+    };
+
+    TestRunnerResult::success(OrderedHashMap::from([("organized_code".to_string(), organized)]))
+}
+
 fn render_code_actions_or_commands(code_actions_or_commands: Vec<CodeActionOrCommand>) -> String {
     if code_actions_or_commands.is_empty() {
         return "No code actions.\n".to_string();
@@ -0,0 +1,87 @@
+use indoc::indoc;
+use lsp_types::{CodeLensParams, lsp_request};
+
+use crate::support::{MockClient, sandbox};
+
+const CAIRO: &str = indoc! {r#"
+    fn add(a: felt252, b: felt252) -> felt252 {
+        a + b
+    }
+
+    #[test]
+    fn test_add() {
+        assert(add(1, 2) == 3, 'wrong sum');
+    }
+
+    trait Foo {
+        fn foo();
+    }
+
+    impl FooImpl of Foo {
+        #[test]
+        fn foo() {}
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::add;
+
+        #[test]
+        fn test_add_zero() {
+            assert(add(1, 0) == 1, 'wrong sum');
+        }
+    }
+"#};
+
+fn code_lens_params(ls: &MockClient) -> CodeLensParams {
+    CodeLensParams {
+        text_document: ls.doc_id("src/lib.cairo"),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+/// Lenses run tests with the manifest of the Scarb package which the crate was loaded from, so
+/// there are none when `cairo_project.toml` takes precedence over `Scarb.toml`.
+#[test]
+fn no_lenses_for_scarb_package_loaded_from_cairo_project() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "Scarb.toml" => indoc! {r#"
+                [package]
+                name = "hello"
+                version = "0.1.0"
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => CAIRO,
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let lenses = ls.send_request::<lsp_request!("textDocument/codeLens")>(code_lens_params(&ls));
+    assert_eq!(lenses, Some(vec![]));
+}
+
+#[test]
+fn no_lenses_outside_scarb_package() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => CAIRO,
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let lenses = ls.send_request::<lsp_request!("textDocument/codeLens")>(code_lens_params(&ls));
+    assert_eq!(lenses, Some(vec![]));
+}
@@ -1,9 +1,18 @@
+use std::convert::identity;
+
 use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
 use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
-use lsp_types::{CompletionParams, TextDocumentPositionParams, lsp_request};
+use lsp_types::{
+    ClientCapabilities, CompletionClientCapabilities, CompletionItem, CompletionItemCapability,
+    CompletionItemCapabilityResolveSupport, CompletionItemKind, CompletionParams,
+    CompletionResponse, CompletionTextEdit, TextDocumentClientCapabilities,
+    TextDocumentPositionParams, lsp_request,
+};
+use serde_json::{Value, json};
 
 use crate::support::cursor::peek_caret;
-use crate::support::{cursors, sandbox};
+use crate::support::text_edits::apply_edits;
+use crate::support::{MockClient, cursors, sandbox};
 
 cairo_lang_test_utils::test_file_test!(
     completions,
@@ -15,6 +24,54 @@ cairo_lang_test_utils::test_file_test!(
 
 );
 
+cairo_lang_test_utils::test_file_test!(
+    completions_auto_import,
+    "tests/test_data/completions",
+    {
+        auto_import: "auto_import.txt",
+    },
+    test_completions_auto_import
+);
+
+cairo_lang_test_utils::test_file_test!(
+    completions_use_paths,
+    "tests/test_data/completions",
+    {
+        use_paths: "use_paths.txt",
+    },
+    test_completions_use_paths
+);
+
+cairo_lang_test_utils::test_file_test!(
+    completions_match_arms,
+    "tests/test_data/completions",
+    {
+        match_arms: "match_arms.txt",
+    },
+    test_completions_match_arms
+);
+
+cairo_lang_test_utils::test_file_test!(
+    completions_keywords,
+    "tests/test_data/completions",
+    {
+        control_flow: "control_flow.txt",
+        keywords: "keywords.txt",
+        postfix: "postfix.txt",
+    },
+    test_completions_keywords
+);
+
+cairo_lang_test_utils::test_file_test!(
+    completions_resolve,
+    "tests/test_data/completions",
+    {
+        fields: "fields.txt",
+        resolve: "resolve.txt",
+    },
+    test_completions_resolve
+);
+
 /// Perform completions text edits test. Notice that the test shows many possible completions,
 /// however in practice only those who have the same prefix as the existing code are shown.
 ///
@@ -77,3 +134,233 @@ fn test_completions_text_edits(
 
     TestRunnerResult::success(completions)
 }
+
+/// Perform completion items resolve test.
+///
+/// This function requests completions at each caret position, resolves the ones of items defined
+/// in `src/lib.cairo` and compares their details and documentation with the expected ones from the
+/// snapshot file.
+fn test_completions_resolve(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    test_completions(inputs, identity, json!({}), |ls, _cairo, completion| {
+        let lib_uri = ls.doc_id("src/lib.cairo").uri;
+        let is_local = completion
+            .data
+            .as_ref()
+            .and_then(|data| data["uri"].as_str())
+            .is_some_and(|uri| uri == lib_uri.as_str());
+        if !is_local {
+            return None;
+        }
+        assert!(completion.documentation.is_none());
+
+        let resolved = ls.send_request::<lsp_request!("completionItem/resolve")>(completion);
+        let mut report = format!("Completion: {}\n", resolved.label);
+        if let Some(detail) = resolved.detail {
+            report.push_str(&format!("Detail: {detail}\n"));
+        }
+        if let Some(lsp_types::Documentation::MarkupContent(doc)) = resolved.documentation {
+            report.push_str(&format!("Documentation: {}\n", doc.value));
+        }
+        Some(report)
+    })
+}
+
+/// Runs a completions test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file
+/// and the code of a dependency crate, if any, in the `dep/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then requests completions at each caret position and reports the completion items
+/// rendered by `render`, which is given the language server, the code without caret markers and the
+/// item. Items for which `render` returns `None` are left out of the report.
+fn test_completions(
+    inputs: &OrderedHashMap<String, String>,
+    client_capabilities: impl FnOnce(ClientCapabilities) -> ClientCapabilities,
+    workspace_configuration: Value,
+    mut render: impl FnMut(&mut MockClient, &str, CompletionItem) -> Option<String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+            "dep/lib.cairo" => inputs.get("dep_code").cloned().unwrap_or_default(),
+        }
+        client_capabilities = client_capabilities;
+        workspace_configuration = workspace_configuration;
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut completions = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let response =
+            ls.send_request::<lsp_request!("textDocument/completion")>(CompletionParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: ls.doc_id("src/lib.cairo"),
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            });
+        let completion_items = match response {
+            Some(CompletionResponse::Array(items)) => items,
+            Some(CompletionResponse::List(list)) => list.items,
+            None => vec![],
+        };
+        for completion in completion_items {
+            if let Some(rendered) = render(&mut ls, &cairo, completion) {
+                report.push_str("--------------------------\n");
+                report.push_str(&rendered);
+            }
+        }
+        completions.insert(format!("Completions #{n}"), report);
+    }
+
+    TestRunnerResult::success(completions)
+}
+
+/// Perform auto-import completions test.
+///
+/// This function adds a dependency crate with the code in the `dep/lib.cairo` file, requests
+/// completions at each caret position and, for each completion adding an import, renders the code
+/// with the import applied.
+/// With `resolve_edits`, the imports are resolved with `completionItem/resolve` requests.
+fn test_completions_auto_import(
+    inputs: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let auto_import = args.get("auto_import").is_none_or(|value| value == "true");
+    let resolve_edits = args.get("resolve_edits").is_some_and(|value| value == "true");
+    let workspace_configuration = json!({
+        "cairo1": {
+            "completions": {
+                "autoImport": auto_import,
+            }
+        }
+    });
+
+    test_completions(
+        inputs,
+        resolve_caps(resolve_edits),
+        workspace_configuration,
+        |ls, cairo, mut completion| {
+            if resolve_edits && completion.additional_text_edits.is_none() {
+                completion = ls.send_request::<lsp_request!("completionItem/resolve")>(completion);
+            }
+            let edits = completion.additional_text_edits.filter(|edits| !edits.is_empty())?;
+            Some(format!(
+                "Completion: {}\n{}\n",
+                completion.label,
+                apply_edits(cairo, &edits).trim_end()
+            ))
+        },
+    )
+}
+
+fn resolve_caps(resolve_edits: bool) -> impl FnOnce(ClientCapabilities) -> ClientCapabilities {
+    let properties = if resolve_edits { vec!["additionalTextEdits".to_owned()] } else { vec![] };
+    move |base| ClientCapabilities {
+        text_document: Some(TextDocumentClientCapabilities {
+            completion: Some(CompletionClientCapabilities {
+                completion_item: Some(CompletionItemCapability {
+                    resolve_support: Some(CompletionItemCapabilityResolveSupport { properties }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..base.text_document.unwrap_or_default()
+        }),
+        ..base
+    }
+}
+
+fn caps(snippet_support: bool) -> impl FnOnce(ClientCapabilities) -> ClientCapabilities {
+    move |base| ClientCapabilities {
+        text_document: Some(TextDocumentClientCapabilities {
+            completion: Some(CompletionClientCapabilities {
+                completion_item: Some(CompletionItemCapability {
+                    snippet_support: Some(snippet_support),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..base.text_document.unwrap_or_default()
+        }),
+        ..base
+    }
+}
+
+/// Perform keyword completions test.
+///
+/// This function requests completions at each caret position and lists the completed keywords and
+/// snippets with their insert texts, or the code with their text edits applied.
+fn test_completions_keywords(
+    inputs: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let snippet_support = args.get("snippet_support").is_none_or(|value| value == "true");
+
+    test_completions(inputs, caps(snippet_support), json!({}), |_ls, cairo, completion| {
+        let kind = match completion.kind {
+            Some(CompletionItemKind::KEYWORD) => "keyword",
+            Some(CompletionItemKind::SNIPPET) => "snippet",
+            _ => return None,
+        };
+        let mut report = format!("Completion: {} ({kind})\n", completion.label);
+        if let Some(text) = completion.insert_text {
+            report.push_str(&format!("Insert text: {text}\n"));
+        }
+        if let Some(CompletionTextEdit::Edit(edit)) = completion.text_edit {
+            report.push_str("Result:\n");
+            report.push_str(apply_edits(cairo, &[edit]).trim_end());
+            report.push('\n');
+        }
+        Some(report)
+    })
+}
+
+/// Perform completions in `use` paths test.
+///
+/// This function requests completions at each caret position and lists their labels and kinds.
+fn test_completions_use_paths(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    test_completions(inputs, identity, json!({}), |_ls, _cairo, completion| {
+        let mut report = format!("Completion: {}", completion.label);
+        if let Some(kind) = completion.kind {
+            report.push_str(&format!(" ({kind:?})"));
+        }
+        report.push('\n');
+        Some(report)
+    })
+}
+
+/// Perform completions of enum variants in `match` arms test.
+///
+/// This function requests completions at each caret position and lists the completed enum variants
+/// with their insert texts.
+fn test_completions_match_arms(
+    inputs: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let snippet_support = args.get("snippet_support").is_none_or(|value| value == "true");
+
+    test_completions(inputs, caps(snippet_support), json!({}), |_ls, _cairo, completion| {
+        if completion.kind != Some(CompletionItemKind::ENUM_MEMBER) {
+            return None;
+        }
+        let mut report = format!("Completion: {}\n", completion.label);
+        if let Some(text) = completion.insert_text {
+            report.push_str(&format!("Insert text: {text}\n"));
+        }
+        Some(report)
+    })
+}
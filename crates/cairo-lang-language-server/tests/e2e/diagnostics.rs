@@ -0,0 +1,285 @@
+use cairo_lang_language_server::lsp::ext::ViewAnalyzedCrates;
+use indoc::indoc;
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, Location,
+    NumberOrString, Position, Range,
+};
+use serde_json::{Value, json};
+
+use crate::support::sandbox;
+
+/// Opens `src/lib.cairo` with the given content and returns its published diagnostics.
+fn diagnostics(cairo: &str) -> Vec<Diagnostic> {
+    diagnostics_with_configuration(cairo, json!({}))
+}
+
+/// Like [`diagnostics`], but with the given workspace configuration.
+fn diagnostics_with_configuration(cairo: &str, workspace_configuration: Value) -> Vec<Diagnostic> {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => cairo,
+        }
+        workspace_configuration = workspace_configuration;
+    };
+    // Make sure the configuration is loaded before diagnostics are computed.
+    ls.send_request::<ViewAnalyzedCrates>(());
+    ls.open_and_wait_for_diagnostics("src/lib.cairo").diagnostics
+}
+
+#[test]
+fn previous_definition_is_related_information() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                fn foo() {}
+                struct foo {}
+            "#},
+        }
+    };
+
+    let diagnostics = ls.open_and_wait_for_diagnostics("src/lib.cairo").diagnostics;
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got: {diagnostics:#?}");
+    };
+    assert_eq!(diagnostic.message, "The name `foo` is defined multiple times.");
+    assert_eq!(
+        diagnostic.related_information,
+        Some(vec![DiagnosticRelatedInformation {
+            location: Location {
+                uri: ls.doc_id("src/lib.cairo").uri,
+                range: Range {
+                    start: Position { line: 0, character: 3 },
+                    end: Position { line: 0, character: 6 },
+                },
+            },
+            message: "`foo` was previously defined here".to_string(),
+        }])
+    );
+}
+
+#[test]
+fn unused_variable_is_unnecessary() {
+    let diagnostics = diagnostics(indoc! {r#"
+        fn main() {
+            let x = 5;
+        }
+    "#});
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got: {diagnostics:#?}");
+    };
+    assert_eq!(diagnostic.message, "Unused variable. Consider ignoring by prefixing with `_`.");
+    assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+}
+
+#[test]
+fn unused_import_is_unnecessary() {
+    let diagnostics = diagnostics(indoc! {r#"
+        use core::integer::u128_safe_divmod;
+        fn main() {}
+    "#});
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got: {diagnostics:#?}");
+    };
+    assert_eq!(diagnostic.message, "Unused import: `hello::u128_safe_divmod`");
+    assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+}
+
+#[test]
+fn deprecated_item_usage_is_deprecated() {
+    let diagnostics = diagnostics(indoc! {r#"
+        mod a {
+            #[deprecated(feature: "old", note: "Use `new` instead.")]
+            pub fn old() {}
+        }
+        fn main() {
+            a::old();
+        }
+    "#});
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got: {diagnostics:#?}");
+    };
+    assert_eq!(
+        diagnostic.message,
+        r#"Usage of deprecated feature `"old"` with no `#[feature("old")]` attribute. Note: "Use `new` instead.""#
+    );
+    assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::DEPRECATED]));
+}
+
+#[test]
+fn other_diagnostics_have_no_tags() {
+    let diagnostics = diagnostics("fn main() -> felt252 { 1_u8 }");
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got: {diagnostics:#?}");
+    };
+    assert_eq!(diagnostic.tags, None);
+}
+
+#[test]
+fn severity_is_overridden() {
+    let diagnostics = diagnostics_with_configuration(
+        indoc! {r#"
+            use core::integer::u128_safe_divmod;
+            fn main() -> felt252 {
+                let x = 5;
+                1_u8
+            }
+        "#},
+        json!({
+            "cairo1": {
+                "diagnostics": {
+                    "severityOverrides": {
+                        "unused_import": "off",
+                        "E0001": "hint",
+                    }
+                }
+            }
+        }),
+    );
+    let severities = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.severity.unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        severities,
+        [
+            ("Unused variable. Consider ignoring by prefixing with `_`.", DiagnosticSeverity::HINT),
+            (
+                r#"Unexpected return type. Expected: "core::felt252", found: "core::integer::u8"."#,
+                DiagnosticSeverity::ERROR
+            ),
+        ]
+    );
+}
+
+#[test]
+fn diagnostics_above_limit_are_suppressed() {
+    let diagnostics = diagnostics_with_configuration(
+        indoc! {r#"
+            fn a() -> felt252 { true }
+            fn b() -> felt252 { true }
+            fn c() -> felt252 { true }
+            fn d() -> felt252 { true }
+        "#},
+        json!({
+            "cairo1": {
+                "diagnostics": {
+                    "maxPerFile": 2,
+                }
+            }
+        }),
+    );
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            (0, r#"Unexpected return type. Expected: "core::felt252", found: "core::bool"."#),
+            (1, r#"Unexpected return type. Expected: "core::felt252", found: "core::bool"."#),
+            (2, "2 more diagnostics suppressed"),
+        ]
+    );
+    assert_eq!(diagnostics[2].severity, Some(DiagnosticSeverity::INFORMATION));
+}
+
+#[test]
+fn error_codes_link_to_docs() {
+    let diagnostics = diagnostics_with_configuration(
+        indoc! {r#"
+            fn main() -> felt252 {
+                let x = 5;
+                1_u8
+            }
+        "#},
+        json!({
+            "cairo1": {
+                "diagnostics": {
+                    "docsBaseUrl": "https://example.com/errors",
+                }
+            }
+        }),
+    );
+    let descriptions = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.code.clone(),
+                diagnostic.code_description.as_ref().map(|description| description.href.as_str()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        descriptions,
+        [
+            (
+                Some(NumberOrString::String("E0001".into())),
+                Some("https://example.com/errors/E0001")
+            ),
+            (None, None),
+        ]
+    );
+}
+
+/// Returns the ranges and messages of diagnostics.
+fn ranges(diagnostics: &[Diagnostic]) -> Vec<(Range, &str)> {
+    diagnostics.iter().map(|diagnostic| (diagnostic.range, diagnostic.message.as_str())).collect()
+}
+
+fn range(line: u32, start: u32, end_line: u32, end: u32) -> Range {
+    Range {
+        start: Position { line, character: start },
+        end: Position { line: end_line, character: end },
+    }
+}
+
+#[test]
+fn derive_diagnostics_point_to_derive() {
+    let diagnostics = diagnostics(indoc! {r#"
+        #[derive(Copy, Drop)]
+        struct A {
+            x: Array<felt252>,
+        }
+    "#});
+    assert_eq!(
+        ranges(&diagnostics),
+        [(
+            range(0, 9, 0, 13),
+            "Invalid copy trait implementation, Trait has no implementation in context: \
+             core::traits::Copy::<core::array::Array::<core::felt252>>."
+        )]
+    );
+}
+
+#[test]
+fn attribute_macro_diagnostics_point_to_original_code() {
+    let diagnostics = diagnostics(indoc! {r#"
+        #[generate_trait]
+        impl FooImpl of FooTrait {
+            fn foo(self: @u32) -> felt252 {
+                true
+            }
+        }
+    "#});
+    assert_eq!(
+        ranges(&diagnostics),
+        [(
+            range(2, 34, 4, 5),
+            r#"Unexpected return type. Expected: "core::felt252", found: "core::bool"."#
+        )]
+    );
+}
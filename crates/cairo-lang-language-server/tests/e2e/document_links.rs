@@ -0,0 +1,65 @@
+use indoc::indoc;
+use lsp_types::{DocumentLinkParams, lsp_request};
+
+use crate::support::sandbox;
+
+#[test]
+fn links_to_module_files() {
+    let lib = indoc! {r#"
+        mod a;
+        mod inline {
+            pub fn bar() {}
+        }
+
+        use a::b::foo;
+        use core::{array::ArrayTrait, option};
+        use inline::bar;
+        use unknown::thing;
+    "#};
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => lib,
+            "src/a.cairo" => "pub mod b;",
+            "src/a/b.cairo" => "pub fn foo() {}",
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let links = ls
+        .send_request::<lsp_request!("textDocument/documentLink")>(DocumentLinkParams {
+            text_document: ls.doc_id("src/lib.cairo"),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap();
+    let lines = lib.lines().collect::<Vec<_>>();
+    let links = links
+        .into_iter()
+        .map(|link| {
+            let line = lines[link.range.start.line as usize];
+            let text =
+                &line[link.range.start.character as usize..link.range.end.character as usize];
+            let target = link.target.unwrap();
+            let target = target.path().rsplit_once("/src/").unwrap().1.to_string();
+            (link.range.start.line, text.to_string(), target)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        links,
+        [
+            (0, "a".to_string(), "a.cairo".to_string()),
+            (5, "a".to_string(), "a.cairo".to_string()),
+            (5, "b".to_string(), "a/b.cairo".to_string()),
+            (6, "core".to_string(), "lib.cairo".to_string()),
+            (6, "array".to_string(), "array.cairo".to_string()),
+            (6, "option".to_string(), "option.cairo".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,66 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams, lsp_request};
+
+use crate::support::sandbox;
+
+cairo_lang_test_utils::test_file_test!(
+    folding_ranges,
+    "tests/test_data/folding_ranges",
+    {
+        comments: "comments.txt",
+        items: "items.txt",
+        uses: "uses.txt",
+    },
+    test_folding_ranges
+);
+
+/// Perform folding ranges test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The function then requests folding ranges for the file and renders each range as its kind
+/// followed by the lines it spans.
+fn test_folding_ranges(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let cairo = &inputs["cairo_code"];
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let ranges = ls
+        .send_request::<lsp_request!("textDocument/foldingRange")>(FoldingRangeParams {
+            text_document: ls.doc_id("src/lib.cairo"),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap_or_default();
+
+    TestRunnerResult::success(OrderedHashMap::from([(
+        "Folding ranges".to_owned(),
+        ranges.iter().map(|range| render_range(cairo, range)).collect::<Vec<_>>().join("\n"),
+    )]))
+}
+
+/// Renders the kind of the folding range followed by the lines it spans.
+fn render_range(cairo: &str, range: &FoldingRange) -> String {
+    let kind = match &range.kind {
+        Some(FoldingRangeKind::Comment) => "comment",
+        Some(FoldingRangeKind::Imports) => "imports",
+        Some(FoldingRangeKind::Region) => "region",
+        None => "code",
+    };
+    let lines = cairo
+        .lines()
+        .skip(range.start_line as usize)
+        .take((range.end_line - range.start_line + 1) as usize)
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("// {kind}: {}..{}\n{lines}\n", range.start_line, range.end_line)
+}
@@ -0,0 +1,109 @@
+use indoc::indoc;
+use lsp_types::{DocumentFormattingParams, FormattingOptions, lsp_request};
+use serde_json::{Value, json};
+
+use crate::support::sandbox;
+use crate::support::text_edits::apply_edits;
+
+/// Sends a formatting request for `src/lib.cairo` with the given options and returns the file
+/// with the edits applied.
+fn format(cairo: &str, options: FormattingOptions) -> String {
+    format_with_configuration(cairo, options, json!({}))
+}
+
+/// Like [`format`], but with the given workspace configuration.
+fn format_with_configuration(
+    cairo: &str,
+    options: FormattingOptions,
+    workspace_configuration: Value,
+) -> String {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => cairo,
+        }
+        workspace_configuration = workspace_configuration;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let edits = ls
+        .send_request::<lsp_request!("textDocument/formatting")>(DocumentFormattingParams {
+            text_document: ls.doc_id("src/lib.cairo"),
+            options,
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap_or_default();
+    apply_edits(cairo, &edits)
+}
+
+const CAIRO: &str = "fn main() {\nlet x = (1,\n2);\nif x.0 == 1 { let _y = x; }\n}";
+
+#[test]
+fn indentation_follows_tab_size() {
+    let options = FormattingOptions { tab_size: 2, insert_spaces: true, ..Default::default() };
+    assert_eq!(
+        format(CAIRO, options),
+        indoc! {"
+            fn main() {
+              let x = (1, 2);
+              if x.0 == 1 {
+                let _y = x;
+              }
+            }
+        "}
+    );
+}
+
+#[test]
+fn indentation_uses_tabs_unless_spaces_are_preferred() {
+    let options = FormattingOptions { tab_size: 4, insert_spaces: false, ..Default::default() };
+    assert_eq!(
+        format(CAIRO, options),
+        "fn main() {\n\tlet x = (1, 2);\n\tif x.0 == 1 {\n\t\tlet _y = x;\n\t}\n}\n"
+    );
+}
+
+#[test]
+fn final_newline_is_not_inserted_if_the_editor_does_not_insert_it() {
+    let options = FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        insert_final_newline: Some(false),
+        ..Default::default()
+    };
+    assert_eq!(
+        format(CAIRO, options),
+        "fn main() {\n    let x = (1, 2);\n    if x.0 == 1 {\n        let _y = x;\n    }\n}"
+    );
+}
+
+#[test]
+fn lines_are_broken_at_configured_width() {
+    let cairo = "fn main() {\n    let _x = (100, 200, 300);\n}\n";
+    let options = FormattingOptions { tab_size: 4, insert_spaces: true, ..Default::default() };
+    let workspace_configuration = json!({
+        "cairo1": {
+            "formatter": {
+                "maxLineWidth": 20,
+            }
+        }
+    });
+    assert_eq!(
+        format_with_configuration(cairo, options, workspace_configuration),
+        indoc! {"
+            fn main() {
+                let _x = (
+                    100,
+                    200,
+                    300,
+                );
+            }
+        "}
+    );
+}
@@ -1,8 +1,10 @@
 use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
 use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use indoc::indoc;
+use lsp_types::request::Request;
 use lsp_types::{
-    ClientCapabilities, GotoCapability, GotoDefinitionParams, GotoDefinitionResponse,
-    TextDocumentClientCapabilities, TextDocumentIdentifier, TextDocumentPositionParams,
+    ClientCapabilities, GotoCapability, GotoDefinitionParams, GotoDefinitionResponse, Position,
+    Range, TextDocumentClientCapabilities, TextDocumentIdentifier, TextDocumentPositionParams,
     lsp_request,
 };
 
@@ -14,10 +16,29 @@ cairo_lang_test_utils::test_file_test!(
     "tests/test_data/goto",
     {
         struct_members: "struct_members.txt",
+        ambiguous_methods: "ambiguous_methods.txt",
     },
     test_goto_members
 );
 
+cairo_lang_test_utils::test_file_test!(
+    goto_type_definition,
+    "tests/test_data/goto",
+    {
+        type_definitions: "type_definitions.txt",
+    },
+    test_goto_type_definition
+);
+
+cairo_lang_test_utils::test_file_test!(
+    goto_declaration,
+    "tests/test_data/goto",
+    {
+        declarations: "declarations.txt",
+    },
+    test_goto_declaration
+);
+
 fn caps(base: ClientCapabilities) -> ClientCapabilities {
     ClientCapabilities {
         text_document: base.text_document.or_else(Default::default).map(|it| {
@@ -26,6 +47,14 @@ fn caps(base: ClientCapabilities) -> ClientCapabilities {
                     dynamic_registration: Some(false),
                     link_support: None,
                 }),
+                declaration: Some(GotoCapability {
+                    dynamic_registration: Some(false),
+                    link_support: None,
+                }),
+                type_definition: Some(GotoCapability {
+                    dynamic_registration: Some(false),
+                    link_support: None,
+                }),
                 ..it
             }
         }),
@@ -71,17 +100,142 @@ fn test_goto_members(
         let goto_definition_response =
             ls.send_request::<lsp_request!("textDocument/definition")>(code_action_params);
 
-        if let Some(goto_definition_response) = goto_definition_response {
-            if let GotoDefinitionResponse::Scalar(location) = goto_definition_response {
+        match goto_definition_response {
+            Some(GotoDefinitionResponse::Scalar(location)) => {
                 report.push_str(&peek_selection(&cairo, &location.range));
-            } else {
-                panic!("Unexpected GotoDefinitionResponse variant.")
             }
-        } else {
-            panic!("Goto definition request failed.");
+            Some(GotoDefinitionResponse::Array(locations)) => {
+                for location in locations {
+                    report.push_str(&peek_selection(&cairo, &location.range));
+                }
+            }
+            Some(_) => panic!("Unexpected GotoDefinitionResponse variant."),
+            None => panic!("Goto definition request failed."),
         }
         goto_definitions.insert(format!("Goto definition #{}", n), report);
     }
 
     TestRunnerResult::success(goto_definitions)
 }
+
+/// Perform goto type definition test.
+///
+/// See [`test_goto_locations`] for details.
+fn test_goto_type_definition(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    test_goto_locations::<lsp_request!("textDocument/typeDefinition")>(inputs, "type definition")
+}
+
+/// Perform goto declaration test.
+///
+/// See [`test_goto_locations`] for details.
+fn test_goto_declaration(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    test_goto_locations::<lsp_request!("textDocument/declaration")>(inputs, "declaration")
+}
+
+/// Perform a test of a goto request.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then sends the request at each caret position and reports the found locations.
+/// Locations outside of `src/lib.cairo` are reported by file name.
+fn test_goto_locations<R>(inputs: &OrderedHashMap<String, String>, name: &str) -> TestRunnerResult
+where
+    R: Request<Params = GotoDefinitionParams, Result = Option<GotoDefinitionResponse>>,
+{
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut results = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let response = ls.send_request::<R>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: ls.doc_id("src/lib.cairo"),
+                position,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+        let locations = match response {
+            Some(GotoDefinitionResponse::Scalar(location)) => vec![location],
+            Some(GotoDefinitionResponse::Array(locations)) => locations,
+            Some(_) => panic!("Unexpected GotoDefinitionResponse variant."),
+            None => vec![],
+        };
+        if locations.is_empty() {
+            report.push_str(&format!("No {name}.\n"));
+        }
+        for location in locations {
+            if location.uri.path().ends_with("src/lib.cairo") {
+                report.push_str(&peek_selection(&cairo, &location.range));
+            } else {
+                let file_name = location.uri.path().rsplit('/').next().unwrap().to_string();
+                report.push_str(&format!("{file_name}\n"));
+            }
+        }
+        results.insert(format!("Goto {name} #{n}"), report);
+    }
+
+    TestRunnerResult::success(results)
+}
+
+#[test]
+fn goto_across_crates_of_cairo_project() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "hello"
+                world = "world"
+
+                [config.override.hello]
+                edition = "2024_07"
+
+                [config.override.hello.dependencies]
+                world = {}
+            "#},
+            "hello/lib.cairo" => indoc! {r#"
+                use world::greet;
+
+                fn main() {
+                    greet();
+                }
+            "#},
+            "world/lib.cairo" => indoc! {r#"
+                pub fn greet() {}
+            "#},
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("hello/lib.cairo");
+
+    let response =
+        ls.send_request::<lsp_request!("textDocument/definition")>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: ls.doc_id("hello/lib.cairo"),
+                position: Position::new(3, 6),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+
+    let Some(GotoDefinitionResponse::Scalar(location)) = response else {
+        panic!("expected a single location, got: {response:#?}");
+    };
+    assert_eq!(location.uri, ls.doc_id("world/lib.cairo").uri);
+    assert_eq!(location.range, Range::new(Position::new(0, 0), Position::new(0, 17)));
+}
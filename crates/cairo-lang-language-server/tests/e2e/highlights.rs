@@ -0,0 +1,89 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{
+    ClientCapabilities, DocumentHighlightKind, DocumentHighlightParams,
+    DynamicRegistrationClientCapabilities, TextDocumentClientCapabilities,
+    TextDocumentPositionParams, lsp_request,
+};
+
+use crate::support::cursor::{peek_caret, peek_selection};
+use crate::support::{cursors, sandbox};
+
+cairo_lang_test_utils::test_file_test!(
+    highlights,
+    "tests/test_data/highlights",
+    {
+        items: "items.txt",
+        variables: "variables.txt",
+    },
+    test_highlights
+);
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: base.text_document.or_else(Default::default).map(|it| {
+            TextDocumentClientCapabilities {
+                document_highlight: Some(DynamicRegistrationClientCapabilities {
+                    dynamic_registration: Some(false),
+                }),
+                ..it
+            }
+        }),
+        ..base
+    }
+}
+
+/// Perform document highlight test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then requests highlights at each caret position and reports each highlighted
+/// occurrence along with its kind.
+fn test_highlights(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+            "src/other.cairo" => inputs.get("other_code").cloned().unwrap_or_default(),
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut highlights = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let result = ls.send_request::<lsp_request!("textDocument/documentHighlight")>(
+            DocumentHighlightParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: ls.doc_id("src/lib.cairo"),
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        );
+        match result {
+            None => report.push_str("No highlights.\n"),
+            Some(result) => {
+                for highlight in result {
+                    let kind = match highlight.kind {
+                        Some(DocumentHighlightKind::READ) => "read",
+                        Some(DocumentHighlightKind::WRITE) => "write",
+                        _ => "text",
+                    };
+                    report
+                        .push_str(&format!("{kind}: {}", peek_selection(&cairo, &highlight.range)));
+                }
+            }
+        }
+        highlights.insert(format!("Highlights #{n}"), report);
+    }
+
+    TestRunnerResult::success(highlights)
+}
@@ -12,25 +12,31 @@ cairo_lang_test_utils::test_file_test!(
     hover,
     "tests/test_data/hover",
     {
+        attributes: "attributes.txt",
         basic: "basic.txt",
         missing_module: "missing_module.txt",
         partial: "partial.txt",
+        plaintext: "plaintext.txt",
         starknet: "starknet.txt",
         literals: "literals.txt",
+        macros: "macros.txt",
     },
     test_hover
 );
 
-fn caps(base: ClientCapabilities) -> ClientCapabilities {
-    ClientCapabilities {
-        text_document: base.text_document.or_else(Default::default).map(|it| {
-            TextDocumentClientCapabilities {
-                hover: Some(HoverClientCapabilities {
-                    dynamic_registration: Some(false),
-                    content_format: Some(vec![MarkupKind::Markdown, MarkupKind::PlainText]),
-                }),
-                ..it
-            }
+fn caps(markdown: bool) -> impl FnOnce(ClientCapabilities) -> ClientCapabilities {
+    let content_format = if markdown {
+        vec![MarkupKind::Markdown, MarkupKind::PlainText]
+    } else {
+        vec![MarkupKind::PlainText]
+    };
+    move |base| ClientCapabilities {
+        text_document: Some(TextDocumentClientCapabilities {
+            hover: Some(HoverClientCapabilities {
+                dynamic_registration: Some(false),
+                content_format: Some(content_format),
+            }),
+            ..base.text_document.unwrap_or_default()
         }),
         ..base
     }
@@ -44,16 +50,17 @@ fn caps(base: ClientCapabilities) -> ClientCapabilities {
 /// the expected hover information from the snapshot file.
 fn test_hover(
     inputs: &OrderedHashMap<String, String>,
-    _args: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
 ) -> TestRunnerResult {
     let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+    let markdown = args.get("markdown").is_none_or(|value| value == "true");
 
     let mut ls = sandbox! {
         files {
             "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
             "src/lib.cairo" => cairo.clone(),
         }
-        client_capabilities = caps;
+        client_capabilities = caps(markdown);
     };
 
     ls.open("src/lib.cairo");
@@ -0,0 +1,92 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{
+    ClientCapabilities, InlayHint, InlayHintClientCapabilities, InlayHintLabel, InlayHintParams,
+    Position, Range, TextDocumentClientCapabilities, lsp_request,
+};
+use serde_json::json;
+
+use crate::support::sandbox;
+
+cairo_lang_test_utils::test_file_test!(
+    inlay_hints,
+    "tests/test_data/inlay_hints",
+    {
+        generic_args: "generic_args.txt",
+        let_types: "let_types.txt",
+    },
+    test_inlay_hints
+);
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: base.text_document.or_else(Default::default).map(|it| {
+            TextDocumentClientCapabilities {
+                inlay_hint: Some(InlayHintClientCapabilities {
+                    dynamic_registration: Some(false),
+                    resolve_support: None,
+                }),
+                ..it
+            }
+        }),
+        ..base
+    }
+}
+
+/// Perform inlay hints test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The function then requests inlay hints for the whole file and renders the code with the hints
+/// inserted, each wrapped in `<hint>` tags.
+fn test_inlay_hints(
+    inputs: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let cairo = &inputs["cairo_code"];
+    let generic_args = args.get("generic_args").is_none_or(|value| value == "true");
+    let type_hints = args.get("type_hints").is_none_or(|value| value == "true");
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+        client_capabilities = caps;
+        workspace_configuration = json!({
+            "cairo1": {
+                "inlayHints": {
+                    "genericArgs": generic_args,
+                    "typeHints": type_hints,
+                }
+            }
+        });
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let hints = ls
+        .send_request::<lsp_request!("textDocument/inlayHint")>(InlayHintParams {
+            text_document: ls.doc_id("src/lib.cairo"),
+            range: Range::new(Position::new(0, 0), Position::new(u32::MAX, 0)),
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap_or_default();
+
+    TestRunnerResult::success(OrderedHashMap::from([(
+        "Inlay hints".to_owned(),
+        render_hints(cairo, hints),
+    )]))
+}
+
+/// Inserts labels of inlay hints into the source code.
+fn render_hints(cairo: &str, mut hints: Vec<InlayHint>) -> String {
+    hints.sort_by_key(|hint| hint.position);
+    let mut lines = cairo.lines().map(ToOwned::to_owned).collect::<Vec<_>>();
+    for hint in hints.iter().rev() {
+        let InlayHintLabel::String(label) = &hint.label else {
+            panic!("expected a plain string label");
+        };
+        let line = &mut lines[hint.position.line as usize];
+        line.insert_str(hint.position.character as usize, &format!("<hint>{label}</hint>"));
+    }
+    lines.join("\n")
+}
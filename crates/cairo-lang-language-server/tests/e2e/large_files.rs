@@ -0,0 +1,158 @@
+use cairo_lang_language_server::lsp::ext::FileTooLarge;
+use indoc::indoc;
+use lsp_server::Message;
+use lsp_types::notification::Notification;
+use lsp_types::{lsp_notification, lsp_request};
+use serde_json::json;
+
+use crate::support::sandbox;
+
+fn caps(base: lsp_types::ClientCapabilities) -> lsp_types::ClientCapabilities {
+    lsp_types::ClientCapabilities {
+        text_document: base.text_document.or_else(Default::default).map(|it| {
+            lsp_types::TextDocumentClientCapabilities {
+                semantic_tokens: Some(lsp_types::SemanticTokensClientCapabilities {
+                    dynamic_registration: Some(false),
+                    requests: lsp_types::SemanticTokensClientCapabilitiesRequests {
+                        range: Some(true),
+                        full: Some(lsp_types::SemanticTokensFullOptions::Bool(true)),
+                    },
+                    ..Default::default()
+                }),
+                ..it
+            }
+        }),
+        ..base
+    }
+}
+
+/// Files exceeding `cairo1.maxFileSizeKb` should only get lightweight analysis:
+/// parser diagnostics are reported, while semantic diagnostics and semantic tokens are skipped,
+/// and the client is notified about the limit.
+#[test]
+fn large_file_skips_expensive_analysis() {
+    let mut code = indoc! {r#"
+        fn main() -> felt252 {
+            undefined_variable
+        }
+
+        fn broken() {
+            let _a = 1
+        }
+    "#}
+    .to_string();
+    while code.len() <= 2 * 1024 {
+        code.push_str("// This line only exists to make the file exceed the size limit.\n");
+    }
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => code,
+        }
+        client_capabilities = caps;
+        workspace_configuration = json!({
+            "cairo1": {
+                "maxFileSizeKb": 1,
+            }
+        });
+    };
+
+    // Make sure the workspace configuration is loaded before opening the file.
+    ls.send_request::<cairo_lang_language_server::lsp::ext::ViewAnalyzedCrates>(());
+
+    let diags = ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    let messages = diags.diagnostics.iter().map(|diag| diag.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(messages, vec!["Missing token TerminalSemicolon."]);
+
+    let notification = ls.wait_for_notification::<FileTooLarge>(|_| true);
+    assert_eq!(notification.uri, ls.doc_id("src/lib.cairo").uri);
+    assert_eq!(notification.size_kb, 3);
+    assert_eq!(notification.max_file_size_kb, 1);
+
+    let tokens = ls.send_request::<lsp_request!("textDocument/semanticTokens/full")>(
+        lsp_types::SemanticTokensParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: ls.doc_id("src/lib.cairo"),
+        },
+    );
+    assert_eq!(tokens, None);
+
+    // Ranges are cheap to highlight, so they are served regardless of the file size.
+    let tokens = ls.send_request::<lsp_request!("textDocument/semanticTokens/range")>(
+        lsp_types::SemanticTokensRangeParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: ls.doc_id("src/lib.cairo"),
+            range: lsp_types::Range::new(
+                lsp_types::Position::new(0, 0),
+                lsp_types::Position::new(3, 0),
+            ),
+        },
+    );
+    assert!(tokens.is_some());
+}
+
+/// Files growing past `cairo1.maxFileSizeKb` while being edited are reported once.
+#[test]
+fn file_growing_too_large_is_reported_once() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+        workspace_configuration = json!({
+            "cairo1": {
+                "maxFileSizeKb": 1,
+            }
+        });
+    };
+
+    ls.send_request::<cairo_lang_language_server::lsp::ext::ViewAnalyzedCrates>(());
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let comment = "// This line only exists to make the file exceed the size limit.\n".repeat(40);
+    for version in 1..=2 {
+        ls.send_notification::<lsp_notification!("textDocument/didChange")>(
+            lsp_types::DidChangeTextDocumentParams {
+                text_document: lsp_types::VersionedTextDocumentIdentifier {
+                    uri: ls.doc_id("src/lib.cairo").uri,
+                    version,
+                },
+                content_changes: vec![lsp_types::TextDocumentContentChangeEvent {
+                    range: Some(lsp_types::Range::new(
+                        lsp_types::Position::new(0, 0),
+                        lsp_types::Position::new(0, 0),
+                    )),
+                    range_length: None,
+                    text: comment.clone(),
+                }],
+            },
+        );
+    }
+
+    let notification = ls.wait_for_notification::<FileTooLarge>(|_| true);
+    assert_eq!(notification.uri, ls.doc_id("src/lib.cairo").uri);
+    assert_eq!(notification.max_file_size_kb, 1);
+
+    // Make sure both changes have been processed before counting notifications.
+    ls.send_request::<cairo_lang_language_server::lsp::ext::ViewAnalyzedCrates>(());
+    let notifications = ls
+        .trace()
+        .iter()
+        .filter(|message| {
+            matches!(
+                message,
+                Message::Notification(notification) if notification.method == FileTooLarge::METHOD
+            )
+        })
+        .count();
+    assert_eq!(notifications, 1);
+}
@@ -0,0 +1,60 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{LinkedEditingRangeParams, TextDocumentPositionParams, lsp_request};
+
+use crate::support::cursor::{peek_caret, peek_selection};
+use crate::support::{cursors, sandbox};
+
+cairo_lang_test_utils::test_file_test!(
+    linked_editing_ranges,
+    "tests/test_data/linked_editing_ranges",
+    {
+        linked_editing_ranges: "linked_editing_ranges.txt",
+    },
+    test_linked_editing_ranges
+);
+
+/// Perform linked editing range test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then requests linked editing ranges at each caret position and reports each range.
+fn test_linked_editing_ranges(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut linked_editing_ranges = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let result = ls.send_request::<lsp_request!("textDocument/linkedEditingRange")>(
+            LinkedEditingRangeParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: ls.doc_id("src/lib.cairo"),
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+            },
+        );
+        match result {
+            None => report.push_str("No linked editing ranges.\n"),
+            Some(result) => {
+                for range in result.ranges {
+                    report.push_str(&peek_selection(&cairo, &range));
+                }
+            }
+        }
+        linked_editing_ranges.insert(format!("Linked editing ranges #{n}"), report);
+    }
+
+    TestRunnerResult::success(linked_editing_ranges)
+}
@@ -1,10 +1,22 @@
-use cairo_lang_language_server::lsp::ext::ExpandMacro;
+use cairo_lang_language_server::lsp::ext::{
+    ExpandMacro, ExpandMacroDocument, ExpandedCrateExported, ProvideVirtualFile,
+    ProvideVirtualFileRequest,
+};
 use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
 use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
-use lsp_types::{TextDocumentIdentifier, TextDocumentPositionParams};
+use indoc::indoc;
+use lsp_types::{
+    ClientCapabilities, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
+    ExecuteCommandParams, GotoDefinitionParams, Position, Range, SemanticTokensClientCapabilities,
+    SemanticTokensClientCapabilitiesRequests, SemanticTokensFullOptions, SemanticTokensParams,
+    SemanticTokensResult, TextDocumentClientCapabilities, TextDocumentContentChangeEvent,
+    TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, Url,
+    VersionedTextDocumentIdentifier, lsp_notification, lsp_request,
+};
+use serde_json::json;
 
 use crate::support::cursor::peek_caret;
-use crate::support::{cursors, sandbox};
+use crate::support::{MockClient, cursors, sandbox};
 
 cairo_lang_test_utils::test_file_test!(
     macro_expand,
@@ -50,7 +62,7 @@ fn test_macro_expand(
 
         report.push_str("// = expansion\n");
         if let Some(expansion) = &macro_expansion {
-            report.push_str(expansion);
+            report.push_str(&expansion.expansion);
         } else {
             report.push_str("No expansion information.\n");
         }
@@ -60,3 +72,340 @@ fn test_macro_expand(
 
     TestRunnerResult::success(results)
 }
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: base.text_document.or_else(Default::default).map(|it| {
+            TextDocumentClientCapabilities {
+                semantic_tokens: Some(SemanticTokensClientCapabilities {
+                    dynamic_registration: Some(false),
+                    requests: SemanticTokensClientCapabilitiesRequests {
+                        full: Some(SemanticTokensFullOptions::Bool(true)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+                ..it
+            }
+        }),
+        ..base
+    }
+}
+
+#[test]
+fn expansion_is_mapped_to_source() {
+    let source = indoc! {r#"
+        fn f() -> Array<felt252> {
+            array![1, 2]
+        }
+    "#};
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => source,
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let response = ls
+        .send_request::<ExpandMacro>(TextDocumentPositionParams {
+            position: Position { line: 1, character: 6 },
+            text_document: ls.doc_id("src/lib.cairo"),
+        })
+        .expect("macro call should be expandable");
+
+    let text_in = |text: &str, range: Range| {
+        text_in_range(text, range).expect("range should fit in the text").to_string()
+    };
+    let mappings = response
+        .code_mappings
+        .iter()
+        .map(|mapping| {
+            (text_in(&response.expansion, mapping.generated), text_in(source, mapping.origin))
+        })
+        .collect::<Vec<_>>();
+
+    let macro_call = "array![1, 2]".to_string();
+    assert_eq!(
+        mappings,
+        [
+            (
+                indoc! {"
+                    {
+                        let mut __array_builder_macro_result__ = core::array::ArrayTrait::new();
+                        core::array::ArrayTrait::append(ref __array_builder_macro_result__,"
+                }
+                .to_string(),
+                macro_call.clone()
+            ),
+            ("1".to_string(), "1".to_string()),
+            (
+                indoc! {"
+                    );
+                        core::array::ArrayTrait::append(ref __array_builder_macro_result__,"
+                }
+                .to_string(),
+                macro_call.clone()
+            ),
+            ("2".to_string(), "2".to_string()),
+            (
+                indoc! {"
+                    );
+                        __array_builder_macro_result__
+                    }"
+                }
+                .to_string(),
+                macro_call
+            ),
+        ]
+    );
+}
+
+/// Returns the text within the range, assuming the text is ASCII.
+fn text_in_range(text: &str, range: Range) -> Option<&str> {
+    let offset = |position: Position| {
+        let line_start: usize =
+            text.split_inclusive('\n').take(position.line as usize).map(str::len).sum();
+        line_start + position.character as usize
+    };
+    text.get(offset(range.start)..offset(range.end))
+}
+
+#[test]
+fn expansion_document_follows_source() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                fn f() -> Array<felt252> {
+                    let x = 1;
+                    array![x]
+                }
+            "#},
+        }
+        client_capabilities = caps;
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let uri = ls
+        .send_request::<ExpandMacroDocument>(TextDocumentPositionParams {
+            position: Position { line: 2, character: 6 },
+            text_document: ls.doc_id("src/lib.cairo"),
+        })
+        .expect("macro call should be expandable");
+    assert_eq!(uri.scheme(), "cairo-expand");
+    assert!(uri.path().ends_with(".cairo"));
+
+    let content = ls.send_request::<ProvideVirtualFile>(ProvideVirtualFileRequest {
+        uri: uri.clone(),
+        range: None,
+    });
+    let content = content.content.unwrap();
+    assert_eq!(
+        content,
+        indoc! {r#"
+            // lib.cairo
+            // ---------
+
+            {
+                let mut __array_builder_macro_result__ = core::array::ArrayTrait::new();
+                core::array::ArrayTrait::append(ref __array_builder_macro_result__, x);
+                __array_builder_macro_result__
+            }"#
+        }
+    );
+
+    // The expansion document is analyzed like other documents once opened.
+    ls.send_notification::<lsp_notification!("textDocument/didOpen")>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "cairo".to_string(),
+            version: 0,
+            text: content.clone(),
+        },
+    });
+    let tokens = ls
+        .send_request::<lsp_request!("textDocument/semanticTokens/full")>(SemanticTokensParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap();
+    let SemanticTokensResult::Tokens(tokens) = tokens else { panic!("expected full tokens") };
+    assert!(!tokens.data.is_empty());
+
+    // Symbols copied from the source are resolved like in the source.
+    let definition_at = |ls: &mut MockClient, uri: &Url, position: Position| {
+        ls.send_request::<lsp_request!("textDocument/definition")>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    };
+    let line = content.lines().position(|line| line.contains(", x);")).unwrap();
+    let character = content.lines().nth(line).unwrap().find(", x);").unwrap() + 2;
+    let definition =
+        definition_at(&mut ls, &uri, Position::new(line as u32, character as u32)).unwrap();
+    let source_uri = ls.doc_id("src/lib.cairo").uri;
+    assert_eq!(Some(definition), definition_at(&mut ls, &source_uri, Position::new(2, 11)));
+
+    // The document follows changes of the call.
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(
+        DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: ls.doc_id("src/lib.cairo").uri,
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: indoc! {r#"
+                    fn f() -> Array<felt252> {
+                        let x = 1;
+                        array![x, 2]
+                    }
+                "#}
+                .to_string(),
+            }],
+        },
+    );
+
+    let content = ls.send_request::<ProvideVirtualFile>(ProvideVirtualFileRequest {
+        uri: uri.clone(),
+        range: None,
+    });
+    let content = content.content.unwrap();
+    assert!(
+        content.contains("append(ref __array_builder_macro_result__, 2)"),
+        "expansion document should follow changes of the macro call"
+    );
+    assert!(
+        content.contains("append(ref __array_builder_macro_result__, x)"),
+        "expansion document should keep expanding the same macro call"
+    );
+
+    let content = ls.send_request::<ProvideVirtualFile>(ProvideVirtualFileRequest {
+        uri: uri.clone(),
+        range: Some(Range {
+            start: Position { line: 5, character: 4 },
+            end: Position { line: 5, character: 35 },
+        }),
+    });
+    assert_eq!(content.content.as_deref(), Some("core::array::ArrayTrait::append"));
+
+    // Once the call is gone, there is nothing to expand.
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(
+        DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: ls.doc_id("src/lib.cairo").uri,
+                version: 2,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: indoc! {r#"
+                    fn f() -> Array<felt252> {
+                        let x = 1;
+                        x
+                    }
+                "#}
+                .to_string(),
+            }],
+        },
+    );
+    let content =
+        ls.send_request::<ProvideVirtualFile>(ProvideVirtualFileRequest { uri, range: None });
+    assert_eq!(content.content, None);
+}
+
+#[test]
+fn export_expanded_crate() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                mod a;
+
+                #[derive(Drop)]
+                struct A {}
+            "#},
+            "src/a.cairo" => indoc! {r#"
+                fn f() -> Array<felt252> {
+                    array![1]
+                }
+            "#},
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let output = ls.as_ref().root_path().join("expanded");
+    let result =
+        ls.send_request::<lsp_request!("workspace/executeCommand")>(ExecuteCommandParams {
+            command: "cairo.exportExpandedCrate".to_string(),
+            arguments: vec![json!({ "crateName": "hello", "output": output })],
+            work_done_progress_params: Default::default(),
+        });
+    assert_eq!(result, None);
+
+    let notification = ls.wait_for_notification::<ExpandedCrateExported>(|_| true);
+    assert_eq!(notification.crate_name, "hello");
+    assert_eq!(notification.output, output);
+    assert_eq!(notification.error, None);
+
+    assert_eq!(
+        ls.as_ref().read_file("expanded/lib.cairo"),
+        indoc! {r#"
+        // lib.cairo
+        // ---------
+
+        mod a;
+
+        #[derive(Drop)]
+        struct A {}
+
+        // impls
+        // -----
+
+        impl ADrop of core::traits::Drop<A>;
+    "#}
+    );
+    assert_eq!(
+        ls.as_ref().read_file("expanded/a.cairo"),
+        indoc! {r#"
+        // a.cairo
+        // -------
+
+        fn f() -> Array<felt252> {
+            {
+                let mut __array_builder_macro_result__ = core::array::ArrayTrait::new();
+                core::array::ArrayTrait::append(ref __array_builder_macro_result__, 1);
+                __array_builder_macro_result__
+            }
+        }
+    "#}
+    );
+}
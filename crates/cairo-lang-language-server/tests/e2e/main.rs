@@ -1,9 +1,36 @@
 mod analysis;
+mod call_hierarchy;
 mod code_actions;
+mod code_lens;
 mod completions;
+mod diagnostics;
+mod document_links;
+mod folding_ranges;
+mod formatting;
 mod goto;
+mod highlights;
 mod hover;
+mod inlay_hints;
+mod large_files;
+mod linked_editing_ranges;
 mod macro_expand;
+mod memory;
+mod on_type_formatting;
+mod open_files_diagnostics;
+mod position_encoding;
+mod pull_diagnostics;
+mod range_formatting;
+mod references;
+mod rename;
+mod request_timeout;
+mod selection_ranges;
 mod semantic_tokens;
+mod signature_help;
+mod syntax_tree;
 mod support;
+mod text_sync;
+mod type_hierarchy;
+mod work_done_progress;
 mod workspace_configuration;
+mod workspace_folders;
+mod workspace_symbols;
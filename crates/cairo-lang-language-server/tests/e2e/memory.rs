@@ -0,0 +1,152 @@
+use cairo_lang_language_server::lsp;
+use indoc::indoc;
+use lsp_types::{
+    DidChangeTextDocumentParams, ExecuteCommandParams, HoverContents, HoverParams, MarkupContent,
+    Position, TextDocumentContentChangeEvent, TextDocumentPositionParams,
+    VersionedTextDocumentIdentifier, lsp_notification, lsp_request,
+};
+use pretty_assertions::assert_eq;
+
+use crate::support::sandbox;
+
+#[test]
+fn view_memory_usage() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let output = ls.send_request::<lsp::ext::ViewMemoryUsage>(());
+
+    // Numbers depend on the contents of the corelib, so only check that they were computed.
+    let counts = output
+        .lines()
+        .filter_map(|line| line.rsplit_once(": "))
+        .map(|(label, count)| (label, count.parse::<usize>().unwrap()))
+        .collect::<Vec<_>>();
+    assert!(counts.iter().all(|&(_, count)| count > 0), "{output}");
+
+    let labels = counts.into_iter().map(|(label, _)| label).collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            "- files",
+            "- crates",
+            "- modules",
+            "- parsed files (`file_syntax`)",
+            "- module definitions (`priv_module_data`)",
+            "- module semantics (`priv_module_semantic_data`)",
+            "- function bodies (`function_body`)",
+            "- lowered functions (`priv_function_with_body_multi_lowering`)",
+            "- module semantic diagnostics (`module_semantic_diagnostics`)",
+            "- module lowering diagnostics (`module_lowering_diagnostics`)",
+            "- total",
+        ]
+    );
+}
+
+#[test]
+fn swap_database_keeps_unsaved_changes() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(
+        DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: ls.doc_id("src/lib.cairo").uri,
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "fn edited() {}".to_string(),
+            }],
+        },
+    );
+
+    ls.send_request::<lsp_request!("workspace/executeCommand")>(ExecuteCommandParams {
+        command: "cairo.swapDatabase".into(),
+        ..Default::default()
+    });
+
+    let hover = ls
+        .send_request::<lsp_request!("textDocument/hover")>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: ls.doc_id("src/lib.cairo"),
+                position: Position { line: 0, character: 4 },
+            },
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap();
+    let HoverContents::Markup(MarkupContent { value, .. }) = hover.contents else {
+        panic!("unexpected hover contents: {:?}", hover.contents);
+    };
+    assert!(value.contains("fn edited()"), "{value}");
+}
+
+#[test]
+fn clear_cache_keeps_unsaved_changes() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(
+        DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: ls.doc_id("src/lib.cairo").uri,
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "fn edited() -> felt252 { true }".to_string(),
+            }],
+        },
+    );
+
+    ls.send_request::<lsp_request!("workspace/executeCommand")>(ExecuteCommandParams {
+        command: "cairo.clearCache".into(),
+        ..Default::default()
+    });
+
+    let hover = ls
+        .send_request::<lsp_request!("textDocument/hover")>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: ls.doc_id("src/lib.cairo"),
+                position: Position { line: 0, character: 4 },
+            },
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap();
+    let HoverContents::Markup(MarkupContent { value, .. }) = hover.contents else {
+        panic!("unexpected hover contents: {:?}", hover.contents);
+    };
+    assert!(value.contains("fn edited()"), "{value}");
+
+    let uri = ls.doc_id("src/lib.cairo").uri;
+    ls.wait_for_notification::<lsp_notification!("textDocument/publishDiagnostics")>(|params| {
+        params.uri == uri && params.diagnostics.len() == 1
+    });
+}
@@ -0,0 +1,113 @@
+use indoc::indoc;
+use lsp_types::{
+    DocumentOnTypeFormattingParams, Position, TextDocumentPositionParams, lsp_request,
+};
+
+use crate::support::sandbox;
+use crate::support::text_edits::apply_edits;
+
+/// Sends an on-type formatting request for the character typed just before the position in
+/// `src/lib.cairo` and returns the file with the edits applied.
+fn format_on_type(cairo: &str, position: Position, ch: &str) -> String {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => cairo,
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let edits = ls
+        .send_request::<lsp_request!("textDocument/onTypeFormatting")>(
+            DocumentOnTypeFormattingParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: ls.doc_id("src/lib.cairo"),
+                    position,
+                },
+                ch: ch.to_string(),
+                options: Default::default(),
+            },
+        )
+        .unwrap_or_default();
+    apply_edits(cairo, &edits)
+}
+
+#[test]
+fn semicolon_formats_only_the_statement() {
+    let cairo = indoc! {"
+        fn main() {
+            let   x=1;
+            let y  =  2;
+        }
+    "};
+    let formatted = format_on_type(cairo, Position { line: 1, character: 14 }, ";");
+    assert_eq!(
+        formatted,
+        indoc! {"
+        fn main() {
+            let x = 1;
+            let y  =  2;
+        }
+    "}
+    );
+}
+
+#[test]
+fn closing_brace_formats_only_the_block() {
+    let cairo = indoc! {"
+        fn a() {
+        let x = 1;
+        }
+        fn   b() {}
+    "};
+    let formatted = format_on_type(cairo, Position { line: 2, character: 1 }, "}");
+    assert_eq!(
+        formatted,
+        indoc! {"
+        fn a() {
+            let x = 1;
+        }
+        fn   b() {}
+    "}
+    );
+}
+
+#[test]
+fn new_line_formats_the_previous_statement() {
+    let cairo = indoc! {"
+        fn main() {
+            let x=1;
+
+            let y  =  2;
+        }
+    "};
+    let formatted = format_on_type(cairo, Position { line: 2, character: 4 }, "\n");
+    assert_eq!(
+        formatted,
+        indoc! {"
+        fn main() {
+            let x = 1;
+
+            let y  =  2;
+        }
+    "}
+    );
+}
+
+#[test]
+fn nothing_is_formatted_in_code_with_syntax_errors() {
+    let cairo = indoc! {"
+        fn main() {
+            let   x=1;
+            let y =
+        }
+    "};
+    let formatted = format_on_type(cairo, Position { line: 1, character: 14 }, ";");
+    assert_eq!(formatted, cairo);
+}
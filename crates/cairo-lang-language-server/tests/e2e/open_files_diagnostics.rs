@@ -0,0 +1,70 @@
+use indoc::indoc;
+use lsp_server::Message;
+use lsp_types::lsp_notification;
+use lsp_types::notification::Notification;
+use serde_json::json;
+
+use crate::support::{MockClient, sandbox};
+
+fn project(open_files_only: bool) -> MockClient {
+    sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                mod a;
+                mod b;
+                fn main() -> felt252 { 1_u8 }
+            "#},
+            "src/a.cairo" => "fn foo() -> felt252 { true }",
+            "src/b.cairo" => "fn bar() -> felt252 { true }",
+        }
+        workspace_configuration = json!({
+            "cairo1": {
+                "diagnostics": {
+                    "openFilesOnly": open_files_only,
+                }
+            }
+        });
+    }
+}
+
+/// Checks whether diagnostics of the file were published so far.
+fn were_published(ls: &MockClient, path: &str) -> bool {
+    let uri = ls.doc_id(path).uri;
+    ls.trace().iter().any(|message| match message {
+        Message::Notification(notification) => {
+            notification.method == <lsp_notification!("textDocument/publishDiagnostics")>::METHOD
+                && notification.params["uri"] == uri.as_str()
+        }
+        _ => false,
+    })
+}
+
+#[test]
+fn closed_files_are_analysed_by_default() {
+    let mut ls = project(false);
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    let diagnostics = ls.wait_for_diagnostics("src/a.cairo");
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+}
+
+#[test]
+fn only_open_files_are_analysed() {
+    let mut ls = project(true);
+
+    // Make sure the workspace configuration is loaded before opening files.
+    ls.send_request::<cairo_lang_language_server::lsp::ext::ViewAnalyzedCrates>(());
+
+    let diagnostics = ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    let diagnostics = ls.open_and_wait_for_diagnostics("src/b.cairo");
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+
+    assert!(!were_published(&ls, "src/a.cairo"));
+}
@@ -0,0 +1,73 @@
+use indoc::indoc;
+use lsp_types::{
+    ClientCapabilities, GeneralClientCapabilities, HoverParams, Position, PositionEncodingKind,
+    Range, TextDocumentPositionParams, lsp_request,
+};
+use pretty_assertions::assert_eq;
+
+use crate::support::sandbox;
+
+/// Line with characters taking 4 bytes in UTF-8 and 2 code units in UTF-16 before the variable `x`.
+const CODE: &str = r#"fn main() { let _s: ByteArray = "😀"; let x = 5; }"#;
+
+fn caps(encoding: PositionEncodingKind) -> impl FnOnce(ClientCapabilities) -> ClientCapabilities {
+    move |base| ClientCapabilities {
+        general: Some(GeneralClientCapabilities {
+            position_encodings: Some(vec![encoding]),
+            ..base.general.unwrap_or_default()
+        }),
+        ..base
+    }
+}
+
+/// Checks that positions of the variable `x` are exchanged in columns counted in the encoding.
+fn test_position_encoding(encoding: PositionEncodingKind, x_col: u32) {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => CODE,
+        }
+        client_capabilities = caps(encoding);
+    };
+
+    let x_range = Range {
+        start: Position { line: 0, character: x_col },
+        end: Position { line: 0, character: x_col + 1 },
+    };
+
+    let diagnostics = ls.open_and_wait_for_diagnostics("src/lib.cairo").diagnostics;
+    let unused_variable = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic.message.starts_with("Unused variable."))
+        .unwrap_or_else(|| panic!("expected an unused variable diagnostic, got: {diagnostics:#?}"));
+    assert_eq!(unused_variable.range, x_range);
+
+    let hover = ls
+        .send_request::<lsp_request!("textDocument/hover")>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: ls.doc_id("src/lib.cairo"),
+                position: x_range.start,
+            },
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap();
+    assert_eq!(hover.range, Some(x_range));
+}
+
+#[test]
+fn utf8() {
+    test_position_encoding(PositionEncodingKind::UTF8, 44);
+}
+
+#[test]
+fn utf16() {
+    test_position_encoding(PositionEncodingKind::UTF16, 42);
+}
+
+#[test]
+fn utf32() {
+    test_position_encoding(PositionEncodingKind::UTF32, 41);
+}
@@ -0,0 +1,155 @@
+use indoc::indoc;
+use lsp_types::{
+    ClientCapabilities, DiagnosticClientCapabilities, DocumentDiagnosticParams,
+    DocumentDiagnosticReport, DocumentDiagnosticReportResult, PreviousResultId,
+    TextDocumentClientCapabilities, WorkspaceDiagnosticParams, WorkspaceDiagnosticReportResult,
+    WorkspaceDocumentDiagnosticReport, lsp_request,
+};
+
+use crate::support::{MockClient, sandbox};
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: Some(TextDocumentClientCapabilities {
+            diagnostic: Some(DiagnosticClientCapabilities {
+                dynamic_registration: Some(false),
+                related_document_support: Some(false),
+            }),
+            ..base.text_document.unwrap_or_default()
+        }),
+        ..base
+    }
+}
+
+fn workspace_diagnostics(
+    ls: &mut MockClient,
+    previous_result_ids: Vec<PreviousResultId>,
+) -> Vec<WorkspaceDocumentDiagnosticReport> {
+    let result =
+        ls.send_request::<lsp_request!("workspace/diagnostic")>(WorkspaceDiagnosticParams {
+            identifier: None,
+            previous_result_ids,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+    match result {
+        WorkspaceDiagnosticReportResult::Report(report) => report.items,
+        WorkspaceDiagnosticReportResult::Partial(_) => panic!("expected a full report"),
+    }
+}
+
+#[test]
+fn workspace_diagnostics_of_closed_files() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                mod a;
+                fn main() -> felt252 { 1_u8 }
+            "#},
+            "src/a.cairo" => "fn foo() -> felt252 { true }",
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    // Diagnostics of the open file are published, so only the closed one is reported.
+    let items = workspace_diagnostics(&mut ls, vec![]);
+    let [WorkspaceDocumentDiagnosticReport::Full(report)] = items.as_slice() else {
+        panic!("expected a single full report, got: {items:#?}");
+    };
+    assert_eq!(report.uri, ls.doc_id("src/a.cairo").uri);
+    let messages = report
+        .full_document_diagnostic_report
+        .items
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [r#"Unexpected return type. Expected: "core::felt252", found: "core::bool"."#]
+    );
+    let result_id = report.full_document_diagnostic_report.result_id.clone().unwrap();
+
+    let items = workspace_diagnostics(
+        &mut ls,
+        vec![PreviousResultId { uri: report.uri.clone(), value: result_id.clone() }],
+    );
+    let [WorkspaceDocumentDiagnosticReport::Unchanged(report)] = items.as_slice() else {
+        panic!("expected a single unchanged report, got: {items:#?}");
+    };
+    assert_eq!(report.unchanged_document_diagnostic_report.result_id, result_id);
+}
+
+#[test]
+fn document_diagnostics_of_open_file_are_empty() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => "fn main() -> felt252 { 1_u8 }",
+        }
+        client_capabilities = caps;
+    };
+    let published = ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    assert!(!published.diagnostics.is_empty());
+
+    let result =
+        ls.send_request::<lsp_request!("textDocument/diagnostic")>(DocumentDiagnosticParams {
+            text_document: ls.doc_id("src/lib.cairo"),
+            identifier: None,
+            previous_result_id: None,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+    let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) = result
+    else {
+        panic!("expected a full report, got: {result:#?}");
+    };
+    assert!(report.full_document_diagnostic_report.items.is_empty());
+}
+
+#[test]
+fn diagnostics_of_closed_files_are_published_without_workspace_pulls() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                mod a;
+                fn main() -> felt252 { 1 }
+            "#},
+            "src/a.cairo" => "fn foo() -> felt252 { true }",
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    // The client supports pulling diagnostics of documents, but it never pulled the workspace.
+    let published = ls.wait_for_diagnostics("src/a.cairo");
+    let messages = published
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [r#"Unexpected return type. Expected: "core::felt252", found: "core::bool"."#]
+    );
+}
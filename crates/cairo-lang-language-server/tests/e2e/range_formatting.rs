@@ -0,0 +1,101 @@
+use indoc::indoc;
+use lsp_types::{DocumentRangeFormattingParams, Position, Range, lsp_request};
+
+use crate::support::sandbox;
+use crate::support::text_edits::apply_edits;
+
+/// Sends a range formatting request for `src/lib.cairo` and returns the file with the edits
+/// applied.
+fn format_range(cairo: &str, range: Range) -> String {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => cairo,
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let edits = ls
+        .send_request::<lsp_request!("textDocument/rangeFormatting")>(
+            DocumentRangeFormattingParams {
+                text_document: ls.doc_id("src/lib.cairo"),
+                range,
+                options: Default::default(),
+                work_done_progress_params: Default::default(),
+            },
+        )
+        .unwrap_or_default();
+    apply_edits(cairo, &edits)
+}
+
+const CAIRO: &str = indoc! {"
+    fn main() {
+        let   x=1;
+        let y  =  2;
+        let z=3;
+    }
+    fn   other() {}
+"};
+
+#[test]
+fn selected_lines_are_formatted() {
+    let range = Range {
+        start: Position { line: 2, character: 0 },
+        end: Position { line: 3, character: 0 },
+    };
+    assert_eq!(
+        format_range(CAIRO, range),
+        indoc! {"
+        fn main() {
+            let   x=1;
+            let y = 2;
+            let z=3;
+        }
+        fn   other() {}
+    "}
+    );
+}
+
+#[test]
+fn partially_selected_statements_are_formatted() {
+    let range = Range {
+        start: Position { line: 1, character: 10 },
+        end: Position { line: 2, character: 7 },
+    };
+    assert_eq!(
+        format_range(CAIRO, range),
+        indoc! {"
+        fn main() {
+            let x = 1;
+            let y = 2;
+            let z=3;
+        }
+        fn   other() {}
+    "}
+    );
+}
+
+#[test]
+fn selection_across_items_formats_whole_items() {
+    let range = Range {
+        start: Position { line: 3, character: 4 },
+        end: Position { line: 5, character: 3 },
+    };
+    assert_eq!(
+        format_range(CAIRO, range),
+        indoc! {"
+        fn main() {
+            let x = 1;
+            let y = 2;
+            let z = 3;
+        }
+        fn other() {}
+    "}
+    );
+}
@@ -0,0 +1,149 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use indoc::indoc;
+use lsp_types::{
+    ClientCapabilities, DidChangeTextDocumentParams, DynamicRegistrationClientCapabilities,
+    ReferenceContext, ReferenceParams, TextDocumentClientCapabilities,
+    TextDocumentContentChangeEvent, TextDocumentPositionParams, VersionedTextDocumentIdentifier,
+    lsp_notification, lsp_request,
+};
+
+use crate::support::cursor::{peek_caret, peek_selection};
+use crate::support::{cursors, sandbox};
+
+cairo_lang_test_utils::test_file_test!(
+    references,
+    "tests/test_data/references",
+    {
+        functions: "functions.txt",
+        variables: "variables.txt",
+    },
+    test_references
+);
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: base.text_document.or_else(Default::default).map(|it| {
+            TextDocumentClientCapabilities {
+                references: Some(DynamicRegistrationClientCapabilities {
+                    dynamic_registration: Some(false),
+                }),
+                ..it
+            }
+        }),
+        ..base
+    }
+}
+
+fn reference_params(
+    ls: &crate::support::MockClient,
+    position: lsp_types::Position,
+    include_declaration: bool,
+) -> ReferenceParams {
+    ReferenceParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: ls.doc_id("src/lib.cairo"),
+            position,
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: ReferenceContext { include_declaration },
+    }
+}
+
+/// Perform references test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then requests references at each caret position and compares the result with the
+/// expected references from the snapshot file.
+fn test_references(
+    inputs: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+    let include_declaration = args.get("include_declaration").is_some_and(|value| value == "true");
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut references = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let locations = ls
+            .send_request::<lsp_request!("textDocument/references")>(reference_params(
+                &ls,
+                position,
+                include_declaration,
+            ))
+            .expect("references request failed");
+        for location in locations {
+            assert_eq!(location.uri, ls.doc_id("src/lib.cairo").uri);
+            report.push_str(&peek_selection(&cairo, &location.range));
+        }
+        references.insert(format!("References #{n}"), report);
+    }
+
+    TestRunnerResult::success(references)
+}
+
+#[test]
+fn references_in_unsaved_content() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                fn foo() {}
+                fn main() {
+                    foo();
+                }
+            "#},
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let (cairo, cursors) = cursors(indoc! {r#"
+        fn foo() {}
+        fn main() {
+            foo();
+            fo<caret>o();
+        }
+    "#});
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(
+        DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: ls.doc_id("src/lib.cairo").uri,
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: cairo.clone(),
+            }],
+        },
+    );
+
+    let locations = ls
+        .send_request::<lsp_request!("textDocument/references")>(reference_params(
+            &ls,
+            cursors.caret(0),
+            false,
+        ))
+        .unwrap();
+    let selections = locations
+        .iter()
+        .map(|location| peek_selection(&cairo, &location.range))
+        .collect::<Vec<_>>();
+    assert_eq!(selections, ["    <sel>foo</sel>();\n", "    <sel>foo</sel>();\n"]);
+    assert_eq!(locations[1].range.start.line, 3);
+}
@@ -0,0 +1,282 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use indoc::indoc;
+use lsp_types::{
+    ClientCapabilities, DocumentChangeOperation, DocumentChanges, OneOf, Position,
+    PrepareRenameResponse, RenameClientCapabilities, RenameFile, RenameParams, ResourceOp,
+    ResourceOperationKind, TextDocumentClientCapabilities, TextDocumentPositionParams,
+    WorkspaceClientCapabilities, WorkspaceEdit, WorkspaceEditClientCapabilities, lsp_request,
+};
+
+use crate::support::cursor::{peek_caret, peek_selection};
+use crate::support::text_edits::apply_edits;
+use crate::support::{MockClient, cursors, sandbox};
+
+cairo_lang_test_utils::test_file_test!(
+    rename,
+    "tests/test_data/rename",
+    {
+        functions: "functions.txt",
+        invalid: "invalid.txt",
+        variables: "variables.txt",
+    },
+    test_rename
+);
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: base.text_document.or_else(Default::default).map(|it| {
+            TextDocumentClientCapabilities {
+                rename: Some(RenameClientCapabilities {
+                    dynamic_registration: Some(false),
+                    prepare_support: Some(true),
+                    ..Default::default()
+                }),
+                ..it
+            }
+        }),
+        ..base
+    }
+}
+
+fn caps_with_file_renames(base: ClientCapabilities) -> ClientCapabilities {
+    let base = caps(base);
+    ClientCapabilities {
+        workspace: base.workspace.or_else(Default::default).map(|it| WorkspaceClientCapabilities {
+            workspace_edit: Some(WorkspaceEditClientCapabilities {
+                document_changes: Some(true),
+                resource_operations: Some(vec![ResourceOperationKind::Rename]),
+                ..Default::default()
+            }),
+            ..it
+        }),
+        ..base
+    }
+}
+
+fn position_params(ls: &MockClient, position: Position) -> TextDocumentPositionParams {
+    TextDocumentPositionParams { text_document: ls.doc_id("src/lib.cairo"), position }
+}
+
+fn rename_params(ls: &MockClient, position: Position, new_name: &str) -> RenameParams {
+    RenameParams {
+        text_document_position: position_params(ls, position),
+        new_name: new_name.to_string(),
+        work_done_progress_params: Default::default(),
+    }
+}
+
+/// Perform rename test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then prepares a rename at each caret position, and if that succeeds, renames the
+/// symbol to the name given in the `new_name` argument and reports the renamed code.
+fn test_rename(
+    inputs: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+    let new_name = &args["new_name"];
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut renames = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let prepared = ls.try_send_request::<lsp_request!("textDocument/prepareRename")>(
+            position_params(&ls, position),
+        );
+        match prepared {
+            Err(error) => report.push_str(&format!("Error: {}\n", error.message)),
+            Ok(None) => report.push_str("No symbol to rename.\n"),
+            Ok(Some(PrepareRenameResponse::Range(range))) => {
+                report.push_str(&peek_selection(&cairo, &range));
+                let edit = ls
+                    .send_request::<lsp_request!("textDocument/rename")>(rename_params(
+                        &ls, position, new_name,
+                    ))
+                    .expect("rename request returned no edit");
+                let edits = edit.changes.as_ref().unwrap();
+                assert_eq!(edits.keys().collect::<Vec<_>>(), [&ls.doc_id("src/lib.cairo").uri]);
+                report.push_str("---\n");
+                report.push_str(&apply_edits(&cairo, &edits[&ls.doc_id("src/lib.cairo").uri]));
+            }
+            Ok(response) => panic!("unexpected prepare rename response: {response:?}"),
+        }
+        renames.insert(format!("Rename #{n}"), report);
+    }
+
+    TestRunnerResult::success(renames)
+}
+
+#[test]
+fn rename_rejects_invalid_names() {
+    let (cairo, cursors) = cursors(indoc! {r#"
+        fn fo<caret>o() {}
+    "#});
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => cairo,
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    for new_name in ["", "1foo", "foo bar", "foo::bar", "match", "_"] {
+        let error = ls.send_request_expect_error::<lsp_request!("textDocument/rename")>(
+            rename_params(&ls, cursors.caret(0), new_name),
+        );
+        assert_eq!(error.message, format!("`{new_name}` is not a valid identifier"));
+    }
+}
+
+#[test]
+fn rename_module_is_refused_without_client_support() {
+    let (cairo, cursors) = cursors(indoc! {r#"
+        mod fo<caret>o;
+        fn main() {
+            foo::bar();
+        }
+    "#});
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => cairo,
+            "src/foo.cairo" => "pub fn bar() {}",
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let expected_message =
+        "cannot rename `foo`, because the client does not support renaming files";
+    let error = ls.send_request_expect_error::<lsp_request!("textDocument/prepareRename")>(
+        position_params(&ls, cursors.caret(0)),
+    );
+    assert_eq!(error.message, expected_message);
+    let error = ls.send_request_expect_error::<lsp_request!("textDocument/rename")>(rename_params(
+        &ls,
+        cursors.caret(0),
+        "baz",
+    ));
+    assert_eq!(error.message, expected_message);
+}
+
+/// Items of path dependencies outside the project are not renamed, even though their files can be
+/// edited.
+#[test]
+fn rename_refuses_items_of_path_dependencies() {
+    let (cairo, cursors) = cursors(indoc! {r#"
+        fn main() {
+            dep::ba<caret>r();
+        }
+    "#});
+    let mut ls = sandbox! {
+        files {
+            "hello/cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+                dep = "../dep/src"
+
+                [config.global]
+                edition = "2024_07"
+
+                [config.global.dependencies]
+                dep = {}
+            "#},
+            "hello/src/lib.cairo" => cairo,
+            "dep/src/lib.cairo" => "pub fn bar() {}",
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("hello/src/lib.cairo");
+
+    let position_params = TextDocumentPositionParams {
+        text_document: ls.doc_id("hello/src/lib.cairo"),
+        position: cursors.caret(0),
+    };
+    let error =
+        ls.send_request_expect_error::<lsp_request!("textDocument/prepareRename")>(position_params);
+    assert_eq!(error.message, "cannot rename `bar`, because it is defined in a dependency");
+}
+
+#[test]
+fn rename_module_renames_its_file() {
+    let (cairo, cursors) = cursors(indoc! {r#"
+        mod fo<caret>o;
+        fn main() {
+            foo::bar();
+        }
+    "#});
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => cairo.clone(),
+            "src/foo.cairo" => "pub fn bar() {}",
+        }
+        client_capabilities = caps_with_file_renames;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let edit = ls
+        .send_request::<lsp_request!("textDocument/rename")>(rename_params(
+            &ls,
+            cursors.caret(0),
+            "baz",
+        ))
+        .unwrap();
+    let WorkspaceEdit { document_changes: Some(DocumentChanges::Operations(operations)), .. } =
+        edit
+    else {
+        panic!("expected document changes operations, got: {edit:?}");
+    };
+
+    let [DocumentChangeOperation::Edit(text_document_edit), DocumentChangeOperation::Op(op)] =
+        operations.as_slice()
+    else {
+        panic!("expected a text edit and a file operation, got: {operations:?}");
+    };
+    assert_eq!(text_document_edit.text_document.uri, ls.doc_id("src/lib.cairo").uri);
+    let edits = text_document_edit
+        .edits
+        .iter()
+        .map(|edit| match edit {
+            OneOf::Left(edit) => edit.clone(),
+            OneOf::Right(edit) => edit.text_edit.clone(),
+        })
+        .collect::<Vec<_>>();
+    let expected = indoc! {r#"
+        mod baz;
+        fn main() {
+            baz::bar();
+        }
+    "#};
+    assert_eq!(apply_edits(&cairo, &edits), expected.trim_end());
+    assert_eq!(
+        op,
+        &ResourceOp::Rename(RenameFile {
+            old_uri: ls.doc_id("src/foo.cairo").uri,
+            new_uri: ls.doc_id("src/baz.cairo").uri,
+            options: None,
+            annotation_id: None,
+        })
+    );
+}
@@ -0,0 +1,94 @@
+use std::thread;
+use std::time::Duration;
+
+use cairo_lang_defs::plugin::{
+    InlineMacroExprPlugin, InlinePluginResult, MacroPluginMetadata, NamedPlugin,
+};
+use cairo_lang_language_server::Tricks;
+use cairo_lang_language_server::lsp::ext::ViewAnalyzedCrates;
+use cairo_lang_semantic::plugin::PluginSuite;
+use cairo_lang_syntax::node::ast;
+use cairo_lang_syntax::node::db::SyntaxGroup;
+use indoc::indoc;
+use lsp_server::ErrorCode;
+use lsp_types::{GotoDefinitionParams, TextDocumentPositionParams, lsp_request};
+use serde_json::json;
+
+use crate::support::{cursors, sandbox};
+
+/// An inline macro that takes much longer to expand than the request timeout used in tests.
+#[derive(Debug, Default)]
+struct SlowMacro;
+
+impl NamedPlugin for SlowMacro {
+    const NAME: &'static str = "slow";
+}
+
+impl InlineMacroExprPlugin for SlowMacro {
+    fn generate_code(
+        &self,
+        _db: &dyn SyntaxGroup,
+        _item_ast: &ast::ExprInlineMacro,
+        _metadata: &MacroPluginMetadata<'_>,
+    ) -> InlinePluginResult {
+        thread::sleep(Duration::from_secs(2));
+        InlinePluginResult { code: None, diagnostics: vec![] }
+    }
+}
+
+fn slow_plugin_suite() -> Vec<PluginSuite> {
+    let mut suite = PluginSuite::default();
+    suite.add_inline_macro_plugin::<SlowMacro>();
+    vec![suite]
+}
+
+#[test]
+fn slow_request_times_out() {
+    let (cairo, cursors) = cursors(indoc! {r#"
+        fn main() {
+            let x = 1;
+            slow!();
+            x<caret>;
+        }
+    "#});
+
+    let mut tricks = Tricks::default();
+    tricks.extra_plugin_suites = Some(&slow_plugin_suite);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => cairo,
+        }
+        workspace_configuration = json!({
+            "cairo1": {
+                "requestTimeoutMs": 100,
+            }
+        });
+        tricks = tricks;
+    };
+
+    // Make sure the workspace configuration is loaded before sending the slow request.
+    ls.send_request::<ViewAnalyzedCrates>(());
+
+    ls.open("src/lib.cairo");
+
+    let error = ls.send_request_expect_error::<lsp_request!("textDocument/definition")>(
+        GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: ls.doc_id("src/lib.cairo"),
+                position: cursors.caret(0),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+    );
+    assert_eq!(error.code, ErrorCode::RequestFailed as i32);
+    assert_eq!(error.message, "request timed out after 100ms");
+
+    // The server keeps handling requests afterwards.
+    ls.send_request::<ViewAnalyzedCrates>(());
+}
@@ -0,0 +1,78 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{Range, SelectionRange, SelectionRangeParams, lsp_request};
+
+use crate::support::cursor::peek_caret;
+use crate::support::{cursors, sandbox};
+
+cairo_lang_test_utils::test_file_test!(
+    selection_ranges,
+    "tests/test_data/selection_ranges",
+    {
+        expressions: "expressions.txt",
+        items: "items.txt",
+    },
+    test_selection_ranges
+);
+
+/// Perform selection ranges test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then requests selection ranges for all caret positions at once and reports the
+/// chain of ranges for each of them, from the innermost one.
+fn test_selection_ranges(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let positions = cursors.carets();
+    let selection_ranges = ls
+        .send_request::<lsp_request!("textDocument/selectionRange")>(SelectionRangeParams {
+            text_document: ls.doc_id("src/lib.cairo"),
+            positions: positions.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .expect("selection range request failed");
+    assert_eq!(selection_ranges.len(), positions.len());
+
+    let mut reports = OrderedHashMap::default();
+    for (n, (position, selection_range)) in positions.into_iter().zip(selection_ranges).enumerate()
+    {
+        let mut report = peek_caret(&cairo, position);
+        let mut selection_range = Some(&selection_range);
+        while let Some(SelectionRange { range, parent }) = selection_range {
+            report.push_str(&render_range(&cairo, range));
+            selection_range = parent.as_deref();
+        }
+        reports.insert(format!("Selection ranges #{n}"), report);
+    }
+
+    TestRunnerResult::success(reports)
+}
+
+/// Renders the text of the range in a single line, eliding the middle of multiline ranges.
+fn render_range(cairo: &str, range: &Range) -> String {
+    let lines = cairo
+        .lines()
+        .skip(range.start.line as usize)
+        .take((range.end.line - range.start.line + 1) as usize)
+        .collect::<Vec<_>>();
+    let start = range.start.character as usize;
+    let end = range.end.character as usize;
+    match lines.as_slice() {
+        [line] => format!("<sel>{}</sel>\n", &line[start..end]),
+        [first, .., last] => format!("<sel>{} … {}</sel>\n", &first[start..], last[..end].trim()),
+        [] => unreachable!(),
+    }
+}
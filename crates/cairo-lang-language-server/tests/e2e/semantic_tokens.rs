@@ -1,6 +1,11 @@
-use lsp_types::lsp_request;
+use cairo_lang_language_server::{SemanticTokensExtension, Tricks};
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_syntax::node::SyntaxNode;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use lsp_server::Message;
+use lsp_types::{lsp_notification, lsp_request};
 
-use crate::support::sandbox;
+use crate::support::{MockClient, sandbox};
 
 fn caps(base: lsp_types::ClientCapabilities) -> lsp_types::ClientCapabilities {
     lsp_types::ClientCapabilities {
@@ -9,8 +14,10 @@ fn caps(base: lsp_types::ClientCapabilities) -> lsp_types::ClientCapabilities {
                 semantic_tokens: Some(lsp_types::SemanticTokensClientCapabilities {
                     dynamic_registration: Some(false),
                     requests: lsp_types::SemanticTokensClientCapabilitiesRequests {
-                        full: Some(lsp_types::SemanticTokensFullOptions::Bool(true)),
-                        ..Default::default()
+                        range: Some(true),
+                        full: Some(lsp_types::SemanticTokensFullOptions::Delta {
+                            delta: Some(true),
+                        }),
                     },
                     ..Default::default()
                 }),
@@ -70,3 +77,349 @@ fn main() {
         are_both_string && are_on_consecutive_lines
     }));
 }
+
+#[test]
+fn range_matches_full_tokens_in_range() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => r#"
+[crate_roots]
+hello = "src"
+
+[config.global]
+edition = "2023_11"
+"#,
+            "src/lib.cairo" => r#"
+struct Foo {
+    a: felt252,
+}
+
+fn foo(x: Foo) -> felt252 {
+    let y = x.a;
+    y + 1
+}
+
+fn bar() {
+    let _ = foo(Foo { a: 1 });
+}
+"#,
+        }
+        client_capabilities = caps;
+    };
+
+    ls.open("src/lib.cairo");
+
+    let res = ls
+        .send_request::<lsp_request!("textDocument/semanticTokens/full")>(
+            lsp_types::SemanticTokensParams {
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                text_document: ls.doc_id("src/lib.cairo"),
+            },
+        )
+        .unwrap();
+    let lsp_types::SemanticTokensResult::Tokens(full) = res else { panic!("expected full tokens") };
+
+    let range = lsp_types::Range {
+        start: lsp_types::Position { line: 6, character: 8 },
+        end: lsp_types::Position { line: 10, character: 0 },
+    };
+    let res = ls
+        .send_request::<lsp_request!("textDocument/semanticTokens/range")>(
+            lsp_types::SemanticTokensRangeParams {
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                text_document: ls.doc_id("src/lib.cairo"),
+                range,
+            },
+        )
+        .unwrap();
+    let lsp_types::SemanticTokensRangeResult::Tokens(in_range) = res else {
+        panic!("expected range tokens")
+    };
+
+    let in_range = absolute_tokens(&in_range.data);
+    let expected = absolute_tokens(&full.data)
+        .into_iter()
+        .filter(|&(line, start, length, _)| {
+            let start = lsp_types::Position { line, character: start };
+            let end = lsp_types::Position { line, character: start.character + length };
+            start < range.end && range.start < end
+        })
+        .collect::<Vec<_>>();
+    assert!(!in_range.is_empty());
+    assert_eq!(in_range, expected);
+}
+
+#[test]
+fn delta_applies_to_previous_tokens() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => r#"
+[crate_roots]
+hello = "src"
+
+[config.global]
+edition = "2023_11"
+"#,
+            "src/lib.cairo" => r#"
+fn foo(x: felt252) -> felt252 {
+    x + 1
+}
+
+fn bar() {
+    let _ = foo(1);
+}
+"#,
+        }
+        client_capabilities = caps;
+    };
+
+    ls.open("src/lib.cairo");
+
+    let previous = full_tokens(&mut ls);
+    let previous_result_id = previous.result_id.clone().expect("expected result id");
+
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(
+        lsp_types::DidChangeTextDocumentParams {
+            text_document: lsp_types::VersionedTextDocumentIdentifier {
+                uri: ls.doc_id("src/lib.cairo").uri,
+                version: 1,
+            },
+            content_changes: vec![lsp_types::TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: r#"
+fn foo(x: felt252, y: felt252) -> felt252 {
+    x + y
+}
+
+fn bar() {
+    let _ = foo(1, 2);
+}
+"#
+                .to_string(),
+            }],
+        },
+    );
+
+    let res = ls
+        .send_request::<lsp_request!("textDocument/semanticTokens/full/delta")>(
+            lsp_types::SemanticTokensDeltaParams {
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                text_document: ls.doc_id("src/lib.cairo"),
+                previous_result_id,
+            },
+        )
+        .unwrap();
+    let lsp_types::SemanticTokensFullDeltaResult::TokensDelta(delta) = res else {
+        panic!("expected tokens delta")
+    };
+    assert_ne!(delta.result_id, previous.result_id);
+    assert!(!delta.edits.is_empty());
+
+    let mut data = previous.data;
+    for edit in delta.edits.iter().rev() {
+        let start = edit.start as usize / 5;
+        let end = start + edit.delete_count as usize / 5;
+        data.splice(start..end, edit.data.clone().unwrap_or_default());
+    }
+    assert_eq!(data, full_tokens(&mut ls).data);
+}
+
+#[test]
+fn delta_with_unknown_result_id_returns_all_tokens() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => r#"
+[crate_roots]
+hello = "src"
+
+[config.global]
+edition = "2023_11"
+"#,
+            "src/lib.cairo" => r#"
+fn main() {}
+"#,
+        }
+        client_capabilities = caps;
+    };
+
+    ls.open("src/lib.cairo");
+
+    let res = ls
+        .send_request::<lsp_request!("textDocument/semanticTokens/full/delta")>(
+            lsp_types::SemanticTokensDeltaParams {
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                text_document: ls.doc_id("src/lib.cairo"),
+                previous_result_id: "unknown".to_string(),
+            },
+        )
+        .unwrap();
+    let lsp_types::SemanticTokensFullDeltaResult::Tokens(tokens) = res else {
+        panic!("expected full tokens")
+    };
+    assert!(tokens.result_id.is_some());
+    assert_eq!(tokens.data, full_tokens(&mut ls).data);
+}
+
+/// Classifies identifiers named `model` as the first extension token type.
+fn classify_models(db: &dyn SemanticGroup, node: &SyntaxNode) -> Option<usize> {
+    let db = db.upcast();
+    (node.kind(db) == SyntaxKind::TokenIdentifier && node.text(db)? == "model").then_some(0)
+}
+
+#[test]
+fn tricks_extend_legend_and_classification() {
+    let mut tricks = Tricks::default();
+    tricks.semantic_tokens_extension = Some(SemanticTokensExtension {
+        token_types: vec![lsp_types::SemanticTokenType::new("model")],
+        classify: &classify_models,
+    });
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => r#"
+[crate_roots]
+hello = "src"
+
+[config.global]
+edition = "2023_11"
+"#,
+            "src/lib.cairo" => "fn main() {\n    let model = 1;\n    let _ = model;\n}\n",
+        }
+        client_capabilities = caps;
+        tricks = tricks;
+    };
+
+    let token_types = ls
+        .trace()
+        .iter()
+        .find_map(|message| match message {
+            Message::Response(response) => response.result.as_ref()?["capabilities"]
+                ["semanticTokensProvider"]["legend"]["tokenTypes"]
+                .as_array()
+                .cloned(),
+            _ => None,
+        })
+        .expect("semantic tokens legend should be sent on initialization");
+    // The default token types come first.
+    assert_eq!(token_types.len(), 22);
+    assert_eq!(token_types[21], "model");
+
+    ls.open("src/lib.cairo");
+
+    let model_tokens = absolute_tokens(&full_tokens(&mut ls).data)
+        .into_iter()
+        .filter(|&(_, _, _, token_type)| token_type == 21)
+        .collect::<Vec<_>>();
+    assert_eq!(model_tokens, [(1, 8, 5, 21), (2, 12, 5, 21)]);
+}
+
+#[test]
+fn modifiers_of_declarations_mutable_variables_and_constants() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => r#"
+[crate_roots]
+hello = "src"
+
+[config.global]
+edition = "2023_11"
+"#,
+            "src/lib.cairo" => r#"/// The initial value.
+const INITIAL: u32 = 1;
+
+fn main(ref a: u32, b: u32) {
+    let mut x = INITIAL;
+    let y = b;
+    x += y;
+    a = x;
+}
+"#,
+        }
+        client_capabilities = caps;
+    };
+
+    let token_modifiers = ls
+        .trace()
+        .iter()
+        .find_map(|message| match message {
+            Message::Response(response) => response.result.as_ref()?["capabilities"]
+                ["semanticTokensProvider"]["legend"]["tokenModifiers"]
+                .as_array()
+                .cloned(),
+            _ => None,
+        })
+        .expect("semantic tokens legend should be sent on initialization");
+    assert_eq!(token_modifiers, ["declaration", "mutable", "readonly", "documentation"]);
+
+    ls.open("src/lib.cairo");
+
+    let tokens = full_tokens(&mut ls);
+    let modifiers_at = |line, start| {
+        let mut position = (0, 0);
+        tokens
+            .data
+            .iter()
+            .find_map(|token| {
+                if token.delta_line > 0 {
+                    position.1 = 0;
+                }
+                position.0 += token.delta_line;
+                position.1 += token.delta_start;
+                (position == (line, start)).then_some(token.token_modifiers_bitset)
+            })
+            .expect("there should be a token at the position")
+    };
+    let (declaration, mutable, readonly, documentation) = (1, 2, 4, 8);
+
+    assert_eq!(modifiers_at(0, 0), documentation);
+    assert_eq!(modifiers_at(1, 6), declaration | readonly);
+    assert_eq!(modifiers_at(3, 3), declaration);
+    assert_eq!(modifiers_at(3, 12), declaration | mutable);
+    assert_eq!(modifiers_at(3, 20), declaration);
+    assert_eq!(modifiers_at(4, 12), declaration | mutable);
+    assert_eq!(modifiers_at(4, 16), readonly);
+    assert_eq!(modifiers_at(5, 8), declaration);
+    assert_eq!(modifiers_at(5, 12), 0);
+    assert_eq!(modifiers_at(6, 4), mutable);
+    assert_eq!(modifiers_at(6, 9), 0);
+    assert_eq!(modifiers_at(7, 4), mutable);
+    assert_eq!(modifiers_at(7, 8), mutable);
+}
+
+fn full_tokens(ls: &mut MockClient) -> lsp_types::SemanticTokens {
+    let res = ls
+        .send_request::<lsp_request!("textDocument/semanticTokens/full")>(
+            lsp_types::SemanticTokensParams {
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                text_document: ls.doc_id("src/lib.cairo"),
+            },
+        )
+        .unwrap();
+    let lsp_types::SemanticTokensResult::Tokens(tokens) = res else {
+        panic!("expected full tokens")
+    };
+    tokens
+}
+
+/// Decodes the relative positions of semantic tokens into `(line, start, length, type)` tuples.
+fn absolute_tokens(tokens: &[lsp_types::SemanticToken]) -> Vec<(u32, u32, u32, u32)> {
+    let mut line = 0;
+    let mut start = 0;
+    tokens
+        .iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                start = 0;
+            }
+            line += token.delta_line;
+            start += token.delta_start;
+            (line, start, token.length, token.token_type)
+        })
+        .collect()
+}
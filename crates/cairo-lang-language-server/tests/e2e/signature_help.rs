@@ -0,0 +1,116 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{
+    ClientCapabilities, Documentation, MarkupContent, ParameterLabel, SignatureHelp,
+    SignatureHelpClientCapabilities, SignatureHelpParams, TextDocumentClientCapabilities,
+    TextDocumentPositionParams, lsp_request,
+};
+
+use crate::support::cursor::peek_caret;
+use crate::support::{cursors, sandbox};
+
+cairo_lang_test_utils::test_file_test!(
+    signature_help,
+    "tests/test_data/signature_help",
+    {
+        functions: "functions.txt",
+        methods: "methods.txt",
+    },
+    test_signature_help
+);
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: base.text_document.or_else(Default::default).map(|it| {
+            TextDocumentClientCapabilities {
+                signature_help: Some(SignatureHelpClientCapabilities {
+                    dynamic_registration: Some(false),
+                    ..Default::default()
+                }),
+                ..it
+            }
+        }),
+        ..base
+    }
+}
+
+/// Perform signature help test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then requests signature help at each caret position and compares the result with
+/// the expected signature from the snapshot file, with the active parameter marked by `<sel>` tags.
+fn test_signature_help(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut signatures = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let signature_help =
+            ls.send_request::<lsp_request!("textDocument/signatureHelp")>(SignatureHelpParams {
+                context: None,
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: ls.doc_id("src/lib.cairo"),
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+            });
+        report.push_str("---\n");
+        report.push_str(&signature_help.map(render).unwrap_or("No signature help.\n".to_string()));
+        signatures.insert(format!("Signature help #{n}"), report);
+    }
+
+    TestRunnerResult::success(signatures)
+}
+
+/// Renders the active signature with the active parameter marked, followed by its documentation.
+fn render(signature_help: SignatureHelp) -> String {
+    let [signature] = signature_help.signatures.as_slice() else {
+        panic!("expected exactly one signature, got: {:#?}", signature_help.signatures);
+    };
+
+    let mut label = signature.label.clone();
+    let active_parameter = signature_help.active_parameter.unwrap() as usize;
+    match signature.parameters.as_ref().unwrap().get(active_parameter) {
+        Some(parameter) => {
+            let ParameterLabel::LabelOffsets([start, end]) = parameter.label else {
+                panic!("expected parameter label offsets, got: {:?}", parameter.label);
+            };
+            label.insert_str(utf16_to_byte_index(&label, end), "</sel>");
+            label.insert_str(utf16_to_byte_index(&label, start), "<sel>");
+        }
+        None => label.push_str(" // no active parameter"),
+    }
+
+    match &signature.documentation {
+        Some(Documentation::MarkupContent(MarkupContent { value, .. })) => {
+            format!("{label}\n{value}\n")
+        }
+        Some(documentation) => panic!("expected markup documentation, got: {documentation:?}"),
+        None => format!("{label}\n"),
+    }
+}
+
+/// Converts an offset in UTF-16 code units, the default position encoding, into a byte index.
+fn utf16_to_byte_index(text: &str, offset: u32) -> usize {
+    let mut utf16_offset = 0;
+    for (index, c) in text.char_indices() {
+        if utf16_offset >= offset as usize {
+            return index;
+        }
+        utf16_offset += c.len_utf16();
+    }
+    text.len()
+}
@@ -4,8 +4,8 @@ use std::path::Path;
 use std::time::Duration;
 use std::{fmt, mem, process};
 
-use cairo_lang_language_server::build_service_for_e2e_tests;
-use lsp_server::{Message, Notification, Request, Response};
+use cairo_lang_language_server::{Config, Tricks, build_service_for_e2e_tests};
+use lsp_server::{Message, Notification, Request, Response, ResponseError};
 use lsp_types::request::{RegisterCapability, Request as LspRequest};
 use lsp_types::{lsp_notification, lsp_request};
 use serde_json::Value;
@@ -34,6 +34,9 @@ impl MockClient {
     /// Starts and initializes CairoLS in the context of a given fixture and given client
     /// capabilities.
     ///
+    /// If `config` is provided, the server uses it instead of pulling the configuration from this
+    /// client.
+    ///
     /// Upon completion of this function, the language server will be in the _initialized_ state
     /// (i.e., the `initialize` request and `initialized` notification both will be completed).
     #[must_use]
@@ -41,8 +44,10 @@ impl MockClient {
         fixture: Fixture,
         capabilities: lsp_types::ClientCapabilities,
         workspace_configuration: Value,
+        tricks: Tricks,
+        config: Option<Config>,
     ) -> Self {
-        let (init, client) = build_service_for_e2e_tests();
+        let (init, client) = build_service_for_e2e_tests(tricks, config);
 
         let mut this = Self {
             fixture,
@@ -91,8 +96,42 @@ impl MockClient {
         serde_json::from_value(result).expect("failed to parse response")
     }
 
+    /// Sends a typed request to the server, expecting it to respond with an error.
+    pub fn send_request_expect_error<R: lsp_types::request::Request>(
+        &mut self,
+        params: R::Params,
+    ) -> ResponseError {
+        let params = serde_json::to_value(params).expect("failed to serialize request params");
+        match self.send_request_raw(R::METHOD, params) {
+            Ok(result) => panic!("expected error response, got: {result:#?}"),
+            Err(err) => err,
+        }
+    }
+
+    /// Sends a typed request to the server, returning either its result or the error response.
+    pub fn try_send_request<R: lsp_types::request::Request>(
+        &mut self,
+        params: R::Params,
+    ) -> Result<R::Result, ResponseError> {
+        let params = serde_json::to_value(params).expect("failed to serialize request params");
+        self.send_request_raw(R::METHOD, params)
+            .map(|result| serde_json::from_value(result).expect("failed to parse response"))
+    }
+
     /// Sends an arbitrary request to the server.
     pub fn send_request_untyped(&mut self, method: &'static str, params: Value) -> Value {
+        match self.send_request_raw(method, params) {
+            Ok(result) => result,
+            Err(err) => panic!("error response: {:#?}", err),
+        }
+    }
+
+    /// Sends an arbitrary request to the server and returns the raw result of the response.
+    fn send_request_raw(
+        &mut self,
+        method: &'static str,
+        params: Value,
+    ) -> Result<Value, ResponseError> {
         let id = self.req_id.next();
         let message = Message::Request(Request::new(id.clone(), method.to_owned(), params));
 
@@ -110,6 +149,11 @@ impl MockClient {
                 }
 
                 Message::Request(req) => {
+                    // This one is answered automatically in `recv`.
+                    if req.method == <lsp_request!("workspace/configuration")>::METHOD {
+                        continue;
+                    }
+
                     if does_expect_requests {
                         if let Some(handler) = expect_request_handlers.pop_front() {
                             let response = (handler.f)(&req);
@@ -128,10 +172,7 @@ impl MockClient {
 
                     assert_eq!(res_id, id);
 
-                    match result {
-                        Ok(result) => return result,
-                        Err(err) => panic!("error response: {:#?}", err),
-                    }
+                    return result;
                 }
             }
         }
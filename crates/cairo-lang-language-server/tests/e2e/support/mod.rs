@@ -4,6 +4,7 @@ pub mod fixture;
 pub mod jsonrpc;
 mod mock_client;
 pub mod normalize;
+pub mod text_edits;
 
 pub use self::cursor::cursors;
 pub use self::mock_client::MockClient;
@@ -19,6 +20,8 @@ macro_rules! sandbox {
         $(files { $($file:expr => $content:expr),* $(,)? })?
         $(client_capabilities = $client_capabilities:expr;)?
         $(workspace_configuration = $workspace_configuration:expr;)?
+        $(tricks = $tricks:expr;)?
+        $(config = $config:expr;)?
     ) => {{
         use $crate::support::{
             client_capabilities,
@@ -45,7 +48,19 @@ macro_rules! sandbox {
             client_capabilities = $client_capabilities(client_capabilities);
         )?
 
-        MockClient::start(fixture, client_capabilities, workspace_configuration)
+        #[allow(unused_assignments, unused_mut)]
+        let mut tricks = cairo_lang_language_server::Tricks::default();
+        $(
+            tricks = $tricks;
+        )?
+
+        #[allow(unused_assignments, unused_mut)]
+        let mut config = None;
+        $(
+            config = Some($config);
+        )?
+
+        MockClient::start(fixture, client_capabilities, workspace_configuration, tricks, config)
     }};
 }
 
@@ -0,0 +1,17 @@
+use lsp_types::{Position, TextEdit};
+
+/// Applies text edits to the given text, assuming it is ASCII.
+pub fn apply_edits(text: &str, edits: &[TextEdit]) -> String {
+    let offset = |position: Position| {
+        text.split_inclusive('\n').take(position.line as usize).map(str::len).sum::<usize>()
+            + position.character as usize
+    };
+
+    let mut text = text.to_string();
+    let mut edits = edits.to_vec();
+    edits.sort_by_key(|edit| std::cmp::Reverse(offset(edit.range.start)));
+    for edit in edits {
+        text.replace_range(offset(edit.range.start)..offset(edit.range.end), &edit.new_text);
+    }
+    text
+}
@@ -0,0 +1,61 @@
+use cairo_lang_language_server::lsp::ext::ShowSyntaxTree;
+use indoc::indoc;
+use lsp_types::{Position, TextDocumentPositionParams};
+use pretty_assertions::assert_eq;
+
+use crate::support::sandbox;
+
+#[test]
+fn syntax_tree_with_cursor() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open("src/lib.cairo");
+
+    let tree = ls.send_request::<ShowSyntaxTree>(TextDocumentPositionParams {
+        text_document: ls.doc_id("src/lib.cairo"),
+        position: Position::new(0, 4),
+    });
+
+    assert_eq!(tree.unwrap(), indoc! {r#"
+        SyntaxFile@0..12
+          ModuleItemList@0..12
+            FunctionWithBody@0..12
+              AttributeList@0..0
+              VisibilityDefault@0..0
+              FunctionDeclaration@0..10
+                TerminalFunction@0..3
+                  TokenFunction@0..2 "fn"
+                  Trivia@2..3
+                    TokenWhitespace@2..3 " "
+                TerminalIdentifier@3..7
+                  TokenIdentifier@3..7 "main" <-- cursor
+                OptionWrappedGenericParamListEmpty@7..7
+                FunctionSignature@7..10
+                  TerminalLParen@7..8
+                    TokenLParen@7..8 "("
+                  ParamList@8..8
+                  TerminalRParen@8..10
+                    TokenRParen@8..9 ")"
+                    Trivia@9..10
+                      TokenWhitespace@9..10 " "
+                  OptionReturnTypeClauseEmpty@10..10
+                  OptionImplicitsClauseEmpty@10..10
+                  OptionTerminalNoPanicEmpty@10..10
+              ExprBlock@10..12
+                TerminalLBrace@10..11
+                  TokenLBrace@10..11 "{"
+                StatementList@11..11
+                TerminalRBrace@11..12
+                  TokenRBrace@11..12 "}"
+          TerminalEndOfFile@12..12
+            TokenEndOfFile@12..12 ""
+    "#});
+}
@@ -0,0 +1,63 @@
+use indoc::indoc;
+use lsp_types::{
+    DidChangeTextDocumentParams, HoverContents, HoverParams, MarkupContent, Position, Range,
+    TextDocumentContentChangeEvent, TextDocumentPositionParams, VersionedTextDocumentIdentifier,
+    lsp_notification, lsp_request,
+};
+
+use crate::support::sandbox;
+
+#[test]
+fn incremental_changes_are_applied() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                fn main() {}
+
+                fn foo() {}
+            "#},
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    let change = |start: (u32, u32), end: (u32, u32), text: &str| TextDocumentContentChangeEvent {
+        range: Some(Range {
+            start: Position { line: start.0, character: start.1 },
+            end: Position { line: end.0, character: end.1 },
+        }),
+        range_length: None,
+        text: text.to_string(),
+    };
+    ls.send_notification::<lsp_notification!("textDocument/didChange")>(
+        DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: ls.doc_id("src/lib.cairo").uri,
+                version: 1,
+            },
+            content_changes: vec![
+                // Rename `foo` to `bar`.
+                change((2, 3), (2, 6), "bar"),
+                // Add a parameter, counting columns after the rename.
+                change((2, 7), (2, 7), "x: felt252"),
+            ],
+        },
+    );
+
+    let hover = ls
+        .send_request::<lsp_request!("textDocument/hover")>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: ls.doc_id("src/lib.cairo"),
+                position: Position { line: 2, character: 4 },
+            },
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap();
+    let HoverContents::Markup(MarkupContent { value, .. }) = hover.contents else {
+        panic!("unexpected hover contents: {:?}", hover.contents);
+    };
+    assert!(value.contains("fn bar(x: felt252)"), "{value}");
+}
@@ -0,0 +1,100 @@
+use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use lsp_types::{
+    TextDocumentPositionParams, TypeHierarchyItem, TypeHierarchyPrepareParams,
+    TypeHierarchySubtypesParams, TypeHierarchySupertypesParams, lsp_request,
+};
+
+use crate::support::cursor::{peek_caret, peek_selection};
+use crate::support::{cursors, sandbox};
+
+cairo_lang_test_utils::test_file_test!(
+    type_hierarchy,
+    "tests/test_data/type_hierarchy",
+    {
+        traits: "traits.txt",
+        types: "types.txt",
+    },
+    test_type_hierarchy
+);
+
+/// Perform type hierarchy test.
+///
+/// This function spawns a sandbox language server with the given code in the `src/lib.cairo` file.
+/// The Cairo source code is expected to contain caret markers.
+/// The function then prepares the type hierarchy at each caret position and reports the supertypes
+/// and subtypes of the found item.
+fn test_type_hierarchy(
+    inputs: &OrderedHashMap<String, String>,
+    _args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    let (cairo, cursors) = cursors(&inputs["cairo_code"]);
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => inputs["cairo_project.toml"].clone(),
+            "src/lib.cairo" => cairo.clone(),
+        }
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let mut outputs = OrderedHashMap::default();
+    for (n, position) in cursors.carets().into_iter().enumerate() {
+        let mut report = peek_caret(&cairo, position);
+        let items = ls.send_request::<lsp_request!("textDocument/prepareTypeHierarchy")>(
+            TypeHierarchyPrepareParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: ls.doc_id("src/lib.cairo"),
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+            },
+        );
+        let Some(item) = items.and_then(|items| items.into_iter().next()) else {
+            report.push_str("No type hierarchy.\n");
+            outputs.insert(format!("Type hierarchy #{n}"), report);
+            continue;
+        };
+        report.push_str(&describe_item(&cairo, &item));
+
+        let supertypes = ls
+            .send_request::<lsp_request!("typeHierarchy/supertypes")>(
+                TypeHierarchySupertypesParams {
+                    item: item.clone(),
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+            )
+            .unwrap_or_default();
+        for supertype in supertypes {
+            report.push_str("\nsupertype ");
+            report.push_str(&describe_item(&cairo, &supertype));
+        }
+
+        let subtypes = ls
+            .send_request::<lsp_request!("typeHierarchy/subtypes")>(TypeHierarchySubtypesParams {
+                item,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap_or_default();
+        for subtype in subtypes {
+            report.push_str("\nsubtype ");
+            report.push_str(&describe_item(&cairo, &subtype));
+        }
+
+        outputs.insert(format!("Type hierarchy #{n}"), report);
+    }
+
+    TestRunnerResult::success(outputs)
+}
+
+/// Describes the item, showing its name in the code if it is defined in `src/lib.cairo`.
+fn describe_item(cairo: &str, item: &TypeHierarchyItem) -> String {
+    let detail = item.detail.as_deref().unwrap_or_default();
+    let mut description = format!("{} ({:?}, {detail})\n", item.name, item.kind);
+    if item.uri.path().ends_with("src/lib.cairo") {
+        description.push_str(&peek_selection(cairo, &item.selection_range));
+    }
+    description
+}
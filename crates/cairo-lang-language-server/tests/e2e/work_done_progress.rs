@@ -0,0 +1,97 @@
+use indoc::indoc;
+use lsp_server::Message;
+use lsp_types::notification::{Notification, Progress};
+use lsp_types::request::{Request, WorkDoneProgressCreate};
+use lsp_types::{
+    ClientCapabilities, ProgressParams, ProgressParamsValue, WindowClientCapabilities,
+    WorkDoneProgress, WorkDoneProgressCreateParams,
+};
+use pretty_assertions::assert_eq;
+
+use crate::support::{MockClient, sandbox};
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        window: Some(WindowClientCapabilities {
+            work_done_progress: Some(true),
+            ..base.window.unwrap_or_default()
+        }),
+        ..base
+    }
+}
+
+/// Returns tokens of created progresses and `$/progress` notifications sent by the server.
+fn progress_messages(ls: &MockClient) -> (Vec<WorkDoneProgressCreateParams>, Vec<ProgressParams>) {
+    let mut created = vec![];
+    let mut progress = vec![];
+    for message in ls.trace() {
+        match message {
+            Message::Request(request) if request.method == WorkDoneProgressCreate::METHOD => {
+                created.push(serde_json::from_value(request.params.clone()).unwrap());
+            }
+            Message::Notification(notification) if notification.method == Progress::METHOD => {
+                progress.push(serde_json::from_value(notification.params.clone()).unwrap());
+            }
+            _ => {}
+        }
+    }
+    (created, progress)
+}
+
+#[test]
+fn reports_project_loading() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+        client_capabilities = caps;
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    // The project is loaded once for the workspace folder, and then again for the opened file.
+    let (created, progress) = progress_messages(&ls);
+    let [
+        WorkDoneProgressCreateParams { token: folder_token },
+        WorkDoneProgressCreateParams { token: file_token },
+    ] = created.as_slice()
+    else {
+        panic!("expected two progresses to be created, got: {created:#?}");
+    };
+    assert_ne!(folder_token, file_token);
+    let tokens = progress.iter().map(|params| &params.token).collect::<Vec<_>>();
+    assert_eq!(tokens, [folder_token, folder_token, file_token, file_token]);
+
+    let values = progress
+        .into_iter()
+        .map(|ProgressParams { value: ProgressParamsValue::WorkDone(value), .. }| match value {
+            WorkDoneProgress::Begin(begin) => format!("begin: {}", begin.title),
+            WorkDoneProgress::Report(report) => format!("report: {}", report.message.unwrap()),
+            WorkDoneProgress::End(_) => "end".to_string(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(values, ["begin: Setting up corelib…", "end", "begin: Setting up corelib…", "end"]);
+}
+
+#[test]
+fn no_progress_without_client_support() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+
+    let (created, progress) = progress_messages(&ls);
+    assert!(created.is_empty(), "{created:#?}");
+    assert!(progress.is_empty(), "{progress:#?}");
+}
@@ -1,7 +1,8 @@
+use cairo_lang_language_server::Config;
 use indoc::indoc;
 use lsp_server::Message;
-use lsp_types::lsp_request;
 use lsp_types::request::Request as _;
+use lsp_types::{MessageType, lsp_notification, lsp_request};
 use serde_json::json;
 
 use crate::support::sandbox;
@@ -54,3 +55,76 @@ fn relative_path_to_core() {
         1
     );
 }
+
+/// A Scarb executable configured at a path which does not exist is ignored, and the user is
+/// warned about it.
+#[test]
+fn missing_scarb_path() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => r#"fn main() -> u8 { 42 }"#,
+        }
+        workspace_configuration = json!({
+            "cairo1": {
+                "scarbPath": "/nonexistent/scarb",
+            }
+        });
+    };
+
+    let diags = ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    assert!(diags.diagnostics.is_empty());
+
+    let message = ls.wait_for_notification::<lsp_notification!("window/showMessage")>(|_| true);
+    assert_eq!(message.typ, MessageType::WARNING);
+    assert!(message.message.contains("cairo1.scarbPath"), "{}", message.message);
+    assert!(message.message.contains("/nonexistent/scarb"), "{}", message.message);
+}
+
+/// A configuration provided upfront is used as is, and it is never pulled from the client, even if
+/// the client supports `workspace/configuration` requests.
+#[test]
+fn fixed_config_is_not_pulled() {
+    let config = Config { diagnostics_max_per_file: Some(1), ..Default::default() };
+
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                fn a() -> felt252 { true }
+                fn b() -> felt252 { true }
+            "#},
+        }
+        workspace_configuration = json!({
+            "cairo1": {
+                "diagnostics": {
+                    "maxPerFile": 2,
+                }
+            }
+        });
+        config = config;
+    };
+
+    let diags = ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    assert_eq!(diags.diagnostics.len(), 2);
+    assert_eq!(diags.diagnostics[1].message, "1 more diagnostics suppressed");
+
+    ls.send_notification::<lsp_notification!("workspace/didChangeConfiguration")>(
+        lsp_types::DidChangeConfigurationParams { settings: json!({}) },
+    );
+    ls.send_request::<lsp_request!("workspace/executeCommand")>(lsp_types::ExecuteCommandParams {
+        command: "cairo.reload".to_owned(),
+        ..Default::default()
+    });
+
+    assert!(!ls.trace().iter().any(|msg| {
+        let Message::Request(req) = msg else { return false };
+        req.method == <lsp_request!("workspace/configuration")>::METHOD
+    }));
+}
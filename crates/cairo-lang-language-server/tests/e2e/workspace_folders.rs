@@ -0,0 +1,58 @@
+use cairo_lang_language_server::lsp;
+use indoc::indoc;
+use lsp_types::notification::DidChangeWorkspaceFolders;
+use lsp_types::{DidChangeWorkspaceFoldersParams, WorkspaceFolder, WorkspaceFoldersChangeEvent};
+
+use crate::support::{MockClient, sandbox};
+
+/// Returns names of crates analyzed by the language server.
+fn analyzed_crates(ls: &mut MockClient) -> Vec<String> {
+    let response = ls.send_request::<lsp::ext::AnalyzedCrates>(());
+    response.crates.into_iter().map(|cr| cr.name).collect()
+}
+
+#[test]
+fn loads_projects_of_initial_workspace_folders() {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "hello"
+                world = "world"
+            "#},
+            "hello/lib.cairo" => "fn main() {}",
+            "world/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    // No file is open, so crates must have been loaded from the workspace folder.
+    assert_eq!(analyzed_crates(&mut ls), ["core", "hello", "world"]);
+}
+
+#[test]
+fn loads_projects_of_added_workspace_folders() {
+    let mut ls = sandbox! {
+        files {
+            "other/cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                other = "src"
+            "#},
+            "other/src/lib.cairo" => "fn main() {}",
+        }
+    };
+
+    // The root folder is not a Cairo project.
+    assert_eq!(analyzed_crates(&mut ls), Vec::<String>::new());
+
+    ls.send_notification::<DidChangeWorkspaceFolders>(DidChangeWorkspaceFoldersParams {
+        event: WorkspaceFoldersChangeEvent {
+            added: vec![WorkspaceFolder {
+                uri: ls.as_ref().file_url("other"),
+                name: "other".to_owned(),
+            }],
+            removed: vec![],
+        },
+    });
+
+    assert_eq!(analyzed_crates(&mut ls), ["core", "other"]);
+}
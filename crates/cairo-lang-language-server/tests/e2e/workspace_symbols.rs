@@ -0,0 +1,121 @@
+use indoc::indoc;
+use lsp_types::{
+    ClientCapabilities, SymbolKind, WorkspaceClientCapabilities, WorkspaceSymbolClientCapabilities,
+    WorkspaceSymbolParams, WorkspaceSymbolResponse, lsp_request,
+};
+use pretty_assertions::assert_eq;
+
+use crate::support::{MockClient, sandbox};
+
+fn caps(base: ClientCapabilities) -> ClientCapabilities {
+    ClientCapabilities {
+        workspace: base.workspace.or_else(Default::default).map(|it| WorkspaceClientCapabilities {
+            symbol: Some(WorkspaceSymbolClientCapabilities {
+                dynamic_registration: Some(false),
+                ..Default::default()
+            }),
+            ..it
+        }),
+        ..base
+    }
+}
+
+fn project() -> MockClient {
+    let mut ls = sandbox! {
+        files {
+            "cairo_project.toml" => indoc! {r#"
+                [crate_roots]
+                hello = "src"
+
+                [config.global]
+                edition = "2024_07"
+            "#},
+            "src/lib.cairo" => indoc! {r#"
+                mod inner;
+
+                #[derive(Drop)]
+                struct FooBar {
+                    speed: u32,
+                }
+
+                fn foo_bar() {}
+
+                const FOOOBAR: felt252 = 0;
+
+                enum Spectrum {
+                    Spark,
+                }
+
+                impl SpinnerImpl of inner::FancyBear<u32> {
+                    fn spin(self: u32) {}
+                }
+            "#},
+            "src/inner.cairo" => indoc! {r#"
+                pub trait FancyBear<T> {
+                    fn spin(self: T);
+                }
+            "#},
+        }
+        client_capabilities = caps;
+    };
+    ls.open_and_wait_for_diagnostics("src/lib.cairo");
+    ls
+}
+
+/// Queries workspace symbols and returns those defined in the sandbox project.
+fn query(ls: &mut MockClient, query: &str) -> Vec<(String, Option<String>, SymbolKind)> {
+    let root = ls.as_ref().root_url();
+    let response = ls.send_request::<lsp_request!("workspace/symbol")>(WorkspaceSymbolParams {
+        query: query.to_owned(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    });
+    // Nested symbols with full locations are indistinguishable from flat ones in JSON.
+    let Some(WorkspaceSymbolResponse::Flat(symbols)) = response else {
+        panic!("expected workspace symbols with locations");
+    };
+    symbols
+        .into_iter()
+        .filter(|symbol| symbol.location.uri.as_str().starts_with(root.as_str()))
+        .map(|symbol| (symbol.name, symbol.container_name, symbol.kind))
+        .collect()
+}
+
+#[test]
+fn fuzzy_matches_symbols_across_modules() {
+    let mut ls = project();
+
+    assert_eq!(
+        query(&mut ls, "fbr"),
+        vec![
+            ("FooBar".to_owned(), Some("hello".to_owned()), SymbolKind::STRUCT),
+            ("foo_bar".to_owned(), Some("hello".to_owned()), SymbolKind::FUNCTION),
+            ("FancyBear".to_owned(), Some("hello::inner".to_owned()), SymbolKind::INTERFACE),
+            ("FOOOBAR".to_owned(), Some("hello".to_owned()), SymbolKind::CONSTANT),
+        ]
+    );
+}
+
+#[test]
+fn matches_members_of_items() {
+    let mut ls = project();
+
+    assert_eq!(
+        query(&mut ls, "sp"),
+        vec![
+            ("Spark".to_owned(), Some("hello::Spectrum".to_owned()), SymbolKind::ENUM_MEMBER),
+            ("Spectrum".to_owned(), Some("hello".to_owned()), SymbolKind::ENUM),
+            ("SpinnerImpl".to_owned(), Some("hello".to_owned()), SymbolKind::OBJECT),
+            ("speed".to_owned(), Some("hello::FooBar".to_owned()), SymbolKind::FIELD),
+            ("spin".to_owned(), Some("hello::SpinnerImpl".to_owned()), SymbolKind::METHOD),
+            ("spin".to_owned(), Some("hello::inner::FancyBear".to_owned()), SymbolKind::METHOD),
+        ]
+    );
+}
+
+#[test]
+fn excludes_generated_items() {
+    let mut ls = project();
+
+    assert_eq!(query(&mut ls, "FooBarDrop"), vec![]);
+}
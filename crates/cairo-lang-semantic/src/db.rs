@@ -1576,6 +1576,22 @@ pub trait SemanticGroup:
         crate_id: CrateId,
         user_module_id: ModuleId,
     ) -> Arc<[(TraitId, String)]>;
+    /// Returns all the module items which can be imported into a module, alongside a visible use
+    /// path to the item.
+    #[salsa::invoke(lsp_helpers::visible_importables_from_module)]
+    fn visible_importables_from_module(
+        &self,
+        module_id: ModuleId,
+    ) -> Option<Arc<OrderedHashMap<ModuleItemId, String>>>;
+    /// Returns all visible module items in a module and its submodules, alongside a visible use
+    /// path to the item relative to `module_id`.
+    /// `user_module_id` is the module from which the items should be visible.
+    #[salsa::invoke(lsp_helpers::visible_importables_in_module)]
+    fn visible_importables_in_module(
+        &self,
+        module_id: ModuleId,
+        user_module_id: ModuleId,
+    ) -> Arc<[(ModuleItemId, String)]>;
 }
 
 impl<T: Upcast<dyn SemanticGroup + 'static>> Elongate for T {
@@ -8,8 +8,8 @@ use cairo_lang_defs::ids::{
 };
 use cairo_lang_defs::plugin::PluginDiagnostic;
 use cairo_lang_diagnostics::{
-    DiagnosticAdded, DiagnosticEntry, DiagnosticLocation, DiagnosticsBuilder, ErrorCode, Severity,
-    error_code,
+    DiagnosticAdded, DiagnosticEntry, DiagnosticLocation, DiagnosticNote, DiagnosticsBuilder,
+    ErrorCode, Severity, error_code,
 };
 use cairo_lang_syntax as syntax;
 use itertools::Itertools;
@@ -67,15 +67,22 @@ pub struct SemanticDiagnostic {
     /// true if the diagnostic should be reported *after* the given location. Normally false, in
     /// which case the diagnostic points to the given location (as-is).
     pub after: bool,
+    /// Additional notes, possibly pointing to related locations, e.g. a previous definition.
+    pub notes: Vec<DiagnosticNote>,
 }
 impl SemanticDiagnostic {
     /// Create a diagnostic in the given location.
     pub fn new(stable_location: StableLocation, kind: SemanticDiagnosticKind) -> Self {
-        SemanticDiagnostic { stable_location, kind, after: false }
+        SemanticDiagnostic { stable_location, kind, after: false, notes: vec![] }
     }
     /// Create a diagnostic in the location after the given location (with width 0).
     pub fn new_after(stable_location: StableLocation, kind: SemanticDiagnosticKind) -> Self {
-        SemanticDiagnostic { stable_location, kind, after: true }
+        SemanticDiagnostic { stable_location, kind, after: true, notes: vec![] }
+    }
+    /// Adds a note to the diagnostic.
+    pub fn with_note(mut self, note: DiagnosticNote) -> Self {
+        self.notes.push(note);
+        self
     }
 }
 impl DiagnosticEntry for SemanticDiagnostic {
@@ -908,6 +915,10 @@ impl DiagnosticEntry for SemanticDiagnostic {
         }
     }
 
+    fn notes(&self, _db: &Self::DbType) -> &[DiagnosticNote] {
+        &self.notes
+    }
+
     fn error_code(&self) -> Option<ErrorCode> {
         self.kind.error_code()
     }
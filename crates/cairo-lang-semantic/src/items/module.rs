@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
 use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::diagnostic_utils::StableLocation;
 use cairo_lang_defs::ids::{
     LanguageElementId, LookupItemId, ModuleId, ModuleItemId, NamedLanguageElementId, TraitId,
 };
-use cairo_lang_diagnostics::{Diagnostics, DiagnosticsBuilder, Maybe};
+use cairo_lang_diagnostics::{DiagnosticNote, Diagnostics, DiagnosticsBuilder, Maybe};
 use cairo_lang_syntax::attribute::structured::{Attribute, AttributeListStructurize};
 use cairo_lang_syntax::node::ast;
 use cairo_lang_syntax::node::helpers::UsePathEx;
@@ -17,7 +18,7 @@ use super::us::SemanticUseEx;
 use super::visibility::Visibility;
 use crate::SemanticDiagnostic;
 use crate::db::{SemanticGroup, get_resolver_data_options};
-use crate::diagnostic::{SemanticDiagnosticKind, SemanticDiagnosticsBuilder};
+use crate::diagnostic::SemanticDiagnosticKind;
 use crate::resolve::ResolvedGenericItem;
 
 /// Information per item in a module.
@@ -98,15 +99,23 @@ pub fn priv_module_semantic_data(
         };
         let visibility = Visibility::from_ast(db.upcast(), &mut diagnostics, &visibility);
         let feature_kind = FeatureKind::from_ast(db.upcast(), &mut diagnostics, &attributes);
-        if items
-            .insert(name.clone(), ModuleItemInfo { item_id, visibility, feature_kind })
-            .is_some()
+        if let Some(previous) =
+            items.insert(name.clone(), ModuleItemInfo { item_id, visibility, feature_kind })
         {
             // `item` is extracted from `module_items` and thus `module_item_name_stable_ptr` is
             // guaranteed to succeed.
-            diagnostics.report(
-                db.module_item_name_stable_ptr(module_id, item_id).unwrap(),
-                SemanticDiagnosticKind::NameDefinedMultipleTimes(name.clone()),
+            let stable_location = |item_id| {
+                StableLocation::new(db.module_item_name_stable_ptr(module_id, item_id).unwrap())
+            };
+            diagnostics.add(
+                SemanticDiagnostic::new(
+                    stable_location(item_id),
+                    SemanticDiagnosticKind::NameDefinedMultipleTimes(name.clone()),
+                )
+                .with_note(DiagnosticNote::with_location(
+                    format!("`{name}` was previously defined here"),
+                    stable_location(previous.item_id).diagnostic_location(def_db),
+                )),
             );
         }
     }
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use cairo_lang_defs::ids::{
-    LanguageElementId, ModuleId, NamedLanguageElementId, TraitFunctionId, TraitId,
+    LanguageElementId, ModuleId, ModuleItemId, NamedLanguageElementId, TraitFunctionId, TraitId,
 };
 use cairo_lang_filesystem::ids::CrateId;
 use cairo_lang_utils::ordered_hash_map::{Entry, OrderedHashMap};
@@ -244,3 +244,108 @@ pub fn visible_traits_from_module(
     }
     Some(result.into())
 }
+
+/// Query implementation of [crate::db::SemanticGroup::visible_importables_in_module].
+pub fn visible_importables_in_module(
+    db: &dyn SemanticGroup,
+    module_id: ModuleId,
+    user_module_id: ModuleId,
+) -> Arc<[(ModuleItemId, String)]> {
+    let mut visited_modules = UnorderedHashSet::default();
+    visible_importables_in_module_ex(db, module_id, user_module_id, &mut visited_modules)
+        .unwrap_or_else(|| Vec::new().into())
+}
+
+/// Returns the visible module items in a module and in the modules it contains or imports.
+/// The visibility is relative to the module `user_module_id`.
+fn visible_importables_in_module_ex(
+    db: &dyn SemanticGroup,
+    module_id: ModuleId,
+    user_module_id: ModuleId,
+    visited_modules: &mut UnorderedHashSet<ModuleId>,
+) -> Option<Arc<[(ModuleItemId, String)]>> {
+    let mut result = Vec::new();
+    if visited_modules.contains(&module_id) {
+        return Some(result.into());
+    }
+    // Check if an item in the current module is visible from the user module.
+    let is_visible = |item_name: SmolStr| {
+        let item_info = db.module_item_info_by_name(module_id, item_name).ok()??;
+        Some(peek_visible_in(db.upcast(), item_info.visibility, module_id, user_module_id))
+    };
+    visited_modules.insert(module_id);
+    // Modules to traverse, along with the names under which they are visible in the current one.
+    let mut modules_to_visit = vec![];
+    for item in db.module_items(module_id).ok()?.iter().copied() {
+        let item_name = item.name(db.upcast());
+        if !is_visible(item_name.clone())? {
+            continue;
+        }
+        match item {
+            // Items imported into the current module are reported by the modules defining them,
+            // but modules imported into the current module are traversed, under the name they
+            // are imported as.
+            ModuleItemId::Use(use_id) => {
+                if let Ok(ResolvedGenericItem::Module(inner_module_id)) =
+                    db.use_resolved_item(use_id)
+                {
+                    modules_to_visit.push((inner_module_id, item_name));
+                }
+            }
+            ModuleItemId::Submodule(submodule_id) => {
+                result.push((item, item_name.to_string()));
+                modules_to_visit.push((ModuleId::Submodule(submodule_id), item_name));
+            }
+            _ => result.push((item, item_name.to_string())),
+        }
+    }
+
+    for (submodule, submodule_name) in modules_to_visit {
+        for (item, path) in
+            visible_importables_in_module_ex(db, submodule, user_module_id, visited_modules)?.iter()
+        {
+            result.push((*item, format!("{submodule_name}::{path}")));
+        }
+    }
+    Some(result.into())
+}
+
+/// Query implementation of [crate::db::SemanticGroup::visible_importables_from_module].
+pub fn visible_importables_from_module(
+    db: &dyn SemanticGroup,
+    module_id: ModuleId,
+) -> Option<Arc<OrderedHashMap<ModuleItemId, String>>> {
+    let mut current_top_module = module_id;
+    while let ModuleId::Submodule(submodule_id) = current_top_module {
+        current_top_module = submodule_id.parent_module(db.upcast());
+    }
+    let current_crate_id = match current_top_module {
+        ModuleId::CrateRoot(crate_id) => crate_id,
+        ModuleId::Submodule(_) => unreachable!("current module is not a top-level module"),
+    };
+
+    let mut module_visible_importables = Vec::new();
+    for crate_id in db.crates() {
+        let crate_name =
+            if crate_id == current_crate_id { "crate".into() } else { crate_id.name(db.upcast()) };
+        module_visible_importables.extend(
+            db.visible_importables_in_module(ModuleId::CrateRoot(crate_id), module_id)
+                .iter()
+                .map(|(item, path)| (*item, format!("{crate_name}::{path}"))),
+        );
+    }
+    let mut result: OrderedHashMap<ModuleItemId, String> = OrderedHashMap::default();
+    for (item, path) in module_visible_importables {
+        match result.entry(item) {
+            Entry::Occupied(existing_path) => {
+                if path.split("::").count() < existing_path.get().split("::").count() {
+                    *existing_path.into_mut() = path;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(path);
+            }
+        }
+    }
+    Some(result.into())
+}
@@ -10,6 +10,7 @@ import {
   registerMacroExpandProvider,
   registerVfsProvider,
   registerViewAnalyzedCratesProvider,
+  registerViewMemoryUsageProvider,
 } from "./textDocumentProviders";
 
 export interface LanguageServerExecutableProvider {
@@ -65,6 +66,7 @@ export async function setupLanguageServer(ctx: Context): Promise<lc.LanguageClie
   registerVfsProvider(client, ctx);
   registerMacroExpandProvider(client, ctx);
   registerViewAnalyzedCratesProvider(client, ctx);
+  registerViewMemoryUsageProvider(client, ctx);
 
   client.onNotification("scarb/could-not-find-scarb-executable", () => notifyScarbMissing(ctx));
 
@@ -86,15 +88,23 @@ export async function setupLanguageServer(ctx: Context): Promise<lc.LanguageClie
   });
 
   client.onNotification(
-    new lc.NotificationType<string>("cairo/corelib-version-mismatch"),
-    async (errorMessage) => {
+    new lc.NotificationType<{
+      message: string;
+      manifestPath: string;
+      expected: string;
+      found: string;
+    }>("cairo/corelib-version-mismatch"),
+    async ({ manifestPath, expected, found }) => {
       const restart = "Restart CairoLS";
       const cleanScarbCache = "Clean Scarb cache and reload";
+      const scarbUpdate = "Run `scarb update`";
 
       const selectedValue = await vscode.window.showErrorMessage(
-        errorMessage,
+        `Corelib version mismatch in ${manifestPath}: CairoLS expects ${expected}, ` +
+          `but the project uses ${found}.`,
         restart,
         cleanScarbCache,
+        scarbUpdate,
       );
 
       const restartLS = async () => {
@@ -112,22 +122,92 @@ export async function setupLanguageServer(ctx: Context): Promise<lc.LanguageClie
           await scarb?.cacheClean(ctx);
           await restartLS();
           break;
+        case scarbUpdate:
+          await client.sendRequest(lc.ExecuteCommandRequest.type, {
+            command: "cairo.scarbUpdate",
+            arguments: [{ manifestPath }],
+          });
+          break;
       }
     },
   );
 
-  client.onNotification(new lc.NotificationType("cairo/scarb-metadata-failed"), async () => {
-    const goToLogs = "Go to logs";
+  client.onNotification(
+    new lc.NotificationType<{ manifestPath: string; error: string }>(
+      "cairo/scarb-metadata-failed",
+    ),
+    async ({ manifestPath, error }) => {
+      const goToLogs = "Go to logs";
 
-    const selectedValue = await vscode.window.showErrorMessage(
-      "`scarb metadata` failed. Check if your project builds correctly via `scarb build`.",
-      goToLogs,
-    );
+      const selectedValue = await vscode.window.showErrorMessage(
+        `\`scarb metadata\` failed for ${manifestPath}: ${error}. ` +
+          "Analysis of the project will be degraded until this is fixed. " +
+          "Check if your project builds correctly via `scarb build`.",
+        goToLogs,
+      );
 
-    if (selectedValue === goToLogs) {
-      client.outputChannel.show(true);
-    }
-  });
+      if (selectedValue === goToLogs) {
+        client.outputChannel.show(true);
+      }
+    },
+  );
+
+  client.onNotification(
+    new lc.NotificationType<{ added: string[]; removed: string[]; changed: string[] }>(
+      "cairo/analyzed-crates-changed",
+    ),
+    ({ added, removed, changed }) => {
+      const summary = [
+        ["added", added],
+        ["removed", removed],
+        ["changed", changed],
+      ]
+        .filter(([, crates]) => crates.length > 0)
+        .map(([kind, crates]) => `${kind}: ${(crates as string[]).join(", ")}`)
+        .join("; ");
+
+      vscode.window.setStatusBarMessage(`Cairo crates ${summary}`, 5000);
+    },
+  );
+
+  client.onNotification(
+    new lc.NotificationType<{ testPath: string; success: boolean; output: string }>(
+      "cairo/test-run-finished",
+    ),
+    async ({ testPath, success, output }) => {
+      client.outputChannel.appendLine(output);
+
+      const showOutput = "Show output";
+      const selectedValue = success
+        ? await vscode.window.showInformationMessage(`Test \`${testPath}\` passed.`, showOutput)
+        : await vscode.window.showErrorMessage(`Test \`${testPath}\` failed.`, showOutput);
+
+      if (selectedValue === showOutput) {
+        client.outputChannel.show(true);
+      }
+    },
+  );
+
+  client.onNotification(
+    new lc.NotificationType<{ manifestPath: string; success: boolean; output: string }>(
+      "cairo/scarb-update-finished",
+    ),
+    async ({ manifestPath, success, output }) => {
+      client.outputChannel.appendLine(output);
+
+      if (!success) {
+        const showOutput = "Show output";
+        const selectedValue = await vscode.window.showErrorMessage(
+          `\`scarb update\` failed for ${manifestPath}.`,
+          showOutput,
+        );
+
+        if (selectedValue === showOutput) {
+          client.outputChannel.show(true);
+        }
+      }
+    },
+  );
 
   await client.start();
 
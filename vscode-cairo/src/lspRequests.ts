@@ -4,13 +4,28 @@ export type ExpandMacroRequest = {
   textDocument: { uri: string };
   position: { line: number; character: number };
 };
-export type ExpandMacroResponse = string | null;
+export type ExpansionCodeMapping = {
+  origin: lc.Range;
+  generated: lc.Range;
+};
+export type ExpandMacroResponse = {
+  expansion: string;
+  codeMappings: ExpansionCodeMapping[];
+} | null;
 export const expandMacro = new lc.RequestType<ExpandMacroRequest, ExpandMacroResponse, void>(
   "cairo/expandMacro",
 );
 
+export type ExpandMacroDocumentResponse = string | null;
+export const expandMacroDocument = new lc.RequestType<
+  ExpandMacroRequest,
+  ExpandMacroDocumentResponse,
+  void
+>("cairo/expandMacroDocument");
+
 export type ProvideVirtualFileRequest = {
   uri: string;
+  range?: lc.Range;
 };
 export type ProvideVirtualFileResponse = {
   content?: string;
@@ -25,3 +40,8 @@ export type ViewAnalyzedCratesResponse = string;
 export const viewAnalyzedCrates = new lc.RequestType0<ViewAnalyzedCratesResponse, void>(
   "cairo/viewAnalyzedCrates",
 );
+
+export type ViewMemoryUsageResponse = string;
+export const viewMemoryUsage = new lc.RequestType0<ViewMemoryUsageResponse, void>(
+  "cairo/viewMemoryUsage",
+);
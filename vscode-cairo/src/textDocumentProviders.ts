@@ -1,7 +1,12 @@
 import * as lc from "vscode-languageclient/node";
 import * as vscode from "vscode";
 import { Context } from "./context";
-import { expandMacro, vfsProvide, viewAnalyzedCrates } from "./lspRequests";
+import {
+  expandMacroDocument,
+  vfsProvide,
+  viewAnalyzedCrates,
+  viewMemoryUsage,
+} from "./lspRequests";
 
 export const registerVfsProvider = (client: lc.LanguageClient, ctx: Context) => {
   const vfsProvider: vscode.TextDocumentContentProvider = {
@@ -20,39 +25,55 @@ export const registerVfsProvider = (client: lc.LanguageClient, ctx: Context) =>
 };
 
 export const registerMacroExpandProvider = (client: lc.LanguageClient, ctx: Context) => {
-  const uri = vscode.Uri.parse("cairo-expand-macro://expandMacro/[EXPANSION].cairo");
   const eventEmitter = new vscode.EventEmitter<vscode.Uri>();
 
+  // Expansion documents are provided by the language server, which keeps them up to date with
+  // the macro calls they were expanded from, so they only need to be refreshed when a source
+  // document changes.
   const tdcp: vscode.TextDocumentContentProvider = {
-    async provideTextDocumentContent(): Promise<string> {
-      const editor = vscode.window.activeTextEditor;
-      if (!editor) return "";
-
-      const position = editor.selection.active;
-
-      const expanded = await client.sendRequest(expandMacro, {
-        textDocument: client.code2ProtocolConverter.asTextDocumentIdentifier(editor.document),
-        position,
+    async provideTextDocumentContent(uri: vscode.Uri): Promise<string> {
+      const res = await client.sendRequest(vfsProvide, {
+        uri: uri.toString(),
       });
 
-      return expanded ?? "Not available";
+      return res.content ?? "Not available";
     },
     onDidChange: eventEmitter.event,
   };
 
   ctx.extension.subscriptions.push(
-    vscode.workspace.registerTextDocumentContentProvider("cairo-expand-macro", tdcp),
+    vscode.workspace.registerTextDocumentContentProvider("cairo-expand", tdcp),
   );
 
   ctx.extension.subscriptions.push(
     vscode.commands.registerCommand("cairo.expandMacro", async () => {
-      const document = await vscode.workspace.openTextDocument(uri);
+      const editor = vscode.window.activeTextEditor;
+      if (!editor) return;
 
-      eventEmitter.fire(uri);
+      const uri = await client.sendRequest(expandMacroDocument, {
+        textDocument: client.code2ProtocolConverter.asTextDocumentIdentifier(editor.document),
+        position: editor.selection.active,
+      });
+      if (!uri) {
+        return vscode.window.showInformationMessage("No macro to expand at the caret.");
+      }
 
+      const document = await vscode.workspace.openTextDocument(vscode.Uri.parse(uri));
       return vscode.window.showTextDocument(document, vscode.ViewColumn.Two, true);
     }),
   );
+
+  ctx.extension.subscriptions.push(
+    vscode.workspace.onDidChangeTextDocument((e) => {
+      if (e.document.languageId !== "cairo" || e.document.uri.scheme === "cairo-expand") return;
+
+      for (const document of vscode.workspace.textDocuments) {
+        if (document.uri.scheme === "cairo-expand") {
+          eventEmitter.fire(document.uri);
+        }
+      }
+    }),
+  );
 };
 
 export const registerViewAnalyzedCratesProvider = (client: lc.LanguageClient, ctx: Context) => {
@@ -94,3 +115,27 @@ export const registerViewAnalyzedCratesProvider = (client: lc.LanguageClient, ct
     }),
   );
 };
+
+export const registerViewMemoryUsageProvider = (client: lc.LanguageClient, ctx: Context) => {
+  const uri = vscode.Uri.parse("cairo-view-memory-usage://viewMemoryUsage/[MEMORY_USAGE].md");
+  const eventEmitter = new vscode.EventEmitter<vscode.Uri>();
+
+  const tdcp: vscode.TextDocumentContentProvider = {
+    provideTextDocumentContent: () => client.sendRequest(viewMemoryUsage),
+    onDidChange: eventEmitter.event,
+  };
+
+  ctx.extension.subscriptions.push(
+    vscode.workspace.registerTextDocumentContentProvider("cairo-view-memory-usage", tdcp),
+  );
+
+  ctx.extension.subscriptions.push(
+    vscode.commands.registerCommand("cairo.viewMemoryUsage", async () => {
+      const document = await vscode.workspace.openTextDocument(uri);
+
+      eventEmitter.fire(uri);
+
+      return vscode.window.showTextDocument(document, vscode.ViewColumn.Two, true);
+    }),
+  );
+};